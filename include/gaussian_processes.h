@@ -0,0 +1,80 @@
+#ifndef GAUSSIAN_PROCESSES_H
+#define GAUSSIAN_PROCESSES_H
+
+/* Generated with cbindgen from src/gp/capi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of a call through the C interface.
+typedef enum GpStatus {
+  GP_STATUS_OK = 0,
+  // A required pointer was null.
+  GP_STATUS_NULL_POINTER = 1,
+  // The kernel is not valid UTF-8 or not a valid kernel in RON.
+  GP_STATUS_INVALID_KERNEL = 2,
+  // The covariance matrix of the training data could not be inverted.
+  GP_STATUS_NOT_INVERTIBLE = 3,
+  // The training data does not fit the model.
+  GP_STATUS_INVALID_INPUT = 4,
+} GpStatus;
+
+// Opaque handle to a fitted Gaussian process.
+typedef struct GpHandle GpHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Fit a Gaussian process to `n` training points, with the kernel given in RON as a null
+// terminated string, e.g. `Rbf((sigma: 1.0, length_scale: 1.0))`. On success the handle is
+// written to `out` and must be released with [`gp_free`].
+//
+// # Safety
+//
+// `x` and `y` must point to `n` values each, `kernel` to a null terminated string and `out`
+// to writable memory for a pointer.
+GpStatus gp_fit(const double *x,
+                const double *y,
+                uintptr_t n,
+                const char *kernel,
+                double noise_sigma,
+                GpHandle **out);
+
+// Predict the mean and variance at `n` inputs, writing `n` values to each of `mean` and
+// `variance`.
+//
+// # Safety
+//
+// `gp` must be a handle from [`gp_fit`] that has not been freed, `x` must point to `n` values
+// and `mean` and `variance` to writable memory for `n` values each.
+GpStatus gp_predict(const GpHandle *gp,
+                    const double *x,
+                    uintptr_t n,
+                    double *mean,
+                    double *variance);
+
+// The log marginal likelihood of the training data.
+//
+// # Safety
+//
+// `gp` must be a handle from [`gp_fit`] that has not been freed.
+double gp_log_marginal_likelihood(const GpHandle *gp);
+
+// Release a handle from [`gp_fit`]. Does nothing for a null pointer.
+//
+// # Safety
+//
+// `gp` must be null or a handle from [`gp_fit`] that has not been freed.
+void gp_free(GpHandle *gp);
+
+// A description of a status, as a static null terminated string.
+const char *gp_status_message(GpStatus status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GAUSSIAN_PROCESSES_H */
@@ -1,219 +1,2757 @@
 use egui::Slider;
-use egui_plot::{Line, PlotResponse};
+use egui_plot::{Line, PlotPoint, PlotResponse};
 use nalgebra as na;
+use rand::SeedableRng;
 
-use crate::gp::RbfKernel;
+mod baselines;
+mod classification;
+mod dataset;
+mod dates;
+mod detach;
+mod diagnostics;
+mod export;
+mod expr;
+mod figure;
+mod forecast;
+mod frames;
+mod game;
+mod generate;
+mod heatmap;
+mod holdout;
+mod kernel_ui;
+mod landscape;
+mod layers;
+mod mcmc;
+mod occam;
+mod optimize;
+mod paste;
+mod plane;
+mod point_menu;
+mod presets;
+mod profiles;
+mod ranges;
+mod selection;
+mod session;
+mod stats;
+mod stream;
+mod style;
+mod surface;
+mod tutorial;
+
+use dataset::Dataset;
+
+use crate::gp::{
+    init_heuristics, ActiveLearningCriterion, BayesianLinearRegression, CircularKernel,
+    DataTransform, GaussianProcess, GpKernel, HeteroscedasticGaussianProcess, Kernel, KernelParams,
+    MaternKernel, MaternSmoothness, RbfKernel, RegressionModel, StudentTGaussianProcess,
+    TransformOptions, EPS,
+};
+
+/// The version of the app state, increased when the meaning of a saved field changes so that
+/// older state is migrated when loaded. Version 1 saves the noise as a standard deviation
+/// instead of a variance.
+const STATE_VERSION: u32 = 1;
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
 pub struct App {
-    x: Vec<f64>,
-    y: Vec<f64>,
-    kernel_length_scale: f64,
-    kernel_sigma: f64,
+    /// The [`STATE_VERSION`] the state was saved with, zero for state from before it was saved.
+    #[serde(default)]
+    state_version: u32,
+    mode: Mode,
+    datasets: Vec<Dataset>,
+    active_dataset: usize,
+    kernel: Kernel,
+    /// The standard deviation of the observation noise.
     noise_sigma: f64,
+    /// The small variance added to the diagonal of the GP covariances for numerical stability.
+    /// It is used by the models of the main plot, their prior and every window that fits the
+    /// hyperparameters of the active dataset. The other modes, which fit their own data, keep
+    /// the default [`EPS`].
+    jitter: f64,
+    /// The model fit to the datasets and drawn in the main plot.
+    model: Model,
+    /// The prior variance of the weights of the linear regression.
+    weight_variance: f64,
+    /// The length of the domain of the circular GP, after which the inputs wrap around.
+    circular_period: f64,
+    /// The degrees of freedom of the noise of the robust GP, where fewer give heavier tails.
+    degrees_of_freedom: f64,
+    compare_kernels: bool,
+    comparison_kernel: Kernel,
+    split_comparison: bool,
+    show_prior: bool,
+    /// Whether the bands show the uncertainty of new observations, including the noise,
+    /// instead of the uncertainty of the latent function.
+    predictive_band: bool,
+    show_suggestion: bool,
+    /// Whether to tint the parts of the plot outside the range of the training inputs.
+    show_extrapolation: bool,
+    suggestion_criterion: ActiveLearningCriterion,
+    num_prior_samples: usize,
+    show_kernel_inspector: bool,
+    show_covariance_matrix: bool,
+    /// The titles of the auxiliary windows shown as separate windows.
+    detached: std::collections::BTreeSet<String>,
+    show_spectrum: bool,
+    show_cholesky_factor: bool,
+    show_residuals: bool,
+    standardize_residuals: bool,
+    show_leave_one_out: bool,
+    loo_threshold: f64,
+    show_calibration: bool,
+    #[serde(skip)]
+    highlighted_point: Option<usize>,
+    #[serde(skip)]
+    selection: selection::Selection,
+    /// The point being dragged, as `(dataset, point)`.
+    #[serde(skip)]
+    dragged_point: Option<(usize, usize)>,
+    /// What the context menu of the main plot was last opened on.
+    #[serde(skip)]
+    menu_target: Option<point_menu::MenuTarget>,
+    /// Whether the app is used on a touch screen, which gets larger targets and gestures in
+    /// place of clicks that are hard to aim.
+    #[serde(skip)]
+    touch: bool,
+    /// Show the labels of the points next to them, rather than only when hovered.
+    show_labels: bool,
+    /// Scrolling over a training point changes its weight rather than the view.
+    brush: bool,
+    /// The ranges of the hyperparameter sliders changed by the user.
+    slider_ranges: ranges::SliderRanges,
+    #[serde(skip)]
+    label_text: String,
+    show_style: bool,
+    style: style::PlotStyle,
+    labels: style::PlotLabels,
+    layers: layers::PlotLayers,
+    baselines: baselines::Baselines,
+    show_stats: bool,
+    /// Larger text, thicker lines and more contrast for projectors, with the advanced
+    /// controls hidden.
+    presentation: bool,
+    /// The presentation mode the style of the UI was last set for.
+    #[serde(skip)]
+    presentation_applied: Option<bool>,
+    #[serde(skip)]
+    stats: stats::PerformanceStats,
+    show_landscape: bool,
+    show_stream: bool,
+    landscape: landscape::LandscapeView,
     #[serde(skip)]
-    gp: Option<crate::gp::GaussianProcess<RbfKernel>>,
+    optimization: optimize::Optimization,
+    hyperparameter_posterior: mcmc::HyperparameterPosterior,
+    model_comparison: occam::ModelComparison,
+    /// The prediction averaged over the sampled hyperparameters, drawn like a snapshot.
+    #[serde(skip)]
+    averaged_prediction: Option<Snapshot>,
+    snapshots: Vec<Snapshot>,
+    stream: stream::StreamPanel,
+    plane: plane::PlaneView,
+    classification: classification::ClassificationView,
+    forecast: forecast::ForecastView,
+    game: game::GameView,
+    export: export::ExportDialog,
+    holdout: holdout::HoldoutDialog,
+    image_export: figure::ImageExportDialog,
+    frame_export: frames::FrameExportDialog,
+    session: session::SessionDialog,
+    /// Named states of the app to switch between, e.g. while teaching.
+    profiles: profiles::Profiles,
+    generate: generate::GenerateDialog,
+    #[serde(skip)]
+    paste: paste::PasteDialog,
+    #[serde(skip)]
+    tutorial: tutorial::Tutorial,
+    /// The seed of the prior samples, kept so the same samples can be drawn again later.
+    sample_seed: u64,
+    #[serde(skip)]
+    prior_samples: Vec<na::DVector<f64>>,
+    #[serde(skip)]
+    comparison_gp: Option<GaussianProcess<Kernel>>,
+    /// Whether the last fit failed for some model, e.g. because its covariance matrix is not
+    /// positive definite. It is only retried once something changes, not on every frame.
+    #[serde(skip)]
+    fit_failed: bool,
+    /// What the main plot showed when last drawn, used when exporting it as an image.
+    #[serde(skip)]
+    plot_bounds: Option<egui_plot::PlotBounds>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            state_version: STATE_VERSION,
+            mode: Mode::Regression,
+            datasets: vec![Dataset {
+                x: vec![1.0, 2.0, 6.0],
+                y: vec![1.0, 1.0, -1.0],
+                ..Dataset::new(0)
+            }],
+            active_dataset: 0,
+            kernel: Kernel::Rbf(RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            }),
+            noise_sigma: 0.1,
+            jitter: EPS,
+            model: Model::GaussianProcess,
+            weight_variance: 1.0,
+            circular_period: std::f64::consts::TAU,
+            degrees_of_freedom: 4.0,
+            compare_kernels: false,
+            comparison_kernel: Kernel::Matern(MaternKernel {
+                smoothness: MaternSmoothness::ThreeHalves,
+                sigma: 1.0,
+                length_scale: 1.0,
+            }),
+            split_comparison: false,
+            show_prior: false,
+            predictive_band: false,
+            show_suggestion: false,
+            show_extrapolation: true,
+            suggestion_criterion: ActiveLearningCriterion::MaxVariance,
+            num_prior_samples: 3,
+            show_kernel_inspector: false,
+            show_covariance_matrix: false,
+            detached: Default::default(),
+            show_spectrum: false,
+            show_cholesky_factor: false,
+            show_residuals: false,
+            standardize_residuals: false,
+            show_leave_one_out: false,
+            loo_threshold: 2.0,
+            show_calibration: false,
+            highlighted_point: None,
+            selection: Default::default(),
+            dragged_point: None,
+            menu_target: None,
+            touch: false,
+            show_labels: true,
+            brush: false,
+            slider_ranges: Default::default(),
+            label_text: String::new(),
+            show_style: false,
+            style: Default::default(),
+            labels: Default::default(),
+            layers: Default::default(),
+            baselines: Default::default(),
+            show_stats: false,
+            presentation: false,
+            presentation_applied: None,
+            stats: Default::default(),
+            show_landscape: false,
+            show_stream: false,
+            landscape: Default::default(),
+            optimization: Default::default(),
+            hyperparameter_posterior: Default::default(),
+            model_comparison: Default::default(),
+            averaged_prediction: None,
+            snapshots: Vec::new(),
+            stream: Default::default(),
+            plane: Default::default(),
+            classification: Default::default(),
+            forecast: Default::default(),
+            game: Default::default(),
+            export: Default::default(),
+            holdout: Default::default(),
+            image_export: Default::default(),
+            frame_export: Default::default(),
+            session: Default::default(),
+            profiles: Default::default(),
+            generate: Default::default(),
+            paste: Default::default(),
+            tutorial: Default::default(),
+            sample_seed: 0,
+            prior_samples: Vec::new(),
+            comparison_gp: None,
+            fit_failed: false,
+            plot_bounds: None,
+        }
+    }
+}
+
+impl App {
+    /// Called once before the first frame.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // This is also where you can customize the look and feel of egui using
+        // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
+
+        // Load previous app state (if any).
+        // Note that you must enable the `persistence` feature for this to work.
+        if let Some(storage) = cc.storage {
+            let mut app: Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            app.migrate();
+            app.ensure_dataset();
+            return app;
+        }
+
+        Default::default()
+    }
+
+    /// Bring state saved by an earlier version up to date.
+    fn migrate(&mut self) {
+        if self.state_version < 1 {
+            // the noise was a variance, and the saved fits were loaded with it as a standard
+            // deviation, so they are redone
+            self.noise_sigma = self.noise_sigma.max(0.0).sqrt();
+            for dataset in &mut self.datasets {
+                dataset.gp = None;
+            }
+        }
+        self.state_version = STATE_VERSION;
+    }
+
+    /// Make sure there is an active dataset to add points to, e.g. after loading old state.
+    fn ensure_dataset(&mut self) {
+        if self.datasets.is_empty() {
+            self.datasets.push(Dataset::new(0));
+        }
+        self.active_dataset = self.active_dataset.min(self.datasets.len() - 1);
+    }
+
+    fn kernel(&self) -> Kernel {
+        self.kernel.clone()
+    }
+
+    fn circular_kernel(&self) -> CircularKernel<Kernel> {
+        CircularKernel {
+            kernel: self.kernel(),
+            period: self.circular_period,
+        }
+    }
+
+    /// Whether a fit the plots need is missing, because it was never made, was invalidated or
+    /// failed.
+    fn missing_fits(&self) -> bool {
+        self.datasets.iter().any(|dataset| dataset.gp.is_none())
+            || (self.model == Model::BayesianLinearRegression
+                && self.datasets.iter().any(|dataset| dataset.linear.is_none()))
+            || (self.model == Model::CircularGaussianProcess
+                && self
+                    .datasets
+                    .iter()
+                    .any(|dataset| dataset.circular.is_none()))
+            || (self.model == Model::HeteroscedasticGaussianProcess
+                && self
+                    .datasets
+                    .iter()
+                    .any(|dataset| dataset.heteroscedastic.is_none()))
+            || (self.model == Model::StudentTGaussianProcess
+                && self.datasets.iter().any(|dataset| dataset.robust.is_none()))
+            || (self.compare_kernels && self.comparison_gp.is_none())
+    }
+
+    /// The GP prior with the noise and jitter of the fits.
+    fn prior<K: GpKernel<f64>>(&self, kernel: K) -> GaussianProcess<K> {
+        GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(self.noise_sigma)
+            .jitter(self.jitter)
+            .build_from_slices(&[], &[])
+            .expect("the prior has no covariance matrix to invert")
+    }
+
+    /// The mean and variance of the prior of the selected model.
+    fn predict_prior(&self, x: &[f64]) -> (na::DVector<f64>, na::DVector<f64>) {
+        let x = na::DVector::from_column_slice(x);
+        if self.model == Model::CircularGaussianProcess {
+            self.prior(self.circular_kernel()).predict(&x)
+        } else {
+            self.prior(self.kernel()).predict(&x)
+        }
+    }
+
+    /// Function samples from the prior of the selected model.
+    fn sample_prior<R: rand::Rng>(
+        &self,
+        x: &[f64],
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<na::DVector<f64>> {
+        let x = na::DVector::from_column_slice(x);
+        if self.model == Model::CircularGaussianProcess {
+            self.prior(self.circular_kernel()).sample(&x, n, rng)
+        } else {
+            self.prior(self.kernel()).sample(&x, n, rng)
+        }
+    }
+
+    /// Frames of function samples from the prior of the selected model, which morph smoothly
+    /// and loop.
+    fn sample_prior_loop<R: rand::Rng>(
+        &self,
+        x: &[f64],
+        n: usize,
+        frames: usize,
+        rng: &mut R,
+    ) -> Vec<Vec<na::DVector<f64>>> {
+        let x = na::DVector::from_column_slice(x);
+        if self.model == Model::CircularGaussianProcess {
+            self.prior(self.circular_kernel())
+                .sample_loop(&x, n, frames, rng)
+        } else {
+            self.prior(self.kernel()).sample_loop(&x, n, frames, rng)
+        }
+    }
+
+    /// The names of the hyperparameters that can be swept: the kernel parameters followed by
+    /// the noise.
+    fn parameter_names(&self) -> Vec<&'static str> {
+        let mut names = self.kernel.param_names();
+        names.push("noise sigma");
+        names
+    }
+
+    /// The hyperparameter with the index into [`Self::parameter_names`].
+    fn parameter_mut(&mut self, index: usize) -> &mut f64 {
+        if index < self.kernel.param_names().len() {
+            self.kernel.params_mut().swap_remove(index)
+        } else {
+            &mut self.noise_sigma
+        }
+    }
+
+    /// Move an input into the domain of the circular GP, if it is the selected model.
+    fn wrap_input(&self, x: f64) -> f64 {
+        if self.model == Model::CircularGaussianProcess {
+            x.rem_euclid(self.circular_period)
+        } else {
+            x
+        }
+    }
+
+    /// The dataset that is currently being edited and inspected.
+    fn dataset(&self) -> &Dataset {
+        &self.datasets[self.active_dataset]
+    }
+
+    /// The GP fit of the active dataset, if a GP is the selected model.
+    fn fit_gp(&self) -> Option<&GaussianProcess<Kernel>> {
+        if self.model == Model::GaussianProcess {
+            self.dataset().gp.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Append the points that arrived from the live stream to the active dataset, updating
+    /// its fit incrementally.
+    fn receive_stream(&mut self) {
+        let samples = self.stream.poll();
+        if samples.is_empty() {
+            return;
+        }
+
+        let dataset = &mut self.datasets[self.active_dataset];
+        for (x, y) in samples {
+            let x = x.unwrap_or_else(|| dataset.x.last().map_or(0.0, |last| last + 1.0));
+            dataset.x.push(x);
+            dataset.y.push(y);
+            // the transformation changes with every point, so the points can not simply be added
+            match &mut dataset.gp {
+                Some(gp) if dataset.transform == TransformOptions::default() => {
+                    gp.add_observation(x, y)
+                }
+                _ => dataset.gp = None,
+            }
+        }
+        // refitting the straight line is cheap
+        let (x, y) = dataset.training_points();
+        if let Some(linear) = &mut dataset.linear {
+            linear.fit(&x, &y).ok();
+        }
+        // the circular, heteroscedastic and robust GPs are refit from scratch with the others
+        dataset.circular = None;
+        dataset.heteroscedastic = None;
+        dataset.robust = None;
+
+        // dropping the oldest points requires a full refit
+        let excess = dataset.x.len().saturating_sub(self.stream.max_points);
+        if excess > 0 {
+            dataset.remove_first(excess);
+            dataset.gp = None;
+            self.highlighted_point = None;
+            self.selection.clear();
+        }
+        self.comparison_gp = None;
+        self.fit_failed = false;
+    }
+
+    /// The style of the plots, enlarged in presentation mode.
+    fn plot_style(&self) -> style::PlotStyle {
+        if self.presentation {
+            self.style.presentation()
+        } else {
+            self.style.clone()
+        }
+    }
+
+    /// The posteriors to plot, grouped by the plot they are shown in. When comparing kernels,
+    /// the comparison is only made for the active dataset.
+    fn posterior_groups(&self) -> Vec<Vec<Posterior<'_>>> {
+        let style = self.plot_style();
+        let mut posteriors = self
+            .datasets
+            .iter()
+            .filter(|dataset| dataset.visible)
+            .filter_map(|dataset| {
+                let mut name = Vec::new();
+                if self.datasets.len() > 1 {
+                    name.push(dataset.name.clone());
+                }
+                if self.compare_kernels {
+                    name.push(self.kernel.description());
+                }
+                Some(Posterior {
+                    name: name.join(" "),
+                    model: dataset.model(self.model)?,
+                    transform: dataset.transform(),
+                    mean_color: dataset.color,
+                    band_color: style.band_color(dataset.color),
+                })
+            })
+            .collect::<Vec<_>>();
+        let comparison = match (self.compare_kernels, &self.comparison_gp) {
+            (true, Some(gp)) => Some(Posterior {
+                name: if self.datasets.len() > 1 {
+                    format!(
+                        "{} {}",
+                        self.dataset().name,
+                        self.comparison_kernel.description()
+                    )
+                } else {
+                    self.comparison_kernel.description()
+                },
+                model: gp,
+                transform: self.dataset().transform(),
+                mean_color: style.comparison_color,
+                band_color: style.band_color(style.comparison_color),
+            }),
+            _ => None,
+        };
+
+        match comparison {
+            Some(comparison) if self.split_comparison => vec![posteriors, vec![comparison]],
+            Some(comparison) => {
+                posteriors.push(comparison);
+                vec![posteriors]
+            }
+            None => vec![posteriors],
+        }
+    }
+
+    /// The main plot as it was last shown, for exporting it as an image.
+    fn figure(&self) -> figure::Figure {
+        use figure::{Item, Shape};
+
+        let style = self.plot_style();
+
+        let x_range = self
+            .plot_bounds
+            .map_or([0.0, 10.0], |bounds| bounds.range_x().into_inner().into());
+        let x = grid(x_range[0], x_range[1]);
+        let mut items = Vec::new();
+        let band = |name: String, color, means: &[f64], variances: &[f64]| {
+            let offset = |sign: f64| {
+                means
+                    .iter()
+                    .zip(variances)
+                    .map(|(mean, variance)| mean + sign * 2.0 * variance.max(0.0).sqrt())
+                    .collect()
+            };
+            Item {
+                name,
+                color,
+                shape: Shape::Band {
+                    x: x.clone(),
+                    lower: offset(-1.0),
+                    upper: offset(1.0),
+                },
+            }
+        };
+        let line = |name: String, color, values: &[f64], width, dashed| Item {
+            name,
+            color,
+            shape: Shape::Line {
+                points: x.iter().zip(values).map(|(x, y)| [*x, *y]).collect(),
+                width,
+                dashed,
+            },
+        };
+
+        let no_data = self.datasets.iter().all(|dataset| dataset.x.is_empty());
+        if self.show_prior || no_data {
+            let (means, variances) = self.predict_prior(&x);
+            let variances = variances.add_scalar(self.band_noise());
+            if self.layers.bands {
+                items.push(band(
+                    "Prior mean ± 2σ".to_owned(),
+                    style.prior_color,
+                    means.as_slice(),
+                    variances.as_slice(),
+                ));
+            }
+            if self.layers.means {
+                items.push(line(
+                    "Prior mean".to_owned(),
+                    style.prior_color,
+                    means.as_slice(),
+                    style.mean_width,
+                    true,
+                ));
+            }
+            // the samples are drawn on the plotted grid, which may differ from the exported one
+            for sample in self.prior_samples.iter().filter(|_| self.layers.samples) {
+                let points = prediction_grid()
+                    .into_iter()
+                    .zip(sample.iter())
+                    .map(|(x, y)| [x, *y])
+                    .collect();
+                items.push(Item {
+                    name: "Prior samples".to_owned(),
+                    color: style.sample_color,
+                    shape: Shape::Line {
+                        points,
+                        width: style.sample_width,
+                        dashed: false,
+                    },
+                });
+            }
+        }
+        let snapshots = self.snapshots.iter().chain(&self.averaged_prediction);
+        for snapshot in snapshots.filter(|_| self.layers.snapshots) {
+            let (means, variances) = (&snapshot.mean, &snapshot.variance);
+            let offset = |sign: f64| {
+                means
+                    .iter()
+                    .zip(variances)
+                    .map(|(mean, variance)| mean + sign * 2.0 * variance.max(0.0).sqrt())
+                    .collect()
+            };
+            items.push(Item {
+                name: format!("{} ± 2σ", snapshot.name),
+                color: SNAPSHOT_COLOR,
+                shape: Shape::Band {
+                    x: snapshot.x.clone(),
+                    lower: offset(-1.0),
+                    upper: offset(1.0),
+                },
+            });
+            items.push(Item {
+                name: snapshot.name.clone(),
+                color: SNAPSHOT_COLOR,
+                shape: Shape::Line {
+                    points: snapshot
+                        .x
+                        .iter()
+                        .zip(means)
+                        .map(|(x, y)| [*x, *y])
+                        .collect(),
+                    width: 1.5,
+                    dashed: true,
+                },
+            });
+        }
+        for posterior in self.posterior_groups().iter().flatten() {
+            let (means, std) = posterior.predict(&x, self.predictive_band);
+            let variances: Vec<f64> = std.iter().map(|std| std * std).collect();
+            let name = posterior.line_name();
+            if self.layers.bands {
+                items.push(band(
+                    format!("{name} ± 2σ"),
+                    posterior.band_color,
+                    means.as_slice(),
+                    variances.as_slice(),
+                ));
+            }
+            if self.layers.means {
+                items.push(line(
+                    name,
+                    posterior.mean_color,
+                    means.as_slice(),
+                    style.mean_width,
+                    false,
+                ));
+            }
+        }
+        for dataset in self.datasets.iter().filter(|dataset| dataset.visible) {
+            let (train_x, train_y) = dataset.untransformed_training_points();
+            for (name, values) in self.baselines.lines(&train_x, &train_y, &x) {
+                for points in baselines::segments(&x, &values) {
+                    items.push(Item {
+                        name: name.clone(),
+                        color: dataset.color,
+                        shape: Shape::Line {
+                            points,
+                            width: style.mean_width,
+                            dashed: true,
+                        },
+                    });
+                }
+            }
+        }
+        for dataset in self.datasets.iter().filter(|dataset| dataset.visible) {
+            let points = dataset
+                .x
+                .iter()
+                .zip(dataset.y.iter())
+                .map(|(x, y)| [*x, *y])
+                .collect::<Vec<_>>();
+            let error_bars = if self.layers.error_bars {
+                dataset.error_bars(self.noise_sigma)
+            } else {
+                Vec::new()
+            };
+            for (i, [low, high]) in error_bars {
+                let x = dataset.x[i];
+                items.push(Item {
+                    name: "Observation noise".to_owned(),
+                    color: dataset.color,
+                    shape: Shape::Line {
+                        points: vec![[x, low], [x, high]],
+                        width: style.error_bar_width,
+                        dashed: false,
+                    },
+                });
+            }
+            if self.layers.points {
+                let (test, training): (Vec<_>, Vec<_>) = points
+                    .into_iter()
+                    .enumerate()
+                    .partition(|(i, _)| dataset.is_held_out(*i));
+                items.push(Item {
+                    name: if self.datasets.len() > 1 {
+                        dataset.name.clone()
+                    } else {
+                        "Training points".to_owned()
+                    },
+                    color: dataset.color,
+                    shape: Shape::Points {
+                        points: training.into_iter().map(|(_, point)| point).collect(),
+                        radius: style.point_radius,
+                    },
+                });
+                if !test.is_empty() {
+                    items.push(Item {
+                        name: "Test points".to_owned(),
+                        color: holdout::TEST_COLOR,
+                        shape: Shape::Points {
+                            points: test.into_iter().map(|(_, point)| point).collect(),
+                            radius: style.point_radius,
+                        },
+                    });
+                }
+            }
+        }
+
+        // without a shown plot, fit the y-axis to everything in the figure
+        let y_range = self.plot_bounds.map_or_else(
+            || {
+                let (min, max) = items
+                    .iter()
+                    .flat_map(|item| match &item.shape {
+                        Shape::Line { points, .. } | Shape::Points { points, .. } => {
+                            points.iter().map(|p| p[1]).collect::<Vec<_>>()
+                        }
+                        Shape::Band { lower, upper, .. } => {
+                            lower.iter().chain(upper).copied().collect()
+                        }
+                    })
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), y| {
+                        (min.min(y), max.max(y))
+                    });
+                let margin = 0.05 * (max - min);
+                [min - margin, max + margin]
+            },
+            |bounds| bounds.range_y().into_inner().into(),
+        );
+
+        figure::Figure {
+            title: self.labels.title.clone(),
+            x_label: self.labels.x_axis(),
+            y_label: self.labels.y_axis(),
+            x_range,
+            y_range,
+            items,
+        }
+    }
+
+    /// The visible training point closest to the screen position, if within `radius` pixels.
+    fn point_near(
+        &self,
+        transform: &egui_plot::PlotTransform,
+        pos: egui::Pos2,
+        radius: f32,
+    ) -> Option<(usize, usize)> {
+        self.datasets
+            .iter()
+            .enumerate()
+            .filter(|(_, dataset)| dataset.visible && self.layers.points)
+            .flat_map(|(i, dataset)| {
+                dataset
+                    .x
+                    .iter()
+                    .zip(dataset.y.iter())
+                    .enumerate()
+                    .map(move |(j, (x, y))| {
+                        let screen = transform.position_from_point(&PlotPoint::new(*x, *y));
+                        ((i, j), screen.distance(pos))
+                    })
+            })
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(point, _)| point)
+    }
+
+    /// The x range to keep in view while following a live stream into the active dataset.
+    fn follow_range(&self) -> Option<(f64, f64)> {
+        if !(self.stream.follow && self.stream.is_connected()) {
+            return None;
+        }
+        let last = self.dataset().x.last()?;
+        Some((last - self.stream.window, last + 0.05 * self.stream.window))
+    }
+
+    /// The variance added to the latent variance of the prior in the bands.
+    fn band_noise(&self) -> f64 {
+        if self.predictive_band {
+            self.noise_sigma.powi(2)
+        } else {
+            0.0
+        }
+    }
+
+    /// Plot the posteriors together with the training data, returning what the pointer did.
+    fn show_plot(
+        &self,
+        ui: &mut egui::Ui,
+        id: &str,
+        posteriors: &[Posterior<'_>],
+    ) -> PlotResponse<PlotInput> {
+        let style = self.plot_style();
+        let follow_range = self.follow_range();
+        let prediction_x = match follow_range {
+            Some((start, end)) => grid(start, end),
+            None => prediction_grid(),
+        };
+
+        // the prior is always shown when there is no data, as it is then all there is
+        let no_data = self.datasets.iter().all(|dataset| dataset.x.is_empty());
+        let prior_lines = (self.show_prior || no_data).then(|| {
+            let (means, variances) = self.predict_prior(&prediction_x);
+            let variances = variances.add_scalar(self.band_noise());
+
+            let (lower, upper) =
+                uncertainty_band(&prediction_x, &means, &variances, style.prior_color);
+            let mean = Line::new(
+                prediction_x
+                    .iter()
+                    .zip(means.iter())
+                    .map(|(x, y)| [*x, *y])
+                    .collect::<Vec<[f64; 2]>>(),
+            )
+            .color(style.prior_color)
+            .width(style.mean_width)
+            .style(egui_plot::LineStyle::dashed_loose());
+            let samples = self
+                .prior_samples
+                .iter()
+                .map(|sample| {
+                    Line::new(
+                        prediction_x
+                            .iter()
+                            .zip(sample.iter())
+                            .map(|(x, y)| [*x, *y])
+                            .collect::<Vec<[f64; 2]>>(),
+                    )
+                    .color(style.sample_color)
+                    .width(style.sample_width)
+                })
+                .collect::<Vec<_>>();
+
+            (
+                lower.width(style.band_width),
+                upper.width(style.band_width),
+                mean,
+                samples,
+            )
+        });
+
+        let snapshot_lines = self
+            .snapshots
+            .iter()
+            .chain(&self.averaged_prediction)
+            .map(|snapshot| {
+                let mean = Line::new(
+                    snapshot
+                        .x
+                        .iter()
+                        .zip(snapshot.mean.iter())
+                        .map(|(x, y)| [*x, *y])
+                        .collect::<Vec<[f64; 2]>>(),
+                )
+                .color(SNAPSHOT_COLOR)
+                .style(egui_plot::LineStyle::dashed_dense());
+                let (lower, upper) = uncertainty_band(
+                    &snapshot.x,
+                    &na::DVector::from_column_slice(&snapshot.mean),
+                    &na::DVector::from_column_slice(&snapshot.variance),
+                    SNAPSHOT_COLOR,
+                );
+                (snapshot.name.clone(), mean, lower, upper)
+            })
+            .collect::<Vec<_>>();
+
+        let predict_start = web_time::Instant::now();
+        let posterior_lines = posteriors
+            .iter()
+            .map(|posterior| {
+                let (means, std) = posterior.predict(&prediction_x, self.predictive_band);
+                let means = na::DVector::from_vec(means);
+                let variances = na::DVector::from_iterator(std.len(), std.iter().map(|s| s * s));
+
+                let mean_points: egui_plot::PlotPoints = means
+                    .iter()
+                    .zip(prediction_x.iter())
+                    .map(|(y, x)| [*x, *y])
+                    .collect();
+                let mean_line = Line::new(mean_points)
+                    .color(posterior.mean_color)
+                    .width(style.mean_width);
+
+                let (lower, upper) =
+                    uncertainty_band(&prediction_x, &means, &variances, posterior.band_color);
+                (
+                    posterior.line_name(),
+                    mean_line,
+                    lower.width(style.band_width),
+                    upper.width(style.band_width),
+                )
+            })
+            .collect::<Vec<_>>();
+        let predict_time = predict_start.elapsed();
+
+        // the points the GPs were trained on, together with error bars showing the assumed
+        // observation noise on each of them
+        let data = self
+            .datasets
+            .iter()
+            .enumerate()
+            .filter(|(_, dataset)| dataset.visible)
+            .map(|(i, dataset)| {
+                let radius = if self.touch {
+                    1.5 * style.point_radius
+                } else {
+                    style.point_radius
+                };
+                // the held-out points share the id, so that clicking them adds no point either
+                let points = |indices: &[usize], radius, color| {
+                    let points: egui_plot::PlotPoints = indices
+                        .iter()
+                        .map(|j| [dataset.x[*j], dataset.y[*j]])
+                        .collect();
+                    egui_plot::Points::new(points)
+                        .color(color)
+                        .radius(radius)
+                        .shape(style.marker.shape())
+                        .id(training_points_id(i))
+                };
+                let (held_out, training): (Vec<usize>, Vec<usize>) =
+                    (0..dataset.x.len()).partition(|j| dataset.is_held_out(*j));
+                let test_points = (!held_out.is_empty())
+                    .then(|| points(&held_out, radius, holdout::TEST_COLOR).name("Test points"));
+                // points with a weight other than one are drawn one by one, sized by the weight
+                let (weighted, unweighted): (Vec<usize>, Vec<usize>) = training
+                    .into_iter()
+                    .partition(|j| dataset.weight(*j) != 1.0);
+                let weighted = weighted
+                    .iter()
+                    .map(|j| {
+                        let radius = radius * dataset.weight(*j).powf(0.25) as f32;
+                        points(&[*j], radius, dataset.color)
+                    })
+                    .collect::<Vec<_>>();
+                let points = points(&unweighted, radius, dataset.color);
+
+                // the noise of a point is divided by the square root of its weight
+                let error_bars = dataset
+                    .error_bars(self.noise_sigma)
+                    .into_iter()
+                    .map(|(j, [low, high])| {
+                        let x = dataset.x[j];
+                        Line::new(vec![[x, low], [x, high]])
+                            .color(dataset.color)
+                            .width(style.error_bar_width)
+                    })
+                    .collect::<Vec<_>>();
+
+                let name = if self.datasets.len() > 1 {
+                    dataset.name.clone()
+                } else {
+                    "Training points".to_owned()
+                };
+                (name, points, weighted, test_points, error_bars)
+            })
+            .collect::<Vec<_>>();
+
+        // simple fits to compare the posteriors with, drawn dotted in the color of the dataset
+        let baseline_lines = self
+            .datasets
+            .iter()
+            .filter(|dataset| dataset.visible)
+            .flat_map(|dataset| {
+                let (x, y) = dataset.untransformed_training_points();
+                self.baselines
+                    .lines(&x, &y, &prediction_x)
+                    .into_iter()
+                    .flat_map(|(name, values)| {
+                        baselines::segments(&prediction_x, &values)
+                            .into_iter()
+                            .map(move |points| {
+                                Line::new(points)
+                                    .color(dataset.color)
+                                    .width(style.mean_width)
+                                    .style(egui_plot::LineStyle::dotted_dense())
+                                    .name(&name)
+                            })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // where the active dataset would benefit most from a new point
+        let suggestion = self
+            .show_suggestion
+            .then(|| {
+                let transform = self.dataset().transform();
+                let x = prediction_x.iter().map(|x| transform.input(*x)).collect();
+                let suggested = self
+                    .dataset()
+                    .gp
+                    .as_ref()?
+                    .suggest_next(&na::DVector::from_vec(x), self.suggestion_criterion)?;
+                Some(transform.inverse_input(suggested))
+            })
+            .flatten()
+            .map(|x| {
+                egui_plot::VLine::new(x)
+                    .color(self.dataset().color)
+                    .style(egui_plot::LineStyle::dashed_loose())
+                    .name("Suggested next sample")
+            });
+
+        // ring around the point selected in one of the diagnostics windows
+        let dataset = self.dataset();
+        let highlight = self
+            .highlighted_point
+            .filter(|i| *i < dataset.x.len())
+            .map(|i| {
+                egui_plot::Points::new(vec![[dataset.x[i], dataset.y[i]]])
+                    .color(egui::Color32::YELLOW)
+                    .radius(9.0)
+                    .filled(false)
+                    .shape(egui_plot::MarkerShape::Circle)
+            });
+
+        // rings around the selected points, and the rectangle being drawn to select them
+        let selection_color = ui.visuals().selection.bg_fill;
+        let selected = (!self.selection.is_empty()).then(|| {
+            egui_plot::Points::new(
+                self.selection
+                    .coordinates(&self.datasets)
+                    .collect::<Vec<_>>(),
+            )
+            .color(selection_color)
+            .radius(8.0)
+            .filled(false)
+            .shape(egui_plot::MarkerShape::Circle)
+        });
+        let selection_box = match &self.selection.drag {
+            Some(selection::SelectionDrag::Box { start, end }) => Some(
+                egui_plot::Polygon::new(vec![
+                    [start.x, start.y],
+                    [end.x, start.y],
+                    [end.x, end.y],
+                    [start.x, end.y],
+                ])
+                .fill_color(selection_color.gamma_multiply(0.2))
+                .stroke(egui::Stroke::new(1.0, selection_color)),
+            ),
+            _ => None,
+        };
+        let selecting = ui.input(|input| input.modifiers.shift);
+
+        // the point the pointer was pressed on, found from where the plot was drawn last frame
+        // so that dragging the point does not also pan the plot
+        let plot_id = ui.make_persistent_id(id);
+        let hit_radius = if self.touch {
+            TOUCH_HIT_RADIUS
+        } else {
+            HIT_RADIUS
+        };
+        let pressed_point = ui
+            .input(|input| input.pointer.press_origin())
+            .zip(egui_plot::PlotMemory::load(ui.ctx(), plot_id))
+            .and_then(|(origin, memory)| self.point_near(&memory.transform(), origin, hit_radius));
+        // scrolling over a point changes its weight instead of the view while brushing
+        let brushing = self.brush
+            && ui
+                .input(|input| input.pointer.hover_pos())
+                .zip(egui_plot::PlotMemory::load(ui.ctx(), plot_id))
+                .and_then(|(pos, memory)| self.point_near(&memory.transform(), pos, hit_radius))
+                .is_some();
+
+        let labels = self
+            .datasets
+            .iter()
+            .filter(|dataset| self.show_labels && dataset.visible)
+            .flat_map(|dataset| {
+                (0..dataset.x.len()).filter_map(|i| {
+                    let label = dataset.label(i)?;
+                    Some(
+                        egui_plot::Text::new(
+                            PlotPoint::new(dataset.x[i], dataset.y[i]),
+                            format!(" {label}"),
+                        )
+                        .color(dataset.color)
+                        .anchor(egui::Align2::LEFT_BOTTOM),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let response = egui_plot::Plot::new(id)
+            .id(plot_id)
+            .link_axis("main_plot", true, true)
+            .link_cursor("main_plot", true, true)
+            .x_axis_label(self.labels.x_axis())
+            .y_axis_label(self.labels.y_axis())
+            .allow_drag(!selecting && pressed_point.is_none())
+            .allow_scroll(!brushing)
+            .allow_zoom(!brushing)
+            .label_formatter(|name, value| {
+                let prefix = if name.is_empty() {
+                    String::new()
+                } else {
+                    format!("{name}\n")
+                };
+                let mut label = format!("{prefix}x = {:.3}\ny = {:.3}", value.x, value.y);
+
+                // the label and weight of the hovered training point
+                if let Some((dataset, index)) = self.datasets.iter().find_map(|dataset| {
+                    let index = (0..dataset.x.len())
+                        .find(|i| dataset.x[*i] == value.x && dataset.y[*i] == value.y)?;
+                    Some((dataset, index))
+                }) {
+                    if let Some(point_label) = dataset.label(index) {
+                        label += &format!("\n“{point_label}”");
+                    }
+                    let weight = dataset.weight(index);
+                    if weight != 1.0 {
+                        label += &format!(
+                            "\nweight {weight:.2} (noise σ = {:.3})",
+                            self.noise_sigma / weight.sqrt()
+                        );
+                    }
+                }
+
+                // show the posteriors at the hovered x-coordinate
+                for posterior in posteriors {
+                    let (mean, std) = posterior.predict(&[value.x], self.predictive_band);
+                    let two_sigma = 2.0 * std[0];
+                    label += &format!(
+                        "\n{} = {:.3} ± {:.3} (2σ)",
+                        posterior.line_name().to_lowercase(),
+                        mean[0],
+                        two_sigma
+                    );
+                }
+                label
+            })
+            .show(ui, |pui| {
+                if let Some((start, end)) = follow_range {
+                    // fit the y-axis to the points in view
+                    let (min, max) = self
+                        .datasets
+                        .iter()
+                        .filter(|dataset| dataset.visible)
+                        .flat_map(|dataset| dataset.x.iter().zip(dataset.y.iter()))
+                        .filter(|(x, _)| (start..=end).contains(*x))
+                        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (_, y)| {
+                            (min.min(*y), max.max(*y))
+                        });
+                    let margin = 0.1 * (max - min) + 2.0 * self.noise_sigma + 0.1;
+                    pui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                        [start, min - margin],
+                        [end, max + margin],
+                    ));
+                }
+                let layers = self.layers;
+                if let Some((lower, upper, mean, samples)) = prior_lines {
+                    if layers.bands {
+                        pui.line(lower.name("Prior mean ± 2σ"));
+                        pui.line(upper.name("Prior mean ± 2σ"));
+                    }
+                    if layers.means {
+                        pui.line(mean.name("Prior mean"));
+                    }
+                    for sample in samples.into_iter().filter(|_| layers.samples) {
+                        pui.line(sample.name("Prior samples"));
+                    }
+                }
+                for (name, mean, lower, upper) in snapshot_lines {
+                    if layers.snapshots {
+                        pui.line(lower.name(format!("{name} ± 2σ")));
+                        pui.line(upper.name(format!("{name} ± 2σ")));
+                        pui.line(mean.name(name));
+                    }
+                }
+                for (name, mean, lower, upper) in posterior_lines {
+                    if layers.bands {
+                        pui.line(lower.name(format!("{name} ± 2σ")));
+                        pui.line(upper.name(format!("{name} ± 2σ")));
+                    }
+                    if layers.means {
+                        pui.line(mean.name(name));
+                    }
+                }
+                for line in baseline_lines {
+                    pui.line(line);
+                }
+                for (name, points, weighted, test_points, error_bars) in data {
+                    for error_bar in error_bars.into_iter().filter(|_| layers.error_bars) {
+                        pui.line(error_bar.name("Observation noise"));
+                    }
+                    if layers.points {
+                        for point in weighted {
+                            pui.points(point.name(&name));
+                        }
+                        pui.points(points.name(name));
+                        if let Some(test_points) = test_points {
+                            pui.points(test_points);
+                        }
+                    }
+                }
+                if let Some(highlight) = highlight {
+                    pui.points(highlight.name("Highlighted point"));
+                }
+                if let Some(suggestion) = suggestion {
+                    pui.vline(suggestion);
+                }
+                if self.model == Model::CircularGaussianProcess {
+                    for x in [0.0, self.circular_period] {
+                        pui.vline(
+                            egui_plot::VLine::new(x)
+                                .color(style.prior_color)
+                                .style(egui_plot::LineStyle::dotted_loose())
+                                .name("Domain boundary"),
+                        );
+                    }
+                }
+                if let Some(selected) = selected {
+                    pui.points(selected.name("Selected points"));
+                }
+                if let Some(selection_box) = selection_box {
+                    pui.polygon(selection_box.name("Selection"));
+                }
+                for label in labels {
+                    pui.text(label);
+                }
+
+                // whether the pointer was pressed on one of the selected points
+                let press_origin = pui.ctx().input(|input| input.pointer.press_origin());
+                let on_selection = press_origin.is_some_and(|origin| {
+                    self.selection.coordinates(&self.datasets).any(|[x, y]| {
+                        pui.screen_from_plot(PlotPoint::new(x, y)).distance(origin) < hit_radius
+                    })
+                });
+                let response = pui.response();
+                let hovered_point = response
+                    .hover_pos()
+                    .and_then(|pos| self.point_near(pui.transform(), pos, hit_radius));
+                PlotInput {
+                    pointer: pui.pointer_coordinate(),
+                    clicked: response.clicked() && !response.long_touched(),
+                    long_touched: response.long_touched(),
+                    secondary_clicked: response.secondary_clicked(),
+                    selecting,
+                    vertical_only: pui.ctx().input(|input| input.modifiers.alt),
+                    on_selection,
+                    pressed_point,
+                    hovered_point,
+                    drag_started: response.drag_started(),
+                    dragged: response.dragged(),
+                    drag_stopped: response.drag_stopped(),
+                    drag_delta: pui.pointer_coordinate_drag_delta(),
+                    scroll: if brushing {
+                        pui.ctx().input(|input| input.smooth_scroll_delta.y)
+                    } else {
+                        0.0
+                    },
+                    predict_time,
+                    grid_size: prediction_x.len(),
+                }
+            });
+
+        if self.show_extrapolation {
+            self.shade_extrapolation(ui, &response.transform);
+        }
+        response
+    }
+
+    /// Tint the plot to the left and right of the training inputs, where the model extrapolates.
+    fn shade_extrapolation(&self, ui: &egui::Ui, transform: &egui_plot::PlotTransform) {
+        let (min, max) = self
+            .datasets
+            .iter()
+            .filter(|dataset| dataset.visible)
+            .flat_map(|dataset| dataset.x.iter())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                (min.min(*x), max.max(*x))
+            });
+        if min > max {
+            return;
+        }
+
+        let frame = *transform.frame();
+        let left = transform
+            .position_from_point_x(min)
+            .clamp(frame.left(), frame.right());
+        let right = transform
+            .position_from_point_x(max)
+            .clamp(frame.left(), frame.right());
+        let tint = ui.visuals().text_color().gamma_multiply(0.06);
+        let painter = ui.painter_at(frame);
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(frame.left()..=left, frame.y_range()),
+            0.0,
+            tint,
+        );
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(right..=frame.right(), frame.y_range()),
+            0.0,
+            tint,
+        );
+    }
+}
+
+/// What kind of data the app works on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum Mode {
+    /// Regression on one-dimensional inputs.
+    Regression,
+    /// Regression on inputs in a plane, shown as heatmaps.
+    Plane,
+    /// Binary classification on one-dimensional inputs.
+    Classification,
+    /// Forecasting a time series with dates as inputs.
+    Forecast,
+    /// A game of finding the maximum of a hidden function with Bayesian optimization.
+    Game,
+}
+
+/// The regression model drawn in the 1D regression mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Model {
+    GaussianProcess,
+    /// A straight line, for comparing against the flexibility of a GP.
+    BayesianLinearRegression,
+    /// A GP on a circular domain such as angles, where the ends of the domain are neighbors.
+    CircularGaussianProcess,
+    /// A GP whose noise changes along the x-axis, estimated by a second GP.
+    HeteroscedasticGaussianProcess,
+    /// A GP with heavy tailed Student-t noise, which is not dragged around by outliers.
+    StudentTGaussianProcess,
+}
+
+impl Model {
+    const ALL: [Model; 5] = [
+        Model::GaussianProcess,
+        Model::BayesianLinearRegression,
+        Model::CircularGaussianProcess,
+        Model::HeteroscedasticGaussianProcess,
+        Model::StudentTGaussianProcess,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Model::GaussianProcess => "Gaussian process",
+            Model::BayesianLinearRegression => "Bayesian linear regression",
+            Model::CircularGaussianProcess => "Circular GP",
+            Model::HeteroscedasticGaussianProcess => "Heteroscedastic GP",
+            Model::StudentTGaussianProcess => "Robust GP",
+        }
+    }
+}
+
+impl Mode {
+    const ALL: [Mode; 5] = [
+        Mode::Regression,
+        Mode::Plane,
+        Mode::Classification,
+        Mode::Forecast,
+        Mode::Game,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Regression => "1D regression",
+            Mode::Plane => "2D regression",
+            Mode::Classification => "Classification",
+            Mode::Forecast => "Time series",
+            Mode::Game => "Bayesian optimization",
+        }
+    }
+}
+
+/// The kernel of the log noise variance of the heteroscedastic GP, which changes slowly compared
+/// to the function itself.
+const NOISE_KERNEL: Kernel = Kernel::Rbf(RbfKernel {
+    sigma: 1.0,
+    length_scale: 3.0,
+});
+
+/// Color of the ghosted overlay of frozen fits.
+const SNAPSHOT_COLOR: egui::Color32 = egui::Color32::from_rgba_premultiplied(100, 100, 100, 100);
+
+/// A frozen copy of the mean and variance of a fit, kept for comparisons while editing.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Snapshot {
+    name: String,
+    x: Vec<f64>,
+    mean: Vec<f64>,
+    variance: Vec<f64>,
+}
+
+impl Snapshot {
+    /// A snapshot of a prediction at the inputs `x` for the transformed inputs, mapped back to
+    /// the units of the points.
+    fn new(
+        name: String,
+        x: Vec<f64>,
+        transform: &DataTransform,
+        mean: &[f64],
+        variance: &[f64],
+    ) -> Self {
+        let std: Vec<f64> = variance.iter().map(|v| v.max(0.0).sqrt()).collect();
+        let (mean, std) = transform.inverse_predictions(&x, mean, &std);
+        Self {
+            name,
+            x,
+            mean,
+            variance: std.iter().map(|std| std * std).collect(),
+        }
+    }
 }
 
-impl Default for App {
-    fn default() -> Self {
-        Self {
-            x: vec![1.0, 2.0, 6.0],
-            y: vec![1.0, 1.0, -1.0],
-            kernel_sigma: 1.0,
-            kernel_length_scale: 1.0,
-            noise_sigma: 0.1,
-            gp: None,
-        }
-    }
-}
+/// What the pointer did in one of the main plots.
+struct PlotInput {
+    pointer: Option<PlotPoint>,
+    clicked: bool,
+    /// Whether the plot was right-clicked, opening its context menu.
+    secondary_clicked: bool,
+    /// Whether the plot was pressed and held on a touch screen.
+    long_touched: bool,
+    /// Whether the modifier for selecting points is held.
+    selecting: bool,
+    /// Whether the modifier for dragging points vertically only is held.
+    vertical_only: bool,
+    /// Whether the pointer was pressed on a selected point.
+    on_selection: bool,
+    /// The training point the pointer was pressed on, as `(dataset, point)`.
+    pressed_point: Option<(usize, usize)>,
+    /// The training point under the pointer, as `(dataset, point)`.
+    hovered_point: Option<(usize, usize)>,
+    drag_started: bool,
+    dragged: bool,
+    drag_stopped: bool,
+    /// How far the pointer was dragged since the last frame, in plot coordinates.
+    drag_delta: egui::Vec2,
+    /// How far the pointer scrolled over a training point while brushing, in points.
+    scroll: f32,
+    /// Time spent predicting the posteriors on the grid.
+    predict_time: web_time::Duration,
+    grid_size: usize,
+}
+
+/// A fitted model to draw in the main plot.
+struct Posterior<'a> {
+    /// Name to tell the posteriors apart, empty if there is only one.
+    name: String,
+    model: &'a dyn RegressionModel,
+    /// The transformation of the points the model was fit to.
+    transform: DataTransform,
+    mean_color: egui::Color32,
+    band_color: egui::Color32,
+}
+
+impl Posterior<'_> {
+    /// The mean and standard deviation at the inputs, of the latent function or, if
+    /// `predictive`, of new observations.
+    fn predict(&self, x: &[f64], predictive: bool) -> (Vec<f64>, Vec<f64>) {
+        let transformed: Vec<f64> = x.iter().map(|x| self.transform.input(*x)).collect();
+        let (mean, mut std) = self.model.predict_mean_std(&transformed);
+        if predictive {
+            let noise = self.model.noise_variance(&transformed);
+            std = std
+                .iter()
+                .zip(noise)
+                .map(|(std, noise)| (std * std + noise).sqrt())
+                .collect();
+        }
+        self.transform.inverse_predictions(x, &mean, &std)
+    }
+
+    fn line_name(&self) -> String {
+        if self.name.is_empty() {
+            "Mean".to_owned()
+        } else {
+            format!("{} mean", self.name)
+        }
+    }
+}
+
+impl eframe::App for App {
+    /// Called by the frame work to save state before shutdown.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
+    }
+
+    /// Called each time the UI needs repainting, which may be many times per second.
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Put your widgets into a `SidePanel`, `TopBottomPanel`, `CentralPanel`, `Window` or `Area`.
+        // For inspiration and more examples, go to https://emilk.github.io/egui
+
+        let mut selected_preset = None;
+        let mut profile_action = None;
+        let mut start_tutorial = false;
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            // The top panel is often a good place for a menu bar:
+
+            egui::menu::bar(ui, |ui| {
+                // NOTE: no File->Quit on web pages!
+                let is_web = cfg!(target_arch = "wasm32");
+                if !is_web {
+                    ui.menu_button("File", |ui| {
+                        if ui
+                            .button("Session…")
+                            .on_hover_text("Save the session to a file or open a saved one")
+                            .clicked()
+                        {
+                            self.session.open = true;
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Export to CSV…").clicked() {
+                            self.export.open = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Export image…").clicked() {
+                            self.image_export.open = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Export frames…").clicked() {
+                            self.frame_export.open = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Quit").clicked() {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                    });
+                    ui.add_space(16.0);
+                }
+
+                ui.menu_button("Data", |ui| {
+                    if ui.button("Paste data…").clicked() {
+                        self.paste.open = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Generate data…").clicked() {
+                        self.generate.open = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui
+                        .button("Copy plot data")
+                        .on_hover_text("The lines, bands and points of the plot as JSON")
+                        .clicked()
+                    {
+                        ctx.copy_text(figure::to_json(&self.figure()));
+                        ui.close_menu();
+                    }
+                });
+                ui.add_space(16.0);
+
+                ui.menu_button("Presets", |ui| {
+                    for preset in presets::Preset::ALL {
+                        if ui
+                            .button(preset.name())
+                            .on_hover_text(preset.description())
+                            .clicked()
+                        {
+                            selected_preset = Some(preset);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.add_space(16.0);
+
+                let profiles_title = match self.profiles.current_name() {
+                    Some(name) => format!("Profiles ({name})"),
+                    None => "Profiles".to_owned(),
+                };
+                ui.menu_button(profiles_title, |ui| {
+                    profile_action = self.profiles.menu(ui);
+                });
+                ui.add_space(16.0);
+
+                ui.menu_button("View", |ui| {
+                    ui.checkbox(&mut self.show_kernel_inspector, "Kernel inspector");
+                    ui.checkbox(&mut self.show_covariance_matrix, "Covariance matrix");
+                    ui.checkbox(&mut self.show_spectrum, "Eigenvalue spectrum");
+                    ui.checkbox(&mut self.show_residuals, "Residuals");
+                    ui.checkbox(&mut self.show_leave_one_out, "Leave-one-out");
+                    ui.checkbox(&mut self.show_calibration, "Calibration");
+                    ui.checkbox(&mut self.holdout.open, "Train/test evaluation");
+                    ui.checkbox(&mut self.show_landscape, "Likelihood landscape");
+                    ui.checkbox(
+                        &mut self.hyperparameter_posterior.open,
+                        "Hyperparameter posterior",
+                    );
+                    ui.checkbox(&mut self.model_comparison.open, "Model comparison");
+                    ui.checkbox(&mut self.show_stream, "Live stream");
+                    ui.checkbox(&mut self.show_style, "Plot style");
+                    ui.menu_button("Plot elements", |ui| self.layers.show(ui));
+                    ui.menu_button("Baselines", |ui| self.baselines.show(ui));
+                    ui.checkbox(&mut self.show_stats, "Performance stats");
+                    ui.separator();
+                    ui.checkbox(&mut self.presentation, "Presentation mode (P)")
+                        .on_hover_text(
+                            "Larger text and plots with more contrast for projectors, without \
+                             the advanced controls",
+                        );
+                });
+                ui.add_space(16.0);
+
+                ui.menu_button("Help", |ui| {
+                    if ui
+                        .button("Tutorial")
+                        .on_hover_text("A guided tour of Gaussian process regression")
+                        .clicked()
+                    {
+                        start_tutorial = true;
+                        ui.close_menu();
+                    }
+                });
+                ui.add_space(16.0);
+
+                egui::widgets::global_theme_preference_buttons(ui);
+            });
+        });
+
+        self.export.show(ctx, &self.datasets, &prediction_grid());
+        if self.image_export.show(ctx) {
+            let figure = self.figure();
+            self.image_export.export(&figure);
+        }
+
+        // pasting outside of any text field opens the paste dialog with the pasted text
+        if ctx.memory(|memory| memory.focused().is_none()) {
+            let pasted = ctx.input(|input| {
+                input.events.iter().find_map(|event| match event {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+            });
+            if let Some(text) = pasted {
+                self.paste.open_with(text);
+            }
+        }
+        egui::Window::new("Live stream")
+            .open(&mut self.show_stream)
+            .resizable(false)
+            .show(ctx, |ui| self.stream.show(ui));
+        self.receive_stream();
+        if ctx.input(|input| input.any_touches()) {
+            self.touch = true;
+        }
+
+        egui::Window::new("Plot style")
+            .open(&mut self.show_style)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if let Some(palette) = self.style.show(ui) {
+                    for (i, dataset) in self.datasets.iter_mut().enumerate() {
+                        dataset.color = palette[i % palette.len()];
+                    }
+                }
+                ui.separator();
+                ui.strong("Labels");
+                self.labels.show(ui);
+            });
+
+        let mut changed = self
+            .paste
+            .show(ctx, &mut self.datasets, &mut self.active_dataset);
+        match self.session.show(ctx) {
+            Some(session::Action::Save) => {
+                let mut dialog = std::mem::take(&mut self.session);
+                dialog.save(self);
+                self.session = dialog;
+            }
+            Some(session::Action::Open) => {
+                if let Some(mut app) = self.session.load() {
+                    // keep the dialog open to show that the session was opened
+                    app.session = std::mem::take(&mut self.session);
+                    app.ensure_dataset();
+                    *self = app;
+                    changed = true;
+                }
+            }
+            None => {}
+        }
+        match profile_action.or_else(|| self.profiles.keys(ctx)) {
+            Some(profiles::ProfileAction::Save(name)) => {
+                // taken out so the profiles are not saved within themselves
+                let mut profiles = std::mem::take(&mut self.profiles);
+                profiles.save(self, name, None);
+                self.profiles = profiles;
+            }
+            Some(profiles::ProfileAction::Update(index)) => {
+                let mut profiles = std::mem::take(&mut self.profiles);
+                profiles.save(self, String::new(), Some(index));
+                self.profiles = profiles;
+            }
+            Some(profiles::ProfileAction::Switch(index)) => {
+                if let Some(mut app) = self.profiles.load(index) {
+                    app.profiles = std::mem::take(&mut self.profiles);
+                    app.session = std::mem::take(&mut self.session);
+                    app.ensure_dataset();
+                    *self = app;
+                    changed = true;
+                }
+            }
+            None => {}
+        }
+        if self
+            .generate
+            .show(ctx, &self.kernel, &mut self.datasets[self.active_dataset])
+        {
+            changed = true;
+        }
+        let exporting_frames = self.frame_export.is_running();
+        match self.frame_export.show(ctx, &self.parameter_names()) {
+            Some(frames::FrameRequest::Start(animation, count, parameter)) => {
+                let samples = if animation == frames::Animation::Samples {
+                    // the samples are only drawn with the prior
+                    self.show_prior = true;
+                    self.layers.samples = true;
+                    self.num_prior_samples = self.num_prior_samples.max(1);
+                    let mut rng = rand::rngs::SmallRng::seed_from_u64(self.sample_seed);
+                    let n = self.num_prior_samples;
+                    self.sample_prior_loop(&prediction_grid(), n, count, &mut rng)
+                } else {
+                    Vec::new()
+                };
+                let value = *self.parameter_mut(parameter);
+                self.frame_export.start(value, samples);
+            }
+            Some(frames::FrameRequest::Restore(parameter, value)) => {
+                *self.parameter_mut(parameter) = value;
+                changed = true;
+            }
+            None => {}
+        }
+        if exporting_frames && !self.frame_export.is_running() {
+            // cancelled, so draw the usual samples again
+            self.prior_samples.clear();
+        }
+        if let Some((parameter, value)) = self.frame_export.parameter() {
+            *self.parameter_mut(parameter) = value;
+            changed = true;
+        }
+        if let Some(preset) = selected_preset {
+            let data = preset.load();
+            self.datasets[self.active_dataset].set_points(data.x, data.y);
+            self.kernel = data.kernel;
+            self.noise_sigma = data.noise_sigma;
+            changed = true;
+        }
+        if start_tutorial {
+            tutorial::start(self);
+            changed = true;
+        }
+        if tutorial::show(ctx, self) {
+            changed = true;
+        }
+        if changed {
+            self.highlighted_point = None;
+            self.selection.clear();
+        }
+
+        let mut kernels = vec![&self.kernel];
+        if self.compare_kernels {
+            kernels.push(&self.comparison_kernel);
+        }
+        detach::auxiliary_window(
+            ctx,
+            "Kernel inspector",
+            [300.0, 200.0],
+            &mut self.show_kernel_inspector,
+            &mut self.detached,
+            |ui| kernel_inspector(ui, &kernels),
+        );
+
+        detach::auxiliary_window(
+            ctx,
+            "Covariance matrix",
+            [300.0, 300.0],
+            &mut self.show_covariance_matrix,
+            &mut self.detached,
+            |ui| {
+                ui.checkbox(&mut self.show_cholesky_factor, "Show Cholesky factor");
+                let dataset = &self.datasets[self.active_dataset];
+                let Some(gp) = &dataset.gp else {
+                    return;
+                };
+                if dataset.x.is_empty() {
+                    ui.label("No training data.");
+                    return;
+                }
+
+                let covariance = gp.covariance_matrix();
+                if self.show_cholesky_factor {
+                    match na::Cholesky::new(covariance) {
+                        Some(cholesky) => {
+                            heatmap::heatmap(ui, &cholesky.l());
+                        }
+                        None => {
+                            ui.label("The covariance matrix is not positive definite.");
+                        }
+                    }
+                } else {
+                    heatmap::heatmap(ui, &covariance);
+                }
+            },
+        );
+
+        detach::auxiliary_window(
+            ctx,
+            "Eigenvalue spectrum",
+            [300.0, 250.0],
+            &mut self.show_spectrum,
+            &mut self.detached,
+            |ui| {
+                if let Some(gp) = &self.datasets[self.active_dataset].gp {
+                    diagnostics::spectrum_panel(ui, gp);
+                }
+            },
+        );
 
-impl App {
-    /// Called once before the first frame.
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // This is also where you can customize the look and feel of egui using
-        // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
+        detach::auxiliary_window(
+            ctx,
+            "Residuals",
+            [300.0, 400.0],
+            &mut self.show_residuals,
+            &mut self.detached,
+            |ui| {
+                let dataset = &self.datasets[self.active_dataset];
+                if let Some(gp) = &dataset.gp {
+                    diagnostics::residuals_panel(
+                        ui,
+                        &dataset.training_points().0,
+                        gp,
+                        &mut self.standardize_residuals,
+                    );
+                }
+            },
+        );
 
-        // Load previous app state (if any).
-        // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        detach::auxiliary_window(
+            ctx,
+            "Leave-one-out",
+            [300.0, 300.0],
+            &mut self.show_leave_one_out,
+            &mut self.detached,
+            |ui| {
+                let dataset = &self.datasets[self.active_dataset];
+                if let Some(gp) = &dataset.gp {
+                    // the fit only knows the training points, which the highlight is mapped to
+                    let (x, y) = dataset.training_points();
+                    let training: Vec<usize> = (0..dataset.x.len())
+                        .filter(|i| !dataset.is_held_out(*i))
+                        .collect();
+                    let mut highlighted = self
+                        .highlighted_point
+                        .and_then(|i| training.iter().position(|j| *j == i));
+                    let before = highlighted;
+                    diagnostics::leave_one_out_panel(
+                        ui,
+                        &x,
+                        &y,
+                        gp,
+                        &mut self.loo_threshold,
+                        &mut highlighted,
+                    );
+                    if highlighted != before {
+                        self.highlighted_point = highlighted.map(|i| training[i]);
+                    }
+                }
+            },
+        );
+
+        detach::auxiliary_window(
+            ctx,
+            "Calibration",
+            [300.0, 300.0],
+            &mut self.show_calibration,
+            &mut self.detached,
+            |ui| {
+                let dataset = &self.datasets[self.active_dataset];
+                if let Some(gp) = &dataset.gp {
+                    diagnostics::calibration_panel(ui, &dataset.training_points().1, gp);
+                }
+            },
+        );
+
+        if ctx.memory(|memory| memory.focused().is_none())
+            && ctx.input(|input| input.modifiers.is_none() && input.key_pressed(egui::Key::P))
+        {
+            self.presentation = !self.presentation;
+        }
+        if self.presentation_applied != Some(self.presentation) {
+            set_presentation_style(ctx, self.presentation);
+            self.presentation_applied = Some(self.presentation);
         }
 
-        Default::default()
-    }
-}
+        if self.show_stats && !self.presentation && self.mode == Mode::Regression {
+            self.stats.show(ctx);
+        }
 
-impl eframe::App for App {
-    /// Called by the frame work to save state before shutdown.
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
-    }
+        if self
+            .holdout
+            .show(ctx, &mut self.datasets[self.active_dataset], self.model)
+        {
+            changed = true;
+        }
 
-    /// Called each time the UI needs repainting, which may be many times per second.
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Put your widgets into a `SidePanel`, `TopBottomPanel`, `CentralPanel`, `Window` or `Area`.
-        // For inspiration and more examples, go to https://emilk.github.io/egui
+        if self.hyperparameter_posterior.show(
+            ctx,
+            &self.datasets[self.active_dataset],
+            self.jitter,
+            &self.kernel,
+            self.noise_sigma,
+        ) {
+            changed = true;
+        }
 
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            // The top panel is often a good place for a menu bar:
+        if let Some((kernel, noise)) = self.model_comparison.show(
+            ctx,
+            &self.datasets[self.active_dataset],
+            self.jitter,
+            self.noise_sigma,
+            &mut self.slider_ranges,
+        ) {
+            self.kernel = kernel;
+            self.noise_sigma = noise;
+            changed = true;
+        }
 
-            egui::menu::bar(ui, |ui| {
-                // NOTE: no File->Quit on web pages!
-                let is_web = cfg!(target_arch = "wasm32");
-                if !is_web {
-                    ui.menu_button("File", |ui| {
-                        if ui.button("Quit").clicked() {
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                        }
-                    });
-                    ui.add_space(16.0);
-                }
+        if let Some((kernel, noise)) = self.optimization.show(ctx) {
+            self.kernel = kernel;
+            self.noise_sigma = noise;
+            changed = true;
+        }
 
-                egui::widgets::global_theme_preference_buttons(ui);
+        let mut selected_hyperparameters = None;
+        egui::Window::new("Log marginal likelihood")
+            .open(&mut self.show_landscape)
+            .default_size([300.0, 350.0])
+            .show(ctx, |ui| {
+                let mut kernel = self.kernel.clone();
+                selected_hyperparameters = self.landscape.show(
+                    ui,
+                    &self.datasets[self.active_dataset],
+                    self.jitter,
+                    &self.kernel,
+                    [
+                        *kernel_ui::length_scale_mut(&mut kernel),
+                        *kernel_ui::sigma_mut(&mut kernel),
+                        self.noise_sigma,
+                    ],
+                );
             });
-        });
+        if let Some([length_scale, sigma, noise]) = selected_hyperparameters {
+            *kernel_ui::length_scale_mut(&mut self.kernel) = length_scale;
+            *kernel_ui::sigma_mut(&mut self.kernel) = sigma;
+            self.noise_sigma = noise;
+            changed = true;
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
             // The central panel the region left after adding TopPanel's and SidePanel's
             ui.heading("Gaussian Processes");
 
+            ui.horizontal(|ui| {
+                ui.label("Mode:");
+                for mode in Mode::ALL {
+                    // the kernel may have been changed in the other mode
+                    if ui
+                        .selectable_value(&mut self.mode, mode, mode.name())
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                }
+            });
+            if self.mode != Mode::Regression {
+                ui.label("Kernel parameters:");
+                if kernel_ui::kernel_controls(
+                    ui,
+                    "kernel",
+                    &mut self.kernel,
+                    &mut self.slider_ranges,
+                ) {
+                    changed = true;
+                }
+                // classification has no observation noise
+                if self.mode != Mode::Classification
+                    && self
+                        .slider_ranges
+                        .slider(
+                            ui,
+                            &mut self.noise_sigma,
+                            "noise σ",
+                            NOISE_SIGMA_RANGE,
+                            "Noise σ",
+                        )
+                        .changed()
+                {
+                    changed = true;
+                }
+                match self.mode {
+                    Mode::Plane => self.plane.show(ui, &self.kernel, self.noise_sigma, changed),
+                    Mode::Classification => self.classification.show(ui, &self.kernel, changed),
+                    Mode::Forecast => {
+                        self.forecast
+                            .show(ui, &self.kernel, self.noise_sigma, changed)
+                    }
+                    Mode::Game => self.game.show(ui, &self.kernel, self.noise_sigma, changed),
+                    Mode::Regression => {}
+                }
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Model:");
+                for model in Model::ALL {
+                    if ui
+                        .selectable_value(&mut self.model, model, model.name())
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                }
+            });
+            if self.model == Model::BayesianLinearRegression
+                && ui
+                    .add(
+                        Slider::new(&mut self.weight_variance, 0.01..=100.0)
+                            .logarithmic(true)
+                            .text("Weight variance"),
+                    )
+                    .on_hover_text("The prior variance of the intercept and slope")
+                    .changed()
+            {
+                changed = true;
+            }
+            if self.model == Model::CircularGaussianProcess
+                && ui
+                    .add(Slider::new(&mut self.circular_period, 1.0..=10.0).text("Period"))
+                    .on_hover_text("The length of the domain, after which the inputs wrap around")
+                    .changed()
+            {
+                for dataset in &mut self.datasets {
+                    for x in &mut dataset.x {
+                        *x = x.rem_euclid(self.circular_period);
+                    }
+                }
+                changed = true;
+            }
+            if self.model == Model::StudentTGaussianProcess
+                && ui
+                    .add(
+                        Slider::new(&mut self.degrees_of_freedom, 3.0..=30.0)
+                            .logarithmic(true)
+                            .text("Degrees of freedom"),
+                    )
+                    .on_hover_text(
+                        "Fewer degrees of freedom give the noise heavier tails, so outliers \
+                         pull less on the fit. Many approach a Gaussian process.",
+                    )
+                    .changed()
+            {
+                changed = true;
+            }
+
             ui.label("Kernel parameters:");
-            let mut changed = false;
             if ui
-                .add(
-                    Slider::new(&mut self.kernel_length_scale, 0.0..=10.0)
-                        .text("Kernel length scale"),
-                )
+                .checkbox(&mut self.compare_kernels, "Compare with a second kernel")
                 .changed()
             {
                 changed = true;
             }
-            if ui
-                .add(Slider::new(&mut self.kernel_sigma, 0.0..=10.0).text("Kernel sigma"))
+            if self.compare_kernels {
+                ui.columns(2, |columns| {
+                    if kernel_ui::kernel_controls(
+                        &mut columns[0],
+                        "kernel",
+                        &mut self.kernel,
+                        &mut self.slider_ranges,
+                    ) {
+                        changed = true;
+                    }
+                    log_marginal_likelihood_label(
+                        &mut columns[0],
+                        self.datasets[self.active_dataset].model(self.model),
+                    );
+                    effective_degrees_of_freedom_label(&mut columns[0], self.fit_gp());
+                    if kernel_ui::kernel_controls(
+                        &mut columns[1],
+                        "comparison_kernel",
+                        &mut self.comparison_kernel,
+                        &mut self.slider_ranges,
+                    ) {
+                        changed = true;
+                    }
+                    log_marginal_likelihood_label(
+                        &mut columns[1],
+                        self.comparison_gp
+                            .as_ref()
+                            .map(|gp| gp as &dyn RegressionModel),
+                    );
+                    effective_degrees_of_freedom_label(
+                        &mut columns[1],
+                        self.comparison_gp.as_ref(),
+                    );
+                });
+                ui.checkbox(&mut self.split_comparison, "Show in separate plots");
+            } else {
+                if kernel_ui::kernel_controls(
+                    ui,
+                    "kernel",
+                    &mut self.kernel,
+                    &mut self.slider_ranges,
+                ) {
+                    changed = true;
+                }
+                log_marginal_likelihood_label(
+                    ui,
+                    self.datasets[self.active_dataset].model(self.model),
+                );
+                effective_degrees_of_freedom_label(ui, self.fit_gp());
+            }
+            if self
+                .slider_ranges
+                .slider(
+                    ui,
+                    &mut self.noise_sigma,
+                    "noise σ",
+                    NOISE_SIGMA_RANGE,
+                    "Noise σ",
+                )
                 .changed()
             {
                 changed = true;
             }
+            if let Some(noise) = self.datasets[self.active_dataset].replicate_noise() {
+                if ui
+                    .button(format!(
+                        "Noise from replicates: {:.4}",
+                        noise.variance.sqrt()
+                    ))
+                    .on_hover_text(format!(
+                        "Set the noise to the pooled standard deviation of the y values at the \
+                         same x ({} repeated x values, {} degrees of freedom)",
+                        noise.groups, noise.degrees_of_freedom
+                    ))
+                    .clicked()
+                {
+                    self.noise_sigma = noise.variance.sqrt();
+                    changed = true;
+                }
+            }
+            // the advanced controls are hidden while presenting
+            if !self.presentation {
+                ui.collapsing("Advanced", |ui| {
+                    if ui
+                        .add(
+                            Slider::new(&mut self.jitter, 1e-12..=1e-2)
+                                .logarithmic(true)
+                                .text("Jitter"),
+                        )
+                        .on_hover_text(
+                            "A small variance added to the diagonal of the covariance matrix so \
+                             it can be inverted. Unlike the noise it is a numerical fix, not part \
+                             of the model.",
+                        )
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                });
+                self.optimization.config_ui(ui);
+            }
             if ui
-                .add(Slider::new(&mut self.noise_sigma, 0.0..=10.0).text("Noise sigma"))
-                .changed()
+                .add_enabled(
+                    !self.optimization.is_running(),
+                    egui::Button::new("Optimize hyperparameters"),
+                )
+                .on_hover_text(
+                    "Maximize the log marginal likelihood of the active dataset. After editing the \
+                     points this continues from the last optimum, which is much faster.",
+                )
+                .clicked()
             {
-                changed = true;
+                let (x, y) = self.dataset().training_points();
+                let weights = self.dataset().training_weights();
+                self.optimization.start(
+                    &x,
+                    &y,
+                    &weights,
+                    self.jitter,
+                    &self.kernel,
+                    self.noise_sigma,
+                );
             }
-
-            ui.label("Click anywhere to add points, click on points to remove them.");
-            if ui.button("Clear all Points").clicked() {
-                self.x.clear();
-                self.y.clear();
+            if ui
+                .button("Guess from data")
+                .on_hover_text(
+                    "Set the length scales to the median distance between the points, the signal \
+                     variance to the variance of the values and the noise to the scatter of each \
+                     point around its neighbors, as a starting point",
+                )
+                .clicked()
+            {
+                let (x, y) = self.dataset().training_points();
+                let initial = init_heuristics(&x, &y);
+                self.kernel.init_from(&initial);
+                self.noise_sigma = initial.noise_sigma;
                 changed = true;
             }
-            ui.label("Ctrl-Scroll to zoom, Scroll and Shift-scroll to pan.");
-
-            if let Some(gp) = &self.gp {
-                // linearly spaced points from 0 to 10
-                let prediction_x = (0..=100)
-                    .map(|i| i as f64 / 100.0 * 10.0)
-                    .collect::<Vec<f64>>();
+            if !self.presentation
+                && ui
+                    .add_enabled(
+                        self.kernel.param_names().contains(&"period"),
+                        egui::Button::new("Guess period"),
+                    )
+                    .on_hover_text(
+                        "Set the periods of the periodic kernels to the strongest periods in the \
+                         active dataset (Lomb-Scargle periodogram), before optimizing",
+                    )
+                    .clicked()
+            {
+                let (x, y) = self.dataset().training_points();
+                if self.kernel.init_periods(&x, &y) {
+                    changed = true;
+                }
+            }
 
-                let (means, variances) = gp.predict(&na::DVector::from_vec(prediction_x.clone()));
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut self.show_prior, "Show prior").changed() {
+                    changed = true;
+                }
+                if ui
+                    .add(Slider::new(&mut self.num_prior_samples, 0..=10).text("Prior samples"))
+                    .changed()
+                {
+                    changed = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Reroll samples")
+                    .on_hover_text("Draw new samples with the next seed")
+                    .clicked()
+                {
+                    self.sample_seed = self.sample_seed.wrapping_add(1);
+                    changed = true;
+                }
+                if ui
+                    .add(egui::DragValue::new(&mut self.sample_seed).prefix("seed "))
+                    .on_hover_text(
+                        "The seed of the random samples: the same seed draws the same samples \
+                         again, e.g. in a later session",
+                    )
+                    .changed()
+                {
+                    changed = true;
+                }
+            });
+            ui.checkbox(&mut self.predictive_band, "Include noise in band")
+                .on_hover_text(
+                    "Show where new observations are expected to fall instead of the \
+                     uncertainty of the underlying function",
+                );
+            ui.checkbox(&mut self.show_extrapolation, "Shade extrapolation")
+                .on_hover_text("Tint the regions beyond the training data");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.show_suggestion, "Suggest next sample")
+                    .on_hover_text("Mark where new data would be most valuable");
+                egui::ComboBox::from_id_salt("suggestion_criterion")
+                    .selected_text(self.suggestion_criterion.name())
+                    .show_ui(ui, |ui| {
+                        for criterion in [
+                            ActiveLearningCriterion::MaxVariance,
+                            ActiveLearningCriterion::MaxInformationGain,
+                        ] {
+                            ui.selectable_value(
+                                &mut self.suggestion_criterion,
+                                criterion,
+                                criterion.name(),
+                            );
+                        }
+                    });
+            });
 
-                let mean_points: egui_plot::PlotPoints = means
-                    .iter()
-                    .zip(prediction_x.iter())
-                    .map(|(y, x)| [*x, *y])
-                    .collect();
-                let mean_line = egui_plot::Line::new(mean_points).color(egui::Color32::RED);
+            egui::CollapsingHeader::new("Datasets").show(ui, |ui| {
+                let active = self.active_dataset;
+                if dataset::datasets_panel(ui, &mut self.datasets, &mut self.active_dataset) {
+                    changed = true;
+                }
+                if active != self.active_dataset {
+                    self.highlighted_point = None;
+                    changed = true;
+                }
+                if changed {
+                    self.selection.clear();
+                }
+            });
 
-                // egui_plot does not support filling non-convex polygons, so we fallback to
-                // drawing some lines to represent the variance instead.
+            if !self.presentation {
+                egui::CollapsingHeader::new("Transformations").show(ui, |ui| {
+                    if dataset::transform_panel(ui, &mut self.datasets[self.active_dataset]) {
+                        changed = true;
+                    }
+                });
 
-                // lower variance points
-                let variance_points = variances
-                    .iter()
-                    .zip(means.iter())
-                    .zip(prediction_x.iter())
-                    .map(|((sigma, mean), x)| [*x, (*mean - *sigma)])
-                    .collect::<Vec<[f64; 2]>>();
-                let lower_variance_line =
-                    Line::new(variance_points).color(egui::Color32::LIGHT_BLUE);
+                if self.touch {
+                    ui.label(
+                        "Tap to add points, drag points to move them, long-press to remove them.",
+                    );
+                } else {
+                    ui.label(
+                        "Click anywhere to add points, drag points to move them, right-click \
+                         points to edit or remove them. Alt-drag points to change only their \
+                         value.",
+                    );
+                }
+                ui.label("Shift-drag to select points, and shift-drag the selection to move it.");
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Clear all Points").clicked() {
+                    self.datasets[self.active_dataset].clear_points();
+                    self.highlighted_point = None;
+                    self.selection.clear();
+                    changed = true;
+                }
+                let delete_pressed = !self.selection.is_empty()
+                    && ui.ctx().memory(|memory| memory.focused().is_none())
+                    && ui.input(|input| {
+                        input.key_pressed(egui::Key::Delete)
+                            || input.key_pressed(egui::Key::Backspace)
+                    });
+                if !self.selection.is_empty()
+                    && (ui
+                        .button(format!("Delete {} selected", self.selection.points.len()))
+                        .on_hover_text("Or press Delete")
+                        .clicked()
+                        || delete_pressed)
+                {
+                    self.selection.delete(&mut self.datasets);
+                    self.highlighted_point = None;
+                    changed = true;
+                }
+                if ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+                    self.selection.clear();
+                }
+            });
+            if !self.presentation {
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(!self.selection.is_empty(), |ui| {
+                        ui.label("Label:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.label_text)
+                                .hint_text("e.g. outlier")
+                                .desired_width(120.0),
+                        );
+                        if ui
+                            .button("Set for selected")
+                            .on_hover_text("Attach the label to the selected points, or clear it")
+                            .clicked()
+                        {
+                            for (dataset, index) in &self.selection.points {
+                                if let Some(dataset) = self.datasets.get_mut(*dataset) {
+                                    dataset.set_label(*index, &self.label_text);
+                                }
+                            }
+                        }
+                    });
+                    ui.checkbox(&mut self.show_labels, "Always show labels");
+                });
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Freeze current fit")
+                    .on_hover_text("Keep the current fit as an overlay for comparison")
+                    .clicked()
+                {
+                    if let Some(gp) = &self.dataset().gp {
+                        let x = prediction_grid();
+                        let transform = self.dataset().transform();
+                        let transformed = x.iter().map(|x| transform.input(*x)).collect();
+                        let (mean, variance) = gp.predict(&na::DVector::from_vec(transformed));
+                        let snapshot = Snapshot::new(
+                            format!("Snapshot {}", self.snapshots.len() + 1),
+                            x,
+                            &transform,
+                            mean.as_slice(),
+                            variance.as_slice(),
+                        );
+                        self.snapshots.push(snapshot);
+                    }
+                }
+                if !self.snapshots.is_empty() && ui.button("Clear snapshots").clicked() {
+                    self.snapshots.clear();
+                }
+            });
+            ui.horizontal(|ui| {
+                if self.touch {
+                    ui.label("Pinch to zoom, drag to pan.");
+                } else {
+                    ui.label("Ctrl-Scroll to zoom, Scroll and Shift-scroll to pan.");
+                }
+                ui.checkbox(&mut self.brush, "Weight brush").on_hover_text(
+                    "Scroll over a training point to change its weight, which divides its noise \
+                     variance: heavier points are trusted more and drawn larger. Only the \
+                     Gaussian process fits use the weights.",
+                );
+            });
 
-                // upper variance points
-                let variance_points = variances
-                    .iter()
-                    .zip(means.iter())
-                    .zip(prediction_x.iter())
-                    .map(|((sigma, mean), x)| [*x, (*mean + *sigma)])
-                    .collect::<Vec<[f64; 2]>>();
-                let upper_variance_line =
-                    Line::new(variance_points).color(egui::Color32::LIGHT_BLUE);
+            if !self.labels.title.is_empty() {
+                ui.vertical_centered(|ui| ui.heading(&self.labels.title));
+            }
+            let groups = self.posterior_groups();
+            let mut interaction = None;
+            let mut long_touched_point = None;
+            let mut drag = None;
+            let mut brushed = None;
+            let mut plot_bounds = None;
+            let mut plot_responses = Vec::new();
+            let mut menu_target = None;
+            let mut predict_time = web_time::Duration::ZERO;
+            let mut grid_size = 0;
+            ui.columns(groups.len(), |columns| {
+                for (i, (ui, group)) in columns.iter_mut().zip(groups).enumerate() {
+                    let PlotResponse {
+                        inner: input,
+                        response,
+                        hovered_plot_item,
+                        transform,
+                    } = self.show_plot(ui, &format!("plot_{i}"), &group);
+                    predict_time += input.predict_time;
+                    grid_size = input.grid_size;
+                    if input.clicked {
+                        interaction = Some((input.pointer, hovered_plot_item));
+                    }
+                    if input.secondary_clicked {
+                        let point = input
+                            .hovered_point
+                            .map(|(dataset, index)| point_menu::MenuTarget::Point(dataset, index));
+                        let empty = input
+                            .pointer
+                            .map(|pointer| point_menu::MenuTarget::Empty([pointer.x, pointer.y]));
+                        menu_target = point.or(empty);
+                    }
+                    plot_responses.push(response);
+                    if input.long_touched {
+                        long_touched_point = long_touched_point.or(input.hovered_point);
+                    }
+                    if input.scroll != 0.0 {
+                        brushed = input.hovered_point.map(|point| (point, input.scroll));
+                    }
+                    if input.drag_started || input.dragged || input.drag_stopped {
+                        drag = Some(input);
+                    }
+                    plot_bounds.get_or_insert(*transform.bounds());
+                }
+            });
+            self.stats.predict_time = predict_time;
+            self.stats.grid_size = grid_size;
+            self.plot_bounds = plot_bounds;
 
-                // the points the GP was trained on
-                let points: egui_plot::PlotPoints = self
-                    .x
-                    .iter()
-                    .zip(self.y.iter())
-                    .map(|(x, y)| [*x, *y])
-                    .collect();
-                let points = egui_plot::Points::new(points)
-                    .color(egui::Color32::LIGHT_GREEN)
-                    .radius(5.0)
-                    .shape(egui_plot::MarkerShape::Circle)
-                    .id(egui::Id::new("training_points"));
-
-                let PlotResponse {
-                    response: _,
-                    inner: (pointer_coordinate, clicked),
-                    hovered_plot_item,
-                    ..
-                } = egui_plot::Plot::new("plot").show(ui, |pui| {
-                    pui.line(lower_variance_line.name("Mean - Variance"));
-                    pui.line(upper_variance_line.name("Mean + Variance"));
-                    pui.line(mean_line.name("Mean"));
-                    pui.points(points.name("Training points"));
-                    (pui.pointer_coordinate(), pui.response().clicked())
+            if menu_target.is_some() {
+                self.menu_target = menu_target;
+            }
+            for response in plot_responses {
+                response.context_menu(|ui| match self.menu_target {
+                    Some(point_menu::MenuTarget::Point(dataset, index)) => {
+                        let Some(dataset) = self.datasets.get_mut(dataset) else {
+                            ui.close_menu();
+                            return;
+                        };
+                        match point_menu::point_menu(ui, dataset, index, self.noise_sigma) {
+                            Some(point_menu::PointEdit::Changed) => changed = true,
+                            Some(point_menu::PointEdit::Deleted) => {
+                                self.highlighted_point = None;
+                                self.selection.clear();
+                                changed = true;
+                            }
+                            None => {}
+                        }
+                    }
+                    Some(point_menu::MenuTarget::Empty([x, y])) => {
+                        if ui.button("Add point here").clicked() {
+                            let x = self.wrap_input(x);
+                            let dataset = &mut self.datasets[self.active_dataset];
+                            dataset.x.push(x);
+                            dataset.y.push(y);
+                            changed = true;
+                            ui.close_menu();
+                        }
+                    }
+                    None => ui.close_menu(),
                 });
+            }
+
+            if let Some(((dataset, index), scroll)) = brushed {
+                let dataset = &mut self.datasets[dataset];
+                let weight = dataset.weight(index) * (BRUSH_RATE * scroll as f64).exp();
+                dataset.set_weight(index, weight.clamp(WEIGHT_RANGE.0, WEIGHT_RANGE.1));
+                changed = true;
+            }
+
+            if let Some((dataset, index)) = long_touched_point {
+                self.datasets[dataset].remove_point(index);
+                self.highlighted_point = None;
+                self.selection.clear();
+                changed = true;
+            }
 
-                if clicked {
-                    if let (Some(hovered_plot_item), Some(pos)) =
-                        (hovered_plot_item, pointer_coordinate)
+            if let Some(input) = drag {
+                if input.drag_started && !input.selecting {
+                    self.dragged_point = input.pressed_point;
+                }
+                if let Some((dataset, index)) = self.dragged_point {
+                    let pointer = input
+                        .pointer
+                        .filter(|_| input.dragged)
+                        .map(|pointer| [self.wrap_input(pointer.x), pointer.y]);
+                    if let (Some([x, y]), Some(dataset)) = (pointer, self.datasets.get_mut(dataset))
                     {
-                        if hovered_plot_item == egui::Id::new("training_points") {
-                            // find the index of the point that was clicked
-
-                            if let Some((index, _)) = self
-                                .x
-                                .iter()
-                                .zip(self.y.iter())
-                                .map(|(x, y)| (*x - pos.x).powf(2.0) + (*y - pos.y).powf(2.0))
-                                .enumerate()
-                                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-                            {
-                                self.x.remove(index);
-                                self.y.remove(index);
-                                changed = true;
+                        if index < dataset.x.len() {
+                            // changing only the observed value keeps the input where it was
+                            if !input.vertical_only {
+                                dataset.x[index] = x;
                             }
+                            dataset.y[index] = y;
+                            changed = true;
+                        }
+                    }
+                }
+                if input.drag_stopped {
+                    self.dragged_point = None;
+                }
+                if input.drag_started && input.selecting {
+                    self.selection.drag = if input.on_selection {
+                        Some(selection::SelectionDrag::Move)
+                    } else {
+                        input
+                            .pointer
+                            .map(|start| selection::SelectionDrag::Box { start, end: start })
+                    };
+                }
+                match &mut self.selection.drag {
+                    Some(selection::SelectionDrag::Box { end, .. }) => {
+                        if let Some(pointer) = input.pointer {
+                            *end = pointer;
                         }
-                    } else if let Some(pointer_coordinate) = pointer_coordinate {
-                        self.x.push(pointer_coordinate.x);
-                        self.y.push(pointer_coordinate.y);
-                        changed = true;
+                    }
+                    Some(selection::SelectionDrag::Move) => {
+                        if input.dragged && input.drag_delta != egui::Vec2::ZERO {
+                            self.selection.move_by(
+                                &mut self.datasets,
+                                input.drag_delta.x as f64,
+                                input.drag_delta.y as f64,
+                            );
+                            changed = true;
+                        }
+                    }
+                    None => {}
+                }
+                if input.drag_stopped {
+                    if let Some(selection::SelectionDrag::Box { start, end }) =
+                        self.selection.drag.take()
+                    {
+                        self.selection.select_box(&self.datasets, start, end);
                     }
                 }
             }
 
-            if changed || self.gp.is_none() {
-                self.gp = Some(crate::gp::GaussianProcess::new(
-                    &na::DVector::from_vec(self.x.clone()),
-                    &na::DVector::from_vec(self.y.clone()),
-                    RbfKernel {
-                        sigma: self.kernel_sigma,
-                        length_scale: self.kernel_length_scale,
-                    },
-                    self.noise_sigma,
-                ));
+            // clicking on a plot item adds nothing; points are removed from their context menu,
+            // as clicking them to remove them easily happens by accident
+            if let Some((Some(pointer_coordinate), None)) = interaction {
+                let x = self.wrap_input(pointer_coordinate.x);
+                let dataset = &mut self.datasets[self.active_dataset];
+                dataset.x.push(x);
+                dataset.y.push(pointer_coordinate.y);
+                changed = true;
+            }
+
+            // the optimization would overwrite the change with a result for the old model
+            if changed {
+                self.optimization.cancel();
+            }
+
+            if changed || (!self.fit_failed && self.missing_fits()) {
+                let fit_start = web_time::Instant::now();
+                for dataset in &mut self.datasets {
+                    let (x, y) = dataset.training_points();
+                    let weights = na::DVector::from_vec(dataset.training_weights());
+                    dataset.gp = GaussianProcess::builder()
+                        .kernel(self.kernel.clone())
+                        .noise(self.noise_sigma)
+                        .jitter(self.jitter)
+                        .weights(weights.clone())
+                        .build_from_slices(&x, &y)
+                        .ok();
+                    dataset.linear = (self.model == Model::BayesianLinearRegression)
+                        .then(|| {
+                            let mut linear = BayesianLinearRegression::new(
+                                self.weight_variance,
+                                self.noise_sigma,
+                            );
+                            linear.fit(&x, &y).ok()?;
+                            Some(linear)
+                        })
+                        .flatten();
+                    dataset.circular = (self.model == Model::CircularGaussianProcess)
+                        .then(|| {
+                            let kernel = CircularKernel {
+                                kernel: self.kernel.clone(),
+                                period: self.circular_period,
+                            };
+                            GaussianProcess::builder()
+                                .kernel(kernel)
+                                .noise(self.noise_sigma)
+                                .jitter(self.jitter)
+                                .weights(weights.clone())
+                                .build_from_slices(&x, &y)
+                                .ok()
+                        })
+                        .flatten();
+                    dataset.heteroscedastic = (self.model == Model::HeteroscedasticGaussianProcess)
+                        .then(|| {
+                            HeteroscedasticGaussianProcess::new(
+                                &x,
+                                &y,
+                                self.kernel.clone(),
+                                NOISE_KERNEL,
+                                self.noise_sigma,
+                                self.jitter,
+                            )
+                            .ok()
+                        })
+                        .flatten();
+                    dataset.robust = (self.model == Model::StudentTGaussianProcess)
+                        .then(|| {
+                            StudentTGaussianProcess::new(
+                                &x,
+                                &y,
+                                self.kernel.clone(),
+                                self.noise_sigma,
+                                self.degrees_of_freedom,
+                                self.jitter,
+                            )
+                            .ok()
+                        })
+                        .flatten();
+                }
+
+                let (x, y) = self.dataset().training_points();
+                let weights = na::DVector::from_vec(self.dataset().training_weights());
+                self.comparison_gp = self
+                    .compare_kernels
+                    .then(|| {
+                        GaussianProcess::builder()
+                            .kernel(self.comparison_kernel.clone())
+                            .noise(self.noise_sigma)
+                            .jitter(self.jitter)
+                            .weights(weights)
+                            .build_from_slices(&x, &y)
+                            .ok()
+                    })
+                    .flatten();
+                self.averaged_prediction = self
+                    .hyperparameter_posterior
+                    .average
+                    .then(|| {
+                        let x = prediction_grid();
+                        let transform = self.dataset().transform();
+                        let transformed: Vec<f64> = x.iter().map(|x| transform.input(*x)).collect();
+                        let (train_x, train_y) = self.dataset().training_points();
+                        let (mean, variance) = self.hyperparameter_posterior.averaged_prediction(
+                            &train_x,
+                            &train_y,
+                            &self.dataset().training_weights(),
+                            self.jitter,
+                            &transformed,
+                        )?;
+                        Some(Snapshot::new(
+                            "Hyperparameter average".to_owned(),
+                            x,
+                            &transform,
+                            mean.as_slice(),
+                            variance.as_slice(),
+                        ))
+                    })
+                    .flatten();
+                self.stats.fit_time = fit_start.elapsed();
+                self.stats.training_points =
+                    self.datasets.iter().map(|dataset| dataset.x.len()).sum();
+                self.fit_failed = self.missing_fits();
+            }
+
+            // the fits are restored on startup, but the samples are not
+            if changed || self.prior_samples.len() != self.num_prior_samples {
+                // using the same seed every time makes the samples morph smoothly when the
+                // hyperparameters change
+                let mut rng = rand::rngs::SmallRng::seed_from_u64(self.sample_seed);
+                self.prior_samples =
+                    self.sample_prior(&prediction_grid(), self.num_prior_samples, &mut rng);
+            }
+
+            // render the plot as it is now as the next frame of an export in progress
+            if self.frame_export.is_running() {
+                if let Some(samples) = self.frame_export.samples() {
+                    self.prior_samples = samples.clone();
+                }
+                let figure = self.figure();
+                if let Some((parameter, value)) = self.frame_export.save_frame(&figure) {
+                    *self.parameter_mut(parameter) = value;
+                    // refit with the restored value on the next update
+                    for dataset in &mut self.datasets {
+                        dataset.gp = None;
+                    }
+                    self.fit_failed = false;
+                }
+                if !self.frame_export.is_running() {
+                    self.prior_samples.clear();
+                }
+                ui.ctx().request_repaint();
             }
 
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
@@ -224,6 +2762,102 @@ impl eframe::App for App {
     }
 }
 
+/// Plot the kernel functions `k(0, x)` to show how the covariance decays with distance.
+fn kernel_inspector(ui: &mut egui::Ui, kernels: &[&Kernel]) {
+    egui_plot::Plot::new("kernel_plot")
+        .allow_scroll(false)
+        .show(ui, |pui| {
+            for kernel in kernels {
+                let points = (-100..=100)
+                    .map(|i| {
+                        let x = i as f64 / 10.0;
+                        [x, kernel.compute(0.0, x)]
+                    })
+                    .collect::<Vec<[f64; 2]>>();
+                pui.line(Line::new(points).name(format!("{}: k(0, x)", kernel.description())));
+            }
+        });
+}
+
+fn log_marginal_likelihood_label(ui: &mut egui::Ui, model: Option<&dyn RegressionModel>) {
+    if let Some(model) = model {
+        ui.label(format!(
+            "Log marginal likelihood: {:.3}",
+            model.log_evidence()
+        ));
+    }
+}
+
+fn effective_degrees_of_freedom_label(ui: &mut egui::Ui, gp: Option<&GaussianProcess<Kernel>>) {
+    if let Some(gp) = gp {
+        ui.label(format!(
+            "Effective degrees of freedom: {:.2} of {}",
+            gp.effective_degrees_of_freedom(),
+            gp.inputs().len()
+        ))
+        .on_hover_text(
+            "How many parameters the fit effectively uses, from close to zero for a smooth fit \
+             that treats the data as noise up to one per point for a fit through every point",
+        );
+    }
+}
+
+/// How far from a training point, in pixels, the pointer can be to grab it.
+const HIT_RADIUS: f32 = 10.0;
+
+/// A larger [`HIT_RADIUS`] for touch screens, where fingers cover the points.
+const TOUCH_HIT_RADIUS: f32 = 24.0;
+
+/// The range of the weights set with the brush.
+const WEIGHT_RANGE: (f64, f64) = (0.01, 100.0);
+
+/// The default range of the noise slider.
+const NOISE_SIGMA_RANGE: (f64, f64) = (0.0, 3.0);
+
+/// How much the brush changes the log weight per scrolled point.
+const BRUSH_RATE: f64 = 0.01;
+
+/// Plot item id of the training points of the dataset with the given index.
+fn training_points_id(index: usize) -> egui::Id {
+    egui::Id::new(("training_points", index))
+}
+
+/// Linearly spaced points from 0 to 10 where the GP is evaluated for plotting.
+fn prediction_grid() -> Vec<f64> {
+    grid(0.0, 10.0)
+}
+
+/// 101 evenly spaced points from `start` to `end`.
+fn grid(start: f64, end: f64) -> Vec<f64> {
+    (0..=100)
+        .map(|i| start + i as f64 / 100.0 * (end - start))
+        .collect()
+}
+
+/// Lines at the mean ± 2 standard deviations.
+///
+/// egui_plot does not support filling non-convex polygons, so we fallback to drawing some
+/// lines to represent the uncertainty instead.
+fn uncertainty_band(
+    x: &[f64],
+    means: &na::DVector<f64>,
+    variances: &na::DVector<f64>,
+    color: egui::Color32,
+) -> (Line, Line) {
+    let offset_points = |sign: f64| {
+        x.iter()
+            .zip(means.iter())
+            .zip(variances.iter())
+            .map(|((x, mean), variance)| [*x, *mean + sign * 2.0 * variance.max(0.0).sqrt()])
+            .collect::<Vec<[f64; 2]>>()
+    };
+
+    (
+        Line::new(offset_points(-1.0)).color(color),
+        Line::new(offset_points(1.0)).color(color),
+    )
+}
+
 fn powered_by_egui_and_eframe(ui: &mut egui::Ui) {
     ui.horizontal(|ui| {
         ui.spacing_mut().item_spacing.x = 0.0;
@@ -237,3 +2871,25 @@ fn powered_by_egui_and_eframe(ui: &mut egui::Ui) {
         ui.label(".");
     });
 }
+
+/// How much larger the text is in presentation mode.
+const PRESENTATION_TEXT_SCALE: f32 = 1.6;
+
+/// Enlarge the text and make it black or white for presentation mode, or restore the defaults.
+fn set_presentation_style(ctx: &egui::Context, presentation: bool) {
+    let text_styles = egui::Style::default().text_styles;
+    ctx.all_styles_mut(|style| {
+        style.text_styles = text_styles.clone();
+        style.visuals.override_text_color = None;
+        if presentation {
+            for font in style.text_styles.values_mut() {
+                font.size *= PRESENTATION_TEXT_SCALE;
+            }
+            style.visuals.override_text_color = Some(if style.visuals.dark_mode {
+                egui::Color32::WHITE
+            } else {
+                egui::Color32::BLACK
+            });
+        }
+    });
+}
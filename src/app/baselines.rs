@@ -0,0 +1,94 @@
+use crate::gp::{moving_average, Polynomial};
+
+/// Simple fits drawn over the GP for comparison, to each visible dataset.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Baselines {
+    /// A least squares polynomial.
+    pub polynomial: bool,
+    pub degree: usize,
+    /// The average of the points within a window around each input.
+    pub moving_average: bool,
+    /// The width of the window of the moving average.
+    pub window: f64,
+}
+
+impl Default for Baselines {
+    fn default() -> Self {
+        Self {
+            polynomial: false,
+            degree: 3,
+            moving_average: false,
+            window: 2.0,
+        }
+    }
+}
+
+impl Baselines {
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.polynomial, "Polynomial")
+                .on_hover_text("The least squares polynomial through the training points");
+            ui.add_enabled(
+                self.polynomial,
+                egui::DragValue::new(&mut self.degree)
+                    .range(0..=10)
+                    .prefix("degree "),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.moving_average, "Moving average")
+                .on_hover_text("The mean of the training points within a window around each x");
+            ui.add_enabled(
+                self.moving_average,
+                egui::DragValue::new(&mut self.window)
+                    .range(0.1..=10.0)
+                    .speed(0.05)
+                    .prefix("window "),
+            );
+        });
+    }
+
+    /// The names and values at `at` of the enabled baselines fit to the points. The moving
+    /// average is NaN where the window holds no points.
+    pub fn lines(&self, x: &[f64], y: &[f64], at: &[f64]) -> Vec<(String, Vec<f64>)> {
+        let mut lines = Vec::new();
+        if let Some(polynomial) = Polynomial::fit(x, y, self.degree).filter(|_| self.polynomial) {
+            let name = format!("Polynomial (degree {})", polynomial.degree());
+            lines.push((name, at.iter().map(|x| polynomial.eval(*x)).collect()));
+        }
+        if self.moving_average && !x.is_empty() {
+            let name = format!("Moving average (window {:.2})", self.window);
+            lines.push((name, moving_average(x, y, self.window, at)));
+        }
+        lines
+    }
+}
+
+/// The points of a line split at the NaN values, which leave gaps.
+pub fn segments(x: &[f64], values: &[f64]) -> Vec<Vec<[f64; 2]>> {
+    let mut segments: Vec<Vec<[f64; 2]>> = vec![Vec::new()];
+    for (x, y) in x.iter().zip(values) {
+        let last = segments.len() - 1;
+        if !y.is_nan() {
+            segments[last].push([*x, *y]);
+        } else if !segments[last].is_empty() {
+            segments.push(Vec::new());
+        }
+    }
+    segments.retain(|segment| !segment.is_empty());
+    segments
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let nan = f64::NAN;
+        let x = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        let segments = segments(&x, &[nan, 1.0, 2.0, nan, nan, 5.0]);
+        assert_eq!(segments, [vec![[1.0, 1.0], [2.0, 2.0]], vec![[5.0, 5.0]]]);
+    }
+}
@@ -0,0 +1,181 @@
+use egui_plot::{Line, Plot, PlotPoints, Points};
+use nalgebra as na;
+
+use super::prediction_grid;
+use crate::gp::{GaussianProcessClassifier, Kernel};
+
+const POSITIVE_COLOR: egui::Color32 = egui::Color32::from_rgb(30, 144, 255);
+const NEGATIVE_COLOR: egui::Color32 = egui::Color32::RED;
+
+fn sigmoid(f: f64) -> f64 {
+    1.0 / (1.0 + (-f).exp())
+}
+
+/// Points labeled with one of two classes, fitted by a GP classifier.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ClassificationView {
+    x: Vec<f64>,
+    /// Whether each point belongs to the positive (+1) class.
+    labels: Vec<bool>,
+    #[serde(skip)]
+    gp: Option<GaussianProcessClassifier<Kernel>>,
+}
+
+impl Default for ClassificationView {
+    fn default() -> Self {
+        Self {
+            x: vec![1.0, 2.0, 3.5, 6.0, 7.5, 8.0],
+            labels: vec![false, false, false, true, true, true],
+            gp: None,
+        }
+    }
+}
+
+impl ClassificationView {
+    /// Show the controls and the plot of the predicted probability, refitting if `changed` or
+    /// the data is edited.
+    pub fn show(&mut self, ui: &mut egui::Ui, kernel: &Kernel, changed: bool) {
+        ui.label(
+            "Left click to add points of class +1, right click to add points of class −1, click \
+            on points to remove them.",
+        );
+        if ui.button("Clear all Points").clicked() {
+            self.x.clear();
+            self.labels.clear();
+            self.gp = None;
+        }
+
+        if changed || self.gp.is_none() {
+            self.gp = Some(GaussianProcessClassifier::new(
+                &na::DVector::from_vec(self.x.clone()),
+                &self.labels,
+                kernel.clone(),
+            ));
+        }
+        let Some(gp) = &self.gp else {
+            return;
+        };
+        ui.label(format!(
+            "Approximate log marginal likelihood: {:.3}",
+            gp.log_marginal_likelihood()
+        ));
+
+        let grid = prediction_grid();
+        let (mean, variance) = gp.predict_latent(&na::DVector::from_vec(grid.clone()));
+        let probability = gp.predict_probability(&na::DVector::from_vec(grid.clone()));
+        let curve = |values: Vec<f64>| {
+            grid.iter()
+                .zip(values)
+                .map(|(x, y)| [*x, y])
+                .collect::<PlotPoints>()
+        };
+        // the band maps the ±2σ interval of the latent function through the logistic function
+        let band = |sign: f64| {
+            curve(
+                mean.iter()
+                    .zip(variance.iter())
+                    .map(|(m, v)| sigmoid(m + sign * 2.0 * v.sqrt()))
+                    .collect(),
+            )
+        };
+
+        let class_points = |positive: bool| {
+            self.x
+                .iter()
+                .zip(self.labels.iter())
+                .filter(|(_, label)| **label == positive)
+                .map(|(x, _)| [*x, if positive { 1.0 } else { 0.0 }])
+                .collect::<Vec<_>>()
+        };
+        let positive_id = egui::Id::new("classification_positive");
+        let negative_id = egui::Id::new("classification_negative");
+
+        let response = Plot::new("classification_plot")
+            .include_y(0.0)
+            .include_y(1.0)
+            .label_formatter(|name, point| {
+                let p = gp.predict_probability(&na::DVector::from_element(1, point.x));
+                let name = if name.is_empty() {
+                    String::new()
+                } else {
+                    format!("{name}\n")
+                };
+                format!("{name}x = {:.2}\np(y = +1 | x) = {:.2}", point.x, p[0])
+            })
+            .show(ui, |pui| {
+                pui.line(
+                    Line::new(band(-1.0))
+                        .color(egui::Color32::GRAY)
+                        .style(egui_plot::LineStyle::dashed_loose())
+                        .name("Latent ± 2σ"),
+                );
+                pui.line(
+                    Line::new(band(1.0))
+                        .color(egui::Color32::GRAY)
+                        .style(egui_plot::LineStyle::dashed_loose())
+                        .name("Latent ± 2σ"),
+                );
+                pui.line(
+                    Line::new(curve(probability.iter().copied().collect()))
+                        .color(egui::Color32::DARK_GREEN)
+                        .name("p(y = +1 | x)"),
+                );
+                pui.points(
+                    Points::new(class_points(true))
+                        .radius(5.0)
+                        .color(POSITIVE_COLOR)
+                        .name("Class +1")
+                        .id(positive_id),
+                );
+                pui.points(
+                    Points::new(class_points(false))
+                        .radius(5.0)
+                        .color(NEGATIVE_COLOR)
+                        .name("Class −1")
+                        .id(negative_id),
+                );
+
+                let response = pui.response();
+                let click = if response.clicked() {
+                    Some(true)
+                } else if response.secondary_clicked() {
+                    Some(false)
+                } else {
+                    None
+                };
+                click.zip(pui.pointer_coordinate())
+            });
+
+        if let Some((primary, pos)) = response.inner {
+            let hovered_class = match response.hovered_plot_item {
+                Some(id) if id == positive_id => Some(true),
+                Some(id) if id == negative_id => Some(false),
+                _ => None,
+            };
+            match hovered_class {
+                Some(positive) => {
+                    if let Some((index, _)) = self
+                        .x
+                        .iter()
+                        .zip(self.labels.iter())
+                        .enumerate()
+                        .filter(|(_, (_, label))| **label == positive)
+                        .map(|(i, (x, _))| (i, (x - pos.x).abs()))
+                        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    {
+                        self.x.remove(index);
+                        self.labels.remove(index);
+                    }
+                }
+                None => {
+                    self.x.push(pos.x);
+                    self.labels.push(primary);
+                }
+            }
+            // refit on the next frame, as the plot has already been drawn
+            self.gp = None;
+            ui.ctx().request_repaint();
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use super::Model;
+use crate::gp::{
+    BayesianLinearRegression, CircularKernel, DataTransform, GaussianProcess,
+    HeteroscedasticGaussianProcess, Kernel, RegressionModel, ReplicateNoise,
+    StudentTGaussianProcess, TransformOptions,
+};
+
+/// Colors given to new datasets, in order.
+pub const PALETTE: [egui::Color32; 6] = [
+    egui::Color32::RED,
+    egui::Color32::from_rgb(30, 144, 255),
+    egui::Color32::from_rgb(50, 180, 50),
+    egui::Color32::from_rgb(160, 80, 220),
+    egui::Color32::from_rgb(230, 130, 0),
+    egui::Color32::from_rgb(0, 170, 170),
+];
+
+/// Inputs closer than this fraction of the range of the inputs count as replicates.
+const REPLICATE_TOLERANCE: f64 = 1e-3;
+
+/// A named set of training points, shown in its own color with its own GP fit.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Dataset {
+    pub name: String,
+    pub color: egui::Color32,
+    pub visible: bool,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    /// Text attached to the points, by index. Points past the end have no label.
+    pub labels: Vec<String>,
+    /// Whether the points are held out of the fits as a test set, by index. Points past the end
+    /// are used for training.
+    pub held_out: Vec<bool>,
+    /// The weights of the points in the GP fits, dividing their noise variance, by index. Points
+    /// past the end have weight 1.
+    pub weights: Vec<f64>,
+    /// The transformations applied to the points before fitting, whose predictions are mapped
+    /// back to the units of the points.
+    pub transform: TransformOptions,
+    /// The fit to the points, saved along with them so it does not need to be redone on
+    /// startup.
+    pub gp: Option<GaussianProcess<Kernel>>,
+    /// The fit of a straight line, only made while it is the selected model.
+    #[serde(skip)]
+    pub linear: Option<BayesianLinearRegression>,
+    /// The fit of a GP on a circular domain, only made while it is the selected model.
+    #[serde(skip)]
+    pub circular: Option<GaussianProcess<CircularKernel<Kernel>>>,
+    /// The fit of a GP with noise varying along the x-axis, only made while it is the selected
+    /// model.
+    #[serde(skip)]
+    pub heteroscedastic: Option<HeteroscedasticGaussianProcess<Kernel>>,
+    /// The fit of a GP with heavy tailed noise, only made while it is the selected model.
+    #[serde(skip)]
+    pub robust: Option<StudentTGaussianProcess<Kernel>>,
+}
+
+impl Default for Dataset {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Dataset {
+    /// An empty dataset, named and colored after its position in the list.
+    pub fn new(index: usize) -> Self {
+        Self {
+            name: format!("Dataset {}", index + 1),
+            color: PALETTE[index % PALETTE.len()],
+            visible: true,
+            x: Vec::new(),
+            y: Vec::new(),
+            labels: Vec::new(),
+            held_out: Vec::new(),
+            weights: Vec::new(),
+            transform: TransformOptions::default(),
+            gp: None,
+            linear: None,
+            circular: None,
+            heteroscedastic: None,
+            robust: None,
+        }
+    }
+
+    /// The fit of the given model, if it has been made.
+    pub fn model(&self, model: Model) -> Option<&dyn RegressionModel> {
+        match model {
+            Model::GaussianProcess => Some(self.gp.as_ref()?),
+            Model::BayesianLinearRegression => Some(self.linear.as_ref()?),
+            Model::CircularGaussianProcess => Some(self.circular.as_ref()?),
+            Model::HeteroscedasticGaussianProcess => Some(self.heteroscedastic.as_ref()?),
+            Model::StudentTGaussianProcess => Some(self.robust.as_ref()?),
+        }
+    }
+
+    /// The noise estimated from the points at (nearly) the same x, if any are repeated.
+    pub fn replicate_noise(&self) -> Option<ReplicateNoise> {
+        let min = self.x.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self.x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        crate::gp::replicate_noise(&self.x, &self.y, REPLICATE_TOLERANCE * (max - min))
+    }
+
+    /// Replace all points, dropping their labels, weights and the test set.
+    pub fn set_points(&mut self, x: Vec<f64>, y: Vec<f64>) {
+        self.x = x;
+        self.y = y;
+        self.labels.clear();
+        self.held_out.clear();
+        self.weights.clear();
+    }
+
+    pub fn clear_points(&mut self) {
+        self.set_points(Vec::new(), Vec::new());
+    }
+
+    pub fn remove_point(&mut self, index: usize) {
+        self.x.remove(index);
+        self.y.remove(index);
+        if index < self.labels.len() {
+            self.labels.remove(index);
+        }
+        if index < self.held_out.len() {
+            self.held_out.remove(index);
+        }
+        if index < self.weights.len() {
+            self.weights.remove(index);
+        }
+    }
+
+    /// Remove the first `count` points, e.g. the oldest ones of a stream.
+    pub fn remove_first(&mut self, count: usize) {
+        self.x.drain(..count);
+        self.y.drain(..count);
+        self.labels.drain(..count.min(self.labels.len()));
+        self.held_out.drain(..count.min(self.held_out.len()));
+        self.weights.drain(..count.min(self.weights.len()));
+    }
+
+    pub fn is_held_out(&self, index: usize) -> bool {
+        self.held_out.get(index).copied().unwrap_or(false)
+    }
+
+    /// The points the models are fit to, leaving out the test set and transformed.
+    pub fn training_points(&self) -> (Vec<f64>, Vec<f64>) {
+        let (x, y) = self.points_where(false);
+        self.transform().apply(&x, &y)
+    }
+
+    /// The weights of the training points, in the order of [`Self::training_points`].
+    pub fn training_weights(&self) -> Vec<f64> {
+        (0..self.x.len())
+            .filter(|i| !self.is_held_out(*i))
+            .map(|i| self.weight(i))
+            .collect()
+    }
+
+    /// The error bars of the training points by index, one standard deviation
+    /// `noise_sigma / sqrt(weight)` of the assumed noise on either side. The noise is in the
+    /// transformed units the models are fit in, so the ends are mapped back to the units of the
+    /// points. Held out points are not fit and have none.
+    pub fn error_bars(&self, noise_sigma: f64) -> Vec<(usize, [f64; 2])> {
+        let transform = self.transform();
+        (0..self.x.len())
+            .filter(|i| !self.is_held_out(*i))
+            .map(|i| {
+                let x = self.x[i];
+                let target = transform.target(x, self.y[i]);
+                let noise = noise_sigma / self.weight(i).sqrt();
+                let end = |target: f64| transform.inverse_prediction(x, target, 0.0).0;
+                (i, [end(target - noise), end(target + noise)])
+            })
+            .collect()
+    }
+
+    pub fn weight(&self, index: usize) -> f64 {
+        self.weights.get(index).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_weight(&mut self, index: usize, weight: f64) {
+        if index >= self.x.len() {
+            return;
+        }
+        if self.weights.len() <= index {
+            self.weights.resize(index + 1, 1.0);
+        }
+        self.weights[index] = weight;
+    }
+
+    /// The training points as they are shown, without the transformation.
+    pub fn untransformed_training_points(&self) -> (Vec<f64>, Vec<f64>) {
+        self.points_where(false)
+    }
+
+    /// The points held out as a test set, transformed like the training points.
+    pub fn test_points(&self) -> (Vec<f64>, Vec<f64>) {
+        let (x, y) = self.points_where(true);
+        self.transform().apply(&x, &y)
+    }
+
+    /// The transformation of the points, fit to the training points.
+    pub fn transform(&self) -> DataTransform {
+        let (x, y) = self.points_where(false);
+        DataTransform::fit(&x, &y, self.transform)
+    }
+
+    fn points_where(&self, held_out: bool) -> (Vec<f64>, Vec<f64>) {
+        (0..self.x.len())
+            .filter(|i| self.is_held_out(*i) == held_out)
+            .map(|i| (self.x[i], self.y[i]))
+            .unzip()
+    }
+
+    /// The label of the point, if it has one.
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.labels
+            .get(index)
+            .map(String::as_str)
+            .filter(|label| !label.is_empty())
+    }
+
+    pub fn set_label(&mut self, index: usize, label: &str) {
+        if index >= self.x.len() {
+            return;
+        }
+        if self.labels.len() <= index {
+            self.labels.resize(index + 1, String::new());
+        }
+        self.labels[index] = label.trim().to_owned();
+    }
+}
+
+/// Checkboxes for the transformations of the dataset. Returns true if they changed, which
+/// requires a refit.
+pub fn transform_panel(ui: &mut egui::Ui, dataset: &mut Dataset) -> bool {
+    let options = &mut dataset.transform;
+    let before = *options;
+    ui.checkbox(&mut options.standardize_x, "Standardize x")
+        .on_hover_text("Shift and scale the inputs to zero mean and unit variance");
+    let positive = dataset.y.iter().all(|y| *y > 0.0);
+    ui.add_enabled(positive, egui::Checkbox::new(&mut options.log_y, "Log y"))
+        .on_hover_text("Model the logarithm of the values, e.g. for positive growing data")
+        .on_disabled_hover_text("Only possible while all values are positive");
+    ui.checkbox(&mut options.detrend, "Detrend")
+        .on_hover_text("Subtract a straight line fit to the values and model what is left");
+    ui.checkbox(&mut options.standardize_y, "Standardize y")
+        .on_hover_text("Shift and scale the values to zero mean and unit variance");
+    ui.label(
+        "The models are fit to the transformed points and their predictions mapped back, while \
+         the diagnostics show the transformed units.",
+    );
+    *options != before
+}
+
+/// List the datasets with controls to select the one being edited, toggle visibility, and add
+/// or remove datasets. Returns true if the datasets changed in a way that requires a refit.
+pub fn datasets_panel(ui: &mut egui::Ui, datasets: &mut Vec<Dataset>, active: &mut usize) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+
+    for (i, dataset) in datasets.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.radio_value(active, i, "")
+                .on_hover_text("Edit this dataset");
+            ui.checkbox(&mut dataset.visible, "")
+                .on_hover_text("Show this dataset");
+            ui.color_edit_button_srgba(&mut dataset.color);
+            ui.add(egui::TextEdit::singleline(&mut dataset.name).desired_width(100.0));
+            ui.label(format!("({} points)", dataset.x.len()));
+            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+
+    // always keep at least one dataset around to add points to
+    if let (Some(i), true) = (remove, datasets.len() > 1) {
+        datasets.remove(i);
+        changed = true;
+    }
+    if ui.button("Add dataset").clicked() {
+        datasets.push(Dataset::new(datasets.len()));
+        *active = datasets.len() - 1;
+        changed = true;
+    }
+    *active = (*active).min(datasets.len() - 1);
+
+    changed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_labels_follow_points() {
+        let mut dataset = Dataset::new(0);
+        dataset.set_points(vec![1.0, 2.0, 3.0], vec![0.0, 0.0, 0.0]);
+        dataset.set_label(2, " outlier ");
+        assert_eq!(dataset.label(2), Some("outlier"));
+        assert_eq!(dataset.label(0), None);
+
+        dataset.remove_point(0);
+        assert_eq!(dataset.label(1), Some("outlier"));
+        dataset.remove_first(1);
+        assert_eq!(dataset.label(0), Some("outlier"));
+
+        dataset.set_points(vec![1.0], vec![1.0]);
+        assert_eq!(dataset.label(0), None);
+    }
+
+    #[test]
+    fn test_error_bars_in_original_units() {
+        let mut dataset = Dataset::new(0);
+        dataset.set_points(vec![0.0, 1.0, 2.0], vec![1.0, 3.0, 5.0]);
+        dataset.set_weight(2, 4.0);
+        dataset.held_out = vec![false, true];
+        let bars = dataset.error_bars(0.5);
+        assert_eq!(bars, vec![(0, [0.5, 1.5]), (2, [4.75, 5.25])]);
+
+        // the noise is in the units of the standardized targets, whose scale is 2
+        dataset.transform.standardize_y = true;
+        let bars = dataset.error_bars(0.5);
+        assert!((bars[0].1[0] - 0.0).abs() < 1e-12 && (bars[0].1[1] - 2.0).abs() < 1e-12);
+
+        // and after a logarithm the bars are asymmetric factors
+        dataset.transform = TransformOptions {
+            log_y: true,
+            ..Default::default()
+        };
+        let [low, high] = dataset.error_bars(0.5)[1].1;
+        assert!((low - 5.0 * (-0.25f64).exp()).abs() < 1e-12);
+        assert!((high - 5.0 * 0.25f64.exp()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,111 @@
+//! Conversion between calendar dates and days since the unix epoch (1970-01-01), which is how
+//! timestamps are represented on the time axis.
+//!
+//! Uses the proleptic Gregorian calendar algorithms by Howard Hinnant, see
+//! <https://howardhinnant.github.io/date_algorithms.html>.
+
+/// Days since the epoch of the given date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The date `(year, month, day)` of the given number of days since the epoch.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Parse a date like `2024-03-01` (or `2024/03/01`), optionally followed by a time like
+/// `T12:30` or ` 12:30:15`, into (fractional) days since the epoch.
+pub fn parse_date(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (date, time) = match text.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut parts = date.split(['-', '/']);
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut days = days_from_civil(year, month, day) as f64;
+
+    if let Some(time) = time {
+        // ignore a trailing timezone designator, e.g. `Z`
+        let mut parts = time.trim_end_matches('Z').split(':');
+        let hours: f64 = parts.next()?.parse().ok()?;
+        let minutes: f64 = parts.next().map_or(Some(0.0), |m| m.parse().ok())?;
+        let seconds: f64 = parts.next().map_or(Some(0.0), |s| s.parse().ok())?;
+        days += (hours + minutes / 60.0 + seconds / 3600.0) / 24.0;
+    }
+
+    Some(days)
+}
+
+/// Format days since the epoch as a date, `2024-03-01`.
+pub fn format_date(days: f64) -> String {
+    let (year, month, day) = civil_from_days(days.floor() as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Format days since the epoch as a date and time, `2024-03-01 12:30`.
+pub fn format_date_time(days: f64) -> String {
+    let minutes = ((days - days.floor()) * 24.0 * 60.0).round() as u32;
+    // rounding up to the next day
+    if minutes == 24 * 60 {
+        return format!("{} 00:00", format_date(days.ceil()));
+    }
+    format!(
+        "{} {:02}:{:02}",
+        format_date(days),
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Some(0.0));
+        assert_eq!(parse_date("2000-03-01"), Some(11017.0));
+        assert_eq!(parse_date("1969/12/31"), Some(-1.0));
+        assert_eq!(parse_date(" 1970-01-02T12:00 "), Some(1.5));
+        assert_eq!(parse_date("1970-01-01 06:00:00Z"), Some(0.25));
+        assert_eq!(parse_date("1970-13-01"), None);
+        assert_eq!(parse_date("12.5"), None);
+        assert_eq!(parse_date("date"), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0.0), "1970-01-01");
+        assert_eq!(format_date(11017.9), "2000-03-01");
+        assert_eq!(format_date(-1.0), "1969-12-31");
+        assert_eq!(format_date_time(1.5), "1970-01-02 12:00");
+        for days in [-800000, -1, 59, 10000, 19782] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
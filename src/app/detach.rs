@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+
+/// Show an auxiliary window inside the main window or, once detached, as a separate window of
+/// the operating system, so it does not crowd the plot. Closing the separate window hides it.
+/// `detached` holds the titles of the detached windows. Where separate windows are not
+/// supported, e.g. on the web, the window is attached again.
+pub fn auxiliary_window(
+    ctx: &egui::Context,
+    title: &str,
+    default_size: [f32; 2],
+    open: &mut bool,
+    detached: &mut BTreeSet<String>,
+    mut add_contents: impl FnMut(&mut egui::Ui),
+) {
+    if !*open {
+        return;
+    }
+    if !detached.contains(title) {
+        egui::Window::new(title)
+            .open(open)
+            .default_size(default_size)
+            .show(ctx, |ui| {
+                if !cfg!(target_arch = "wasm32")
+                    && ui
+                        .small_button("⬈ Detach")
+                        .on_hover_text("Show in a separate window")
+                        .clicked()
+                {
+                    detached.insert(title.to_owned());
+                }
+                add_contents(ui);
+            });
+        return;
+    }
+
+    let builder = egui::ViewportBuilder::default()
+        .with_title(title)
+        .with_inner_size(default_size);
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of(title),
+        builder,
+        |ctx, class| {
+            if class == egui::ViewportClass::Embedded {
+                detached.remove(title);
+                return;
+            }
+            egui::CentralPanel::default().show(ctx, |ui| {
+                if ui
+                    .small_button("⬋ Attach")
+                    .on_hover_text("Show inside the main window again")
+                    .clicked()
+                {
+                    detached.remove(title);
+                }
+                add_contents(ui);
+            });
+            if ctx.input(|input| input.viewport().close_requested()) {
+                *open = false;
+            }
+        },
+    );
+}
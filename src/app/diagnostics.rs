@@ -0,0 +1,238 @@
+use egui_plot::{Bar, BarChart, Line, Plot, Points};
+
+use crate::gp::{calibration_curve, calibration_error, GaussianProcess, GpKernel};
+
+/// Width of the histogram bins for the standardized residuals.
+const BIN_WIDTH: f64 = 0.5;
+
+/// The number of nominal levels of the reliability diagram.
+const CALIBRATION_LEVELS: usize = 20;
+
+/// Plot the residuals of the fit at the training points and a histogram of the standardized
+/// residuals, which should roughly follow a standard normal distribution for a good fit.
+pub fn residuals_panel<K: GpKernel>(
+    ui: &mut egui::Ui,
+    x: &[f64],
+    gp: &GaussianProcess<K>,
+    standardize: &mut bool,
+) {
+    if x.is_empty() {
+        ui.label("No training data.");
+        return;
+    }
+
+    let (residuals, standardized) = gp.residuals();
+
+    ui.checkbox(standardize, "Standardize residuals");
+    let shown = if *standardize {
+        &standardized
+    } else {
+        &residuals
+    };
+    let points = x
+        .iter()
+        .zip(shown.iter())
+        .map(|(x, r)| [*x, *r])
+        .collect::<Vec<[f64; 2]>>();
+
+    Plot::new("residuals_plot")
+        .height(ui.available_height() / 2.0)
+        .allow_scroll(false)
+        .show(ui, |pui| {
+            pui.hline(egui_plot::HLine::new(0.0).color(egui::Color32::GRAY));
+            pui.points(Points::new(points).radius(3.0).name("Residuals"));
+        });
+
+    ui.label("Standardized residuals:");
+    let mut counts = std::collections::BTreeMap::<i64, usize>::new();
+    for r in standardized.iter().filter(|r| r.is_finite()) {
+        *counts.entry((r / BIN_WIDTH).floor() as i64).or_default() += 1;
+    }
+    let bars = counts
+        .into_iter()
+        .map(|(bin, count)| Bar::new((bin as f64 + 0.5) * BIN_WIDTH, count as f64).width(BIN_WIDTH))
+        .collect();
+
+    // the density of a standard normal, scaled to the histogram counts
+    let scale = standardized.len() as f64 * BIN_WIDTH;
+    let normal = (-40..=40)
+        .map(|i| {
+            let z = i as f64 / 10.0;
+            [
+                z,
+                scale * (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt(),
+            ]
+        })
+        .collect::<Vec<[f64; 2]>>();
+
+    Plot::new("residuals_histogram")
+        .allow_scroll(false)
+        .show(ui, |pui| {
+            pui.bar_chart(BarChart::new(bars).name("Standardized residuals"));
+            pui.line(
+                Line::new(normal)
+                    .color(egui::Color32::GRAY)
+                    .name("Standard normal"),
+            );
+        });
+}
+
+/// List the leave-one-out predictions for all training points, flagging the points whose
+/// z-score exceeds the threshold as likely outliers. Clicking an entry toggles its highlight.
+pub fn leave_one_out_panel<K: GpKernel>(
+    ui: &mut egui::Ui,
+    x: &[f64],
+    y: &[f64],
+    gp: &GaussianProcess<K>,
+    threshold: &mut f64,
+    highlighted: &mut Option<usize>,
+) {
+    if x.is_empty() {
+        ui.label("No training data.");
+        return;
+    }
+
+    ui.add(egui::Slider::new(threshold, 0.5..=5.0).text("Outlier z-score threshold"));
+
+    let (mean, variance) = gp.leave_one_out();
+    let z_scores = y
+        .iter()
+        .zip(mean.iter().zip(variance.iter()))
+        .map(|(y, (mean, variance))| (y - mean) / variance.sqrt())
+        .collect::<Vec<f64>>();
+    let flagged = z_scores.iter().filter(|z| z.abs() > *threshold).count();
+    ui.label(format!("{flagged} of {} points flagged.", x.len()));
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("loo_grid").striped(true).show(ui, |ui| {
+            ui.strong("#");
+            ui.strong("x");
+            ui.strong("y");
+            ui.strong("LOO mean ± 2σ");
+            ui.strong("z");
+            ui.end_row();
+
+            for (i, z) in z_scores.iter().enumerate() {
+                let color = if z.abs() > *threshold {
+                    ui.visuals().error_fg_color
+                } else {
+                    ui.visuals().text_color()
+                };
+                let text = |s: String| egui::RichText::new(s).color(color);
+
+                if ui
+                    .selectable_label(*highlighted == Some(i), text(i.to_string()))
+                    .clicked()
+                {
+                    *highlighted = if *highlighted == Some(i) {
+                        None
+                    } else {
+                        Some(i)
+                    };
+                }
+                ui.label(text(format!("{:.3}", x[i])));
+                ui.label(text(format!("{:.3}", y[i])));
+                ui.label(text(format!(
+                    "{:.3} ± {:.3}",
+                    mean[i],
+                    2.0 * variance[i].sqrt()
+                )));
+                ui.label(text(format!("{z:.2}")));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+/// Plot the reliability diagram of the leave-one-out predictions: the fraction of the training
+/// points inside the central predictive interval of each nominal level, when each is held out
+/// in turn. A curve along the diagonal means the uncertainties are honest.
+pub fn calibration_panel<K: GpKernel>(ui: &mut egui::Ui, y: &[f64], gp: &GaussianProcess<K>) {
+    if y.is_empty() {
+        ui.label("No training data.");
+        return;
+    }
+
+    let (mean, variance) = gp.leave_one_out();
+    let levels: Vec<f64> = (1..=CALIBRATION_LEVELS)
+        .map(|i| i as f64 / CALIBRATION_LEVELS as f64)
+        .collect();
+    let curve = calibration_curve(mean.as_slice(), variance.as_slice(), y, &levels);
+    ui.label(format!(
+        "Mean calibration error: {:.3}",
+        calibration_error(&curve)
+    ))
+    .on_hover_text(
+        "Below the diagonal the intervals are too narrow (overconfident), above it too wide.",
+    );
+
+    let points = std::iter::once([0.0, 0.0])
+        .chain(curve.iter().map(|point| [point.nominal, point.empirical]))
+        .collect::<Vec<[f64; 2]>>();
+    Plot::new("calibration_plot")
+        .data_aspect(1.0)
+        .include_x(0.0)
+        .include_x(1.0)
+        .include_y(0.0)
+        .include_y(1.0)
+        .x_axis_label("Nominal coverage")
+        .y_axis_label("Empirical coverage")
+        .allow_scroll(false)
+        .show(ui, |pui| {
+            pui.line(
+                Line::new(vec![[0.0, 0.0], [1.0, 1.0]])
+                    .color(egui::Color32::GRAY)
+                    .name("Calibrated"),
+            );
+            pui.line(Line::new(points.clone()).name("Leave-one-out"));
+            pui.points(Points::new(points).radius(3.0));
+        });
+}
+
+/// Plot the eigenvalues of the covariance matrix of the training data on a log scale, with the
+/// floor set by the noise and the jitter. A spectrum spanning many decades means an
+/// ill-conditioned matrix, whose fit loses accuracy.
+pub fn spectrum_panel<K: GpKernel>(ui: &mut egui::Ui, gp: &GaussianProcess<K>) {
+    let spectrum = gp.covariance_spectrum();
+    let (Some(largest), Some(smallest)) = (spectrum.first(), spectrum.last()) else {
+        ui.label("No training data.");
+        return;
+    };
+    ui.label(format!("Condition number: {:.3e}", largest / smallest))
+        .on_hover_text(
+            "The ratio of the largest to the smallest eigenvalue. Around 1e12 and above the \
+             fit becomes numerically unreliable, which more noise or jitter fixes.",
+        );
+
+    let points = spectrum
+        .iter()
+        .enumerate()
+        .map(|(i, eigenvalue)| [i as f64 + 1.0, eigenvalue.max(f64::MIN_POSITIVE).log10()])
+        .collect::<Vec<[f64; 2]>>();
+    let noise = gp.noise_sigma().powi(2);
+    Plot::new("spectrum_plot")
+        .x_axis_label("Index")
+        .y_axis_label("Eigenvalue")
+        .y_axis_formatter(|mark, _| format!("1e{}", mark.value))
+        .label_formatter(|_, value| format!("#{:.0}\n{:.3e}", value.x, 10f64.powf(value.y)))
+        .allow_scroll(false)
+        .show(ui, |pui| {
+            if noise > 0.0 {
+                pui.hline(
+                    egui_plot::HLine::new((noise + gp.jitter()).log10())
+                        .color(egui::Color32::GRAY)
+                        .name("Noise variance + jitter"),
+                );
+            }
+            if gp.jitter() > 0.0 {
+                pui.hline(
+                    egui_plot::HLine::new(gp.jitter().log10())
+                        .color(egui::Color32::GRAY)
+                        .style(egui_plot::LineStyle::dashed_loose())
+                        .name("Jitter"),
+                );
+            }
+            pui.line(Line::new(points.clone()).name("Eigenvalues"));
+            pui.points(Points::new(points).radius(3.0));
+        });
+}
@@ -0,0 +1,91 @@
+use nalgebra as na;
+
+use super::dataset::Dataset;
+
+/// Quote a CSV field if it contains characters that would otherwise break the format.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Format the training points and the predictions on the grid of all datasets as CSV.
+///
+/// Training points fill the `y` column and predictions the `mean`, `std`, `lower` and `upper`
+/// (mean ± 2 std) columns, so both can be told apart by the `kind` column.
+pub fn predictions_csv(datasets: &[Dataset], grid: &[f64]) -> String {
+    let mut csv = String::from("dataset,kind,x,y,mean,std,lower,upper\n");
+
+    for dataset in datasets {
+        let name = csv_field(&dataset.name);
+        for (x, y) in dataset.x.iter().zip(dataset.y.iter()) {
+            csv += &format!("{name},data,{x},{y},,,,\n");
+        }
+
+        let Some(gp) = &dataset.gp else {
+            continue;
+        };
+        let (means, variances) = gp.predict(&na::DVector::from_column_slice(grid));
+        for ((x, mean), variance) in grid.iter().zip(means.iter()).zip(variances.iter()) {
+            let std = variance.max(0.0).sqrt();
+            let (lower, upper) = (mean - 2.0 * std, mean + 2.0 * std);
+            csv += &format!("{name},prediction,{x},,{mean},{std},{lower},{upper}\n");
+        }
+    }
+
+    csv
+}
+
+/// A small dialog asking for the file to export to.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ExportDialog {
+    pub open: bool,
+    path: String,
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for ExportDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: "gaussian_process.csv".to_owned(),
+            status: None,
+        }
+    }
+}
+
+impl ExportDialog {
+    pub fn show(&mut self, ctx: &egui::Context, datasets: &[Dataset], grid: &[f64]) {
+        let mut open = self.open;
+        egui::Window::new("Export to CSV")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.text_edit_singleline(&mut self.path);
+                });
+                if ui.button("Export").clicked() {
+                    self.status = Some(
+                        std::fs::write(&self.path, predictions_csv(datasets, grid))
+                            .map(|_| format!("Exported to {}", self.path))
+                            .map_err(|e| format!("Failed to export: {e}")),
+                    );
+                }
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, message);
+                    }
+                    None => {}
+                }
+            });
+        self.open = open;
+    }
+}
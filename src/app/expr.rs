@@ -0,0 +1,273 @@
+//! A small evaluator for expressions of a single variable `x`, e.g. `sin(x) + 0.1*x^2`.
+//!
+//! Supports numbers, `x`, the constants `pi` and `e`, the operators `+ - * / ^` (with `^` being
+//! right associative and binding tighter than unary minus), parentheses, and the functions
+//! `sin cos tan exp ln log sqrt abs`.
+
+/// A parsed expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    X,
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "exp" => Function::Exp,
+            "ln" | "log" => Function::Ln,
+            "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs,
+            _ => return None,
+        })
+    }
+}
+
+impl Expr {
+    /// Parse an expression, returning a message describing the problem if it is invalid.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+        };
+        let expr = parser.sum()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => Err(format!("unexpected '{c}' at position {}", parser.pos + 1)),
+        }
+    }
+
+    pub fn eval(&self, x: f64) -> f64 {
+        match self {
+            Expr::Number(value) => *value,
+            Expr::X => x,
+            Expr::Neg(expr) => -expr.eval(x),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(x), rhs.eval(x));
+                match op {
+                    Op::Add => lhs + rhs,
+                    Op::Sub => lhs - rhs,
+                    Op::Mul => lhs * rhs,
+                    Op::Div => lhs / rhs,
+                    Op::Pow => lhs.powf(rhs),
+                }
+            }
+            Expr::Call(function, arg) => {
+                let arg = arg.eval(x);
+                match function {
+                    Function::Sin => arg.sin(),
+                    Function::Cos => arg.cos(),
+                    Function::Tan => arg.tan(),
+                    Function::Exp => arg.exp(),
+                    Function::Ln => arg.ln(),
+                    Function::Sqrt => arg.sqrt(),
+                    Function::Abs => arg.abs(),
+                }
+            }
+        }
+    }
+}
+
+/// Recursive descent parser, with one method per precedence level.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume the next non-whitespace character if it is `c`.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Sub
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            // the exponent may itself be negated, e.g. `x^-2`
+            Ok(Expr::Binary(
+                Op::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.sum()?;
+                if !self.eat(')') {
+                    return Err(format!("expected ')' at position {}", self.pos + 1));
+                }
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.pos += 1;
+                }
+                // scientific notation, e.g. `1e-3`
+                if self.peek() == Some('e')
+                    && self.chars[self.pos + 1..]
+                        .iter()
+                        .find(|c| **c != '-' && **c != '+')
+                        .is_some_and(char::is_ascii_digit)
+                {
+                    self.pos += 2;
+                    while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.pos += 1;
+                    }
+                }
+                let number: String = self.chars[start..self.pos].iter().collect();
+                number
+                    .parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("invalid number '{number}' at position {}", start + 1))
+            }
+            Some(c) if c.is_alphabetic() => {
+                while self.peek().is_some_and(char::is_alphanumeric) {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                match name.as_str() {
+                    "x" => Ok(Expr::X),
+                    "pi" => Ok(Expr::Number(std::f64::consts::PI)),
+                    "e" => Ok(Expr::Number(std::f64::consts::E)),
+                    _ => {
+                        let function = Function::from_name(&name).ok_or_else(|| {
+                            format!("unknown name '{name}' at position {}", start + 1)
+                        })?;
+                        if !self.eat('(') {
+                            return Err(format!("expected '(' after '{name}'"));
+                        }
+                        let arg = self.sum()?;
+                        if !self.eat(')') {
+                            return Err(format!("expected ')' at position {}", self.pos + 1));
+                        }
+                        Ok(Expr::Call(function, Box::new(arg)))
+                    }
+                }
+            }
+            Some(c) => Err(format!("unexpected '{c}' at position {}", self.pos + 1)),
+            None => Err("unexpected end of expression".to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn eval(text: &str, x: f64) -> f64 {
+        Expr::parse(text).unwrap().eval(x)
+    }
+
+    #[test]
+    fn test_eval() {
+        assert_eq!(eval("1 + 2 * 3", 0.0), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", 0.0), 9.0);
+        assert_eq!(eval("8 / 4 / 2", 0.0), 1.0);
+        assert_eq!(eval("2^3^2", 0.0), 512.0);
+        assert_eq!(eval("-x^2", 3.0), -9.0);
+        assert_eq!(eval("x^-1", 4.0), 0.25);
+        assert_eq!(eval("1.5e-1 * x", 2.0), 0.3);
+        assert_eq!(eval("2*e", 0.0), 2.0 * std::f64::consts::E);
+        assert!((eval("sin(x) + 0.1*x^2", 2.0) - (2f64.sin() + 0.4)).abs() < 1e-12);
+        assert!((eval("sqrt(abs(-pi))", 0.0) - std::f64::consts::PI.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Expr::parse("").is_err());
+        assert!(Expr::parse("1 +").is_err());
+        assert!(Expr::parse("(x").is_err());
+        assert!(Expr::parse("x)").is_err());
+        assert!(Expr::parse("foo(x)").is_err());
+        assert!(Expr::parse("sin x").is_err());
+    }
+}
@@ -0,0 +1,1031 @@
+use ab_glyph::{Font as _, FontRef, PxScale, ScaleFont as _};
+use egui::{Align2, Color32, Pos2, Rect};
+
+/// Every pixel of an exported PNG is averaged from this many samples per side, for antialiasing.
+const SUPERSAMPLING: usize = 2;
+
+/// Opacity of the fill between the lines of an uncertainty band.
+const BAND_OPACITY: f32 = 0.25;
+
+/// What an item of a figure draws, in plot coordinates.
+pub enum Shape {
+    Line {
+        points: Vec<[f64; 2]>,
+        width: f32,
+        dashed: bool,
+    },
+    /// The area between two curves sharing their x-coordinates, outlined by the curves.
+    Band {
+        x: Vec<f64>,
+        lower: Vec<f64>,
+        upper: Vec<f64>,
+    },
+    Points {
+        points: Vec<[f64; 2]>,
+        radius: f32,
+    },
+}
+
+pub struct Item {
+    /// Name shown in the legend, items without a name are left out of it.
+    pub name: String,
+    pub color: Color32,
+    pub shape: Shape,
+}
+
+/// A plot that can be rendered to image files outside of the UI.
+pub struct Figure {
+    /// Drawn above the plot, if not empty.
+    pub title: String,
+    /// Drawn below the x-axis, if not empty.
+    pub x_label: String,
+    /// Drawn along the y-axis, if not empty.
+    pub y_label: String,
+    pub x_range: [f64; 2],
+    pub y_range: [f64; 2],
+    pub items: Vec<Item>,
+}
+
+/// Evenly spaced values at round steps (1, 2 or 5 times a power of ten) within the range, with
+/// at most about `max_count` of them. Returns the values and the step.
+fn ticks(min: f64, max: f64, max_count: usize) -> (Vec<f64>, f64) {
+    if !(min.is_finite() && max.is_finite() && max > min) {
+        return (Vec::new(), 1.0);
+    }
+    let raw_step = (max - min) / max_count.max(1) as f64;
+    let magnitude = 10f64.powf(raw_step.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= raw_step)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    ((first..=last).map(|i| i as f64 * step).collect(), step)
+}
+
+/// Format a tick value with just enough decimals to tell ticks `step` apart.
+fn tick_label(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    format!("{value:.decimals$}")
+}
+
+/// The font of the UI, used for all text in the figures.
+fn with_font<R>(f: impl FnOnce(&FontRef<'_>) -> R) -> Result<R, String> {
+    let definitions = egui::FontDefinitions::default();
+    let data = definitions.families[&egui::FontFamily::Proportional]
+        .first()
+        .and_then(|name| definitions.font_data.get(name))
+        .ok_or_else(|| "No font available".to_owned())?;
+    let font = FontRef::try_from_slice_and_index(&data.font, data.index)
+        .map_err(|e| format!("Invalid font: {e}"))?;
+    Ok(f(&font))
+}
+
+/// Width of a line of text set in `font` at `size` pixels.
+fn text_width(font: &FontRef<'_>, text: &str, size: f32) -> f32 {
+    let font = font.as_scaled(PxScale::from(size));
+    let mut previous = None;
+    let mut width = 0.0;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, id);
+        }
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Something the figure can be drawn on, in pixel coordinates.
+trait Backend {
+    /// Restrict drawing to the rectangle, or lift the restriction.
+    fn clip(&mut self, rect: Option<Rect>);
+    fn polyline(&mut self, points: &[Pos2], color: Color32, width: f32, dash: Option<f32>);
+    fn polygon(&mut self, points: &[Pos2], color: Color32);
+    fn circle(&mut self, center: Pos2, radius: f32, color: Color32);
+    fn text(&mut self, pos: Pos2, align: Align2, text: &str, size: f32, color: Color32);
+    /// Text reading upwards, centered on `pos`.
+    fn vertical_text(&mut self, pos: Pos2, text: &str, size: f32, color: Color32);
+}
+
+/// Draw the figure on a canvas of `size` pixels, with text and lines scaled to the canvas.
+fn draw(figure: &Figure, font: &FontRef<'_>, size: [f32; 2], backend: &mut impl Backend) {
+    let scale = size[0].min(size[1]) / 600.0;
+    let font_size = 14.0 * scale;
+    let text_color = Color32::from_gray(40);
+
+    let (x_ticks, x_step) = ticks(figure.x_range[0], figure.x_range[1], 10);
+    let (y_ticks, y_step) = ticks(figure.y_range[0], figure.y_range[1], 8);
+    let y_labels = y_ticks
+        .iter()
+        .map(|y| tick_label(*y, y_step))
+        .collect::<Vec<_>>();
+    let label_width = y_labels
+        .iter()
+        .map(|label| text_width(font, label, font_size))
+        .fold(0.0, f32::max);
+
+    let title_size = 1.25 * font_size;
+    let title_height = if figure.title.is_empty() {
+        0.0
+    } else {
+        title_size + 8.0 * scale
+    };
+    let x_label_height = if figure.x_label.is_empty() {
+        0.0
+    } else {
+        font_size + 6.0 * scale
+    };
+    let y_label_width = if figure.y_label.is_empty() {
+        0.0
+    } else {
+        font_size + 8.0 * scale
+    };
+    let plot = Rect::from_min_max(
+        Pos2::new(
+            y_label_width + label_width + 16.0 * scale,
+            title_height + 16.0 * scale,
+        ),
+        Pos2::new(
+            size[0] - 16.0 * scale,
+            size[1] - x_label_height - font_size - 16.0 * scale,
+        ),
+    );
+    let to_screen = |[x, y]: [f64; 2]| {
+        let [x0, x1] = figure.x_range;
+        let [y0, y1] = figure.y_range;
+        Pos2::new(
+            plot.left() + ((x - x0) / (x1 - x0)) as f32 * plot.width(),
+            plot.top() + ((y1 - y) / (y1 - y0)) as f32 * plot.height(),
+        )
+    };
+
+    backend.polygon(
+        &[
+            Pos2::ZERO,
+            Pos2::new(size[0], 0.0),
+            Pos2::new(size[0], size[1]),
+            Pos2::new(0.0, size[1]),
+        ],
+        Color32::WHITE,
+    );
+
+    // grid and tick labels
+    let grid_color = Color32::from_gray(225);
+    for x in &x_ticks {
+        let screen = to_screen([*x, figure.y_range[0]]).x;
+        backend.polyline(
+            &[
+                Pos2::new(screen, plot.top()),
+                Pos2::new(screen, plot.bottom()),
+            ],
+            grid_color,
+            scale,
+            None,
+        );
+        backend.text(
+            Pos2::new(screen, plot.bottom() + 4.0 * scale),
+            Align2::CENTER_TOP,
+            &tick_label(*x, x_step),
+            font_size,
+            text_color,
+        );
+    }
+    for (y, label) in y_ticks.iter().zip(&y_labels) {
+        let screen = to_screen([figure.x_range[0], *y]).y;
+        backend.polyline(
+            &[
+                Pos2::new(plot.left(), screen),
+                Pos2::new(plot.right(), screen),
+            ],
+            grid_color,
+            scale,
+            None,
+        );
+        backend.text(
+            Pos2::new(plot.left() - 6.0 * scale, screen),
+            Align2::RIGHT_CENTER,
+            label,
+            font_size,
+            text_color,
+        );
+    }
+
+    // title and axis labels
+    if !figure.title.is_empty() {
+        backend.text(
+            Pos2::new(plot.center().x, 12.0 * scale),
+            Align2::CENTER_TOP,
+            &figure.title,
+            title_size,
+            text_color,
+        );
+    }
+    if !figure.x_label.is_empty() {
+        backend.text(
+            Pos2::new(plot.center().x, size[1] - 10.0 * scale),
+            Align2::CENTER_BOTTOM,
+            &figure.x_label,
+            font_size,
+            text_color,
+        );
+    }
+    if !figure.y_label.is_empty() {
+        backend.vertical_text(
+            Pos2::new(10.0 * scale + 0.5 * font_size, plot.center().y),
+            &figure.y_label,
+            font_size,
+            text_color,
+        );
+    }
+
+    backend.clip(Some(plot));
+    for item in &figure.items {
+        match &item.shape {
+            Shape::Line {
+                points,
+                width,
+                dashed,
+            } => {
+                let points = points.iter().map(|p| to_screen(*p)).collect::<Vec<_>>();
+                let dash = dashed.then_some(8.0 * scale);
+                backend.polyline(&points, item.color, width * scale, dash);
+            }
+            Shape::Band { x, lower, upper } => {
+                let lower = x
+                    .iter()
+                    .zip(lower)
+                    .map(|(x, y)| to_screen([*x, *y]))
+                    .collect::<Vec<_>>();
+                let upper = x
+                    .iter()
+                    .zip(upper)
+                    .map(|(x, y)| to_screen([*x, *y]))
+                    .collect::<Vec<_>>();
+                let outline = upper
+                    .iter()
+                    .chain(lower.iter().rev())
+                    .copied()
+                    .collect::<Vec<_>>();
+                backend.polygon(&outline, item.color.gamma_multiply(BAND_OPACITY));
+                backend.polyline(&lower, item.color, scale, None);
+                backend.polyline(&upper, item.color, scale, None);
+            }
+            Shape::Points { points, radius } => {
+                for point in points {
+                    backend.circle(to_screen(*point), radius * scale, item.color);
+                }
+            }
+        }
+    }
+    backend.clip(None);
+
+    // frame around the plot
+    backend.polyline(
+        &[
+            plot.left_top(),
+            plot.right_top(),
+            plot.right_bottom(),
+            plot.left_bottom(),
+            plot.left_top(),
+        ],
+        Color32::from_gray(120),
+        scale,
+        None,
+    );
+
+    // legend in the top right corner, with one entry per name
+    let mut entries: Vec<&Item> = Vec::new();
+    for item in &figure.items {
+        if !item.name.is_empty() && entries.iter().all(|entry| entry.name != item.name) {
+            entries.push(item);
+        }
+    }
+    if entries.is_empty() {
+        return;
+    }
+    let row_height = 1.4 * font_size;
+    let swatch_width = 2.0 * font_size;
+    let padding = 0.5 * font_size;
+    let legend_width = entries
+        .iter()
+        .map(|entry| text_width(font, &entry.name, font_size))
+        .fold(0.0, f32::max)
+        + swatch_width
+        + 3.0 * padding;
+    let legend = Rect::from_min_size(
+        Pos2::new(plot.right() - legend_width - padding, plot.top() + padding),
+        egui::vec2(
+            legend_width,
+            entries.len() as f32 * row_height + 2.0 * padding,
+        ),
+    );
+    let corners = [
+        legend.left_top(),
+        legend.right_top(),
+        legend.right_bottom(),
+        legend.left_bottom(),
+    ];
+    backend.polygon(&corners, Color32::from_white_alpha(220));
+    backend.polyline(
+        &[corners[0], corners[1], corners[2], corners[3], corners[0]],
+        Color32::from_gray(180),
+        scale,
+        None,
+    );
+    for (i, entry) in entries.iter().enumerate() {
+        let center_y = legend.top() + padding + (i as f32 + 0.5) * row_height;
+        let swatch = Rect::from_center_size(
+            Pos2::new(legend.left() + padding + 0.5 * swatch_width, center_y),
+            egui::vec2(swatch_width, 0.6 * row_height),
+        );
+        match &entry.shape {
+            Shape::Line { width, dashed, .. } => backend.polyline(
+                &[swatch.left_center(), swatch.right_center()],
+                entry.color,
+                width * scale,
+                dashed.then_some(0.3 * swatch_width),
+            ),
+            Shape::Band { .. } => backend.polygon(
+                &[
+                    swatch.left_top(),
+                    swatch.right_top(),
+                    swatch.right_bottom(),
+                    swatch.left_bottom(),
+                ],
+                entry.color.gamma_multiply(BAND_OPACITY * 2.0),
+            ),
+            Shape::Points { radius, .. } => {
+                backend.circle(swatch.center(), radius * scale, entry.color)
+            }
+        }
+        backend.text(
+            Pos2::new(swatch.right() + padding, center_y),
+            Align2::LEFT_CENTER,
+            &entry.name,
+            font_size,
+            text_color,
+        );
+    }
+}
+
+/// Builds an SVG document.
+struct Svg<'a> {
+    font: &'a FontRef<'a>,
+    body: String,
+    clipped: bool,
+}
+
+/// An SVG color attribute, with the opacity given separately.
+fn svg_color(attribute: &str, color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    format!(
+        "{attribute}=\"rgb({r},{g},{b})\" {attribute}-opacity=\"{:.3}\"",
+        a as f32 / 255.0
+    )
+}
+
+fn svg_points(points: &[Pos2]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{:.2},{:.2}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Backend for Svg<'_> {
+    fn clip(&mut self, rect: Option<Rect>) {
+        if self.clipped {
+            self.body += "</g>\n";
+        }
+        self.clipped = rect.is_some();
+        if let Some(rect) = rect {
+            self.body += &format!(
+                "<clipPath id=\"plot\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>\n\
+                 <g clip-path=\"url(#plot)\">\n",
+                rect.left(),
+                rect.top(),
+                rect.width(),
+                rect.height()
+            );
+        }
+    }
+
+    fn polyline(&mut self, points: &[Pos2], color: Color32, width: f32, dash: Option<f32>) {
+        let dash = dash
+            .map(|dash| format!(" stroke-dasharray=\"{dash:.2}\""))
+            .unwrap_or_default();
+        self.body += &format!(
+            "<polyline points=\"{}\" fill=\"none\" {} stroke-width=\"{width:.2}\" \
+             stroke-linejoin=\"round\" stroke-linecap=\"round\"{dash}/>\n",
+            svg_points(points),
+            svg_color("stroke", color)
+        );
+    }
+
+    fn polygon(&mut self, points: &[Pos2], color: Color32) {
+        self.body += &format!(
+            "<polygon points=\"{}\" {}/>\n",
+            svg_points(points),
+            svg_color("fill", color)
+        );
+    }
+
+    fn circle(&mut self, center: Pos2, radius: f32, color: Color32) {
+        self.body += &format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{radius:.2}\" {}/>\n",
+            center.x,
+            center.y,
+            svg_color("fill", color)
+        );
+    }
+
+    fn text(&mut self, pos: Pos2, align: Align2, text: &str, size: f32, color: Color32) {
+        // position the baseline from the font metrics, as renderers differ in their support
+        // for vertical alignment
+        let font = self.font.as_scaled(PxScale::from(size));
+        let baseline = match align.y() {
+            egui::Align::Min => pos.y + font.ascent(),
+            egui::Align::Center => pos.y + 0.5 * (font.ascent() + font.descent()),
+            egui::Align::Max => pos.y + font.descent(),
+        };
+        let anchor = match align.x() {
+            egui::Align::Min => "start",
+            egui::Align::Center => "middle",
+            egui::Align::Max => "end",
+        };
+        self.body += &format!(
+            "<text x=\"{:.2}\" y=\"{baseline:.2}\" font-family=\"Ubuntu, sans-serif\" \
+             font-size=\"{size:.2}\" text-anchor=\"{anchor}\" {}>{}</text>\n",
+            pos.x,
+            svg_color("fill", color),
+            escape_xml(text)
+        );
+    }
+
+    fn vertical_text(&mut self, pos: Pos2, text: &str, size: f32, color: Color32) {
+        self.body += &format!("<g transform=\"rotate(-90 {:.2} {:.2})\">\n", pos.x, pos.y);
+        self.text(pos, Align2::CENTER_CENTER, text, size, color);
+        self.body += "</g>\n";
+    }
+}
+
+/// Render the figure as an SVG document of `width` by `height` pixels.
+pub fn to_svg(figure: &Figure, width: u32, height: u32) -> Result<String, String> {
+    with_font(|font| {
+        let mut svg = Svg {
+            font,
+            body: String::new(),
+            clipped: false,
+        };
+        draw(figure, font, [width as f32, height as f32], &mut svg);
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n{}</svg>\n",
+            svg.body
+        )
+    })
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            '\n' => json += "\\n",
+            c if c.is_control() => json += &format!("\\u{:04x}", c as u32),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// A JSON array of numbers, with `null` in place of the non-finite ones JSON can't represent.
+fn json_numbers(values: impl IntoIterator<Item = f64>) -> String {
+    let values = values
+        .into_iter()
+        .map(|value| {
+            if value.is_finite() {
+                value.to_string()
+            } else {
+                "null".to_owned()
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", values.join(","))
+}
+
+/// The data of the figure as JSON, to plot it again elsewhere. Every item has its name, its
+/// color as `#rrggbbaa`, a `kind` of `"line"`, `"band"` or `"points"`, and its coordinates in
+/// `x` and `y`, or in `x`, `lower` and `upper` for bands.
+pub fn to_json(figure: &Figure) -> String {
+    let items = figure
+        .items
+        .iter()
+        .map(|item| {
+            let head = format!(
+                "{{\"name\":{},\"color\":\"{}\"",
+                json_string(&item.name),
+                item.color.to_hex()
+            );
+            let xy = |points: &[[f64; 2]]| {
+                format!(
+                    "\"x\":{},\"y\":{}",
+                    json_numbers(points.iter().map(|p| p[0])),
+                    json_numbers(points.iter().map(|p| p[1]))
+                )
+            };
+            match &item.shape {
+                Shape::Line { points, dashed, .. } => {
+                    format!(
+                        "{head},\"kind\":\"line\",\"dashed\":{dashed},{}}}",
+                        xy(points)
+                    )
+                }
+                Shape::Band { x, lower, upper } => format!(
+                    "{head},\"kind\":\"band\",\"x\":{},\"lower\":{},\"upper\":{}}}",
+                    json_numbers(x.iter().copied()),
+                    json_numbers(lower.iter().copied()),
+                    json_numbers(upper.iter().copied())
+                ),
+                Shape::Points { points, .. } => {
+                    format!("{head},\"kind\":\"points\",{}}}", xy(points))
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\n\"title\":{},\n\"x_label\":{},\n\"y_label\":{},\n\"x_range\":{},\n\
+         \"y_range\":{},\n\"items\":[\n{}\n]\n}}\n",
+        json_string(&figure.title),
+        json_string(&figure.x_label),
+        json_string(&figure.y_label),
+        json_numbers(figure.x_range),
+        json_numbers(figure.y_range),
+        items.join(",\n")
+    )
+}
+
+/// A pixel buffer that shapes are rasterized on. Each shape first marks the pixels it covers in
+/// a mask, which is then painted at once so that overlapping parts are not blended twice.
+struct Canvas<'a> {
+    font: &'a FontRef<'a>,
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 3]>,
+    mask: Vec<bool>,
+    /// Bounds of the marked pixels of the mask, as `[min x, min y, max x, max y]`.
+    mask_bounds: Option<[usize; 4]>,
+    clip: Rect,
+}
+
+impl<'a> Canvas<'a> {
+    fn new(font: &'a FontRef<'a>, width: usize, height: usize) -> Self {
+        Self {
+            font,
+            width,
+            height,
+            pixels: vec![[1.0; 3]; width * height],
+            mask: vec![false; width * height],
+            mask_bounds: None,
+            clip: Self::full(width, height),
+        }
+    }
+
+    fn full(width: usize, height: usize) -> Rect {
+        Rect::from_min_size(Pos2::ZERO, egui::vec2(width as f32, height as f32))
+    }
+
+    /// The range of pixels with their centers within `min..max` and the clip rectangle.
+    fn pixel_range(&self, min: f32, max: f32, clip_min: f32, clip_max: f32) -> (usize, usize) {
+        let start = (min.max(clip_min) - 0.5).ceil().max(0.0) as usize;
+        let end = (max.min(clip_max) - 0.5).floor() + 1.0;
+        (start, end.max(0.0) as usize)
+    }
+
+    fn mark(&mut self, x: usize, y: usize) {
+        self.mask[y * self.width + x] = true;
+        let bounds = self.mask_bounds.get_or_insert([x, y, x, y]);
+        *bounds = [
+            bounds[0].min(x),
+            bounds[1].min(y),
+            bounds[2].max(x),
+            bounds[3].max(y),
+        ];
+    }
+
+    /// Mark the pixels inside the polygon, using the even-odd rule.
+    fn mark_polygon(&mut self, points: &[Pos2]) {
+        if points.len() < 3 {
+            return;
+        }
+        let (min_y, max_y) = points
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+                (min.min(p.y), max.max(p.y))
+            });
+        let (start, end) = self.pixel_range(min_y, max_y, self.clip.top(), self.clip.bottom());
+        let mut crossings = Vec::new();
+        for y in start..end.min(self.height) {
+            let center = y as f32 + 0.5;
+            crossings.clear();
+            for (i, p) in points.iter().enumerate() {
+                let q = points[(i + 1) % points.len()];
+                if (p.y <= center) != (q.y <= center) {
+                    crossings.push(p.x + (center - p.y) * (q.x - p.x) / (q.y - p.y));
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                let (from, to) =
+                    self.pixel_range(span[0], span[1], self.clip.left(), self.clip.right());
+                for x in from..to.min(self.width) {
+                    self.mark(x, y);
+                }
+            }
+        }
+    }
+
+    fn mark_circle(&mut self, center: Pos2, radius: f32) {
+        let (top, bottom) = self.pixel_range(
+            center.y - radius,
+            center.y + radius,
+            self.clip.top(),
+            self.clip.bottom(),
+        );
+        let (left, right) = self.pixel_range(
+            center.x - radius,
+            center.x + radius,
+            self.clip.left(),
+            self.clip.right(),
+        );
+        for y in top..bottom.min(self.height) {
+            for x in left..right.min(self.width) {
+                let offset = Pos2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+                if offset.length_sq() <= radius * radius {
+                    self.mark(x, y);
+                }
+            }
+        }
+    }
+
+    /// Mark a thick line through the points, with round joins and caps.
+    fn mark_polyline(&mut self, points: &[Pos2], width: f32) {
+        let half = 0.5 * width.max(1.0);
+        for segment in points.windows(2) {
+            let direction = (segment[1] - segment[0]).normalized();
+            let normal = egui::vec2(-direction.y, direction.x) * half;
+            self.mark_polygon(&[
+                segment[0] + normal,
+                segment[1] + normal,
+                segment[1] - normal,
+                segment[0] - normal,
+            ]);
+        }
+        for point in points {
+            self.mark_circle(*point, half);
+        }
+    }
+
+    /// Paint the marked pixels in the color and clear the mask.
+    fn paint(&mut self, color: Color32) {
+        let Some([min_x, min_y, max_x, max_y]) = self.mask_bounds.take() else {
+            return;
+        };
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let index = y * self.width + x;
+                if std::mem::take(&mut self.mask[index]) {
+                    self.blend(index, color, 1.0);
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, index: usize, color: Color32, coverage: f32) {
+        let [r, g, b, a] = egui::Rgba::from(color).to_rgba_unmultiplied();
+        let alpha = a * coverage;
+        for (pixel, value) in self.pixels[index].iter_mut().zip([r, g, b]) {
+            *pixel += (value - *pixel) * alpha;
+        }
+    }
+
+    /// Average the samples into the pixels of the final image.
+    fn downsample(&self, factor: usize) -> image::RgbImage {
+        let (width, height) = (self.width / factor, self.height / factor);
+        image::RgbImage::from_fn(width as u32, height as u32, |x, y| {
+            let mut sum = [0.0; 3];
+            for dy in 0..factor {
+                for dx in 0..factor {
+                    let index = (y as usize * factor + dy) * self.width + x as usize * factor + dx;
+                    for (sum, value) in sum.iter_mut().zip(self.pixels[index]) {
+                        *sum += value;
+                    }
+                }
+            }
+            let rgba = egui::Rgba::from_rgb(
+                sum[0] / (factor * factor) as f32,
+                sum[1] / (factor * factor) as f32,
+                sum[2] / (factor * factor) as f32,
+            );
+            let [r, g, b, _] = Color32::from(rgba).to_array();
+            image::Rgb([r, g, b])
+        })
+    }
+}
+
+impl Backend for Canvas<'_> {
+    fn clip(&mut self, rect: Option<Rect>) {
+        let full = Self::full(self.width, self.height);
+        self.clip = rect.map_or(full, |rect| rect.intersect(full));
+    }
+
+    fn polyline(&mut self, points: &[Pos2], color: Color32, width: f32, dash: Option<f32>) {
+        match dash {
+            None => self.mark_polyline(points, width),
+            Some(dash) => {
+                // alternate between drawing and skipping `dash` along the line
+                let mut drawing = true;
+                let mut remaining = dash;
+                let mut current = points.first().into_iter().copied().collect::<Vec<_>>();
+                for segment in points.windows(2) {
+                    let (mut from, to) = (segment[0], segment[1]);
+                    let mut length = (to - from).length();
+                    while length > remaining {
+                        from += (to - from) * (remaining / length);
+                        length -= remaining;
+                        if drawing {
+                            current.push(from);
+                            self.mark_polyline(&current, width);
+                        }
+                        current = vec![from];
+                        drawing = !drawing;
+                        remaining = dash;
+                    }
+                    remaining -= length;
+                    current.push(to);
+                }
+                if drawing {
+                    self.mark_polyline(&current, width);
+                }
+            }
+        }
+        self.paint(color);
+    }
+
+    fn polygon(&mut self, points: &[Pos2], color: Color32) {
+        self.mark_polygon(points);
+        self.paint(color);
+    }
+
+    fn circle(&mut self, center: Pos2, radius: f32, color: Color32) {
+        self.mark_circle(center, radius);
+        self.paint(color);
+    }
+
+    fn text(&mut self, pos: Pos2, align: Align2, text: &str, size: f32, color: Color32) {
+        self.glyphs(pos, align, text, size, color, false);
+    }
+
+    fn vertical_text(&mut self, pos: Pos2, text: &str, size: f32, color: Color32) {
+        self.glyphs(pos, Align2::CENTER_CENTER, text, size, color, true);
+    }
+}
+
+impl Canvas<'_> {
+    /// Rasterize the text, turned a quarter counterclockwise around `pos` if `rotated`.
+    fn glyphs(
+        &mut self,
+        pos: Pos2,
+        align: Align2,
+        text: &str,
+        size: f32,
+        color: Color32,
+        rotated: bool,
+    ) {
+        let font = self.font.as_scaled(PxScale::from(size));
+        let width = text_width(self.font, text, size);
+        let mut caret = match align.x() {
+            egui::Align::Min => pos.x,
+            egui::Align::Center => pos.x - 0.5 * width,
+            egui::Align::Max => pos.x - width,
+        };
+        let baseline = match align.y() {
+            egui::Align::Min => pos.y + font.ascent(),
+            egui::Align::Center => pos.y + 0.5 * (font.ascent() + font.descent()),
+            egui::Align::Max => pos.y + font.descent(),
+        };
+
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += font.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(size, ab_glyph::point(caret, baseline));
+            caret += font.h_advance(id);
+            previous = Some(id);
+
+            let Some(outline) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|x, y, coverage| {
+                let x = bounds.min.x as i64 + x as i64;
+                let y = bounds.min.y as i64 + y as i64;
+                let (x, y) = if rotated {
+                    let (center_x, center_y) = (pos.x.round() as i64, pos.y.round() as i64);
+                    (center_x + (y - center_y), center_y - (x - center_x))
+                } else {
+                    (x, y)
+                };
+                if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+                    self.blend(y as usize * self.width + x as usize, color, coverage);
+                }
+            });
+        }
+    }
+}
+
+/// Render the figure as an image of `width` by `height` pixels.
+pub fn to_image(figure: &Figure, width: u32, height: u32) -> Result<image::RgbImage, String> {
+    with_font(|font| {
+        let (width, height) = (width as usize, height as usize);
+        let mut canvas = Canvas::new(font, width * SUPERSAMPLING, height * SUPERSAMPLING);
+        draw(
+            figure,
+            font,
+            [canvas.width as f32, canvas.height as f32],
+            &mut canvas,
+        );
+        canvas.downsample(SUPERSAMPLING)
+    })
+}
+
+/// A dialog for saving the main plot as PNG and SVG images.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ImageExportDialog {
+    pub open: bool,
+    /// Path of the files, without the extension.
+    path: String,
+    width: u32,
+    height: u32,
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for ImageExportDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: "gaussian_process".to_owned(),
+            width: 1200,
+            height: 800,
+            status: None,
+        }
+    }
+}
+
+impl ImageExportDialog {
+    /// Show the dialog, returning true if the figure should be exported with [`Self::export`].
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = self.open;
+        let mut export = false;
+        egui::Window::new("Export image")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.text_edit_singleline(&mut self.path);
+                    ui.label(".png / .svg");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Size:");
+                    ui.add(egui::DragValue::new(&mut self.width).range(100..=8000));
+                    ui.label("×");
+                    ui.add(egui::DragValue::new(&mut self.height).range(100..=8000));
+                    ui.label("pixels");
+                });
+                if ui.button("Export").clicked() {
+                    export = true;
+                }
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, message);
+                    }
+                    None => {}
+                }
+            });
+        self.open = open;
+        export
+    }
+
+    /// Write the figure to a PNG and an SVG file.
+    pub fn export(&mut self, figure: &Figure) {
+        let png = format!("{}.png", self.path);
+        let svg = format!("{}.svg", self.path);
+        self.status = Some(
+            to_image(figure, self.width, self.height)
+                .and_then(|image| {
+                    image
+                        .save(&png)
+                        .map_err(|e| format!("Failed to write {png}: {e}"))
+                })
+                .and_then(|_| to_svg(figure, self.width, self.height))
+                .and_then(|document| {
+                    std::fs::write(&svg, document)
+                        .map_err(|e| format!("Failed to write {svg}: {e}"))
+                })
+                .map(|_| format!("Exported to {png} and {svg}")),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ticks() {
+        let (values, step) = ticks(0.0, 10.0, 10);
+        assert_eq!(step, 1.0);
+        assert_eq!(values.len(), 11);
+
+        let (values, step) = ticks(-0.33, 0.87, 5);
+        assert_eq!(step, 0.5);
+        assert_eq!(values, vec![0.0, 0.5]);
+        assert_eq!(tick_label(0.5, step), "0.5");
+
+        assert!(ticks(1.0, 1.0, 5).0.is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let figure = Figure {
+            title: "Fit".to_owned(),
+            x_label: "Time (s)".to_owned(),
+            y_label: "Height (m)".to_owned(),
+            x_range: [0.0, 10.0],
+            y_range: [-2.0, 2.0],
+            items: vec![
+                Item {
+                    name: "Mean ± 2σ".to_owned(),
+                    color: Color32::LIGHT_BLUE,
+                    shape: Shape::Band {
+                        x: vec![0.0, 5.0, 10.0],
+                        lower: vec![-1.0, -0.5, -1.0],
+                        upper: vec![1.0, 0.5, 1.0],
+                    },
+                },
+                Item {
+                    name: "Training <points>".to_owned(),
+                    color: Color32::RED,
+                    shape: Shape::Points {
+                        points: vec![[5.0, 0.0]],
+                        radius: 5.0,
+                    },
+                },
+            ],
+        };
+
+        let svg = to_svg(&figure, 300, 200).unwrap();
+        assert!(svg.contains("<polygon"));
+        assert!(svg.contains("<circle"));
+        assert!(svg.contains("Training &lt;points&gt;"));
+        assert!(svg.contains(">Time (s)</text>"));
+        assert!(svg.contains("rotate(-90"));
+
+        let json = to_json(&figure);
+        assert!(json.contains("\"title\":\"Fit\""));
+        assert!(json.contains(
+            "\"kind\":\"band\",\"x\":[0,5,10],\"lower\":[-1,-0.5,-1],\"upper\":[1,0.5,1]"
+        ));
+        assert!(json.contains("\"kind\":\"points\",\"x\":[5],\"y\":[0]"));
+        assert_eq!(json_numbers([1.5, f64::NAN]), "[1.5,null]");
+        assert_eq!(json_string("a \"b\"\n"), "\"a \\\"b\\\"\\n\"");
+
+        let image = to_image(&figure, 300, 200).unwrap();
+        assert_eq!(image.dimensions(), (300, 200));
+        // the point in the middle of the plot is painted red
+        let plot_center = image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0 == [255, 0, 0])
+            .count();
+        assert!(plot_center > 10);
+    }
+}
@@ -0,0 +1,289 @@
+use egui::Slider;
+use egui_plot::{Line, Plot, PlotPoints, Points, VLine};
+use nalgebra as na;
+
+use super::dates::{format_date, format_date_time, parse_date};
+use super::uncertainty_band;
+use crate::gp::{GaussianProcess, Kernel};
+
+/// Number of points the forecast is evaluated at.
+const RESOLUTION: usize = 200;
+
+/// The unit of time the kernel hyperparameters are expressed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum TimeUnit {
+    Hours,
+    Days,
+    Weeks,
+    Years,
+}
+
+impl TimeUnit {
+    const ALL: [TimeUnit; 4] = [
+        TimeUnit::Hours,
+        TimeUnit::Days,
+        TimeUnit::Weeks,
+        TimeUnit::Years,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            TimeUnit::Hours => "hours",
+            TimeUnit::Days => "days",
+            TimeUnit::Weeks => "weeks",
+            TimeUnit::Years => "years",
+        }
+    }
+
+    fn days(self) -> f64 {
+        match self {
+            TimeUnit::Hours => 1.0 / 24.0,
+            TimeUnit::Days => 1.0,
+            TimeUnit::Weeks => 7.0,
+            TimeUnit::Years => 365.25,
+        }
+    }
+}
+
+/// Parse rows of a date and a value separated by a tab, comma or semicolon, e.g. a CSV file
+/// with a header. Returns the points and the (1-based) line numbers that could not be parsed.
+fn parse_time_series(text: &str) -> (Vec<(f64, f64)>, Vec<usize>) {
+    let mut points = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split(['\t', ',', ';']);
+        let date = fields.next().and_then(parse_date);
+        let value = fields.next().and_then(|value| value.trim().parse().ok());
+        match (date, value) {
+            (Some(date), Some(value)) => points.push((date, value)),
+            _ if i == 0 => {} // probably a header
+            _ => invalid.push(i + 1),
+        }
+    }
+
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    (points, invalid)
+}
+
+/// A time series with dates on the x-axis, forecast a configurable horizon into the future.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ForecastView {
+    /// Timestamps, in days since the epoch.
+    t: Vec<f64>,
+    y: Vec<f64>,
+    unit: TimeUnit,
+    /// How far to forecast beyond the last observation, in `unit`.
+    horizon: f64,
+    import_text: String,
+    import_path: String,
+    #[serde(skip)]
+    import_status: Option<Result<String, String>>,
+    #[serde(skip)]
+    gp: Option<GaussianProcess<Kernel>>,
+}
+
+impl Default for ForecastView {
+    fn default() -> Self {
+        // three years of monthly observations with a trend and a yearly cycle
+        let start = parse_date("2020-01-01").unwrap_or_default();
+        let (t, y) = (0..36)
+            .map(|month| {
+                let t = start + month as f64 * 365.25 / 12.0;
+                let season = (2.0 * std::f64::consts::PI * month as f64 / 12.0).sin();
+                (t, 10.0 + 0.05 * month as f64 + season)
+            })
+            .unzip();
+        Self {
+            t,
+            y,
+            unit: TimeUnit::Years,
+            horizon: 1.0,
+            import_text: String::new(),
+            import_path: "time_series.csv".to_owned(),
+            import_status: None,
+            gp: None,
+        }
+    }
+}
+
+impl ForecastView {
+    /// The mean of the observations, which the GP is fitted relative to so that the forecast
+    /// reverts to it rather than to zero.
+    fn offset(&self) -> f64 {
+        if self.y.is_empty() {
+            0.0
+        } else {
+            self.y.iter().sum::<f64>() / self.y.len() as f64
+        }
+    }
+
+    /// Show the controls and the forecast plot, refitting if `changed` or the data is edited.
+    pub fn show(&mut self, ui: &mut egui::Ui, kernel: &Kernel, noise_sigma: f64, changed: bool) {
+        let mut changed = changed;
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("forecast_unit")
+                .selected_text(format!("Time unit: {}", self.unit.name()))
+                .show_ui(ui, |ui| {
+                    for unit in TimeUnit::ALL {
+                        if ui
+                            .selectable_value(&mut self.unit, unit, unit.name())
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("The unit of the kernel length scale and period");
+            ui.add(
+                Slider::new(&mut self.horizon, 0.0..=10.0)
+                    .text(format!("Forecast horizon ({})", self.unit.name())),
+            );
+        });
+        egui::CollapsingHeader::new("Import CSV").show(ui, |ui| {
+            if self.import_ui(ui) {
+                changed = true;
+            }
+        });
+
+        if changed || self.gp.is_none() {
+            let offset = self.offset();
+            let unit = self.unit.days();
+            self.gp = Some(GaussianProcess::new(
+                &na::DVector::from_iterator(self.t.len(), self.t.iter().map(|t| t / unit)),
+                &na::DVector::from_iterator(self.y.len(), self.y.iter().map(|y| y - offset)),
+                kernel.clone(),
+                noise_sigma,
+            ));
+        }
+        let Some(gp) = &self.gp else {
+            return;
+        };
+        let (Some(&first), Some(&last)) = (self.t.first(), self.t.last()) else {
+            ui.label("No data, import a time series to forecast.");
+            return;
+        };
+
+        let unit = self.unit.days();
+        let offset = self.offset();
+        let end = last + self.horizon * unit;
+        let grid = (0..RESOLUTION)
+            .map(|i| first + (end - first) * i as f64 / (RESOLUTION - 1) as f64)
+            .collect::<Vec<_>>();
+        let (means, variances) = gp.predict(&na::DVector::from_iterator(
+            grid.len(),
+            grid.iter().map(|t| t / unit),
+        ));
+        let means = means.add_scalar(offset);
+        let (lower, upper) = uncertainty_band(&grid, &means, &variances, egui::Color32::LIGHT_BLUE);
+        let mean = Line::new(
+            grid.iter()
+                .zip(means.iter())
+                .map(|(t, m)| [*t, *m])
+                .collect::<PlotPoints>(),
+        )
+        .color(egui::Color32::BLUE);
+        let points = Points::new(
+            self.t
+                .iter()
+                .zip(self.y.iter())
+                .map(|(t, y)| [*t, *y])
+                .collect::<Vec<_>>(),
+        )
+        .radius(3.0)
+        .color(egui::Color32::RED);
+
+        Plot::new("forecast_plot")
+            .x_axis_formatter(|mark, _| format_date(mark.value))
+            .label_formatter(|name, point| {
+                let name = if name.is_empty() {
+                    String::new()
+                } else {
+                    format!("{name}\n")
+                };
+                format!("{name}{}\ny = {:.3}", format_date_time(point.x), point.y)
+            })
+            .show(ui, |pui| {
+                pui.line(lower.name("Mean ± 2σ"));
+                pui.line(upper.name("Mean ± 2σ"));
+                pui.line(mean.name("Mean"));
+                pui.vline(
+                    VLine::new(last)
+                        .color(egui::Color32::GRAY)
+                        .name("Last observation"),
+                );
+                pui.points(points.name("Observations"));
+            });
+    }
+
+    /// Import a time series by pasting it or (on native) reading it from a file. Returns true
+    /// if the data was replaced.
+    fn import_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.label("Rows of a date (e.g. 2024-03-01) and a value, separated by commas or tabs:");
+        egui::ScrollArea::vertical()
+            .max_height(100.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.import_text)
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            });
+
+        let mut text = None;
+        ui.horizontal(|ui| {
+            if ui.button("Import pasted text").clicked() {
+                text = Some(self.import_text.clone());
+            }
+            if !cfg!(target_arch = "wasm32") {
+                ui.separator();
+                ui.label("File:");
+                ui.text_edit_singleline(&mut self.import_path);
+                if ui.button("Import file").clicked() {
+                    match std::fs::read_to_string(&self.import_path) {
+                        Ok(contents) => text = Some(contents),
+                        Err(e) => self.import_status = Some(Err(format!("Failed to read: {e}"))),
+                    }
+                }
+            }
+        });
+
+        let mut imported = false;
+        if let Some(text) = text {
+            let (points, invalid) = parse_time_series(&text);
+            self.import_status = Some(if points.is_empty() {
+                Err("No rows could be parsed.".to_owned())
+            } else {
+                (self.t, self.y) = points.into_iter().unzip();
+                imported = true;
+                if invalid.is_empty() {
+                    Ok(format!("Imported {} points.", self.t.len()))
+                } else {
+                    Ok(format!(
+                        "Imported {} points, skipped {} invalid rows.",
+                        self.t.len(),
+                        invalid.len()
+                    ))
+                }
+            });
+        }
+        match &self.import_status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(ui.visuals().error_fg_color, message);
+            }
+            None => {}
+        }
+
+        imported
+    }
+}
@@ -0,0 +1,224 @@
+use nalgebra as na;
+
+use super::figure::{to_image, Figure};
+
+/// What changes from frame to frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Animation {
+    /// The prior samples morph and loop back to the start.
+    Samples,
+    /// A hyperparameter sweeps from one value to another.
+    Parameter,
+}
+
+/// An export of the main plot being rendered, one frame per update so the animation also plays
+/// on screen.
+struct Run {
+    frame: usize,
+    frames: usize,
+    /// The index of the swept hyperparameter, its value before the export and the values of
+    /// the frames.
+    parameter: Option<(usize, f64, Vec<f64>)>,
+    /// The prior samples of the frames.
+    samples: Vec<Vec<na::DVector<f64>>>,
+}
+
+/// A dialog for rendering an animation of the main plot to a sequence of PNG images at a fixed
+/// size, e.g. for slides.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct FrameExportDialog {
+    pub open: bool,
+    /// Path of the files, without the frame number and the extension.
+    path: String,
+    width: u32,
+    height: u32,
+    frames: usize,
+    animation: Animation,
+    /// The index of the swept hyperparameter, with the noise after the kernel parameters.
+    parameter: usize,
+    from: f64,
+    to: f64,
+    #[serde(skip)]
+    run: Option<Run>,
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for FrameExportDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: "frames/gaussian_process".to_owned(),
+            width: 1200,
+            height: 800,
+            frames: 60,
+            animation: Animation::Samples,
+            parameter: 0,
+            from: 0.2,
+            to: 3.0,
+            run: None,
+            status: None,
+        }
+    }
+}
+
+/// What the dialog asks of the app.
+pub enum FrameRequest {
+    /// Start an export with [`FrameExportDialog::start`]: the animation, the number of frames
+    /// and the index of the parameter to sweep.
+    Start(Animation, usize, usize),
+    /// Set the swept parameter back to its value before the cancelled export.
+    Restore(usize, f64),
+}
+
+impl FrameExportDialog {
+    /// Show the dialog, with the names of the parameters that can be swept.
+    pub fn show(&mut self, ctx: &egui::Context, parameters: &[&str]) -> Option<FrameRequest> {
+        let mut open = self.open;
+        let mut request = None;
+        let running = self.run.is_some();
+        egui::Window::new("Export frames")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!running, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Files:");
+                        ui.text_edit_singleline(&mut self.path);
+                        ui.label("_0000.png");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Size:");
+                        ui.add(egui::DragValue::new(&mut self.width).range(100..=4000));
+                        ui.label("×");
+                        ui.add(egui::DragValue::new(&mut self.height).range(100..=4000));
+                        ui.label("pixels");
+                    });
+                    ui.add(egui::Slider::new(&mut self.frames, 2..=300).text("Frames"));
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.animation, Animation::Samples, "Samples")
+                            .on_hover_text("Prior samples morphing smoothly, looping at the end");
+                        ui.radio_value(&mut self.animation, Animation::Parameter, "Sweep")
+                            .on_hover_text("A hyperparameter changing from one value to another");
+                    });
+                    if self.animation == Animation::Parameter {
+                        self.parameter = self.parameter.min(parameters.len().saturating_sub(1));
+                        egui::ComboBox::from_label("Parameter")
+                            .selected_text(parameters.get(self.parameter).copied().unwrap_or(""))
+                            .show_ui(ui, |ui| {
+                                for (i, name) in parameters.iter().enumerate() {
+                                    ui.selectable_value(&mut self.parameter, i, *name);
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            ui.label("From");
+                            ui.add(egui::DragValue::new(&mut self.from).speed(0.01));
+                            ui.label("to");
+                            ui.add(egui::DragValue::new(&mut self.to).speed(0.01));
+                        });
+                    }
+                    if ui.button("Export").clicked() {
+                        request = Some(FrameRequest::Start(
+                            self.animation,
+                            self.frames,
+                            self.parameter,
+                        ));
+                    }
+                });
+                if let Some(Run { frame, frames, .. }) = self.run {
+                    ui.add(
+                        egui::ProgressBar::new(frame as f32 / frames as f32)
+                            .text(format!("Frame {} of {frames}", frame + 1)),
+                    );
+                    if ui.button("Cancel").clicked() {
+                        let restore = self.finish(Ok(format!("Cancelled after {frame} frames")));
+                        request = restore.map(|(index, value)| FrameRequest::Restore(index, value));
+                    }
+                }
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, message);
+                    }
+                    None => {}
+                }
+            });
+        self.open = open;
+        request
+    }
+
+    /// Start rendering frames, sweeping the selected parameter from its current `value` or
+    /// showing the prior samples of each frame.
+    pub fn start(&mut self, value: f64, samples: Vec<Vec<na::DVector<f64>>>) {
+        let parameter = (self.animation == Animation::Parameter).then(|| {
+            let values = (0..self.frames)
+                .map(|i| self.from + (self.to - self.from) * i as f64 / (self.frames - 1) as f64)
+                .collect();
+            (self.parameter, value, values)
+        });
+        if let Some(directory) = std::path::Path::new(&self.path).parent() {
+            if let Err(e) = std::fs::create_dir_all(directory) {
+                self.status = Some(Err(format!(
+                    "Failed to create {}: {e}",
+                    directory.display()
+                )));
+                return;
+            }
+        }
+        self.status = None;
+        self.run = Some(Run {
+            frame: 0,
+            frames: self.frames,
+            parameter,
+            samples,
+        });
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// The index and value of the swept parameter in the current frame.
+    pub fn parameter(&self) -> Option<(usize, f64)> {
+        let run = self.run.as_ref()?;
+        let (index, _, values) = run.parameter.as_ref()?;
+        Some((*index, values[run.frame]))
+    }
+
+    /// The prior samples of the current frame, when animating them.
+    pub fn samples(&self) -> Option<&Vec<na::DVector<f64>>> {
+        let run = self.run.as_ref()?;
+        run.samples.get(run.frame)
+    }
+
+    /// Write the figure as the current frame and move on to the next one. Returns the index
+    /// and the original value of the swept parameter once the export is done, to restore it.
+    pub fn save_frame(&mut self, figure: &Figure) -> Option<(usize, f64)> {
+        let run = self.run.as_mut()?;
+        let path = format!("{}_{:04}.png", self.path, run.frame);
+        let saved = to_image(figure, self.width, self.height).and_then(|image| {
+            image
+                .save(&path)
+                .map_err(|e| format!("Failed to write {path}: {e}"))
+        });
+        run.frame += 1;
+        match saved {
+            Err(e) => self.finish(Err(e)),
+            Ok(()) if run.frame == run.frames => {
+                let message = format!("Exported {} frames to {}_*.png", run.frames, self.path);
+                self.finish(Ok(message))
+            }
+            Ok(()) => None,
+        }
+    }
+
+    /// Stop the export, returning the swept parameter and its original value.
+    fn finish(&mut self, status: Result<String, String>) -> Option<(usize, f64)> {
+        self.status = Some(status);
+        let (index, value, _) = self.run.take()?.parameter?;
+        Some((index, value))
+    }
+}
@@ -0,0 +1,258 @@
+use egui::Slider;
+use egui_plot::{Line, Plot, PlotPoints, Points, VLine};
+use nalgebra as na;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+use super::{prediction_grid, uncertainty_band};
+use crate::gp::{GaussianProcess, Kernel};
+
+/// A hidden objective function, made up of a few gaussian bumps, to be maximized.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+struct Objective {
+    /// `(center, width, height)` of each bump.
+    bumps: Vec<(f64, f64, f64)>,
+}
+
+impl Objective {
+    fn random(rng: &mut impl Rng) -> Self {
+        let bumps = (0..rng.gen_range(3..=6))
+            .map(|_| {
+                (
+                    rng.gen_range(0.0..10.0),
+                    rng.gen_range(0.3..1.5),
+                    rng.gen_range(-1.0..2.0),
+                )
+            })
+            .collect();
+        Self { bumps }
+    }
+
+    fn eval(&self, x: f64) -> f64 {
+        self.bumps
+            .iter()
+            .map(|(center, width, height)| height * (-0.5 * ((x - center) / width).powi(2)).exp())
+            .sum()
+    }
+
+    /// The maximum value on the plotted domain, found by a dense search.
+    fn max(&self) -> f64 {
+        (0..=1000)
+            .map(|i| self.eval(i as f64 / 100.0))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+}
+
+/// A game of Bayesian optimization: find the maximum of a hidden function with as few noisy
+/// queries as possible, either picking them by hand or following the expected improvement.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct GameView {
+    objective: Objective,
+    /// Standard deviation of the noise on each observation.
+    noise: f64,
+    budget: usize,
+    x: Vec<f64>,
+    y: Vec<f64>,
+    reveal: bool,
+    seed: u64,
+    #[serde(skip)]
+    gp: Option<GaussianProcess<Kernel>>,
+}
+
+impl Default for GameView {
+    fn default() -> Self {
+        let mut game = Self {
+            objective: Objective::default(),
+            noise: 0.1,
+            budget: 10,
+            x: Vec::new(),
+            y: Vec::new(),
+            reveal: false,
+            seed: 0,
+            gp: None,
+        };
+        game.new_game();
+        game
+    }
+}
+
+impl GameView {
+    fn new_game(&mut self) {
+        self.seed = self.seed.wrapping_add(1);
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(self.seed);
+        self.objective = Objective::random(&mut rng);
+        self.x.clear();
+        self.y.clear();
+        self.reveal = false;
+        self.gp = None;
+    }
+
+    fn game_over(&self) -> bool {
+        self.x.len() >= self.budget
+    }
+
+    /// Observe the hidden function with noise at `x`.
+    fn query(&mut self, x: f64) {
+        // a different noise sample for every query of the game
+        let seed = self.seed ^ ((self.x.len() as u64) << 32);
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(seed);
+        let noise = self.noise * rng.sample::<f64, _>(StandardNormal);
+        self.x.push(x);
+        self.y.push(self.objective.eval(x) + noise);
+        self.gp = None;
+    }
+
+    /// The regret of the best query so far, i.e. how far its true value is from the maximum.
+    fn regret(&self) -> Option<f64> {
+        let best = self
+            .x
+            .iter()
+            .map(|x| self.objective.eval(*x))
+            .fold(f64::NEG_INFINITY, f64::max);
+        (!self.x.is_empty()).then(|| self.objective.max() - best)
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, kernel: &Kernel, noise_sigma: f64, changed: bool) {
+        ui.horizontal(|ui| {
+            if ui.button("New game").clicked() {
+                self.new_game();
+            }
+            ui.add_enabled(
+                self.x.is_empty(),
+                Slider::new(&mut self.budget, 1..=30).text("Query budget"),
+            );
+            ui.add_enabled(
+                self.x.is_empty(),
+                Slider::new(&mut self.noise, 0.0..=1.0).text("Observation noise"),
+            );
+        });
+
+        if changed || self.gp.is_none() {
+            self.gp = Some(GaussianProcess::new(
+                &na::DVector::from_vec(self.x.clone()),
+                &na::DVector::from_vec(self.y.clone()),
+                kernel.clone(),
+                noise_sigma,
+            ));
+        }
+        let Some(gp) = &self.gp else {
+            return;
+        };
+
+        let grid = prediction_grid();
+        let grid_vector = na::DVector::from_vec(grid.clone());
+        let best = self.y.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let improvement = if self.y.is_empty() {
+            // without observations, anything is an improvement over the prior mean
+            gp.expected_improvement(&grid_vector, 0.0)
+        } else {
+            gp.expected_improvement(&grid_vector, best)
+        };
+        let suggestion = grid[improvement.argmax().0];
+
+        let game_over = self.game_over();
+        let regret = self.regret();
+        let mut query = None;
+        ui.horizontal(|ui| {
+            ui.label(format!("Queries: {} / {}", self.x.len(), self.budget));
+            if let Some(regret) = regret {
+                ui.separator();
+                ui.label(format!("Regret: {regret:.3}"))
+                    .on_hover_text("How far the best query is from the true maximum");
+            }
+            ui.separator();
+            if ui
+                .add_enabled(!game_over, egui::Button::new("Query at max EI"))
+                .on_hover_text("Let the expected improvement choose the next query")
+                .clicked()
+            {
+                query = Some(suggestion);
+            }
+            ui.checkbox(&mut self.reveal, "Reveal hidden function");
+        });
+        if game_over {
+            ui.label("Out of queries! The hidden function is revealed.");
+        } else {
+            ui.label("Click in the plot to query the hidden function at that point.");
+        }
+
+        let (means, variances) = gp.predict(&grid_vector);
+        let (lower, upper) = uncertainty_band(&grid, &means, &variances, egui::Color32::LIGHT_BLUE);
+        let line = |values: &na::DVector<f64>| {
+            Line::new(
+                grid.iter()
+                    .zip(values.iter())
+                    .map(|(x, y)| [*x, *y])
+                    .collect::<PlotPoints>(),
+            )
+        };
+        let hidden = (self.reveal || game_over).then(|| {
+            Line::new(
+                grid.iter()
+                    .map(|x| [*x, self.objective.eval(*x)])
+                    .collect::<PlotPoints>(),
+            )
+            .color(egui::Color32::DARK_GREEN)
+            .style(egui_plot::LineStyle::dashed_loose())
+            .name("Hidden function")
+        });
+
+        let plot_height = ui.available_height() * 0.7;
+        let response = Plot::new("game_plot")
+            .height(plot_height)
+            .link_axis("game_plot", true, false)
+            .link_cursor("game_plot", true, false)
+            .show(ui, |pui| {
+                pui.line(lower.name("Mean ± 2σ"));
+                pui.line(upper.name("Mean ± 2σ"));
+                pui.line(line(&means).color(egui::Color32::BLUE).name("Mean"));
+                if let Some(hidden) = hidden {
+                    pui.line(hidden);
+                }
+                pui.points(
+                    Points::new(
+                        self.x
+                            .iter()
+                            .zip(self.y.iter())
+                            .map(|(x, y)| [*x, *y])
+                            .collect::<Vec<_>>(),
+                    )
+                    .radius(5.0)
+                    .color(egui::Color32::RED)
+                    .name("Queries"),
+                );
+                pui.response()
+                    .clicked()
+                    .then(|| pui.pointer_coordinate())
+                    .flatten()
+            });
+        if let (Some(pos), false) = (response.inner, game_over) {
+            query = Some(pos.x);
+        }
+
+        Plot::new("game_acquisition_plot")
+            .link_axis("game_plot", true, false)
+            .link_cursor("game_plot", true, false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .show(ui, |pui| {
+                pui.line(
+                    line(&improvement)
+                        .color(egui::Color32::from_rgb(230, 130, 0))
+                        .name("Expected improvement"),
+                );
+                pui.vline(
+                    VLine::new(suggestion)
+                        .color(egui::Color32::GRAY)
+                        .name("Max EI"),
+                );
+            });
+
+        if let Some(x) = query {
+            self.query(x);
+            ui.ctx().request_repaint();
+        }
+    }
+}
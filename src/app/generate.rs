@@ -0,0 +1,126 @@
+use egui::Slider;
+use nalgebra as na;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+use super::dataset::Dataset;
+use super::expr::Expr;
+use crate::gp::{GaussianProcess, Kernel};
+
+/// Where the function that is observed with noise comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum Source {
+    /// A sample from the prior of the current kernel, e.g. to check whether its hyperparameters
+    /// can be recovered from the data.
+    Prior,
+    /// A formula typed in by the user, for building specific scenarios.
+    Formula,
+}
+
+/// A dialog for generating training data by observing a function with noise at random
+/// locations.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct GenerateDialog {
+    pub open: bool,
+    source: Source,
+    formula: String,
+    num_points: usize,
+    noise: f64,
+    #[serde(skip)]
+    seed: u64,
+}
+
+impl Default for GenerateDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            source: Source::Prior,
+            formula: "sin(x) + 0.1*x^2".to_owned(),
+            num_points: 20,
+            noise: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+impl GenerateDialog {
+    /// Returns true if the dataset was replaced by generated data.
+    pub fn show(&mut self, ctx: &egui::Context, kernel: &Kernel, dataset: &mut Dataset) -> bool {
+        let mut generate = false;
+        let mut formula = None;
+        let mut open = self.open;
+        egui::Window::new("Generate data")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.source, Source::Prior, "Sample from prior");
+                    ui.radio_value(&mut self.source, Source::Formula, "Formula");
+                });
+                match self.source {
+                    Source::Prior => {
+                        ui.label(format!(
+                            "Sample a function from the prior of the current {} kernel and \
+                            observe it with noise at random locations.",
+                            kernel.name()
+                        ));
+                    }
+                    Source::Formula => {
+                        ui.horizontal(|ui| {
+                            ui.label("y =");
+                            ui.text_edit_singleline(&mut self.formula);
+                        });
+                        formula = Some(Expr::parse(&self.formula));
+                        if let Some(Err(message)) = &formula {
+                            ui.colored_label(ui.visuals().error_fg_color, message);
+                        }
+                    }
+                }
+                ui.add(Slider::new(&mut self.num_points, 1..=100).text("Number of points"));
+                ui.add(Slider::new(&mut self.noise, 0.0..=2.0).text("Noise standard deviation"));
+                let valid = !matches!(formula, Some(Err(_)));
+                generate = ui
+                    .add_enabled(valid, egui::Button::new("Generate"))
+                    .clicked();
+            });
+        self.open = open;
+
+        if !generate {
+            return false;
+        }
+
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(self.seed);
+        self.seed += 1;
+
+        let mut x = (0..self.num_points)
+            .map(|_| rng.gen_range(0.0..10.0))
+            .collect::<Vec<f64>>();
+        x.sort_by(f64::total_cmp);
+
+        let f = match formula {
+            Some(Ok(expr)) => x.iter().map(|&x| expr.eval(x)).collect(),
+            Some(Err(_)) => return false,
+            None => {
+                let prior = GaussianProcess::prior(kernel.clone(), 0.0);
+                let Some(f) = prior
+                    .sample(&na::DVector::from_column_slice(&x), 1, &mut rng)
+                    .pop()
+                else {
+                    return false;
+                };
+                f.iter().copied().collect::<Vec<_>>()
+            }
+        };
+
+        // skip points where the formula is undefined, e.g. `ln(x)` at negative x
+        let (x, y) = x
+            .into_iter()
+            .zip(f)
+            .filter(|(_, f)| f.is_finite())
+            .map(|(x, f)| (x, f + self.noise * rng.sample::<f64, _>(StandardNormal)))
+            .unzip();
+        dataset.set_points(x, y);
+        true
+    }
+}
@@ -0,0 +1,69 @@
+use nalgebra as na;
+
+/// Control points of an approximation of the viridis colormap.
+const VIRIDIS: [[u8; 3]; 5] = [
+    [68, 1, 84],
+    [59, 82, 139],
+    [33, 145, 140],
+    [94, 201, 98],
+    [253, 231, 37],
+];
+
+/// Map a value in `0.0..=1.0` to a color by interpolating between the colormap control points.
+pub fn colormap(t: f64) -> egui::Color32 {
+    let t = if t.is_finite() {
+        t.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let scaled = t * (VIRIDIS.len() - 1) as f64;
+    let index = (scaled.floor() as usize).min(VIRIDIS.len() - 2);
+    let fraction = scaled - index as f64;
+
+    let [r, g, b] = std::array::from_fn(|c| {
+        let from = VIRIDIS[index][c] as f64;
+        let to = VIRIDIS[index + 1][c] as f64;
+        (from + (to - from) * fraction).round() as u8
+    });
+    egui::Color32::from_rgb(r, g, b)
+}
+
+/// Paint a matrix as a square grid of colored cells, scaled between its min and max values.
+/// Hovering a cell shows its index and value.
+pub fn heatmap(ui: &mut egui::Ui, matrix: &na::DMatrix<f64>) -> egui::Response {
+    let size = ui.available_width().min(ui.available_height()).max(50.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::hover());
+    let rect = response.rect;
+
+    if matrix.is_empty() {
+        return response;
+    }
+
+    let min = matrix.min();
+    let max = matrix.max();
+    let range = if max > min { max - min } else { 1.0 };
+
+    let cell = egui::vec2(
+        rect.width() / matrix.ncols() as f32,
+        rect.height() / matrix.nrows() as f32,
+    );
+    for i in 0..matrix.nrows() {
+        for j in 0..matrix.ncols() {
+            let min_corner = rect.min + egui::vec2(j as f32 * cell.x, i as f32 * cell.y);
+            painter.rect_filled(
+                egui::Rect::from_min_size(min_corner, cell),
+                0.0,
+                colormap((matrix[(i, j)] - min) / range),
+            );
+        }
+    }
+
+    if let Some(pos) = response.hover_pos() {
+        let j = (((pos.x - rect.min.x) / cell.x) as usize).min(matrix.ncols() - 1);
+        let i = (((pos.y - rect.min.y) / cell.y) as usize).min(matrix.nrows() - 1);
+        let value = matrix[(i, j)];
+        return response.on_hover_text_at_pointer(format!("[{i}, {j}] = {value:.4}"));
+    }
+
+    response
+}
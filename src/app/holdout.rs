@@ -0,0 +1,98 @@
+use super::dataset::Dataset;
+use super::Model;
+use crate::gp::{holdout_metrics, holdout_split};
+
+/// The color the held-out points are drawn in, whatever their dataset.
+pub const TEST_COLOR: egui::Color32 = egui::Color32::from_rgb(120, 120, 120);
+
+/// A window holding out a random part of the active dataset as a test set, showing how well the
+/// fit to the rest predicts it.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct HoldoutDialog {
+    pub open: bool,
+    /// The share of the points to hold out, in percent.
+    percent: f64,
+    /// Seed of the last split, increased for every new one.
+    seed: u64,
+}
+
+impl Default for HoldoutDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            percent: 20.0,
+            seed: 0,
+        }
+    }
+}
+
+impl HoldoutDialog {
+    /// Returns true if the test set changed, which requires a refit.
+    pub fn show(&mut self, ctx: &egui::Context, dataset: &mut Dataset, model: Model) -> bool {
+        let mut changed = false;
+        let mut open = self.open;
+        egui::Window::new("Train/test evaluation")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.percent, 5.0..=50.0)
+                        .text("% held out")
+                        .integer(),
+                );
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("New split")
+                        .on_hover_text("Hold out a new random selection of the active dataset")
+                        .clicked()
+                    {
+                        self.seed += 1;
+                        dataset.held_out =
+                            holdout_split(dataset.x.len(), self.percent / 100.0, self.seed);
+                        changed = true;
+                    }
+                    if ui.button("Use all for training").clicked() {
+                        dataset.held_out.clear();
+                        changed = true;
+                    }
+                });
+                ui.separator();
+
+                let (x, y) = dataset.test_points();
+                let metrics = dataset
+                    .model(model)
+                    .and_then(|fit| holdout_metrics(fit, &x, &y));
+                let Some(metrics) = metrics else {
+                    ui.label("No points held out.");
+                    return;
+                };
+                ui.label(format!(
+                    "{} of {} points held out (gray).",
+                    x.len(),
+                    dataset.x.len()
+                ));
+                egui::Grid::new("holdout_metrics")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("RMSE");
+                        ui.label(format!("{:.4}", metrics.rmse));
+                        ui.end_row();
+
+                        ui.label("NLPD")
+                            .on_hover_text("Mean negative log predictive density, lower is better");
+                        ui.label(format!("{:.4}", metrics.nlpd));
+                        ui.end_row();
+
+                        ui.label("Coverage").on_hover_text(
+                            "Share of the held-out points within the ±2σ band, including the \
+                             noise, which should be about 95 %",
+                        );
+                        ui.label(format!("{:.1} %", 100.0 * metrics.coverage));
+                        ui.end_row();
+                    });
+            });
+        self.open = open;
+        changed
+    }
+}
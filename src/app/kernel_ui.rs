@@ -0,0 +1,169 @@
+use super::ranges::SliderRanges;
+use crate::gp::{Kernel, KernelParams, MaternKernel, MaternSmoothness, PeriodicKernel, RbfKernel};
+
+/// All kernel types that can be chosen in the UI, using the given shared hyperparameters.
+fn kernel_choices(sigma: f64, length_scale: f64) -> [Kernel; 5] {
+    let matern = |smoothness| {
+        Kernel::Matern(MaternKernel {
+            smoothness,
+            sigma,
+            length_scale,
+        })
+    };
+    [
+        Kernel::Rbf(RbfKernel {
+            sigma,
+            length_scale,
+        }),
+        matern(MaternSmoothness::Half),
+        matern(MaternSmoothness::ThreeHalves),
+        matern(MaternSmoothness::FiveHalves),
+        Kernel::Periodic(PeriodicKernel {
+            sigma,
+            length_scale,
+            period: 2.0,
+        }),
+    ]
+}
+
+/// The length scale parameter, which all base kernel types have. Composite kernels give the one
+/// of their first term.
+pub fn length_scale_mut(kernel: &mut Kernel) -> &mut f64 {
+    match kernel {
+        Kernel::Rbf(k) => &mut k.length_scale,
+        Kernel::Matern(k) => &mut k.length_scale,
+        Kernel::Periodic(k) => &mut k.length_scale,
+        Kernel::Sum(terms) | Kernel::Product(terms) => length_scale_mut(&mut terms[0]),
+    }
+}
+
+/// The signal variance parameter, which all base kernel types have. Composite kernels give the
+/// one of their first term.
+pub fn sigma_mut(kernel: &mut Kernel) -> &mut f64 {
+    match kernel {
+        Kernel::Rbf(k) => &mut k.sigma,
+        Kernel::Matern(k) => &mut k.sigma,
+        Kernel::Periodic(k) => &mut k.sigma,
+        Kernel::Sum(terms) | Kernel::Product(terms) => sigma_mut(&mut terms[0]),
+    }
+}
+
+/// A new term for a composite kernel.
+fn default_term() -> Kernel {
+    Kernel::Rbf(RbfKernel {
+        sigma: 1.0,
+        length_scale: 1.0,
+    })
+}
+
+/// Controls for choosing the kernel type and its hyperparameters, building composite kernels as
+/// a tree of terms, with sliders in the given ranges. Returns true if the kernel was changed.
+pub fn kernel_controls(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    kernel: &mut Kernel,
+    ranges: &mut SliderRanges,
+) -> bool {
+    let mut changed = false;
+
+    let sigma = *sigma_mut(kernel);
+    let length_scale = *length_scale_mut(kernel);
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(kernel.name())
+        .show_ui(ui, |ui| {
+            for choice in kernel_choices(sigma, length_scale) {
+                if ui
+                    .selectable_label(kernel.name() == choice.name(), choice.name())
+                    .clicked()
+                    && kernel.name() != choice.name()
+                {
+                    *kernel = choice;
+                    changed = true;
+                }
+            }
+
+            ui.separator();
+            for (name, description) in [
+                ("Sum", "Add another kernel to this one"),
+                ("Product", "Multiply this kernel by another one"),
+            ] {
+                if ui
+                    .selectable_label(kernel.name() == name, name)
+                    .on_hover_text(description)
+                    .clicked()
+                    && kernel.name() != name
+                {
+                    // switching between sums and products keeps the terms
+                    let terms = match std::mem::replace(kernel, Kernel::Sum(Vec::new())) {
+                        Kernel::Sum(terms) | Kernel::Product(terms) => terms,
+                        base => vec![base, default_term()],
+                    };
+                    *kernel = if name == "Sum" {
+                        Kernel::Sum(terms)
+                    } else {
+                        Kernel::Product(terms)
+                    };
+                    changed = true;
+                }
+            }
+        });
+
+    if let Kernel::Sum(terms) | Kernel::Product(terms) = kernel {
+        if terms_controls(ui, id_salt, terms, ranges) {
+            changed = true;
+        }
+        return changed;
+    }
+
+    let names = kernel.param_names();
+    let bounds = kernel.param_bounds();
+    for ((name, bounds), value) in names.into_iter().zip(bounds).zip(kernel.params_mut()) {
+        if ranges
+            .slider(ui, value, name, bounds, format!("Kernel {name}"))
+            .changed()
+        {
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+/// Controls for each term of a composite kernel, which can be composite themselves.
+fn terms_controls(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    terms: &mut Vec<Kernel>,
+    ranges: &mut SliderRanges,
+) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+
+    let count = terms.len();
+    ui.indent(id_salt, |ui| {
+        for (i, term) in terms.iter_mut().enumerate() {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Term {}", i + 1));
+                    // a composite kernel always keeps at least one term
+                    if count > 1 && ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+                if kernel_controls(ui, &format!("{id_salt}_{i}"), term, ranges) {
+                    changed = true;
+                }
+            });
+        }
+        if ui.button("Add term").clicked() {
+            terms.push(default_term());
+            changed = true;
+        }
+    });
+
+    if let Some(i) = remove {
+        terms.remove(i);
+        changed = true;
+    }
+    changed
+}
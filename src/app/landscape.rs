@@ -0,0 +1,231 @@
+use egui_plot::{Plot, PlotImage, PlotPoint, Points};
+use nalgebra as na;
+
+use super::dataset::Dataset;
+use super::heatmap::colormap;
+use super::kernel_ui::{length_scale_mut, sigma_mut};
+use crate::gp::{GaussianProcess, Kernel};
+
+/// Range of the swept hyperparameters, in log10 units.
+const LOG_RANGE: std::ops::RangeInclusive<f64> = -2.0..=2.0;
+
+/// Number of grid cells along each axis of the landscape.
+const RESOLUTION: usize = 30;
+
+/// How far below the maximum (in log likelihood units) the colormap extends.
+const COLOR_RANGE: f64 = 50.0;
+
+/// A hyperparameter of the model that can be swept over.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum Hyperparameter {
+    LengthScale,
+    Sigma,
+    Noise,
+}
+
+impl Hyperparameter {
+    const ALL: [Hyperparameter; 3] = [
+        Hyperparameter::LengthScale,
+        Hyperparameter::Sigma,
+        Hyperparameter::Noise,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Hyperparameter::LengthScale => "Kernel length scale",
+            Hyperparameter::Sigma => "Kernel sigma",
+            Hyperparameter::Noise => "Noise sigma",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The hyperparameter values `[length_scale, sigma, noise]`, indexed by [`Hyperparameter`].
+pub type Hyperparameters = [f64; 3];
+
+/// The inputs the landscape was computed from, so we know when to recompute it.
+#[derive(PartialEq)]
+struct LandscapeKey {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    weights: Vec<f64>,
+    jitter: f64,
+    kernel: Kernel,
+    fixed: Hyperparameters,
+    axes: (Hyperparameter, Hyperparameter),
+}
+
+/// A heatmap of the log marginal likelihood over a grid of two hyperparameters.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct LandscapeView {
+    x_param: Hyperparameter,
+    y_param: Hyperparameter,
+    #[serde(skip)]
+    cache: Option<(LandscapeKey, egui::TextureHandle)>,
+}
+
+impl Default for LandscapeView {
+    fn default() -> Self {
+        Self {
+            x_param: Hyperparameter::LengthScale,
+            y_param: Hyperparameter::Noise,
+            cache: None,
+        }
+    }
+}
+
+impl LandscapeView {
+    /// Show the landscape for the training points of the dataset, fit with the given jitter, and
+    /// kernel type. Clicking in the heatmap returns the hyperparameters at that location.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        dataset: &Dataset,
+        jitter: f64,
+        kernel: &Kernel,
+        current: Hyperparameters,
+    ) -> Option<Hyperparameters> {
+        ui.horizontal(|ui| {
+            parameter_combo(ui, "landscape_x", "x-axis", &mut self.x_param);
+            parameter_combo(ui, "landscape_y", "y-axis", &mut self.y_param);
+        });
+        if self.x_param == self.y_param {
+            ui.label("Choose two different hyperparameters.");
+            return None;
+        }
+
+        // the swept parameters do not influence the landscape, so don't recompute for them
+        let mut fixed = current;
+        fixed[self.x_param.index()] = 0.0;
+        fixed[self.y_param.index()] = 0.0;
+        let mut kernel = kernel.clone();
+        *length_scale_mut(&mut kernel) = 0.0;
+        *sigma_mut(&mut kernel) = 0.0;
+        let (x, y) = dataset.training_points();
+        let key = LandscapeKey {
+            x,
+            y,
+            weights: dataset.training_weights(),
+            jitter,
+            kernel,
+            fixed,
+            axes: (self.x_param, self.y_param),
+        };
+        if self
+            .cache
+            .as_ref()
+            .map_or(true, |(cached, _)| *cached != key)
+        {
+            let image = self.compute(&key, current);
+            let texture =
+                ui.ctx()
+                    .load_texture("lml_landscape", image, egui::TextureOptions::NEAREST);
+            self.cache = Some((key, texture));
+        }
+        let (_, texture) = self.cache.as_ref()?;
+
+        ui.label(format!(
+            "log10({}) vs log10({}), click to select.",
+            self.x_param.label(),
+            self.y_param.label()
+        ));
+
+        let width = LOG_RANGE.end() - LOG_RANGE.start();
+        let center = (LOG_RANGE.start() + LOG_RANGE.end()) / 2.0;
+        let marker = [
+            current[self.x_param.index()].log10(),
+            current[self.y_param.index()].log10(),
+        ];
+
+        let response = Plot::new("lml_landscape_plot")
+            .data_aspect(1.0)
+            .allow_scroll(false)
+            .show(ui, |pui| {
+                pui.image(PlotImage::new(
+                    texture,
+                    PlotPoint::new(center, center),
+                    [width as f32, width as f32],
+                ));
+                pui.points(
+                    Points::new(vec![marker])
+                        .color(egui::Color32::RED)
+                        .radius(5.0)
+                        .name("Current"),
+                );
+                pui.response()
+                    .clicked()
+                    .then(|| pui.pointer_coordinate())
+                    .flatten()
+            });
+
+        let clicked = response.inner?;
+        let mut selected = current;
+        selected[self.x_param.index()] = 10f64.powf(clicked.x);
+        selected[self.y_param.index()] = 10f64.powf(clicked.y);
+        Some(selected)
+    }
+
+    /// Evaluate the log marginal likelihood on the grid and map it to colors.
+    fn compute(&self, key: &LandscapeKey, current: Hyperparameters) -> egui::ColorImage {
+        let x = na::DVector::from_column_slice(&key.x);
+        let y = na::DVector::from_column_slice(&key.y);
+        let weights = na::DVector::from_column_slice(&key.weights);
+        let log_value = |i: usize| {
+            LOG_RANGE.start()
+                + (i as f64 + 0.5) / RESOLUTION as f64 * (LOG_RANGE.end() - LOG_RANGE.start())
+        };
+
+        // image rows go from top to bottom, so the y-axis is flipped
+        let mut values = Vec::with_capacity(RESOLUTION * RESOLUTION);
+        for row in (0..RESOLUTION).rev() {
+            for col in 0..RESOLUTION {
+                let mut params = current;
+                params[self.x_param.index()] = 10f64.powf(log_value(col));
+                params[self.y_param.index()] = 10f64.powf(log_value(row));
+                let [length_scale, sigma, noise] = params;
+
+                let mut kernel = key.kernel.clone();
+                *length_scale_mut(&mut kernel) = length_scale;
+                *sigma_mut(&mut kernel) = sigma;
+                // hyperparameters that can not be fit get the lowest color
+                values.push(
+                    GaussianProcess::builder()
+                        .kernel(kernel)
+                        .noise(noise)
+                        .jitter(key.jitter)
+                        .weights(weights.clone())
+                        .build(&x, &y)
+                        .map_or(f64::NEG_INFINITY, |gp| gp.log_marginal_likelihood()),
+                );
+            }
+        }
+
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let low = min.max(max - COLOR_RANGE);
+        let range = if max > low { max - low } else { 1.0 };
+
+        let pixels = values
+            .iter()
+            .map(|value| colormap((value - low) / range))
+            .collect();
+        egui::ColorImage {
+            size: [RESOLUTION, RESOLUTION],
+            pixels,
+        }
+    }
+}
+
+fn parameter_combo(ui: &mut egui::Ui, id: &str, label: &str, value: &mut Hyperparameter) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(format!("{label}: {}", value.label()))
+        .show_ui(ui, |ui| {
+            for parameter in Hyperparameter::ALL {
+                ui.selectable_value(value, parameter, parameter.label());
+            }
+        });
+}
@@ -0,0 +1,59 @@
+/// Which elements of the main plot are drawn, e.g. to reveal them one at a time in a demo.
+/// The prior itself is shown with its own checkbox.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlotLayers {
+    pub means: bool,
+    /// The ±2σ bands around the means.
+    pub bands: bool,
+    /// The function samples drawn from the prior.
+    pub samples: bool,
+    pub points: bool,
+    /// The bars showing the observation noise on the points.
+    pub error_bars: bool,
+    /// The frozen fits and the prediction averaged over hyperparameter samples.
+    pub snapshots: bool,
+}
+
+impl Default for PlotLayers {
+    fn default() -> Self {
+        Self {
+            means: true,
+            bands: true,
+            samples: true,
+            points: true,
+            error_bars: true,
+            snapshots: true,
+        }
+    }
+}
+
+impl PlotLayers {
+    fn all_mut(&mut self) -> [(&mut bool, &'static str); 6] {
+        [
+            (&mut self.means, "Means"),
+            (&mut self.bands, "Uncertainty bands"),
+            (&mut self.samples, "Prior samples"),
+            (&mut self.points, "Training points"),
+            (&mut self.error_bars, "Noise bars"),
+            (&mut self.snapshots, "Snapshots"),
+        ]
+    }
+
+    /// A checkbox for each element, and buttons to show or hide them all.
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        for (visible, name) in self.all_mut() {
+            ui.checkbox(visible, name);
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Show all").clicked() {
+                *self = Self::default();
+            }
+            if ui.button("Hide all").clicked() {
+                for (visible, _) in self.all_mut() {
+                    *visible = false;
+                }
+            }
+        });
+    }
+}
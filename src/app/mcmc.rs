@@ -0,0 +1,307 @@
+use egui_plot::{Bar, BarChart, Plot, Points};
+use nalgebra as na;
+use rand::SeedableRng;
+use web_time::Instant;
+
+use super::dataset::Dataset;
+use super::optimize::{progress_window, ProgressAction, FRAME_BUDGET};
+use crate::gp::{GaussianProcess, HyperparameterSampler, Kernel, KernelParams};
+
+/// Fraction of the chain discarded as burn-in before it has reached the posterior.
+const BURN_IN: f64 = 0.2;
+
+/// Number of bins in the histograms of the hyperparameters.
+const BINS: usize = 20;
+
+/// The number of samples the averaged prediction is computed from, as every sample needs its
+/// own fit.
+const AVERAGED_SAMPLES: usize = 20;
+
+/// A chain being sampled, a number of steps per frame.
+struct Run {
+    sampler: HyperparameterSampler<Kernel>,
+    rng: rand::rngs::SmallRng,
+    trace: Vec<f64>,
+}
+
+/// Samples of the hyperparameter posterior from MCMC, shown as histograms and a pair plot.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct HyperparameterPosterior {
+    pub open: bool,
+    num_samples: usize,
+    /// Show the prediction averaged over the sampled hyperparameters in the main plot.
+    pub average: bool,
+    /// The hyperparameters shown against each other in the pair plot.
+    pair: (usize, usize),
+    #[serde(skip)]
+    run: Option<Run>,
+    /// The kernel the samples were drawn for, with its parameter names.
+    #[serde(skip)]
+    kernel: Option<Kernel>,
+    /// The samples after the burn-in, as `[kernel params.., noise]`.
+    #[serde(skip)]
+    samples: Vec<Vec<f64>>,
+    #[serde(skip)]
+    acceptance_rate: f64,
+}
+
+impl Default for HyperparameterPosterior {
+    fn default() -> Self {
+        Self {
+            open: false,
+            num_samples: 1000,
+            average: false,
+            pair: (0, 1),
+            run: None,
+            kernel: None,
+            samples: Vec::new(),
+            acceptance_rate: 0.0,
+        }
+    }
+}
+
+impl HyperparameterPosterior {
+    fn names(&self) -> Vec<&'static str> {
+        let mut names = self
+            .kernel
+            .as_ref()
+            .map(|kernel| kernel.param_names())
+            .unwrap_or_default();
+        names.push("noise");
+        names
+    }
+
+    /// Show the window with the samples of the hyperparameters of the dataset, fit with the
+    /// given jitter. Returns true if new samples are available, which changes the averaged
+    /// prediction.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        dataset: &Dataset,
+        jitter: f64,
+        kernel: &Kernel,
+        noise_sigma: f64,
+    ) -> bool {
+        let mut changed = self.advance(ctx);
+
+        let mut open = self.open;
+        egui::Window::new("Hyperparameter posterior")
+            .open(&mut open)
+            .default_size([350.0, 500.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.num_samples)
+                            .range(100..=10000)
+                            .prefix("Samples: "),
+                    );
+                    if ui
+                        .add_enabled(self.run.is_none(), egui::Button::new("Run MCMC"))
+                        .on_hover_text("Sample the hyperparameters of the active dataset")
+                        .clicked()
+                    {
+                        self.start(dataset, jitter, kernel, noise_sigma);
+                    }
+                });
+                if self.samples.is_empty() {
+                    ui.label("No samples yet.");
+                    return;
+                }
+                ui.label(format!(
+                    "{} samples after burn-in, acceptance rate {:.0} %",
+                    self.samples.len(),
+                    self.acceptance_rate * 100.0
+                ));
+                if ui
+                    .checkbox(&mut self.average, "Show the averaged prediction")
+                    .on_hover_text("Average the predictions over the sampled hyperparameters")
+                    .changed()
+                {
+                    changed = true;
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.histograms(ui);
+                    ui.separator();
+                    self.pair_plot(ui);
+                });
+            });
+        self.open = open;
+        changed
+    }
+
+    fn start(&mut self, dataset: &Dataset, jitter: f64, kernel: &Kernel, noise_sigma: f64) {
+        let (x, y) = dataset.training_points();
+        let sampler = HyperparameterSampler::new(
+            &na::DVector::from_vec(x),
+            &na::DVector::from_vec(y),
+            kernel.clone(),
+            noise_sigma,
+        )
+        .with_observation_noise(na::DVector::from_vec(dataset.training_weights()), jitter);
+        self.run = Some(Run {
+            sampler,
+            rng: rand::rngs::SmallRng::seed_from_u64(0),
+            trace: Vec::new(),
+        });
+        self.kernel = Some(kernel.clone());
+    }
+
+    /// Take the next steps of the chain and show the progress. Returns true when done.
+    fn advance(&mut self, ctx: &egui::Context) -> bool {
+        let Some(run) = &mut self.run else {
+            return false;
+        };
+
+        let start = Instant::now();
+        let trace = &mut run.trace;
+        let remaining = self.num_samples.saturating_sub(trace.len());
+        run.sampler.run(remaining, &mut run.rng, |event| {
+            trace.push(event.log_marginal_likelihood);
+            start.elapsed() < FRAME_BUDGET
+        });
+        let done = run.trace.len() >= self.num_samples;
+
+        let action = progress_window(
+            ctx,
+            "Sampling hyperparameters",
+            run.trace.len(),
+            self.num_samples,
+            &run.trace,
+        );
+        match action {
+            Some(ProgressAction::Cancel) => {
+                self.run = None;
+                false
+            }
+            Some(ProgressAction::Stop) => self.finish(),
+            None if done => self.finish(),
+            None => {
+                ctx.request_repaint();
+                false
+            }
+        }
+    }
+
+    fn finish(&mut self) -> bool {
+        let Some(run) = self.run.take() else {
+            return false;
+        };
+        let samples = run.sampler.samples();
+        let burn_in = (samples.len() as f64 * BURN_IN) as usize;
+        self.samples = samples[burn_in..].to_vec();
+        self.acceptance_rate = run.sampler.acceptance_rate();
+        let count = self.names().len();
+        self.pair = (self.pair.0.min(count - 1), self.pair.1.min(count - 1));
+        true
+    }
+
+    fn histograms(&self, ui: &mut egui::Ui) {
+        for (i, name) in self.names().into_iter().enumerate() {
+            // log spaced bins, as the hyperparameters are sampled in log space
+            let values: Vec<f64> = self.samples.iter().map(|s| s[i].log10()).collect();
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let width = ((max - min) / BINS as f64).max(1e-3);
+            let mut counts = [0usize; BINS];
+            for value in &values {
+                counts[(((value - min) / width) as usize).min(BINS - 1)] += 1;
+            }
+            let bars = counts
+                .iter()
+                .enumerate()
+                .map(|(bin, count)| {
+                    Bar::new(min + (bin as f64 + 0.5) * width, *count as f64).width(width)
+                })
+                .collect();
+
+            ui.label(format!("log10({name})"));
+            Plot::new(("hyperparameter_histogram", i))
+                .height(80.0)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .allow_scroll(false)
+                .show_y(false)
+                .show(ui, |pui| pui.bar_chart(BarChart::new(bars).name(name)));
+        }
+    }
+
+    fn pair_plot(&mut self, ui: &mut egui::Ui) {
+        let names = self.names();
+        ui.horizontal(|ui| {
+            for (id, index) in [("pair_x", &mut self.pair.0), ("pair_y", &mut self.pair.1)] {
+                egui::ComboBox::from_id_salt(id)
+                    .selected_text(names[*index])
+                    .show_ui(ui, |ui| {
+                        for (i, name) in names.iter().enumerate() {
+                            ui.selectable_value(index, i, *name);
+                        }
+                    });
+            }
+        });
+        let (a, b) = self.pair;
+        let points: Vec<[f64; 2]> = self
+            .samples
+            .iter()
+            .map(|s| [s[a].log10(), s[b].log10()])
+            .collect();
+        ui.label(format!("log10({}) vs log10({})", names[a], names[b]));
+        Plot::new("hyperparameter_pairs")
+            .height(200.0)
+            .allow_scroll(false)
+            .show(ui, |pui| pui.points(Points::new(points).radius(1.5)));
+    }
+
+    /// The prediction at `grid` averaged over evenly spaced samples, as the mean and variance
+    /// of the mixture of the predictive distributions. The training points are fit with their
+    /// weights and the jitter, like the samples were drawn.
+    pub fn averaged_prediction(
+        &self,
+        x: &[f64],
+        y: &[f64],
+        weights: &[f64],
+        jitter: f64,
+        grid: &[f64],
+    ) -> Option<(na::DVector<f64>, na::DVector<f64>)> {
+        let kernel = self.kernel.as_ref()?;
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let x = na::DVector::from_column_slice(x);
+        let y = na::DVector::from_column_slice(y);
+        let weights = na::DVector::from_column_slice(weights);
+        let grid = na::DVector::from_column_slice(grid);
+        let stride = (self.samples.len() / AVERAGED_SAMPLES).max(1);
+
+        let mut mean = na::DVector::zeros(grid.len());
+        let mut second_moment = na::DVector::zeros(grid.len());
+        let mut count = 0.0;
+        for sample in self.samples.iter().step_by(stride) {
+            let mut kernel = kernel.clone();
+            for (param, value) in kernel.params_mut().into_iter().zip(sample) {
+                *param = *value;
+            }
+            let Ok(gp) = GaussianProcess::builder()
+                .kernel(kernel)
+                .noise(sample[sample.len() - 1])
+                .jitter(jitter)
+                .weights(weights.clone())
+                .build(&x, &y)
+            else {
+                continue;
+            };
+            let (m, v) = gp.predict(&grid);
+            second_moment += v + m.component_mul(&m);
+            mean += m;
+            count += 1.0;
+        }
+        if count == 0.0 {
+            return None;
+        }
+        mean /= count;
+        let variance = second_moment / count - mean.component_mul(&mean);
+        Some((mean, variance.map(|v| v.max(0.0))))
+    }
+}
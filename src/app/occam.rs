@@ -0,0 +1,291 @@
+use egui_plot::{Bar, BarChart, Plot};
+use nalgebra as na;
+use web_time::Instant;
+
+use super::dataset::Dataset;
+use super::kernel_ui::kernel_controls;
+use super::optimize::{progress_window, ProgressAction, FRAME_BUDGET, MAX_ITERATIONS};
+use super::ranges::SliderRanges;
+use crate::gp::{HyperparameterOptimizer, Kernel, PeriodicKernel, RbfKernel};
+
+/// A kernel after optimizing its hyperparameters.
+struct Fitted {
+    kernel: Kernel,
+    noise_sigma: f64,
+    log_marginal_likelihood: f64,
+}
+
+/// The kernels optimized so far and the optimization of the next one.
+struct Run {
+    x: na::DVector<f64>,
+    y: na::DVector<f64>,
+    /// The weights of the points and the jitter every kernel is fit with.
+    weights: na::DVector<f64>,
+    jitter: f64,
+    noise_sigma: f64,
+    fitted: Vec<Fitted>,
+    optimizer: HyperparameterOptimizer<Kernel>,
+    trace: Vec<f64>,
+}
+
+/// Compares a list of kernels by their log marginal likelihood after optimizing each, which
+/// penalizes needlessly flexible models (Occam's razor).
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ModelComparison {
+    pub open: bool,
+    /// The kernels to compare, with the hyperparameters the optimization starts from.
+    candidates: Vec<Kernel>,
+    #[serde(skip)]
+    run: Option<Run>,
+    #[serde(skip)]
+    results: Vec<Fitted>,
+}
+
+impl Default for ModelComparison {
+    fn default() -> Self {
+        let rbf = Kernel::Rbf(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        });
+        let periodic = Kernel::Periodic(PeriodicKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+            period: 2.0,
+        });
+        Self {
+            open: false,
+            candidates: vec![
+                rbf.clone(),
+                periodic.clone(),
+                Kernel::Sum(vec![rbf.clone(), periodic.clone()]),
+                Kernel::Product(vec![rbf, periodic]),
+            ],
+            run: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl ModelComparison {
+    /// Show the panel for the data of the active dataset, fit with the given jitter. Returns the
+    /// kernel and noise to switch to if asked to.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        dataset: &Dataset,
+        jitter: f64,
+        noise_sigma: f64,
+        ranges: &mut SliderRanges,
+    ) -> Option<(Kernel, f64)> {
+        self.advance(ctx);
+
+        let mut selected = None;
+        let mut open = self.open;
+        egui::Window::new("Model comparison")
+            .open(&mut open)
+            .default_size([350.0, 450.0])
+            .show(ctx, |ui| {
+                egui::CollapsingHeader::new(format!("Kernels ({})", self.candidates.len()))
+                    .id_salt("model_comparison_candidates")
+                    .show(ui, |ui| {
+                        // the running comparison goes through the list by index
+                        ui.add_enabled_ui(self.run.is_none(), |ui| {
+                            self.candidates_controls(ui, ranges)
+                        });
+                    });
+
+                if ui
+                    .add_enabled(
+                        self.run.is_none() && !self.candidates.is_empty(),
+                        egui::Button::new("Optimize and compare"),
+                    )
+                    .clicked()
+                {
+                    self.start(dataset, jitter, noise_sigma);
+                }
+
+                if self.results.is_empty() {
+                    ui.label("No results yet.");
+                    return;
+                }
+                selected = self.results_view(ui);
+            });
+        self.open = open;
+        selected
+    }
+
+    fn candidates_controls(&mut self, ui: &mut egui::Ui, ranges: &mut SliderRanges) {
+        let mut remove = None;
+        for (i, kernel) in self.candidates.iter_mut().enumerate() {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Kernel {}", i + 1));
+                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+                kernel_controls(ui, &format!("model_comparison_{i}"), kernel, ranges);
+            });
+        }
+        if let Some(i) = remove {
+            self.candidates.remove(i);
+        }
+        if ui.button("Add kernel").clicked() {
+            self.candidates.push(Kernel::Rbf(RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            }));
+        }
+    }
+
+    fn results_view(&self, ui: &mut egui::Ui) -> Option<(Kernel, f64)> {
+        let mut selected = None;
+        let best = self
+            .results
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                a.log_marginal_likelihood
+                    .total_cmp(&b.log_marginal_likelihood)
+            })
+            .map(|(i, _)| i);
+
+        let bars = self
+            .results
+            .iter()
+            .enumerate()
+            .map(|(i, fitted)| {
+                let bar = Bar::new(i as f64, fitted.log_marginal_likelihood)
+                    .name(fitted.kernel.description())
+                    .width(0.6);
+                if Some(i) == best {
+                    bar.fill(egui::Color32::from_rgb(0, 158, 115))
+                } else {
+                    bar
+                }
+            })
+            .collect();
+        let names: Vec<String> = self
+            .results
+            .iter()
+            .map(|fitted| fitted.kernel.description())
+            .collect();
+        Plot::new("model_comparison_plot")
+            .height(150.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .x_axis_formatter(move |mark, _| {
+                let i = mark.value.round();
+                if (mark.value - i).abs() < 1e-6 && i >= 0.0 {
+                    names.get(i as usize).cloned().unwrap_or_default()
+                } else {
+                    String::new()
+                }
+            })
+            .y_axis_label("Log marginal likelihood")
+            .show(ui, |pui| pui.bar_chart(BarChart::new(bars)));
+
+        egui::Grid::new("model_comparison_results")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (i, fitted) in self.results.iter().enumerate() {
+                    let name = fitted.kernel.description();
+                    if Some(i) == best {
+                        ui.strong(name);
+                    } else {
+                        ui.label(name);
+                    }
+                    ui.monospace(format!("{:.3}", fitted.log_marginal_likelihood));
+                    if ui.button("Use").clicked() {
+                        selected = Some((fitted.kernel.clone(), fitted.noise_sigma));
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(best) = best.map(|i| &self.results[i]) {
+            if ui
+                .button(format!("Switch to the best: {}", best.kernel.description()))
+                .clicked()
+            {
+                selected = Some((best.kernel.clone(), best.noise_sigma));
+            }
+        }
+        selected
+    }
+
+    fn start(&mut self, dataset: &Dataset, jitter: f64, noise_sigma: f64) {
+        let (x, y) = dataset.training_points();
+        let (x, y) = (na::DVector::from_vec(x), na::DVector::from_vec(y));
+        let weights = na::DVector::from_vec(dataset.training_weights());
+        let optimizer =
+            HyperparameterOptimizer::new(&x, &y, self.candidates[0].clone(), noise_sigma)
+                .with_observation_noise(weights.clone(), jitter);
+        self.run = Some(Run {
+            x,
+            y,
+            weights,
+            jitter,
+            noise_sigma,
+            fitted: Vec::new(),
+            trace: vec![optimizer.best_value()],
+            optimizer,
+        });
+    }
+
+    /// Optimize the kernels one after the other, a few iterations per frame.
+    fn advance(&mut self, ctx: &egui::Context) {
+        let Some(run) = &mut self.run else {
+            return;
+        };
+
+        let start = Instant::now();
+        while start.elapsed() < FRAME_BUDGET {
+            if !run.optimizer.converged() && run.optimizer.iteration() < MAX_ITERATIONS {
+                let trace = &mut run.trace;
+                run.optimizer.run(MAX_ITERATIONS, |event| {
+                    trace.push(event.log_marginal_likelihood);
+                    start.elapsed() < FRAME_BUDGET
+                });
+                continue;
+            }
+
+            let (kernel, noise_sigma) = run.optimizer.best();
+            run.fitted.push(Fitted {
+                kernel,
+                noise_sigma,
+                log_marginal_likelihood: run.optimizer.best_value(),
+            });
+            let Some(next) = self.candidates.get(run.fitted.len()) else {
+                break;
+            };
+            run.optimizer =
+                HyperparameterOptimizer::new(&run.x, &run.y, next.clone(), run.noise_sigma)
+                    .with_observation_noise(run.weights.clone(), run.jitter);
+            run.trace = vec![run.optimizer.best_value()];
+        }
+        let done = run.fitted.len() >= self.candidates.len();
+
+        let action = progress_window(
+            ctx,
+            "Comparing kernels",
+            run.optimizer.iteration(),
+            MAX_ITERATIONS,
+            &run.trace,
+        );
+        match action {
+            Some(ProgressAction::Cancel) => self.run = None,
+            Some(ProgressAction::Stop) => self.finish(),
+            None if done => self.finish(),
+            None => ctx.request_repaint(),
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(run) = self.run.take() {
+            self.results = run.fitted;
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use egui_plot::{Line, Plot};
+use nalgebra as na;
+use web_time::{Duration, Instant};
+
+use crate::gp::{HyperparameterOptimizer, Kernel, OptimizerConfig};
+
+/// Time spent optimizing each frame, so the UI stays responsive during long optimizations.
+pub const FRAME_BUDGET: Duration = Duration::from_millis(30);
+
+/// Give up after this many iterations if the optimization has not converged.
+pub const MAX_ITERATIONS: usize = 1000;
+
+/// What the user asked a progress window to do.
+pub enum ProgressAction {
+    /// Stop and keep the best result so far.
+    Stop,
+    /// Stop and discard the result.
+    Cancel,
+}
+
+/// A window showing the progress of a long running computation, with a small plot of how its
+/// objective developed.
+pub fn progress_window(
+    ctx: &egui::Context,
+    title: &str,
+    iteration: usize,
+    max_iterations: usize,
+    trace: &[f64],
+) -> Option<ProgressAction> {
+    let mut action = None;
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::ProgressBar::new(iteration as f32 / max_iterations as f32)
+                    .text(format!("Iteration {iteration}")),
+            );
+            if let Some(last) = trace.last() {
+                ui.label(format!("Log marginal likelihood: {last:.3}"));
+            }
+            let points: Vec<[f64; 2]> = trace
+                .iter()
+                .enumerate()
+                .map(|(i, value)| [i as f64, *value])
+                .collect();
+            Plot::new(format!("{title}_trace"))
+                .height(100.0)
+                .width(250.0)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .allow_scroll(false)
+                .show(ui, |pui| pui.line(Line::new(points)));
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Stop")
+                    .on_hover_text("Stop and keep the result so far")
+                    .clicked()
+                {
+                    action = Some(ProgressAction::Stop);
+                }
+                if ui
+                    .button("Cancel")
+                    .on_hover_text("Stop and discard the result")
+                    .clicked()
+                {
+                    action = Some(ProgressAction::Cancel);
+                }
+            });
+        });
+    action
+}
+
+/// Maximizing the log marginal likelihood of the active dataset in the background, a few
+/// iterations per frame.
+#[derive(Default)]
+pub struct Optimization {
+    run: Option<(HyperparameterOptimizer<Kernel>, Vec<f64>)>,
+    /// The kernel and noise the last optimization ended with. Optimizing again from them, e.g.
+    /// after editing a few points, starts near the new optimum.
+    last_optimum: Option<(Kernel, f64)>,
+    /// The method to optimize with.
+    pub config: OptimizerConfig,
+}
+
+impl Optimization {
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// Start optimizing from the given kernel and noise, with the weights of the points and the
+    /// jitter the fitted model uses. The simplex method warm starts if they are the result of
+    /// the last optimization.
+    pub fn start(
+        &mut self,
+        x: &[f64],
+        y: &[f64],
+        weights: &[f64],
+        jitter: f64,
+        kernel: &Kernel,
+        noise_sigma: f64,
+    ) {
+        let (x, y) = (
+            &na::DVector::from_column_slice(x),
+            &na::DVector::from_column_slice(y),
+        );
+        let warm = self.config == OptimizerConfig::NelderMead
+            && self.last_optimum.as_ref() == Some(&(kernel.clone(), noise_sigma));
+        let optimizer = if warm {
+            HyperparameterOptimizer::warm_start(x, y, kernel.clone(), noise_sigma)
+        } else {
+            HyperparameterOptimizer::with_config(x, y, kernel.clone(), noise_sigma, self.config)
+        }
+        .with_observation_noise(na::DVector::from_column_slice(weights), jitter);
+        let trace = vec![optimizer.best_value()];
+        self.run = Some((optimizer, trace));
+    }
+
+    /// Choose the optimization method.
+    pub fn config_ui(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::from_label("Optimizer")
+            .selected_text(self.config.name())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(
+                    &mut self.config,
+                    OptimizerConfig::NelderMead,
+                    OptimizerConfig::NelderMead.name(),
+                )
+                .on_hover_text("Few evaluations, climbs to the nearest local maximum");
+                let cma_es = match self.config {
+                    OptimizerConfig::CmaEs { .. } => self.config,
+                    OptimizerConfig::NelderMead => OptimizerConfig::CmaEs { seed: 0 },
+                };
+                ui.selectable_value(&mut self.config, cma_es, cma_es.name())
+                    .on_hover_text(
+                        "Samples many hyperparameters at random: slower, but often escapes local \
+                         maxima such as a wrong period",
+                    );
+            });
+    }
+
+    pub fn cancel(&mut self) {
+        self.run = None;
+    }
+
+    /// Advance the optimization and show its progress. Returns the optimized kernel and noise
+    /// once done.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<(Kernel, f64)> {
+        let (optimizer, trace) = self.run.as_mut()?;
+
+        let start = Instant::now();
+        optimizer.run(MAX_ITERATIONS, |event| {
+            log::debug!(
+                "Iteration {}: log marginal likelihood {:.3} at {:?}",
+                event.iteration,
+                event.log_marginal_likelihood,
+                event.hyperparameters
+            );
+            trace.push(event.log_marginal_likelihood);
+            start.elapsed() < FRAME_BUDGET
+        });
+        let done = optimizer.converged() || optimizer.iteration() >= MAX_ITERATIONS;
+
+        let action = progress_window(
+            ctx,
+            "Optimizing hyperparameters",
+            optimizer.iteration(),
+            MAX_ITERATIONS,
+            trace,
+        );
+        match action {
+            Some(ProgressAction::Cancel) => {
+                self.run = None;
+                None
+            }
+            Some(ProgressAction::Stop) => self.finish(),
+            None if done => self.finish(),
+            None => {
+                ctx.request_repaint();
+                None
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Option<(Kernel, f64)> {
+        let (optimizer, _) = self.run.take()?;
+        self.last_optimum = Some(optimizer.best());
+        self.last_optimum.clone()
+    }
+}
@@ -0,0 +1,165 @@
+use super::dataset::Dataset;
+
+/// Maximum number of rows shown in the preview.
+const PREVIEW_ROWS: usize = 10;
+
+/// Parse the first two numbers of a row separated by tabs, commas, semicolons or spaces.
+pub fn parse_row(line: &str) -> Option<(f64, f64)> {
+    let mut fields = line
+        .split(['\t', ',', ';', ' '])
+        .filter(|field| !field.is_empty())
+        .map(|field| field.trim().parse::<f64>());
+    match (fields.next(), fields.next()) {
+        (Some(Ok(x)), Some(Ok(y))) => Some((x, y)),
+        _ => None,
+    }
+}
+
+/// Parse two columns of numbers separated by tabs, commas, semicolons or spaces, e.g. as copied
+/// from a spreadsheet. A header on the first line is skipped, any other line that cannot be
+/// parsed is reported by its (1-based) line number.
+pub fn parse_xy(text: &str) -> (Vec<(f64, f64)>, Vec<usize>) {
+    let mut points = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_row(line) {
+            Some(point) => points.push(point),
+            None if i == 0 => {} // probably a header
+            None => invalid.push(i + 1),
+        }
+    }
+
+    (points, invalid)
+}
+
+/// How pasted points should be added to the datasets.
+enum PasteAction {
+    Replace,
+    Append,
+    NewDataset,
+}
+
+/// A dialog previewing pasted data before it is loaded.
+#[derive(Default)]
+pub struct PasteDialog {
+    pub open: bool,
+    text: String,
+}
+
+impl PasteDialog {
+    /// Open the dialog with the given text, e.g. from a paste event.
+    pub fn open_with(&mut self, text: String) {
+        self.text = text;
+        self.open = true;
+    }
+
+    /// Returns true if the datasets were changed.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        datasets: &mut Vec<Dataset>,
+        active: &mut usize,
+    ) -> bool {
+        let mut action = None;
+        let mut open = self.open;
+        egui::Window::new("Paste data")
+            .open(&mut open)
+            .default_size([300.0, 400.0])
+            .show(ctx, |ui| {
+                ui.label("Paste two columns of numbers (x and y) below:");
+                egui::ScrollArea::vertical()
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.text)
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+
+                let (points, invalid) = parse_xy(&self.text);
+                ui.label(format!("{} points parsed.", points.len()));
+                if !invalid.is_empty() {
+                    let lines = invalid
+                        .iter()
+                        .map(|line| line.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("Could not parse line(s) {lines}."),
+                    );
+                }
+
+                egui::Grid::new("paste_preview")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("x");
+                        ui.strong("y");
+                        ui.end_row();
+                        for (x, y) in points.iter().take(PREVIEW_ROWS) {
+                            ui.label(x.to_string());
+                            ui.label(y.to_string());
+                            ui.end_row();
+                        }
+                        if points.len() > PREVIEW_ROWS {
+                            ui.label("…");
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_enabled_ui(!points.is_empty(), |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Replace active dataset").clicked() {
+                            action = Some((PasteAction::Replace, points.clone()));
+                        }
+                        if ui.button("Append").clicked() {
+                            action = Some((PasteAction::Append, points.clone()));
+                        }
+                        if ui.button("New dataset").clicked() {
+                            action = Some((PasteAction::NewDataset, points.clone()));
+                        }
+                    });
+                });
+            });
+        self.open = open;
+
+        let Some((action, points)) = action else {
+            return false;
+        };
+        match action {
+            PasteAction::Replace => datasets[*active].clear_points(),
+            PasteAction::Append => {}
+            PasteAction::NewDataset => {
+                datasets.push(Dataset::new(datasets.len()));
+                *active = datasets.len() - 1;
+            }
+        }
+        let dataset = &mut datasets[*active];
+        for (x, y) in points {
+            dataset.x.push(x);
+            dataset.y.push(y);
+        }
+
+        self.open = false;
+        self.text.clear();
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_xy() {
+        let (points, invalid) = parse_xy("x\ty\n1\t2\n3,4\n\n5; 6\n7 8\nfoo\n");
+        assert_eq!(points, vec![(1.0, 2.0), (3.0, 4.0), (5.0, 6.0), (7.0, 8.0)]);
+        assert_eq!(invalid, vec![7]);
+    }
+}
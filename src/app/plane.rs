@@ -0,0 +1,427 @@
+use egui::Slider;
+use egui_plot::{Bar, BarChart, Line, Plot, PlotImage, PlotPoint, Points};
+use nalgebra as na;
+
+use super::heatmap::colormap;
+use super::surface::{self, Camera, Surface};
+use crate::gp::{
+    AnisotropicRbfKernel, ArdKernel, GaussianProcess, GpKernel, HyperparameterOptimizer, Kernel,
+};
+
+/// Extent of the plane along both axes.
+const EXTENT: f64 = 10.0;
+
+/// Number of pixels along each axis of the rendered images.
+const RESOLUTION: usize = 60;
+
+/// Iterations of the optimizer fitting the length scales of the ARD kernel.
+const ARD_ITERATIONS: usize = 300;
+
+/// The kernel of the plane, either the shared kernel or one specific to two dimensions.
+type PlaneKernel = Box<dyn GpKernel<[f64; 2]>>;
+
+/// Training points in a plane, each with a value, for interpolating a surface (kriging).
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlaneView {
+    x: Vec<[f64; 2]>,
+    y: Vec<f64>,
+    /// The value given to new points.
+    new_value: f64,
+    show_std: bool,
+    /// Whether to also draw the mean as a rotatable surface.
+    show_surface: bool,
+    camera: Camera,
+    /// Whether to use the anisotropic kernel instead of the shared one.
+    use_anisotropic: bool,
+    anisotropic: AnisotropicRbfKernel,
+    #[serde(skip)]
+    gp: Option<GaussianProcess<PlaneKernel, [f64; 2]>>,
+    #[serde(skip)]
+    images: Option<PlaneImages>,
+    /// A kernel with a length scale per axis fit to the points, showing which axis matters.
+    #[serde(skip)]
+    ard: Option<ArdKernel<2>>,
+}
+
+impl Default for PlaneView {
+    fn default() -> Self {
+        Self {
+            x: vec![[2.0, 2.0], [5.0, 7.0], [8.0, 4.0]],
+            y: vec![1.0, -1.0, 0.5],
+            new_value: 1.0,
+            show_std: true,
+            show_surface: false,
+            camera: Default::default(),
+            use_anisotropic: false,
+            anisotropic: AnisotropicRbfKernel {
+                sigma: 1.0,
+                length_scales: [3.0, 1.0],
+                angle: std::f64::consts::FRAC_PI_4,
+            },
+            gp: None,
+            images: None,
+            ard: None,
+        }
+    }
+}
+
+/// The posterior mean and standard deviation rendered as images, with their value ranges.
+struct PlaneImages {
+    mean: egui::TextureHandle,
+    mean_range: (f64, f64),
+    std: egui::TextureHandle,
+    std_range: (f64, f64),
+    surface: Surface,
+}
+
+impl PlaneView {
+    /// Show the controls and plots of the plane, refitting if `changed` or the data is edited.
+    pub fn show(&mut self, ui: &mut egui::Ui, kernel: &Kernel, noise_sigma: f64, changed: bool) {
+        let mut changed = changed;
+
+        ui.horizontal(|ui| {
+            ui.add(Slider::new(&mut self.new_value, -3.0..=3.0).text("Value of new points"));
+            if ui.button("Clear all Points").clicked() {
+                self.x.clear();
+                self.y.clear();
+                changed = true;
+            }
+            ui.checkbox(&mut self.show_std, "Show standard deviation");
+            ui.checkbox(&mut self.show_surface, "3D surface")
+                .on_hover_text("The mean as a surface, colored by the standard deviation");
+        });
+        egui::CollapsingHeader::new("Points").show(ui, |ui| {
+            if self.points_table(ui) {
+                changed = true;
+            }
+        });
+        egui::CollapsingHeader::new("Input relevance").show(ui, |ui| {
+            self.relevance_panel(ui, noise_sigma);
+        });
+        egui::CollapsingHeader::new("Anisotropic kernel").show(ui, |ui| {
+            if self.anisotropy_panel(ui, noise_sigma) {
+                changed = true;
+            }
+        });
+        ui.label("Click anywhere to add points, click on points to remove them.");
+
+        if changed || self.gp.is_none() {
+            let kernel: PlaneKernel = if self.use_anisotropic {
+                Box::new(self.anisotropic.clone())
+            } else {
+                Box::new(kernel.clone())
+            };
+            self.gp = Some(GaussianProcess::new(
+                &na::DVector::from_vec(self.x.clone()),
+                &na::DVector::from_vec(self.y.clone()),
+                kernel,
+                noise_sigma,
+            ));
+            self.images = None;
+            self.ard = None;
+        }
+        let Some(gp) = &self.gp else {
+            return;
+        };
+        let images = self
+            .images
+            .get_or_insert_with(|| PlaneImages::compute(ui.ctx(), gp));
+
+        let (mean_min, mean_max) = images.mean_range;
+        let points_id = egui::Id::new("plane_points");
+        let mut clicked = None;
+        let plots = if self.show_std { 2 } else { 1 };
+        let columns = plots + self.show_surface as usize;
+        ui.columns(columns, |columns| {
+            for (i, ui) in columns.iter_mut().enumerate() {
+                if i == plots {
+                    surface::show(ui, &images.surface, &mut self.camera, &self.x, &self.y);
+                    continue;
+                }
+                let (texture, (min, max), title) = if i == 0 {
+                    (&images.mean, images.mean_range, "Mean")
+                } else {
+                    (&images.std, images.std_range, "Standard deviation")
+                };
+                ui.label(format!("{title} (color range {min:.2} to {max:.2})"));
+
+                let response = Plot::new(format!("plane_plot_{i}"))
+                    .data_aspect(1.0)
+                    .include_x(0.0)
+                    .include_x(EXTENT)
+                    .include_y(0.0)
+                    .include_y(EXTENT)
+                    .link_axis("plane_plot", true, true)
+                    .link_cursor("plane_plot", true, true)
+                    .label_formatter(|_, point| {
+                        let (mean, variance) =
+                            gp.predict(&na::DVector::from_element(1, [point.x, point.y]));
+                        format!(
+                            "x1 = {:.2}\nx2 = {:.2}\nmean = {:.2}\nstd = {:.2}",
+                            point.x,
+                            point.y,
+                            mean[0],
+                            variance[0].max(0.0).sqrt()
+                        )
+                    })
+                    .show(ui, |pui| {
+                        pui.image(PlotImage::new(
+                            texture,
+                            PlotPoint::new(EXTENT / 2.0, EXTENT / 2.0),
+                            [EXTENT as f32, EXTENT as f32],
+                        ));
+                        if self.use_anisotropic {
+                            pui.line(
+                                Line::new(correlation_ellipse(&self.anisotropic))
+                                    .color(egui::Color32::WHITE)
+                                    .style(egui_plot::LineStyle::dashed_loose())
+                                    .name("One length scale from the center"),
+                            );
+                        }
+                        // an outline keeps the points visible on top of the colors they share
+                        let points = self.x.iter().map(|x| [x[0], x[1]]).collect::<Vec<_>>();
+                        pui.points(
+                            Points::new(points)
+                                .radius(6.0)
+                                .color(egui::Color32::WHITE)
+                                .id(points_id),
+                        );
+                        for (x, y) in self.x.iter().zip(self.y.iter()) {
+                            let t = (y - mean_min) / (mean_max - mean_min);
+                            pui.points(
+                                Points::new(vec![[x[0], x[1]]])
+                                    .radius(4.5)
+                                    .color(colormap(t))
+                                    .id(points_id),
+                            );
+                        }
+                        pui.response()
+                            .clicked()
+                            .then(|| pui.pointer_coordinate())
+                            .flatten()
+                    });
+                if let Some(pos) = response.inner {
+                    clicked = Some((pos, response.hovered_plot_item == Some(points_id)));
+                }
+            }
+        });
+
+        if let Some((pos, on_point)) = clicked {
+            let nearest = self
+                .x
+                .iter()
+                .map(|x| (x[0] - pos.x).powi(2) + (x[1] - pos.y).powi(2))
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| index);
+            match (on_point, nearest) {
+                (true, Some(index)) => {
+                    self.x.remove(index);
+                    self.y.remove(index);
+                }
+                _ => {
+                    self.x.push([pos.x, pos.y]);
+                    self.y.push(self.new_value);
+                }
+            }
+            // refit on the next frame, as the plots have already been drawn
+            self.gp = None;
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Fit a kernel with one length scale per axis (automatic relevance determination) and show
+    /// how much the values depend on each axis.
+    fn relevance_panel(&mut self, ui: &mut egui::Ui, noise_sigma: f64) {
+        if ui
+            .button("Fit length scale per axis")
+            .on_hover_text(
+                "Maximize the log marginal likelihood of an RBF kernel with a separate length \
+                 scale for x1 and x2. A short length scale means the values change quickly along \
+                 that axis, so it is relevant.",
+            )
+            .clicked()
+        {
+            let mut optimizer = HyperparameterOptimizer::new(
+                &na::DVector::from_vec(self.x.clone()),
+                &na::DVector::from_vec(self.y.clone()),
+                ArdKernel::isotropic(1.0, 1.0),
+                noise_sigma,
+            );
+            optimizer.run(ARD_ITERATIONS, |_| true);
+            self.ard = Some(optimizer.best().0);
+        }
+        let Some(ard) = &self.ard else {
+            return;
+        };
+
+        let relevance = ard.relevance();
+        let bars = relevance
+            .iter()
+            .enumerate()
+            .map(|(axis, relevance)| {
+                Bar::new(axis as f64 + 1.0, *relevance)
+                    .width(0.6)
+                    .name(format!("x{}", axis + 1))
+            })
+            .collect();
+        ui.label(format!(
+            "Length scales: x1 {:.2}, x2 {:.2}",
+            ard.length_scales[0], ard.length_scales[1]
+        ));
+        Plot::new("plane_relevance")
+            .height(100.0)
+            .include_y(0.0)
+            .include_y(1.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .x_axis_formatter(|mark, _| {
+                if mark.value == 1.0 || mark.value == 2.0 {
+                    format!("x{}", mark.value)
+                } else {
+                    String::new()
+                }
+            })
+            .show(ui, |pui| {
+                pui.bar_chart(BarChart::new(bars).name("Relevance"));
+            });
+    }
+
+    /// Choose and fit the rotated anisotropic kernel. Returns true if it was changed.
+    fn anisotropy_panel(&mut self, ui: &mut egui::Ui, noise_sigma: f64) -> bool {
+        let mut changed = ui
+            .checkbox(&mut self.use_anisotropic, "Use instead of the kernel above")
+            .on_hover_text(
+                "An RBF kernel with different length scales along two perpendicular, rotated \
+                 axes, for structures elongated in any direction",
+            )
+            .changed();
+        ui.add_enabled_ui(self.use_anisotropic, |ui| {
+            let kernel = &mut self.anisotropic;
+            let [along, across] = &mut kernel.length_scales;
+            let mut degrees = kernel.angle.to_degrees();
+            changed |= ui
+                .add(Slider::new(along, 0.1..=10.0).text("Length scale along"))
+                .changed();
+            changed |= ui
+                .add(Slider::new(across, 0.1..=10.0).text("Length scale across"))
+                .changed();
+            if ui
+                .add(Slider::new(&mut degrees, 0.0..=180.0).text("Angle (degrees)"))
+                .changed()
+            {
+                kernel.angle = degrees.to_radians();
+                changed = true;
+            }
+            changed |= ui
+                .add(Slider::new(&mut kernel.sigma, 0.1..=10.0).text("Sigma"))
+                .changed();
+            if ui
+                .button("Fit to points")
+                .on_hover_text("Maximize the log marginal likelihood of the points")
+                .clicked()
+            {
+                let mut optimizer = HyperparameterOptimizer::new(
+                    &na::DVector::from_vec(self.x.clone()),
+                    &na::DVector::from_vec(self.y.clone()),
+                    kernel.clone(),
+                    noise_sigma,
+                );
+                optimizer.run(ARD_ITERATIONS, |_| true);
+                *kernel = optimizer.best().0;
+                kernel.angle = kernel.angle.rem_euclid(std::f64::consts::PI);
+                changed = true;
+            }
+        });
+        changed
+    }
+
+    /// An editable table of the points. Returns true if any point was changed.
+    fn points_table(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let mut remove = None;
+        egui::Grid::new("plane_points_table")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("x1");
+                ui.strong("x2");
+                ui.strong("value");
+                ui.end_row();
+                for (i, ([x1, x2], y)) in self.x.iter_mut().zip(self.y.iter_mut()).enumerate() {
+                    for value in [x1, x2, y] {
+                        if ui.add(egui::DragValue::new(value).speed(0.05)).changed() {
+                            changed = true;
+                        }
+                    }
+                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(i) = remove {
+            self.x.remove(i);
+            self.y.remove(i);
+            changed = true;
+        }
+        changed
+    }
+}
+
+impl PlaneImages {
+    fn compute(ctx: &egui::Context, gp: &GaussianProcess<PlaneKernel, [f64; 2]>) -> Self {
+        let coordinate = |i: usize| (i as f64 + 0.5) / RESOLUTION as f64 * EXTENT;
+
+        // image rows go from top to bottom, so the y-axis is flipped
+        let grid = (0..RESOLUTION)
+            .rev()
+            .flat_map(|row| (0..RESOLUTION).map(move |col| [coordinate(col), coordinate(row)]))
+            .collect::<Vec<_>>();
+        let (mean, variance) = gp.predict(&na::DVector::from_vec(grid));
+        let std = variance.map(|v| v.max(0.0).sqrt());
+
+        let (mean, mean_range) = image(&mean, mean.min(), mean.max());
+        let (std, std_range) = image(&std, 0.0, std.max());
+        Self {
+            mean: ctx.load_texture("plane_mean", mean, egui::TextureOptions::LINEAR),
+            mean_range,
+            std: ctx.load_texture("plane_std", std, egui::TextureOptions::LINEAR),
+            std_range,
+            surface: Surface::compute(gp, EXTENT),
+        }
+    }
+}
+
+/// The points one length scale from the center of the plane in the metric of the kernel, an
+/// ellipse along its rotated axes.
+fn correlation_ellipse(kernel: &AnisotropicRbfKernel) -> Vec<[f64; 2]> {
+    let (sin, cos) = kernel.angle.sin_cos();
+    let [along, across] = kernel.length_scales;
+    (0..=64)
+        .map(|i| {
+            let t = i as f64 / 64.0 * std::f64::consts::TAU;
+            let (u, v) = (along * t.cos(), across * t.sin());
+            [
+                EXTENT / 2.0 + cos * u - sin * v,
+                EXTENT / 2.0 + sin * u + cos * v,
+            ]
+        })
+        .collect()
+}
+
+/// Map the values to colors between `min` and `max`, returning the image and the range used.
+fn image(values: &na::DVector<f64>, min: f64, max: f64) -> (egui::ColorImage, (f64, f64)) {
+    // avoid dividing by zero for flat surfaces, e.g. the prior mean
+    let max = if max - min < 1e-9 { min + 1.0 } else { max };
+    let pixels = values
+        .iter()
+        .map(|value| colormap((value - min) / (max - min)))
+        .collect();
+    let image = egui::ColorImage {
+        size: [RESOLUTION, RESOLUTION],
+        pixels,
+    };
+    (image, (min, max))
+}
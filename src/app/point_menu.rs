@@ -0,0 +1,107 @@
+use super::dataset::Dataset;
+use super::WEIGHT_RANGE;
+
+/// What the context menu of the main plot was opened on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuTarget {
+    /// A training point, as `(dataset, point)`.
+    Point(usize, usize),
+    /// An empty spot, with its coordinates.
+    Empty([f64; 2]),
+}
+
+/// What the context menu of a training point changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointEdit {
+    Changed,
+    Deleted,
+}
+
+/// The contents of the context menu of a training point: its exact coordinates, its noise, its
+/// label and actions to copy or delete it. The noise of the point is set through its weight,
+/// which divides the noise variance `noise_sigma²` of all points.
+pub fn point_menu(
+    ui: &mut egui::Ui,
+    dataset: &mut Dataset,
+    index: usize,
+    noise_sigma: f64,
+) -> Option<PointEdit> {
+    if index >= dataset.x.len() {
+        ui.close_menu();
+        return None;
+    }
+    let mut edit = None;
+    ui.strong(format!("Point {} of {}", index + 1, dataset.name));
+
+    egui::Grid::new("point_menu").num_columns(2).show(ui, |ui| {
+        ui.label("x");
+        if ui
+            .add(egui::DragValue::new(&mut dataset.x[index]).speed(0.01))
+            .changed()
+        {
+            edit = Some(PointEdit::Changed);
+        }
+        ui.end_row();
+        ui.label("y");
+        if ui
+            .add(egui::DragValue::new(&mut dataset.y[index]).speed(0.01))
+            .changed()
+        {
+            edit = Some(PointEdit::Changed);
+        }
+        ui.end_row();
+
+        ui.label("Noise σ");
+        let mut sigma = noise_sigma / dataset.weight(index).sqrt();
+        let (min, max) = WEIGHT_RANGE;
+        let response = ui.add_enabled(
+            noise_sigma > 0.0,
+            egui::DragValue::new(&mut sigma)
+                .speed(0.005)
+                .range(noise_sigma / max.sqrt()..=noise_sigma / min.sqrt()),
+        );
+        if response
+            .on_hover_text("The noise of this point, through its weight")
+            .on_disabled_hover_text("Without observation noise the points have no weights")
+            .changed()
+        {
+            dataset.set_weight(index, (noise_sigma / sigma).powi(2).clamp(min, max));
+            edit = Some(PointEdit::Changed);
+        }
+        ui.end_row();
+
+        // the label is trimmed when set, so typing goes to a buffer kept while it matches
+        ui.label("Label");
+        let label = dataset.label(index).unwrap_or_default().to_owned();
+        let id = ui.id().with(("point_label", index));
+        let mut text = ui
+            .data(|data| data.get_temp::<String>(id))
+            .filter(|text| text.trim() == label)
+            .unwrap_or(label);
+        if ui
+            .add(
+                egui::TextEdit::singleline(&mut text)
+                    .hint_text("e.g. outlier")
+                    .desired_width(100.0),
+            )
+            .changed()
+        {
+            dataset.set_label(index, &text);
+        }
+        ui.data_mut(|data| data.insert_temp(id, text));
+        ui.end_row();
+    });
+
+    ui.separator();
+    if ui.button("Copy value").clicked() {
+        ui.ctx()
+            .copy_text(format!("{}, {}", dataset.x[index], dataset.y[index]));
+        ui.close_menu();
+    }
+    if ui.button("Delete").clicked() {
+        dataset.remove_point(index);
+        edit = Some(PointEdit::Deleted);
+        ui.close_menu();
+    }
+    edit
+}
@@ -0,0 +1,119 @@
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+use crate::gp::{Kernel, MaternKernel, MaternSmoothness, RbfKernel};
+
+/// Canonical example datasets with suggested kernel settings, for demonstrating the app.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    NoisySine,
+    Step,
+    SeasonalTrend,
+    Heteroscedastic,
+}
+
+/// Training data together with the kernel and noise level suggested for it.
+pub struct PresetData {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub kernel: Kernel,
+    pub noise_sigma: f64,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        Preset::NoisySine,
+        Preset::Step,
+        Preset::SeasonalTrend,
+        Preset::Heteroscedastic,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::NoisySine => "Noisy sine",
+            Preset::Step => "Step function",
+            Preset::SeasonalTrend => "Seasonal trend",
+            Preset::Heteroscedastic => "Heteroscedastic noise",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Preset::NoisySine => "A sine wave with a little noise, fitted well by the RBF kernel.",
+            Preset::Step => "A discontinuity, which the rough Matérn 1/2 kernel can follow.",
+            Preset::SeasonalTrend => {
+                "A rising trend with a seasonal cycle, in the style of the Mauna Loa CO₂ data. \
+                A short length scale follows it, but extrapolates poorly."
+            }
+            Preset::Heteroscedastic => {
+                "Noise growing along x, which a single noise level can only average over."
+            }
+        }
+    }
+
+    /// Generate the data of the preset. The noise is seeded so a preset always looks the same.
+    pub fn load(&self) -> PresetData {
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(*self as u64);
+        let mut noise = |scale: f64| scale * rng.sample::<f64, _>(StandardNormal);
+
+        let (x, kernel, noise_sigma): (Vec<f64>, _, _) = match self {
+            Preset::NoisySine => (
+                (0..20).map(|i| 0.25 + i as f64 * 0.5).collect(),
+                Kernel::Rbf(RbfKernel {
+                    sigma: 1.0,
+                    length_scale: 1.0,
+                }),
+                0.2,
+            ),
+            Preset::Step => (
+                (0..20).map(|i| 0.25 + i as f64 * 0.5).collect(),
+                Kernel::Matern(MaternKernel {
+                    smoothness: MaternSmoothness::Half,
+                    sigma: 1.0,
+                    length_scale: 2.0,
+                }),
+                0.05,
+            ),
+            Preset::SeasonalTrend => (
+                (0..40).map(|i| 0.125 + i as f64 * 0.25).collect(),
+                Kernel::Rbf(RbfKernel {
+                    sigma: 2.0,
+                    length_scale: 0.3,
+                }),
+                0.05,
+            ),
+            Preset::Heteroscedastic => (
+                (0..30).map(|i| 0.15 + i as f64 / 3.0).collect(),
+                Kernel::Rbf(RbfKernel {
+                    sigma: 1.0,
+                    length_scale: 1.5,
+                }),
+                0.45,
+            ),
+        };
+
+        let y = x
+            .iter()
+            .map(|&x| match self {
+                Preset::NoisySine => x.sin() + noise(0.2),
+                Preset::Step => {
+                    let step = if x < 5.0 { -1.0 } else { 1.0 };
+                    step + noise(0.05)
+                }
+                Preset::SeasonalTrend => {
+                    let trend = 0.3 * x - 1.5;
+                    let season = 0.5 * (2.0 * std::f64::consts::PI * x).sin();
+                    trend + season + noise(0.05)
+                }
+                Preset::Heteroscedastic => (0.8 * x).sin() + noise(0.05 + 0.08 * x),
+            })
+            .collect();
+
+        PresetData {
+            x,
+            y,
+            kernel,
+            noise_sigma,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use super::{session, App};
+
+/// A named state of the app, stored as a session document.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+struct Profile {
+    name: String,
+    session: String,
+}
+
+/// What the user asked the profiles menu to do.
+pub enum ProfileAction {
+    /// Save the current state as a new profile with the name.
+    Save(String),
+    /// Overwrite the profile with the current state.
+    Update(usize),
+    /// Restore the state of the profile.
+    Switch(usize),
+}
+
+/// Named states of the datasets, kernels, view settings and open panels, e.g. the steps of a
+/// lecture prepared in advance and switched to live from the menu or with Page Up and Page Down,
+/// which presentation remotes send.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+    /// The profile last switched to or saved.
+    current: Option<usize>,
+    /// The name of the next profile to save.
+    #[serde(skip)]
+    new_name: String,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Profiles {
+    /// The contents of the profiles menu.
+    pub fn menu(&mut self, ui: &mut egui::Ui) -> Option<ProfileAction> {
+        let mut action = None;
+        let mut remove = None;
+        for (i, profile) in self.profiles.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(self.current == Some(i), &profile.name)
+                    .clicked()
+                {
+                    action = Some(ProfileAction::Switch(i));
+                    ui.close_menu();
+                }
+                if ui
+                    .small_button("💾")
+                    .on_hover_text("Overwrite with the current state")
+                    .clicked()
+                {
+                    action = Some(ProfileAction::Update(i));
+                }
+                if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.remove(i);
+        }
+        if !self.profiles.is_empty() {
+            ui.horizontal(|ui| {
+                if ui.button("◀ Previous").clicked() {
+                    action = self.step(-1);
+                }
+                if ui.button("Next ▶").clicked() {
+                    action = self.step(1);
+                }
+            });
+            ui.separator();
+        }
+
+        ui.horizontal(|ui| {
+            let default_name = format!("Profile {}", self.profiles.len() + 1);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_name)
+                    .hint_text(&default_name)
+                    .desired_width(120.0),
+            );
+            if ui
+                .button("Save current")
+                .on_hover_text("Save the data, kernel, view settings and open panels")
+                .clicked()
+            {
+                let name = match self.new_name.trim() {
+                    "" => default_name,
+                    name => name.to_owned(),
+                };
+                self.new_name.clear();
+                action = Some(ProfileAction::Save(name));
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        action
+    }
+
+    /// Switch to the next or previous profile with Page Down and Page Up, outside of text
+    /// fields.
+    pub fn keys(&self, ctx: &egui::Context) -> Option<ProfileAction> {
+        if self.profiles.is_empty() || ctx.memory(|memory| memory.focused().is_some()) {
+            return None;
+        }
+        let (next, previous) = ctx.input(|input| {
+            (
+                input.key_pressed(egui::Key::PageDown),
+                input.key_pressed(egui::Key::PageUp),
+            )
+        });
+        if next {
+            self.step(1)
+        } else if previous {
+            self.step(-1)
+        } else {
+            None
+        }
+    }
+
+    /// Switch to the profile `offset` places from the current one, staying at the ends.
+    fn step(&self, offset: isize) -> Option<ProfileAction> {
+        let last = self.profiles.len().checked_sub(1)?;
+        let index = match self.current {
+            Some(current) => current.saturating_add_signed(offset).min(last),
+            None => 0,
+        };
+        (Some(index) != self.current).then_some(ProfileAction::Switch(index))
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.profiles.remove(index);
+        self.current = match self.current {
+            Some(current) if current == index => None,
+            Some(current) if current > index => Some(current - 1),
+            current => current,
+        };
+    }
+
+    /// Save the state of the app under the name, or in place of the profile at `index`. The
+    /// profiles must have been taken out of the app so they are not saved with it.
+    pub fn save(&mut self, app: &App, name: String, index: Option<usize>) {
+        self.error = None;
+        let session = match session::to_string(app) {
+            Ok(session) => session,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+        match index.and_then(|index| self.profiles.get_mut(index).map(|p| (index, p))) {
+            Some((index, profile)) => {
+                profile.session = session;
+                self.current = Some(index);
+            }
+            None => {
+                self.profiles.push(Profile { name, session });
+                self.current = Some(self.profiles.len() - 1);
+            }
+        }
+    }
+
+    /// The state of the app in the profile at `index`.
+    pub fn load(&mut self, index: usize) -> Option<App> {
+        let profile = self.profiles.get(index)?;
+        match session::from_str(&profile.session) {
+            Ok(app) => {
+                self.error = None;
+                self.current = Some(index);
+                Some(app)
+            }
+            Err(e) => {
+                self.error = Some(format!("{}: {e}", profile.name));
+                None
+            }
+        }
+    }
+
+    /// The name of the profile last switched to or saved.
+    pub fn current_name(&self) -> Option<&str> {
+        let profile = self.profiles.get(self.current?)?;
+        Some(&profile.name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        let mut profiles = Profiles::default();
+        let mut app = App::default();
+        profiles.save(&app, "Prior".to_owned(), None);
+        app.noise_sigma = 0.75;
+        profiles.save(&app, "Noisy".to_owned(), None);
+        assert_eq!(profiles.current_name(), Some("Noisy"));
+
+        // stepping stays at the ends
+        assert!(profiles.step(1).is_none());
+        let Some(ProfileAction::Switch(0)) = profiles.step(-1) else {
+            panic!("expected to switch to the first profile");
+        };
+        assert_eq!(
+            profiles.load(0).unwrap().noise_sigma,
+            App::default().noise_sigma
+        );
+        assert_eq!(profiles.load(1).unwrap().noise_sigma, 0.75);
+
+        // removing a profile before the current one keeps the current one
+        profiles.remove(0);
+        assert_eq!(profiles.current_name(), Some("Noisy"));
+        profiles.remove(0);
+        assert_eq!(profiles.current_name(), None);
+        assert!(profiles.step(1).is_none());
+    }
+}
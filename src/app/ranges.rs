@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use egui::Slider;
+
+/// The ranges of the hyperparameter sliders by parameter name, for the parameters whose range
+/// differs from the default one. A range is edited by right-clicking its slider, and expands to
+/// include values typed above it.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SliderRanges(BTreeMap<String, (f64, f64)>);
+
+impl SliderRanges {
+    /// The range of the parameter, `default` unless it was changed.
+    pub fn range(&self, name: &str, default: (f64, f64)) -> (f64, f64) {
+        self.0.get(name).copied().unwrap_or(default)
+    }
+
+    fn set(&mut self, name: &str, default: (f64, f64), range: (f64, f64)) {
+        if range == default {
+            self.0.remove(name);
+        } else {
+            self.0.insert(name.to_owned(), range);
+        }
+    }
+
+    /// Raise the maximum of the parameter to the value if it is above it.
+    fn include(&mut self, name: &str, default: (f64, f64), value: f64) {
+        let (min, max) = self.range(name, default);
+        if value.is_finite() && value > max {
+            self.set(name, default, (min, value));
+        }
+    }
+
+    /// A slider for the named parameter within its range. Right-clicking it edits the range.
+    pub fn slider(
+        &mut self,
+        ui: &mut egui::Ui,
+        value: &mut f64,
+        name: &str,
+        default: (f64, f64),
+        text: impl Into<egui::WidgetText>,
+    ) -> egui::Response {
+        let (min, max) = self.range(name, default);
+        let response = ui
+            .add(
+                Slider::new(value, min..=max)
+                    .clamping(egui::SliderClamping::Never)
+                    .text(text),
+            )
+            .on_hover_text("Right-click to change the range");
+        if response.changed() {
+            // the minimum keeps parameters like length scales valid
+            *value = value.max(min);
+            self.include(name, default, *value);
+        }
+        response.context_menu(|ui| {
+            let (mut min, mut max) = (min, max);
+            ui.label(format!("Range of {name}"));
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut min).speed(0.01).prefix("min "));
+                ui.add(egui::DragValue::new(&mut max).speed(0.01).prefix("max "));
+            });
+            if min < max {
+                self.set(name, default, (min, max));
+            }
+            if ui.button("Reset").clicked() {
+                self.set(name, default, default);
+                ui.close_menu();
+            }
+        });
+        response
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_include() {
+        let default = (0.0, 10.0);
+        let mut ranges = SliderRanges::default();
+        ranges.include("length scale", default, 5.0);
+        assert_eq!(ranges, SliderRanges::default());
+
+        ranges.include("length scale", default, 40.0);
+        ranges.include("length scale", default, -1.0);
+        ranges.include("length scale", default, f64::NAN);
+        assert_eq!(ranges.range("length scale", default), (0.0, 40.0));
+        assert_eq!(ranges.range("sigma", default), default);
+
+        // setting the default range forgets it
+        ranges.set("length scale", default, default);
+        assert_eq!(ranges, SliderRanges::default());
+    }
+}
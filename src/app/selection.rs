@@ -0,0 +1,131 @@
+use egui_plot::PlotPoint;
+
+use super::dataset::Dataset;
+
+/// What a drag with the selection modifier held is doing.
+pub enum SelectionDrag {
+    /// Drawing a rectangle to select the points within.
+    Box { start: PlotPoint, end: PlotPoint },
+    /// Moving the selected points along with the pointer.
+    Move,
+}
+
+/// Training points selected in the main plot, to be deleted or moved together.
+#[derive(Default)]
+pub struct Selection {
+    /// Indices of the selected points, as `(dataset, point)`.
+    pub points: Vec<(usize, usize)>,
+    pub drag: Option<SelectionDrag>,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.drag = None;
+    }
+
+    /// The coordinates of the selected points that still exist.
+    pub fn coordinates<'a>(
+        &'a self,
+        datasets: &'a [Dataset],
+    ) -> impl Iterator<Item = [f64; 2]> + 'a {
+        self.points.iter().filter_map(|(dataset, index)| {
+            let dataset = datasets.get(*dataset)?;
+            Some([*dataset.x.get(*index)?, *dataset.y.get(*index)?])
+        })
+    }
+
+    /// Select the points of the visible datasets within the rectangle spanned by the corners.
+    pub fn select_box(&mut self, datasets: &[Dataset], a: PlotPoint, b: PlotPoint) {
+        let (x_range, y_range) = (a.x.min(b.x)..=a.x.max(b.x), a.y.min(b.y)..=a.y.max(b.y));
+        self.points = datasets
+            .iter()
+            .enumerate()
+            .filter(|(_, dataset)| dataset.visible)
+            .flat_map(|(i, dataset)| {
+                dataset
+                    .x
+                    .iter()
+                    .zip(dataset.y.iter())
+                    .enumerate()
+                    .filter(|(_, (x, y))| x_range.contains(x) && y_range.contains(y))
+                    .map(move |(j, _)| (i, j))
+            })
+            .collect();
+    }
+
+    /// Move all selected points by the offset.
+    pub fn move_by(&self, datasets: &mut [Dataset], dx: f64, dy: f64) {
+        for (dataset, index) in &self.points {
+            let Some(dataset) = datasets.get_mut(*dataset) else {
+                continue;
+            };
+            if let (Some(x), Some(y)) = (dataset.x.get_mut(*index), dataset.y.get_mut(*index)) {
+                *x += dx;
+                *y += dy;
+            }
+        }
+    }
+
+    /// Remove the selected points from their datasets and clear the selection.
+    pub fn delete(&mut self, datasets: &mut [Dataset]) {
+        // remove from the back so the remaining indices stay valid
+        self.points.sort_unstable();
+        for (dataset, index) in self.points.drain(..).rev() {
+            let Some(dataset) = datasets.get_mut(dataset) else {
+                continue;
+            };
+            if index < dataset.x.len() {
+                dataset.remove_point(index);
+            }
+        }
+        self.drag = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_select_move_delete() {
+        let mut datasets = vec![
+            Dataset {
+                x: vec![1.0, 2.0, 3.0, 4.0],
+                y: vec![0.0, 1.0, 0.0, 1.0],
+                ..Dataset::new(0)
+            },
+            Dataset {
+                x: vec![2.5],
+                y: vec![0.5],
+                ..Dataset::new(1)
+            },
+        ];
+
+        let mut selection = Selection::default();
+        selection.select_box(
+            &datasets,
+            PlotPoint::new(3.5, 1.5),
+            PlotPoint::new(1.5, -0.5),
+        );
+        assert_eq!(selection.points, vec![(0, 1), (0, 2), (1, 0)]);
+
+        selection.move_by(&mut datasets, 1.0, -1.0);
+        assert_eq!(datasets[0].x, vec![1.0, 3.0, 4.0, 4.0]);
+        assert_eq!(datasets[0].y, vec![0.0, 0.0, -1.0, 1.0]);
+        assert_eq!(
+            selection.coordinates(&datasets).collect::<Vec<_>>(),
+            vec![[3.0, 0.0], [4.0, -1.0], [3.5, -0.5]]
+        );
+
+        selection.delete(&mut datasets);
+        assert!(selection.is_empty());
+        assert_eq!(datasets[0].x, vec![1.0, 4.0]);
+        assert_eq!(datasets[0].y, vec![0.0, 1.0]);
+        assert!(datasets[1].x.is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+use super::App;
+
+/// Version of the session file format, increased on changes that old versions cannot read.
+/// Version 2 saves the noise as a standard deviation instead of a variance.
+const VERSION: u32 = 2;
+
+#[derive(serde::Serialize)]
+struct SessionRef<'a> {
+    version: u32,
+    app: &'a App,
+}
+
+#[derive(serde::Deserialize)]
+struct Session {
+    version: u32,
+    app: App,
+}
+
+/// Serialize the datasets, kernels and view settings of the app as a RON document.
+pub fn to_string(app: &App) -> Result<String, String> {
+    let session = SessionRef {
+        version: VERSION,
+        app,
+    };
+    ron::ser::to_string_pretty(&session, ron::ser::PrettyConfig::default())
+        .map_err(|e| format!("Failed to serialize the session: {e}"))
+}
+
+/// Read an app from a session document written by [`to_string`].
+pub fn from_str(text: &str) -> Result<App, String> {
+    let session: Session = ron::from_str(text).map_err(|e| format!("Invalid session: {e}"))?;
+    if session.version > VERSION {
+        return Err(format!(
+            "The session is from a newer version (format {}, expected at most {VERSION})",
+            session.version
+        ));
+    }
+    let mut app = session.app;
+    app.migrate();
+    Ok(app)
+}
+
+/// What the user asked the session dialog to do.
+pub enum Action {
+    Save,
+    Open,
+}
+
+/// A dialog for saving the session to and opening it from a file, independent of the state
+/// that is restored automatically on startup.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SessionDialog {
+    pub open: bool,
+    path: String,
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for SessionDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: "session.ron".to_owned(),
+            status: None,
+        }
+    }
+}
+
+impl SessionDialog {
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Action> {
+        let mut open = self.open;
+        let mut action = None;
+        egui::Window::new("Session")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.text_edit_singleline(&mut self.path);
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        action = Some(Action::Save);
+                    }
+                    if ui.button("Open").clicked() {
+                        action = Some(Action::Open);
+                    }
+                });
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, message);
+                    }
+                    None => {}
+                }
+            });
+        self.open = open;
+        action
+    }
+
+    /// Write the session of the app to the file.
+    pub fn save(&mut self, app: &App) {
+        self.status = Some(
+            to_string(app)
+                .and_then(|text| {
+                    std::fs::write(&self.path, text).map_err(|e| format!("Failed to save: {e}"))
+                })
+                .map(|_| format!("Saved to {}", self.path)),
+        );
+    }
+
+    /// Read the session from the file, reporting any errors in the dialog.
+    pub fn load(&mut self) -> Option<App> {
+        let result = std::fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to open: {e}"))
+            .and_then(|text| from_str(&text));
+        self.status = Some(
+            result
+                .as_ref()
+                .map(|_| format!("Opened {}", self.path))
+                .map_err(Clone::clone),
+        );
+        result.ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let mut app = App::default();
+        app.datasets[0].x = vec![0.5, 1.5];
+        app.datasets[0].y = vec![-1.0, 2.0];
+        app.noise_sigma = 0.25;
+        app.sample_seed = 42;
+
+        let text = to_string(&app).unwrap();
+        let loaded = from_str(&text).unwrap();
+        assert_eq!(loaded.datasets[0].x, app.datasets[0].x);
+        assert_eq!(loaded.datasets[0].y, app.datasets[0].y);
+        assert_eq!(loaded.noise_sigma, 0.25);
+        assert_eq!(loaded.sample_seed, 42);
+        assert_eq!(loaded.kernel.name(), app.kernel.name());
+
+        // sessions from before the noise was a standard deviation saved its variance
+        let old = text
+            .replacen(&format!("version: {VERSION}"), "version: 1", 1)
+            .replacen("state_version: 1,", "", 1)
+            .replacen("noise_sigma: 0.25,", "noise_sigma: 0.0625,", 1);
+        assert!(!old.contains("state_version") && old.contains("noise_sigma: 0.0625,"));
+        assert_eq!(from_str(&old).unwrap().noise_sigma, 0.25);
+
+        let newer = text.replacen(&format!("version: {VERSION}"), "version: 999", 1);
+        assert!(from_str(&newer).is_err());
+        assert!(from_str("not a session").is_err());
+    }
+}
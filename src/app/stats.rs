@@ -0,0 +1,43 @@
+use web_time::Duration;
+
+/// Timings of the last fit and prediction, to show how the cost grows with the data.
+#[derive(Default)]
+pub struct PerformanceStats {
+    pub fit_time: Duration,
+    pub predict_time: Duration,
+    pub training_points: usize,
+    pub grid_size: usize,
+}
+
+impl PerformanceStats {
+    /// Show the stats in a small overlay in the bottom right corner.
+    pub fn show(&self, ctx: &egui::Context) {
+        egui::Area::new(egui::Id::new("performance_stats"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    egui::Grid::new("performance_stats_grid")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("Training points");
+                            ui.monospace(self.training_points.to_string());
+                            ui.end_row();
+                            ui.label("Grid size");
+                            ui.monospace(self.grid_size.to_string());
+                            ui.end_row();
+                            ui.label("Fit");
+                            ui.monospace(milliseconds(self.fit_time));
+                            ui.end_row();
+                            ui.label("Predict");
+                            ui.monospace(milliseconds(self.predict_time));
+                            ui.end_row();
+                        });
+                });
+            });
+    }
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
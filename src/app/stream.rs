@@ -0,0 +1,350 @@
+use std::sync::mpsc;
+
+use egui::Slider;
+
+use super::paste::parse_row;
+
+/// A point received from a stream. Streams that only send values get their x assigned as the
+/// next integer after the last point.
+pub type Sample = (Option<f64>, f64);
+
+/// Parse a line of either `x,y` (or any separator accepted by [`parse_row`]) or just `y`.
+fn parse_sample(line: &str) -> Option<Sample> {
+    parse_row(line)
+        .map(|(x, y)| (Some(x), y))
+        .or_else(|| line.trim().parse().ok().map(|y| (None, y)))
+}
+
+/// Messages sent from the connection to the UI.
+enum Message {
+    Sample(Sample),
+    /// The connection was closed, with the reason.
+    Closed(String),
+}
+
+/// Where points are streamed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum Source {
+    #[cfg(not(target_arch = "wasm32"))]
+    Stdin,
+    #[cfg(not(target_arch = "wasm32"))]
+    Tcp,
+    WebSocket,
+}
+
+impl Source {
+    #[cfg(not(target_arch = "wasm32"))]
+    const ALL: &'static [Source] = &[Source::Stdin, Source::Tcp, Source::WebSocket];
+    #[cfg(target_arch = "wasm32")]
+    const ALL: &'static [Source] = &[Source::WebSocket];
+
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Source::Stdin => "Standard input",
+            #[cfg(not(target_arch = "wasm32"))]
+            Source::Tcp => "TCP",
+            Source::WebSocket => "WebSocket",
+        }
+    }
+}
+
+/// An open connection, delivering messages until it is dropped.
+struct Connection {
+    receiver: mpsc::Receiver<Message>,
+    /// Closes the underlying stream when dropped, if it can be closed.
+    _handle: Option<Box<dyn std::any::Any>>,
+}
+
+/// A panel for appending points to the active dataset as they arrive in real time, one point
+/// per line (or WebSocket message).
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct StreamPanel {
+    source: Source,
+    tcp_address: String,
+    websocket_url: String,
+    /// Scroll the plot to keep the latest points in view.
+    pub follow: bool,
+    /// Width of the x range shown when following.
+    pub window: f64,
+    /// Oldest points are removed beyond this number, to keep refitting fast.
+    pub max_points: usize,
+    #[serde(skip)]
+    connection: Option<Connection>,
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for StreamPanel {
+    fn default() -> Self {
+        Self {
+            source: Source::WebSocket,
+            tcp_address: "127.0.0.1:7878".to_owned(),
+            websocket_url: "ws://127.0.0.1:8080".to_owned(),
+            follow: true,
+            window: 20.0,
+            max_points: 200,
+            connection: None,
+            status: None,
+        }
+    }
+}
+
+impl StreamPanel {
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(!self.is_connected(), |ui| {
+            egui::ComboBox::from_id_salt("stream_source")
+                .selected_text(self.source.name())
+                .show_ui(ui, |ui| {
+                    for source in Source::ALL {
+                        ui.selectable_value(&mut self.source, *source, source.name());
+                    }
+                });
+            match self.source {
+                #[cfg(not(target_arch = "wasm32"))]
+                Source::Stdin => {
+                    ui.label("Reads lines of `x,y` or `y` from the standard input of the app.");
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                Source::Tcp => {
+                    ui.horizontal(|ui| {
+                        ui.label("Address:");
+                        ui.text_edit_singleline(&mut self.tcp_address);
+                    });
+                }
+                Source::WebSocket => {
+                    ui.horizontal(|ui| {
+                        ui.label("URL:");
+                        ui.text_edit_singleline(&mut self.websocket_url);
+                    });
+                }
+            }
+        });
+
+        if self.is_connected() {
+            if ui.button("Disconnect").clicked() {
+                self.connection = None;
+                self.status = Some(Ok("Disconnected.".to_owned()));
+            }
+        } else if ui.button("Connect").clicked() {
+            let (sender, receiver) = mpsc::channel();
+            let handle = match self.source {
+                #[cfg(not(target_arch = "wasm32"))]
+                Source::Stdin => native::stdin(sender, ui.ctx().clone()),
+                #[cfg(not(target_arch = "wasm32"))]
+                Source::Tcp => native::tcp(&self.tcp_address, sender, ui.ctx().clone()),
+                #[cfg(not(target_arch = "wasm32"))]
+                Source::WebSocket => {
+                    native::websocket(&self.websocket_url, sender, ui.ctx().clone())
+                }
+                #[cfg(target_arch = "wasm32")]
+                Source::WebSocket => web::websocket(&self.websocket_url, sender, ui.ctx().clone()),
+            };
+            match handle {
+                Ok(handle) => {
+                    self.connection = Some(Connection {
+                        receiver,
+                        _handle: handle,
+                    });
+                    self.status = Some(Ok("Connected.".to_owned()));
+                }
+                Err(message) => self.status = Some(Err(message)),
+            }
+        }
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(ui.visuals().error_fg_color, message);
+            }
+            None => {}
+        }
+
+        ui.separator();
+        ui.checkbox(&mut self.follow, "Scroll to follow the latest points");
+        ui.add(Slider::new(&mut self.window, 1.0..=100.0).text("Visible x range"));
+        ui.add(Slider::new(&mut self.max_points, 10..=1000).text("Maximum points"));
+    }
+
+    /// Take the samples that arrived since the last call.
+    pub fn poll(&mut self) -> Vec<Sample> {
+        let Some(connection) = &self.connection else {
+            return Vec::new();
+        };
+
+        let mut samples = Vec::new();
+        let mut closed = None;
+        loop {
+            match connection.receiver.try_recv() {
+                Ok(Message::Sample(sample)) => samples.push(sample),
+                Ok(Message::Closed(reason)) => closed = Some(reason),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    closed.get_or_insert_with(|| "Connection closed.".to_owned());
+                    break;
+                }
+            }
+        }
+        if let Some(reason) = closed {
+            self.connection = None;
+            self.status = Some(Err(reason));
+        }
+        samples
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::io::BufRead;
+    use std::sync::mpsc::Sender;
+
+    use super::{parse_sample, Message};
+
+    /// Forward the parsed lines to the UI until the reader ends or the UI disconnects.
+    fn forward_lines(reader: impl BufRead, sender: &Sender<Message>, ctx: &egui::Context) {
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(sample) = parse_sample(&line) {
+                if sender.send(Message::Sample(sample)).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        }
+        sender
+            .send(Message::Closed("End of stream.".to_owned()))
+            .ok();
+        ctx.request_repaint();
+    }
+
+    pub fn stdin(
+        sender: Sender<Message>,
+        ctx: egui::Context,
+    ) -> Result<Option<Box<dyn std::any::Any>>, String> {
+        std::thread::spawn(move || forward_lines(std::io::stdin().lock(), &sender, &ctx));
+        Ok(None)
+    }
+
+    /// Shuts down the TCP stream when dropped, which ends the reading thread.
+    struct TcpHandle(std::net::TcpStream);
+
+    impl Drop for TcpHandle {
+        fn drop(&mut self) {
+            self.0.shutdown(std::net::Shutdown::Both).ok();
+        }
+    }
+
+    pub fn tcp(
+        address: &str,
+        sender: Sender<Message>,
+        ctx: egui::Context,
+    ) -> Result<Option<Box<dyn std::any::Any>>, String> {
+        let stream =
+            std::net::TcpStream::connect(address).map_err(|e| format!("Failed to connect: {e}"))?;
+        let handle = stream
+            .try_clone()
+            .map_err(|e| format!("Failed to connect: {e}"))?;
+        std::thread::spawn(move || {
+            forward_lines(std::io::BufReader::new(stream), &sender, &ctx);
+        });
+        Ok(Some(Box::new(TcpHandle(handle))))
+    }
+
+    pub fn websocket(
+        url: &str,
+        sender: Sender<Message>,
+        ctx: egui::Context,
+    ) -> Result<Option<Box<dyn std::any::Any>>, String> {
+        let (mut socket, _) =
+            tungstenite::connect(url).map_err(|e| format!("Failed to connect: {e}"))?;
+        std::thread::spawn(move || loop {
+            let message = match socket.read() {
+                Ok(tungstenite::Message::Text(text)) => text,
+                Ok(tungstenite::Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    sender
+                        .send(Message::Closed(format!("Connection lost: {e}")))
+                        .ok();
+                    break;
+                }
+            };
+            for sample in message.lines().filter_map(parse_sample) {
+                if sender.send(Message::Sample(sample)).is_err() {
+                    socket.close(None).ok();
+                    return;
+                }
+            }
+            ctx.request_repaint();
+        });
+        Ok(None)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::sync::mpsc::Sender;
+
+    use eframe::wasm_bindgen::{closure::Closure, JsCast as _};
+
+    use super::{parse_sample, Message};
+
+    /// Closes the socket when dropped, keeping the callbacks alive until then.
+    struct WebSocketHandle {
+        socket: web_sys::WebSocket,
+        _on_message: Closure<dyn FnMut(web_sys::MessageEvent)>,
+        _on_close: Closure<dyn FnMut(web_sys::Event)>,
+    }
+
+    impl Drop for WebSocketHandle {
+        fn drop(&mut self) {
+            self.socket.set_onmessage(None);
+            self.socket.set_onclose(None);
+            self.socket.close().ok();
+        }
+    }
+
+    pub fn websocket(
+        url: &str,
+        sender: Sender<Message>,
+        ctx: egui::Context,
+    ) -> Result<Option<Box<dyn std::any::Any>>, String> {
+        let socket =
+            web_sys::WebSocket::new(url).map_err(|e| format!("Failed to connect: {e:?}"))?;
+
+        let on_message = {
+            let sender = sender.clone();
+            let ctx = ctx.clone();
+            Closure::<dyn FnMut(_)>::new(move |event: web_sys::MessageEvent| {
+                if let Some(text) = event.data().as_string() {
+                    for sample in text.lines().filter_map(parse_sample) {
+                        sender.send(Message::Sample(sample)).ok();
+                    }
+                    ctx.request_repaint();
+                }
+            })
+        };
+        let on_close = Closure::<dyn FnMut(_)>::new(move |_: web_sys::Event| {
+            sender
+                .send(Message::Closed("Connection closed.".to_owned()))
+                .ok();
+            ctx.request_repaint();
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Ok(Some(Box::new(WebSocketHandle {
+            socket,
+            _on_message: on_message,
+            _on_close: on_close,
+        })))
+    }
+}
@@ -0,0 +1,231 @@
+use egui::{Color32, Slider};
+
+/// The Okabe-Ito palette, which stays distinguishable with the common kinds of color blindness.
+const COLORBLIND_PALETTE: [Color32; 6] = [
+    Color32::from_rgb(230, 159, 0),
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(0, 158, 115),
+    Color32::from_rgb(213, 94, 0),
+    Color32::from_rgb(0, 114, 178),
+    Color32::from_rgb(204, 121, 167),
+];
+
+/// Shapes the training points can be drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Marker {
+    Circle,
+    Square,
+    Diamond,
+    Cross,
+    Plus,
+    Triangle,
+}
+
+impl Marker {
+    const ALL: [Marker; 6] = [
+        Marker::Circle,
+        Marker::Square,
+        Marker::Diamond,
+        Marker::Cross,
+        Marker::Plus,
+        Marker::Triangle,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Marker::Circle => "Circle",
+            Marker::Square => "Square",
+            Marker::Diamond => "Diamond",
+            Marker::Cross => "Cross",
+            Marker::Plus => "Plus",
+            Marker::Triangle => "Triangle",
+        }
+    }
+
+    pub fn shape(self) -> egui_plot::MarkerShape {
+        match self {
+            Marker::Circle => egui_plot::MarkerShape::Circle,
+            Marker::Square => egui_plot::MarkerShape::Square,
+            Marker::Diamond => egui_plot::MarkerShape::Diamond,
+            Marker::Cross => egui_plot::MarkerShape::Cross,
+            Marker::Plus => egui_plot::MarkerShape::Plus,
+            Marker::Triangle => egui_plot::MarkerShape::Up,
+        }
+    }
+}
+
+/// Colors, line widths and markers of the main plot.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlotStyle {
+    pub mean_width: f32,
+    pub band_width: f32,
+    /// Opacity of the band lines relative to the mean line of the same color.
+    pub band_opacity: f32,
+    pub prior_color: Color32,
+    pub sample_color: Color32,
+    pub sample_width: f32,
+    pub comparison_color: Color32,
+    pub marker: Marker,
+    pub point_radius: f32,
+    pub error_bar_width: f32,
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        Self {
+            mean_width: 1.5,
+            band_width: 1.5,
+            band_opacity: 0.5,
+            prior_color: Color32::GRAY,
+            sample_color: Color32::GRAY,
+            sample_width: 0.5,
+            comparison_color: Color32::from_rgb(230, 130, 0),
+            marker: Marker::Circle,
+            point_radius: 5.0,
+            error_bar_width: 1.5,
+        }
+    }
+}
+
+impl PlotStyle {
+    /// The style with thicker lines, larger points and more opaque bands, legible on a
+    /// projector.
+    pub fn presentation(&self) -> Self {
+        Self {
+            mean_width: 2.0 * self.mean_width,
+            band_width: 2.0 * self.band_width,
+            band_opacity: self.band_opacity.max(0.8),
+            sample_width: 2.0 * self.sample_width,
+            point_radius: 1.75 * self.point_radius,
+            error_bar_width: 2.0 * self.error_bar_width,
+            ..self.clone()
+        }
+    }
+
+    /// The color of the uncertainty band around a mean of the given color.
+    pub fn band_color(&self, color: Color32) -> Color32 {
+        color.gamma_multiply(self.band_opacity)
+    }
+
+    /// Show the settings, returning the palette to recolor the datasets with if asked to.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<&'static [Color32]> {
+        let mut palette = None;
+        egui::Grid::new("plot_style").num_columns(2).show(ui, |ui| {
+            ui.label("Mean");
+            ui.add(Slider::new(&mut self.mean_width, 0.5..=5.0).text("width"));
+            ui.end_row();
+
+            ui.label("Band");
+            ui.horizontal(|ui| {
+                ui.add(Slider::new(&mut self.band_width, 0.5..=5.0).text("width"));
+                ui.add(Slider::new(&mut self.band_opacity, 0.1..=1.0).text("opacity"));
+            });
+            ui.end_row();
+
+            ui.label("Prior");
+            ui.color_edit_button_srgba(&mut self.prior_color);
+            ui.end_row();
+
+            ui.label("Prior samples");
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut self.sample_color);
+                ui.add(Slider::new(&mut self.sample_width, 0.25..=3.0).text("width"));
+            });
+            ui.end_row();
+
+            ui.label("Kernel comparison");
+            ui.color_edit_button_srgba(&mut self.comparison_color);
+            ui.end_row();
+
+            ui.label("Points");
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("point_marker")
+                    .selected_text(self.marker.name())
+                    .show_ui(ui, |ui| {
+                        for marker in Marker::ALL {
+                            ui.selectable_value(&mut self.marker, marker, marker.name());
+                        }
+                    });
+                ui.add(Slider::new(&mut self.point_radius, 1.0..=10.0).text("radius"));
+            });
+            ui.end_row();
+
+            ui.label("Error bars");
+            ui.add(Slider::new(&mut self.error_bar_width, 0.5..=5.0).text("width"));
+            ui.end_row();
+        });
+
+        ui.label("Dataset colors are set in the datasets list, or all at once:");
+        ui.horizontal(|ui| {
+            if ui.button("Default palette").clicked() {
+                palette = Some(&super::dataset::PALETTE[..]);
+            }
+            if ui
+                .button("Colorblind-friendly palette")
+                .on_hover_text("The Okabe-Ito palette")
+                .clicked()
+            {
+                palette = Some(&COLORBLIND_PALETTE[..]);
+            }
+        });
+        if ui.button("Reset style").clicked() {
+            *self = Self::default();
+        }
+        palette
+    }
+}
+
+/// The title of the main plot and the labels of its axes, for figures that go into reports.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlotLabels {
+    pub title: String,
+    x_label: String,
+    x_unit: String,
+    y_label: String,
+    y_unit: String,
+}
+
+/// An axis label with its unit in parentheses, like "Time (s)".
+fn axis_label(label: &str, unit: &str) -> String {
+    match (label.trim(), unit.trim()) {
+        (label, "") => label.to_owned(),
+        ("", unit) => format!("({unit})"),
+        (label, unit) => format!("{label} ({unit})"),
+    }
+}
+
+impl PlotLabels {
+    pub fn x_axis(&self) -> String {
+        axis_label(&self.x_label, &self.x_unit)
+    }
+
+    pub fn y_axis(&self) -> String {
+        axis_label(&self.y_label, &self.y_unit)
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("plot_labels")
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.label("Title");
+                ui.text_edit_singleline(&mut self.title);
+                ui.end_row();
+
+                for (axis, label, unit) in [
+                    ("x-axis", &mut self.x_label, &mut self.x_unit),
+                    ("y-axis", &mut self.y_label, &mut self.y_unit),
+                ] {
+                    ui.label(axis);
+                    ui.add(egui::TextEdit::singleline(label).hint_text("label"));
+                    ui.add(
+                        egui::TextEdit::singleline(unit)
+                            .hint_text("unit")
+                            .desired_width(60.0),
+                    );
+                    ui.end_row();
+                }
+            });
+    }
+}
@@ -0,0 +1,211 @@
+use nalgebra as na;
+
+use super::heatmap::colormap;
+use crate::gp::{GaussianProcess, GpKernel};
+
+/// Number of grid points along each axis of the surface.
+const RESOLUTION: usize = 30;
+
+/// Radians the surface turns per point dragged.
+const ROTATION_SPEED: f64 = 0.01;
+
+/// The posterior of a GP on inputs in a square, sampled on a grid to draw as a surface.
+pub struct Surface {
+    extent: f64,
+    /// The mean and the standard deviation at the grid points, row by row along x2.
+    mean: Vec<f64>,
+    std: Vec<f64>,
+}
+
+impl Surface {
+    /// Predict on a grid covering `0.0..=extent` along both axes.
+    pub fn compute<K: GpKernel<[f64; 2]>>(gp: &GaussianProcess<K, [f64; 2]>, extent: f64) -> Self {
+        let coordinate = |i: usize| i as f64 / (RESOLUTION - 1) as f64 * extent;
+        let grid = (0..RESOLUTION)
+            .flat_map(|row| (0..RESOLUTION).map(move |col| [coordinate(col), coordinate(row)]))
+            .collect::<Vec<_>>();
+        let (mean, variance) = gp.predict(&na::DVector::from_vec(grid));
+        Self {
+            extent,
+            mean: mean.as_slice().to_vec(),
+            std: variance.iter().map(|v| v.max(0.0).sqrt()).collect(),
+        }
+    }
+}
+
+/// The orientation of the view of the surface.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Camera {
+    /// Rotation around the vertical axis, in radians.
+    pub yaw: f64,
+    /// Angle of the view above the plane, in radians.
+    pub pitch: f64,
+    /// Vertical exaggeration of the surface.
+    pub height: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            yaw: 0.6,
+            pitch: 0.5,
+            height: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    /// Project a point orthographically to the screen, returning its position with x to the
+    /// right and y up, and its depth, larger further away.
+    fn project(&self, [x, y, z]: [f64; 3]) -> ([f64; 2], f64) {
+        let (sin, cos) = self.yaw.sin_cos();
+        let (right, forward) = (cos * x - sin * y, sin * x + cos * y);
+        let (sin, cos) = self.pitch.sin_cos();
+        ([right, cos * z + sin * forward], cos * forward - sin * z)
+    }
+}
+
+/// Draw the mean as a surface colored by the standard deviation, with the training points on
+/// top. Dragging rotates the view.
+pub fn show(ui: &mut egui::Ui, surface: &Surface, camera: &mut Camera, x: &[[f64; 2]], y: &[f64]) {
+    ui.horizontal(|ui| {
+        ui.add(egui::Slider::new(&mut camera.height, 0.2..=3.0).text("Height"));
+        if ui.button("Reset view").clicked() {
+            *camera = Camera {
+                height: camera.height,
+                ..Default::default()
+            };
+        }
+    });
+    let std_max = surface.std.iter().copied().fold(0.0, f64::max);
+    ui.label(format!(
+        "Height: mean, color: standard deviation (0 to {std_max:.2})"
+    ));
+
+    let size = ui.available_width().min(ui.available_height()).max(100.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::drag());
+    let response = response.on_hover_text("Drag to rotate");
+    let delta = response.drag_delta();
+    camera.yaw -= delta.x as f64 * ROTATION_SPEED;
+    camera.pitch =
+        (camera.pitch + delta.y as f64 * ROTATION_SPEED).clamp(0.0, std::f64::consts::FRAC_PI_2);
+
+    // fit the surface into a unit cube centered at the origin
+    let (min, max) = surface
+        .mean
+        .iter()
+        .chain(y)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        });
+    let range = if max - min > 1e-9 { max - min } else { 1.0 };
+    let to_cube = |[x1, x2]: [f64; 2], value: f64| {
+        [
+            x1 / surface.extent - 0.5,
+            x2 / surface.extent - 0.5,
+            ((value - min) / range - 0.5) * 0.5 * camera.height,
+        ]
+    };
+    let rect = response.rect;
+    let to_screen = |point: [f64; 3]| {
+        let ([right, up], depth) = camera.project(point);
+        let scale = 0.65 * rect.width() as f64;
+        let position = rect.center() + egui::vec2((right * scale) as f32, -(up * scale) as f32);
+        (position, depth)
+    };
+
+    let coordinate = |i: usize| i as f64 / (RESOLUTION - 1) as f64 * surface.extent;
+    let corner = |row: usize, col: usize| {
+        let index = row * RESOLUTION + col;
+        let point = to_cube([coordinate(col), coordinate(row)], surface.mean[index]);
+        (point, surface.std[index])
+    };
+
+    // painter's algorithm: draw the cells from back to front
+    let mut cells = Vec::with_capacity((RESOLUTION - 1) * (RESOLUTION - 1));
+    for row in 0..RESOLUTION - 1 {
+        for col in 0..RESOLUTION - 1 {
+            let corners = [
+                corner(row, col),
+                corner(row, col + 1),
+                corner(row + 1, col + 1),
+                corner(row + 1, col),
+            ];
+            let std = corners.iter().map(|(_, std)| std).sum::<f64>() / 4.0;
+            let points = corners.map(|(point, _)| point);
+            let depth = points.iter().map(|p| camera.project(*p).1).sum::<f64>() / 4.0;
+            cells.push((depth, points, std));
+        }
+    }
+    cells.sort_by(|(a, ..), (b, ..)| b.total_cmp(a));
+
+    let mut mesh = egui::Mesh::default();
+    for (_, [a, b, c, d], std) in cells {
+        // shade by the slope so the shape stays visible where the color is uniform
+        let normal = cross(sub(c, a), sub(d, b));
+        let length = normal.iter().map(|n| n * n).sum::<f64>().sqrt();
+        let light = if length > 0.0 {
+            0.6 + 0.4 * (normal[2] / length).abs()
+        } else {
+            1.0
+        };
+        let color = colormap(if std_max > 0.0 { std / std_max } else { 0.0 });
+        let color = egui::Color32::from_rgb(
+            (color.r() as f64 * light) as u8,
+            (color.g() as f64 * light) as u8,
+            (color.b() as f64 * light) as u8,
+        );
+        let first = mesh.vertices.len() as u32;
+        for point in [a, b, c, d] {
+            mesh.colored_vertex(to_screen(point).0, color);
+        }
+        mesh.add_triangle(first, first + 1, first + 2);
+        mesh.add_triangle(first, first + 2, first + 3);
+    }
+    painter.add(mesh);
+
+    let stroke = egui::Stroke::new(1.0, ui.visuals().text_color());
+    for (x, y) in x.iter().zip(y) {
+        let (position, _) = to_screen(to_cube(*x, *y));
+        painter.circle(position, 4.0, egui::Color32::WHITE, stroke);
+    }
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_project() {
+        // seen from the side, the height is up and the far side is deeper
+        let side = Camera {
+            yaw: 0.0,
+            pitch: 0.0,
+            height: 1.0,
+        };
+        assert_eq!(side.project([0.5, 0.0, 0.25]), ([0.5, 0.25], 0.0));
+        assert_eq!(side.project([0.0, 0.5, 0.0]).1, 0.5);
+
+        // seen from above, the far side is up and the higher points are closer
+        let above = Camera {
+            pitch: std::f64::consts::FRAC_PI_2,
+            ..side
+        };
+        let ([right, up], depth) = above.project([0.5, 0.5, 0.25]);
+        assert!((right - 0.5).abs() < 1e-12 && (up - 0.5).abs() < 1e-12);
+        assert!((depth + 0.25).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,168 @@
+use super::{kernel_ui, App, Mode};
+use crate::gp::{Kernel, MaternKernel, MaternSmoothness, RbfKernel};
+
+/// A step of the tutorial, setting up the app state it talks about when entered.
+struct Step {
+    title: &'static str,
+    text: &'static str,
+    setup: fn(&mut App),
+}
+
+const STEPS: [Step; 7] = [
+    Step {
+        title: "The prior",
+        text: "A Gaussian process is a distribution over functions. Before seeing any data, \
+            the prior says that the function is zero on average (the gray line) and stays \
+            within the gray band (±2 standard deviations) 95% of the time. The thin lines are \
+            functions sampled from the prior.",
+        setup: |app| {
+            app.mode = Mode::Regression;
+            app.kernel = Kernel::Rbf(RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            });
+            app.noise_sigma = 0.1;
+            app.compare_kernels = false;
+            app.show_prior = true;
+            app.num_prior_samples = 3;
+            app.datasets[app.active_dataset].clear_points();
+        },
+    },
+    Step {
+        title: "Adding a point",
+        text: "We have observed the function at x = 3. The posterior (in color) now passes \
+            close to the observation, as it only keeps the functions of the prior that agree \
+            with the data. You can click anywhere in the plot to add points yourself, and \
+            right-click a point to edit or remove it.",
+        setup: |app| {
+            app.show_prior = true;
+            app.datasets[app.active_dataset].set_points(vec![3.0], vec![1.0]);
+        },
+    },
+    Step {
+        title: "Shrinking uncertainty",
+        text: "With more observations the band shrinks around the data, where we know the \
+            function well. Far from the data, the uncertainty grows back to that of the prior \
+            and the mean returns to zero.",
+        setup: |app| {
+            app.show_prior = false;
+            app.datasets[app.active_dataset].set_points(
+                vec![1.0, 2.0, 3.0, 5.0, 6.0],
+                vec![0.2, 0.6, 1.0, -0.4, -1.0],
+            );
+        },
+    },
+    Step {
+        title: "Length scale",
+        text: "The kernel length scale sets how quickly the function can change. We have \
+            shortened it, so the mean wiggles between the points and the uncertainty grows \
+            quickly away from them. Try dragging the length scale slider to see the effect.",
+        setup: |app| {
+            *kernel_ui::length_scale_mut(&mut app.kernel) = 0.3;
+        },
+    },
+    Step {
+        title: "Noise",
+        text: "The noise sigma says how much the observations are trusted. With more noise, \
+            the mean no longer has to pass through every point, giving a smoother fit. The \
+            vertical bars on the points show the assumed noise.",
+        setup: |app| {
+            *kernel_ui::length_scale_mut(&mut app.kernel) = 1.0;
+            app.noise_sigma = 0.5;
+        },
+    },
+    Step {
+        title: "Kernel choice",
+        text: "The kernel type sets the character of the functions. Here the rough Matérn 1/2 \
+            kernel is compared to the smooth RBF kernel on the same data. The log marginal \
+            likelihood below each kernel tells which one explains the data best.",
+        setup: |app| {
+            app.noise_sigma = 0.1;
+            app.kernel = Kernel::Rbf(RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            });
+            app.compare_kernels = true;
+            app.comparison_kernel = Kernel::Matern(MaternKernel {
+                smoothness: MaternSmoothness::Half,
+                sigma: 1.0,
+                length_scale: 1.0,
+            });
+        },
+    },
+    Step {
+        title: "Explore",
+        text: "That's it! Keep adding data and changing the hyperparameters, or have a look at \
+            the presets and the windows in the View menu.",
+        setup: |_| {},
+    },
+];
+
+/// Progress through the tutorial, if it is running.
+#[derive(Default)]
+pub struct Tutorial {
+    step: Option<usize>,
+}
+
+/// Start the tutorial from the first step.
+pub fn start(app: &mut App) {
+    enter(app, 0);
+}
+
+fn enter(app: &mut App, step: usize) {
+    app.tutorial.step = Some(step);
+    (STEPS[step].setup)(app);
+}
+
+/// Show the window of the current step. Returns true if the app state was changed.
+pub fn show(ctx: &egui::Context, app: &mut App) -> bool {
+    let Some(step) = app.tutorial.step else {
+        return false;
+    };
+
+    let mut next = None;
+    let mut open = true;
+    let mut finished = false;
+    egui::Window::new(format!(
+        "Tutorial ({}/{}): {}",
+        step + 1,
+        STEPS.len(),
+        STEPS[step].title
+    ))
+    .id(egui::Id::new("tutorial"))
+    .open(&mut open)
+    .collapsible(false)
+    .resizable(false)
+    .anchor(egui::Align2::RIGHT_TOP, [-10.0, 40.0])
+    .default_width(300.0)
+    .show(ctx, |ui| {
+        ui.label(STEPS[step].text);
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(step > 0, egui::Button::new("◀ Back"))
+                .clicked()
+            {
+                next = Some(step - 1);
+            }
+            if step + 1 < STEPS.len() {
+                if ui.button("Next ▶").clicked() {
+                    next = Some(step + 1);
+                }
+            } else if ui.button("Finish").clicked() {
+                finished = true;
+            }
+        });
+    });
+
+    if !open || finished {
+        app.tutorial.step = None;
+        return false;
+    }
+    match next {
+        Some(step) => {
+            enter(app, step);
+            true
+        }
+        None => false,
+    }
+}
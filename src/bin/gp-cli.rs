@@ -0,0 +1,586 @@
+//! Fit a Gaussian process to training data from a CSV file and print its predictions, for use
+//! in scripts and pipelines.
+
+#![warn(clippy::all, rust_2018_idioms)]
+
+use std::fmt::Write as _;
+
+use gaussian_processes::gp::{
+    smooth, sweep_csv, GaussianProcess, HyperparameterOptimizer, Kernel, RbfKernel, SmoothOptions,
+    SweepSpace,
+};
+use nalgebra as na;
+
+const USAGE: &str = "\
+Usage: gp-cli <train.csv> [options]
+       gp-cli --model <model.ron> [options]
+       gp-cli smooth <data.csv> [options]
+       gp-cli sweep <data.csv> --range <low:high:n>... [options]
+
+Fits a Gaussian process to the first two columns (x, y) of the training data, or loads one
+saved with --save, and prints the predictions as CSV or JSON.
+
+The smooth command standardizes the data, optimizes the hyperparameters and prints the
+smoothed values and derivatives with their standard deviations at the inputs. The kernel and
+noise are where the optimization starts.
+
+The sweep command evaluates the log marginal likelihood and the leave-one-out log predictive
+density of the data over a grid of the kernel hyperparameters and the noise, and prints them as
+CSV while it runs. Give one --range for each hyperparameter, in the order of the kernel's
+parameters followed by the noise. The values are spaced evenly in log space.
+
+Options:
+  --kernel <ron>        The kernel in RON [default: Rbf((sigma: 1.0, length_scale: 1.0)), or
+                        a length scale guessed from the data when smoothing]
+  --noise <sigma>       The standard deviation of the observation noise [default: 0.1]
+  --normalize           Standardize the targets before fitting
+  --aggregate           Replace repeated x values with the mean of their targets
+  --optimize            Optimize the kernel hyperparameters and the noise first
+  --verbose             Print the progress of the optimization to standard error
+  --grid <start:end:n>  Predict at n evenly spaced points [default: the data range, 100 points]
+  --test <test.csv>     Predict at the values in the first column of a file instead
+  --format <csv|json>   The output format [default: csv]
+  --output <path>       Write to a file instead of standard output
+  --save <model.ron>    Save the fitted model
+  --model <model.ron>   Predict with a saved model instead of fitting one
+  --range <low:high:n>  Sweep a hyperparameter over n values from low to high; the n may be
+                        left out with --random
+  --random <n>          Sweep n points drawn log-uniformly within the ranges instead of a grid
+  --seed <n>            The seed of the random sweep [default: 0]
+  --help                Print this message
+";
+
+/// Give up optimizing after this many iterations if it has not converged.
+const MAX_ITERATIONS: usize = 1000;
+
+#[derive(Debug, PartialEq)]
+enum Inputs {
+    Grid { start: f64, end: f64, n: usize },
+    Test(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+enum Source {
+    Train(String),
+    Model(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Predict,
+    Smooth,
+    Sweep,
+}
+
+/// The range of one hyperparameter in a sweep.
+#[derive(Debug, PartialEq)]
+struct SweepRange {
+    low: f64,
+    high: f64,
+    n: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    source: Source,
+    kernel: Option<Kernel>,
+    noise_sigma: f64,
+    normalize: bool,
+    aggregate: bool,
+    optimize: bool,
+    verbose: bool,
+    inputs: Option<Inputs>,
+    format: Format,
+    output: Option<String>,
+    save: Option<String>,
+    ranges: Vec<SweepRange>,
+    random: Option<usize>,
+    seed: u64,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut args = args.into_iter().peekable();
+    let mut source = None;
+    let command = match args
+        .next_if(|arg| arg == "smooth" || arg == "sweep")
+        .as_deref()
+    {
+        Some("smooth") => Command::Smooth,
+        Some(_) => Command::Sweep,
+        None => Command::Predict,
+    };
+    let mut parsed = Args {
+        command,
+        source: Source::Train(String::new()),
+        kernel: None,
+        noise_sigma: 0.1,
+        normalize: false,
+        aggregate: false,
+        optimize: false,
+        verbose: false,
+        inputs: None,
+        format: Format::Csv,
+        output: None,
+        save: None,
+        ranges: Vec::new(),
+        random: None,
+        seed: 0,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--kernel" => {
+                parsed.kernel =
+                    Some(ron::from_str(&value()?).map_err(|e| format!("invalid kernel: {e}"))?);
+            }
+            "--noise" => {
+                parsed.noise_sigma = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid noise: {e}"))?;
+            }
+            "--normalize" => parsed.normalize = true,
+            "--aggregate" => parsed.aggregate = true,
+            "--optimize" => parsed.optimize = true,
+            "--verbose" => parsed.verbose = true,
+            "--grid" => {
+                let grid = value()?;
+                let fields: Vec<&str> = grid.split(':').collect();
+                let invalid = || format!("invalid grid {grid:?}, expected start:end:n");
+                let [start, end, n] = fields[..] else {
+                    return Err(invalid());
+                };
+                parsed.inputs = Some(Inputs::Grid {
+                    start: start.parse().map_err(|_| invalid())?,
+                    end: end.parse().map_err(|_| invalid())?,
+                    n: n.parse().map_err(|_| invalid())?,
+                });
+            }
+            "--test" => parsed.inputs = Some(Inputs::Test(value()?)),
+            "--format" => {
+                parsed.format = match value()?.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    format => return Err(format!("unknown format {format:?}")),
+                };
+            }
+            "--range" => {
+                let range = value()?;
+                let fields: Vec<&str> = range.split(':').collect();
+                let invalid = || format!("invalid range {range:?}, expected low:high:n");
+                let (low, high, n) = match fields[..] {
+                    [low, high] => (low, high, None),
+                    [low, high, n] => (low, high, Some(n.parse().map_err(|_| invalid())?)),
+                    _ => return Err(invalid()),
+                };
+                let (low, high): (f64, f64) = (
+                    low.parse().map_err(|_| invalid())?,
+                    high.parse().map_err(|_| invalid())?,
+                );
+                if !(low > 0.0 && high > 0.0) {
+                    return Err(format!(
+                        "invalid range {range:?}, the bounds must be positive"
+                    ));
+                }
+                parsed.ranges.push(SweepRange { low, high, n });
+            }
+            "--random" => {
+                parsed.random = Some(
+                    value()?
+                        .parse()
+                        .map_err(|e| format!("invalid number of points: {e}"))?,
+                );
+            }
+            "--seed" => {
+                parsed.seed = value()?.parse().map_err(|e| format!("invalid seed: {e}"))?;
+            }
+            "--output" => parsed.output = Some(value()?),
+            "--save" => parsed.save = Some(value()?),
+            "--model" if source.is_some() => {
+                return Err("give either training data or a model, not both".to_owned())
+            }
+            "--model" => source = Some(Source::Model(value()?)),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if source.is_none() => source = Some(Source::Train(arg)),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+
+    parsed.source = source.ok_or("missing the training data")?;
+    let only_data = matches!(parsed.source, Source::Model(_))
+        || parsed.inputs.is_some()
+        || parsed.save.is_some();
+    match parsed.command {
+        Command::Smooth if only_data => {
+            return Err(
+                "smooth takes only data, without --model, --grid, --test or --save".to_owned(),
+            );
+        }
+        Command::Sweep if only_data || parsed.optimize => {
+            return Err(
+                "sweep takes only data, without --model, --grid, --test, --save or --optimize"
+                    .to_owned(),
+            );
+        }
+        Command::Sweep if parsed.format == Format::Json => {
+            return Err("sweep only writes CSV".to_owned());
+        }
+        Command::Sweep if parsed.ranges.is_empty() => {
+            return Err("sweep needs a --range for each hyperparameter".to_owned());
+        }
+        Command::Sweep
+            if parsed.random.is_none() && parsed.ranges.iter().any(|r| r.n.is_none()) =>
+        {
+            return Err("the ranges of a grid sweep need a number of values".to_owned());
+        }
+        Command::Predict | Command::Smooth
+            if !parsed.ranges.is_empty() || parsed.random.is_some() =>
+        {
+            return Err("--range and --random are only used by sweep".to_owned());
+        }
+        _ => {}
+    }
+    Ok(parsed)
+}
+
+/// Parse the numbers in the first `N` columns of each row, separated by commas, semicolons,
+/// tabs or spaces. A header on the first line is skipped.
+fn parse_csv<const N: usize>(text: &str) -> Result<Vec<[f64; N]>, String> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line
+            .split(['\t', ',', ';', ' '])
+            .filter(|field| !field.is_empty())
+            .map(|field| field.trim().parse::<f64>());
+        let row: Option<Vec<f64>> = (0..N).map(|_| fields.next()?.ok()).collect();
+        match row {
+            Some(row) => rows.push(row.try_into().expect("should have N columns")),
+            None if i == 0 => {} // probably a header
+            None => return Err(format!("invalid row on line {}: {line:?}", i + 1)),
+        }
+    }
+    Ok(rows)
+}
+
+fn read_csv<const N: usize>(path: &str) -> Result<Vec<[f64; N]>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("could not read {path}: {e}"))?;
+    parse_csv(&text).map_err(|e| format!("{path}: {e}"))
+}
+
+fn linspace(start: f64, end: f64, n: usize) -> Vec<f64> {
+    match n {
+        0 => Vec::new(),
+        1 => vec![start],
+        _ => (0..n)
+            .map(|i| start + (end - start) * i as f64 / (n - 1) as f64)
+            .collect(),
+    }
+}
+
+fn default_kernel() -> Kernel {
+    Kernel::Rbf(RbfKernel {
+        sigma: 1.0,
+        length_scale: 1.0,
+    })
+}
+
+/// Fit a Gaussian process to the training data in a CSV file.
+fn fit(path: &str, args: &Args) -> Result<GaussianProcess<Kernel>, String> {
+    let data = read_csv::<2>(path)?;
+    let x: Vec<f64> = data.iter().map(|[x, _]| *x).collect();
+    let y: Vec<f64> = data.iter().map(|[_, y]| *y).collect();
+
+    let mut kernel = args.kernel.clone().unwrap_or_else(default_kernel);
+    let mut noise_sigma = args.noise_sigma;
+    if args.optimize {
+        let mut optimizer = HyperparameterOptimizer::new(
+            &na::DVector::from_column_slice(&x),
+            &na::DVector::from_column_slice(&y),
+            kernel,
+            noise_sigma,
+        );
+        optimizer.run(MAX_ITERATIONS, |event| {
+            if args.verbose {
+                eprintln!(
+                    "iteration {}: log marginal likelihood {:.6}, hyperparameters {:?}",
+                    event.iteration, event.log_marginal_likelihood, event.hyperparameters
+                );
+            }
+            true
+        });
+        (kernel, noise_sigma) = optimizer.best();
+    }
+
+    GaussianProcess::builder()
+        .kernel(kernel)
+        .noise(noise_sigma)
+        .normalize(args.normalize)
+        .aggregate_replicates(args.aggregate)
+        .build_from_slices(&x, &y)
+        .map_err(|e| format!("could not fit: {e}"))
+}
+
+fn write_output(out: String, path: Option<&str>) -> Result<(), String> {
+    match path {
+        Some(path) => std::fs::write(path, out).map_err(|e| format!("could not write {path}: {e}")),
+        None => {
+            print!("{out}");
+            Ok(())
+        }
+    }
+}
+
+fn array(values: &[f64]) -> String {
+    let values: Vec<String> = values.iter().map(f64::to_string).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Smooth the data in a CSV file and print the smoothed values and derivatives.
+fn run_smooth(path: &str, args: &Args) -> Result<(), String> {
+    let data = read_csv::<2>(path)?;
+    let x: Vec<f64> = data.iter().map(|[x, _]| *x).collect();
+    let y: Vec<f64> = data.iter().map(|[_, y]| *y).collect();
+    let options = SmoothOptions {
+        kernel: args.kernel.clone(),
+        noise_sigma: args.noise_sigma,
+        max_iterations: MAX_ITERATIONS,
+    };
+    let smoothed = smooth(&x, &y, &options).map_err(|e| format!("could not fit: {e}"))?;
+    if args.verbose {
+        eprintln!(
+            "kernel {}, noise {}",
+            smoothed.gp.kernel().description(),
+            smoothed.gp.noise_sigma()
+        );
+    }
+
+    let mut out = String::new();
+    match args.format {
+        Format::Csv => {
+            out += "x,value,std,derivative,derivative_std\n";
+            for (i, x) in x.iter().enumerate() {
+                writeln!(
+                    out,
+                    "{x},{},{},{},{}",
+                    smoothed.value[i],
+                    smoothed.value_std[i],
+                    smoothed.derivative[i],
+                    smoothed.derivative_std[i]
+                )
+                .unwrap();
+            }
+        }
+        Format::Json => {
+            writeln!(out, "{{").unwrap();
+            writeln!(out, "  \"x\": {},", array(&x)).unwrap();
+            writeln!(out, "  \"value\": {},", array(&smoothed.value)).unwrap();
+            writeln!(out, "  \"std\": {},", array(&smoothed.value_std)).unwrap();
+            writeln!(out, "  \"derivative\": {},", array(&smoothed.derivative)).unwrap();
+            let derivative_std = array(&smoothed.derivative_std);
+            writeln!(out, "  \"derivative_std\": {derivative_std}").unwrap();
+            writeln!(out, "}}").unwrap();
+        }
+    }
+    write_output(out, args.output.as_deref())
+}
+
+/// Sweep the hyperparameters over the data in a CSV file, writing the results as they come.
+fn run_sweep(path: &str, args: &Args) -> Result<(), String> {
+    let data = read_csv::<2>(path)?;
+    let x: Vec<f64> = data.iter().map(|[x, _]| *x).collect();
+    let y: Vec<f64> = data.iter().map(|[_, y]| *y).collect();
+    let kernel = args.kernel.clone().unwrap_or_else(default_kernel);
+    let space = match args.random {
+        Some(samples) => SweepSpace::Random {
+            bounds: args.ranges.iter().map(|r| (r.low, r.high)).collect(),
+            samples,
+            seed: args.seed,
+        },
+        None => {
+            let axes: Vec<(f64, f64, usize)> = args
+                .ranges
+                .iter()
+                .map(|r| (r.low, r.high, r.n.expect("checked when parsing")))
+                .collect();
+            SweepSpace::log_grid(&axes)
+        }
+    };
+
+    let writer: Box<dyn std::io::Write> = match &args.output {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).map_err(|e| format!("could not write {path}: {e}"))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let rows = sweep_csv(&x, &y, &kernel, &space, writer).map_err(|e| format!("{e}"))?;
+    if args.verbose {
+        eprintln!("evaluated {rows} points");
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> Result<(), String> {
+    if args.command != Command::Predict {
+        let Source::Train(path) = &args.source else {
+            unreachable!("smooth and sweep are only parsed with training data");
+        };
+        return match args.command {
+            Command::Smooth => run_smooth(path, &args),
+            _ => run_sweep(path, &args),
+        };
+    }
+    let gp = match &args.source {
+        Source::Train(path) => fit(path, &args)?,
+        Source::Model(path) => {
+            GaussianProcess::load(path).map_err(|e| format!("could not load {path}: {e}"))?
+        }
+    };
+    if let Some(path) = &args.save {
+        gp.save(path)
+            .map_err(|e| format!("could not save {path}: {e}"))?;
+    }
+
+    let test = match args.inputs {
+        Some(Inputs::Grid { start, end, n }) => linspace(start, end, n),
+        Some(Inputs::Test(path)) => read_csv::<1>(&path)?.into_iter().map(|[x]| x).collect(),
+        None if gp.inputs().is_empty() => Vec::new(),
+        None => linspace(gp.inputs().min(), gp.inputs().max(), 100),
+    };
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let (mean, variance) = gp.predict_parallel(&test, threads);
+
+    let mut out = String::new();
+    match args.format {
+        Format::Csv => {
+            out += "x,mean,std,lower,upper\n";
+            for ((x, mean), variance) in test.iter().zip(&mean).zip(&variance) {
+                let std = variance.sqrt();
+                let (lower, upper) = (mean - 2.0 * std, mean + 2.0 * std);
+                writeln!(out, "{x},{mean},{std},{lower},{upper}").unwrap();
+            }
+        }
+        Format::Json => {
+            let kernel = ron::to_string(gp.kernel()).map_err(|e| e.to_string())?;
+            let kernel = kernel.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(out, "{{").unwrap();
+            writeln!(out, "  \"kernel\": \"{kernel}\",").unwrap();
+            writeln!(out, "  \"noise_sigma\": {},", gp.noise_sigma()).unwrap();
+            let lml = gp.log_marginal_likelihood();
+            writeln!(out, "  \"log_marginal_likelihood\": {lml},").unwrap();
+            writeln!(out, "  \"x\": {},", array(&test)).unwrap();
+            writeln!(out, "  \"mean\": {},", array(&mean)).unwrap();
+            writeln!(out, "  \"variance\": {}", array(&variance)).unwrap();
+            writeln!(out, "}}").unwrap();
+        }
+    }
+
+    write_output(out, args.output.as_deref())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help") {
+        print!("{USAGE}");
+        return;
+    }
+
+    if let Err(error) = parse_args(args).and_then(run) {
+        eprintln!("error: {error}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = args(&[
+            "train.csv",
+            "--kernel",
+            "Periodic((sigma: 1.0, length_scale: 2.0, period: 3.0))",
+            "--noise",
+            "0.5",
+            "--optimize",
+            "--grid",
+            "0:10:11",
+            "--format",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(parsed.source, Source::Train("train.csv".to_owned()));
+        assert_eq!(parsed.kernel.unwrap().name(), "Periodic");
+        assert_eq!(parsed.command, Command::Predict);
+        assert_eq!(parsed.noise_sigma, 0.5);
+        assert!(parsed.optimize && !parsed.normalize);
+        assert_eq!(
+            parsed.inputs,
+            Some(Inputs::Grid {
+                start: 0.0,
+                end: 10.0,
+                n: 11
+            })
+        );
+        assert_eq!(parsed.format, Format::Json);
+
+        let parsed = args(&["--model", "model.ron", "--test", "test.csv"]).unwrap();
+        assert_eq!(parsed.source, Source::Model("model.ron".to_owned()));
+        assert_eq!(parsed.inputs, Some(Inputs::Test("test.csv".to_owned())));
+
+        let parsed = args(&["smooth", "data.csv", "--format", "json"]).unwrap();
+        assert!(parsed.command == Command::Smooth && parsed.kernel.is_none());
+        assert_eq!(parsed.source, Source::Train("data.csv".to_owned()));
+        assert!(args(&["smooth", "data.csv", "--grid", "0:1:2"]).is_err());
+        assert!(args(&["smooth", "--model", "model.ron"]).is_err());
+
+        let parsed = args(&[
+            "sweep", "data.csv", "--range", "0.1:10:5", "--range", "1:2:2",
+        ])
+        .unwrap();
+        assert_eq!(parsed.command, Command::Sweep);
+        assert_eq!(
+            parsed.ranges[0],
+            SweepRange {
+                low: 0.1,
+                high: 10.0,
+                n: Some(5)
+            }
+        );
+        let parsed = args(&["sweep", "data.csv", "--range", "0.1:10", "--random", "50"]).unwrap();
+        assert_eq!((parsed.ranges[0].n, parsed.random), (None, Some(50)));
+        assert!(args(&["sweep", "data.csv"]).is_err());
+        assert!(args(&["sweep", "data.csv", "--range", "0.1:10"]).is_err());
+        assert!(args(&["sweep", "data.csv", "--range", "0:10:5"]).is_err());
+        assert!(args(&["sweep", "data.csv", "--range", "1:2:2", "--format", "json"]).is_err());
+        assert!(args(&["train.csv", "--range", "1:2:2"]).is_err());
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["train.csv", "--model", "model.ron"]).is_err());
+        assert!(args(&["train.csv", "--grid", "0:10"]).is_err());
+        assert!(args(&["train.csv", "--noise"]).is_err());
+        assert!(args(&["train.csv", "--unknown"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv::<2>("x,y\n1,2\n\n3;4.5\n").unwrap();
+        assert_eq!(rows, vec![[1.0, 2.0], [3.0, 4.5]]);
+        assert!(parse_csv::<2>("1,2\n3\n").is_err());
+        assert_eq!(parse_csv::<1>("1\n2,5\n").unwrap(), vec![[1.0], [2.0]]);
+    }
+}
@@ -1,61 +1,456 @@
+//! Gaussian process regression and classification, independent of the app.
+//!
+//! ```
+//! use gaussian_processes::gp::{GaussianProcess, RbfKernel};
+//! use nalgebra::DVector;
+//!
+//! let x = DVector::from_vec(vec![1.0, 2.0, 6.0]);
+//! let y = DVector::from_vec(vec![1.0, 1.0, -1.0]);
+//! let kernel = RbfKernel {
+//!     sigma: 1.0,
+//!     length_scale: 1.0,
+//! };
+//! let gp = GaussianProcess::new(&x, &y, kernel, 0.1);
+//! let (_, variance) = gp.predict(&DVector::from_vec(vec![1.5, 4.0]));
+//! assert!(variance[0] < variance[1]);
+//! ```
+
 use nalgebra as na;
 
-pub struct GaussianProcess<K: GpKernel> {
+mod acquisition;
+mod baseline;
+mod builder;
+mod calibration;
+#[cfg(feature = "capi")]
+mod capi;
+mod classification;
+mod committee;
+mod conformal;
+mod deep_kernel;
+mod drift;
+mod gplvm;
+mod gradient;
+mod heteroscedastic;
+mod heuristics;
+mod holdout;
+#[cfg(test)]
+mod invariants;
+mod kernel;
+#[cfg(feature = "linfa")]
+mod linfa_interop;
+mod local_experts;
+mod mcmc;
+mod model;
+mod multi_fidelity;
+#[cfg(feature = "ndarray")]
+mod ndarray_interop;
+mod optimize;
+mod parallel;
+mod projection;
+mod replicates;
+mod serialize;
+mod smooth;
+mod spectral;
+mod student_t;
+mod sweep;
+mod transform;
+mod uncertain_input;
+mod vecchia;
+#[cfg(feature = "wasm")]
+mod wasm;
+pub use acquisition::*;
+pub use baseline::*;
+pub use builder::*;
+pub use calibration::*;
+#[cfg(feature = "capi")]
+pub use capi::*;
+pub use classification::*;
+pub use committee::*;
+pub use conformal::*;
+pub use deep_kernel::*;
+pub use drift::*;
+pub use gplvm::*;
+pub use gradient::*;
+pub use heteroscedastic::*;
+pub use heuristics::*;
+pub use holdout::*;
+pub use kernel::*;
+#[cfg(feature = "linfa")]
+pub use linfa_interop::*;
+pub use local_experts::*;
+pub use mcmc::*;
+pub use model::*;
+pub use multi_fidelity::*;
+#[cfg(feature = "ndarray")]
+pub use ndarray_interop::*;
+pub use optimize::*;
+pub use projection::*;
+pub use replicates::*;
+pub use serialize::{ModelFileError, MODEL_FORMAT_VERSION};
+pub use smooth::*;
+pub use spectral::*;
+pub use student_t::*;
+pub use sweep::*;
+pub use transform::*;
+pub use vecchia::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+
+pub struct GaussianProcess<K: GpKernel<I>, I: GpInput = f64> {
     kernel: K,
-    x: na::DVector<f64>,
+    x: na::DVector<I>,
+    /// The targets after subtracting `y_offset` and dividing by `y_scale`, which is what the
+    /// kernel models.
     y: na::DVector<f64>,
+    /// The standard deviation of the observation noise of the normalized targets.
+    noise_sigma: f64,
+    /// Added to the diagonal of the covariance matrices to keep them numerically positive
+    /// definite, unlike the noise without any statistical meaning.
+    jitter: f64,
+    /// How much each observation counts, dividing the noise variance of that observation.
+    weights: na::DVector<f64>,
+    y_offset: f64,
+    y_scale: f64,
     input_cov_matrix_inv: na::DMatrix<f64>,
 }
 
-pub trait GpKernel {
-    fn compute(&self, x: f64, x2: f64) -> f64;
+/// Errors from fitting a Gaussian process.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GpError {
+    /// The builder was not given a kernel.
+    MissingKernel,
+    /// There is not one target for every input.
+    LengthMismatch { x: usize, y: usize },
+    /// The covariance matrix of the training data could not be inverted.
+    NotInvertible,
+    /// The inputs have a different number of dimensions than the model.
+    DimensionMismatch { expected: usize, got: usize },
+    /// The observation weights are not positive, or there is not one for every input.
+    InvalidWeights,
+}
 
-    fn compute_matrix(&self, x: &na::DVector<f64>, x2: &na::DVector<f64>) -> na::DMatrix<f64> {
-        let mut matrix = na::DMatrix::zeros(x.len(), x2.len());
-        for i in 0..x.len() {
-            for j in 0..x2.len() {
-                matrix[(i, j)] = self.compute(x[i], x2[j]);
+impl std::fmt::Display for GpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GpError::MissingKernel => write!(f, "no kernel was given"),
+            GpError::LengthMismatch { x, y } => {
+                write!(f, "got {x} inputs but {y} targets")
+            }
+            GpError::NotInvertible => write!(f, "the covariance matrix is not invertible"),
+            GpError::DimensionMismatch { expected, got } => {
+                write!(f, "expected {expected} dimensional inputs but got {got}")
+            }
+            GpError::InvalidWeights => {
+                write!(f, "there must be one positive weight for every input")
             }
         }
-        matrix //.transpose() // TOD, correct?
     }
 }
 
-/// Radial basis function kernel
-pub struct RbfKernel {
-    pub sigma: f64,
-    pub length_scale: f64,
-}
-
-impl GpKernel for RbfKernel {
-    fn compute(&self, x: f64, x2: f64) -> f64 {
-        self.sigma * (-0.5 * (x - x2).powi(2) / self.length_scale.powi(2)).exp()
-    }
-}
+impl std::error::Error for GpError {}
 
-/// Constant to add to make sure matrices are positive definite
-const EPS: f64 = 1e-6;
+/// Constant to add to make sure matrices are positive definite, the default jitter.
+pub const EPS: f64 = 1e-6;
 
-impl<K: GpKernel> GaussianProcess<K> {
+impl<K: GpKernel<I>, I: GpInput> GaussianProcess<K, I> {
     pub fn new(
-        x: &na::DVector<f64>,
+        x: &na::DVector<I>,
+        y: &na::DVector<f64>,
+        kernel: K,
+        noise_sigma: f64,
+    ) -> GaussianProcess<K, I> {
+        Self::builder()
+            .kernel(kernel)
+            .noise(noise_sigma)
+            .build(x, y)
+            .expect("should be invertible")
+    }
+
+    /// Like [`Self::new`], but with a weight for each observation that divides its noise
+    /// variance, e.g. the number of measurements averaged into it.
+    pub fn new_weighted(
+        x: &na::DVector<I>,
         y: &na::DVector<f64>,
+        weights: &na::DVector<f64>,
+        kernel: K,
+        noise_sigma: f64,
+    ) -> Result<GaussianProcess<K, I>, GpError> {
+        Self::builder()
+            .kernel(kernel)
+            .noise(noise_sigma)
+            .weights(weights.clone())
+            .build(x, y)
+    }
+
+    /// Like [`Self::new`], but taking the training data as slices.
+    pub fn from_slices(
+        x: &[I],
+        y: &[f64],
         kernel: K,
         noise_sigma: f64,
-    ) -> GaussianProcess<K> {
-        let k = kernel.compute_matrix(x, x)
-            + na::DMatrix::identity(x.len(), x.len()) * (noise_sigma + EPS);
-        let inverse = k.try_inverse().expect("should be invertible");
+    ) -> Result<GaussianProcess<K, I>, GpError> {
+        Self::builder()
+            .kernel(kernel)
+            .noise(noise_sigma)
+            .build_from_slices(x, y)
+    }
 
-        GaussianProcess {
+    /// Configure a Gaussian process with more options than [`Self::new`] has.
+    pub fn builder() -> GaussianProcessBuilder<K, I> {
+        GaussianProcessBuilder::default()
+    }
+
+    /// Create a Gaussian process without any training data, i.e. the prior.
+    pub fn prior(kernel: K, noise_sigma: f64) -> GaussianProcess<K, I> {
+        Self::new(
+            &na::DVector::from_vec(Vec::new()),
+            &na::DVector::zeros(0),
             kernel,
-            x: x.clone(),
-            y: y.clone(),
-            input_cov_matrix_inv: inverse,
+            noise_sigma,
+        )
+    }
+
+    /// Add a single observation with weight one, updating the inverse of the covariance matrix
+    /// in `O(n^2)` instead of refitting in `O(n^3)`.
+    pub fn add_observation(&mut self, x: I, y: f64) {
+        let n = self.x.len();
+        let b = self
+            .kernel
+            .compute_matrix(&self.x, &na::DVector::from_element(1, x))
+            .column(0)
+            .into_owned();
+        let c = self.kernel.compute(x, x) + self.noise_sigma.powi(2) + self.jitter;
+
+        // block inverse of [[K, b], [b^T, c]] using the Schur complement s of K
+        let u = &self.input_cov_matrix_inv * &b;
+        let s = c - b.dot(&u);
+        let mut inverse = na::DMatrix::zeros(n + 1, n + 1);
+        inverse
+            .view_mut((0, 0), (n, n))
+            .copy_from(&(&self.input_cov_matrix_inv + &u * u.transpose() / s));
+        inverse.view_mut((0, n), (n, 1)).copy_from(&(-&u / s));
+        inverse
+            .view_mut((n, 0), (1, n))
+            .copy_from(&(-u.transpose() / s));
+        inverse[(n, n)] = 1.0 / s;
+
+        self.input_cov_matrix_inv = inverse;
+        self.x = self.x.push(x);
+        self.y = self.y.push((y - self.y_offset) / self.y_scale);
+        self.weights = self.weights.push(1.0);
+    }
+
+    /// A new posterior that is also conditioned on the (hypothetical) observations, with
+    /// weight one, leaving this one untouched. The inverse of the covariance matrix is extended
+    /// blockwise in `O(n^2 m + m^3)` for `m` new observations instead of refitting.
+    ///
+    /// This is what lookahead acquisition functions and what-if questions are built on: how
+    /// would the fit look if we observed `y` at `x`?
+    pub fn condition_on(&self, x: &[I], y: &[f64]) -> Result<Self, GpError>
+    where
+        K: Clone,
+    {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
         }
+        let n = self.x.len();
+        let m = x.len();
+        let x_new = na::DVector::from_column_slice(x);
+        let b = self.kernel.compute_matrix(&self.x, &x_new);
+        let c = self.kernel.compute_matrix(&x_new, &x_new)
+            + na::DMatrix::identity(m, m) * (self.noise_sigma.powi(2) + self.jitter);
+
+        // block inverse of [[K, B], [B^T, C]] using the Schur complement S of K
+        let u = &self.input_cov_matrix_inv * &b;
+        let s_inv = na::Cholesky::new(c - b.transpose() * &u)
+            .ok_or(GpError::NotInvertible)?
+            .inverse();
+        let u_s_inv = &u * &s_inv;
+        let mut inverse = na::DMatrix::zeros(n + m, n + m);
+        inverse
+            .view_mut((0, 0), (n, n))
+            .copy_from(&(&self.input_cov_matrix_inv + &u_s_inv * u.transpose()));
+        inverse.view_mut((0, n), (n, m)).copy_from(&-&u_s_inv);
+        inverse
+            .view_mut((n, 0), (m, n))
+            .copy_from(&-u_s_inv.transpose());
+        inverse.view_mut((n, n), (m, m)).copy_from(&s_inv);
+
+        let all_x = na::DVector::from_iterator(n + m, self.x.iter().chain(x).copied());
+        let all_y = na::DVector::from_iterator(
+            n + m,
+            self.y
+                .iter()
+                .copied()
+                .chain(y.iter().map(|y| (y - self.y_offset) / self.y_scale)),
+        );
+        Ok(Self {
+            kernel: self.kernel.clone(),
+            x: all_x,
+            y: all_y,
+            noise_sigma: self.noise_sigma,
+            jitter: self.jitter,
+            weights: self.weights.clone().resize_vertically(n + m, 1.0),
+            y_offset: self.y_offset,
+            y_scale: self.y_scale,
+            input_cov_matrix_inv: inverse,
+        })
+    }
+
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+
+    /// The standard deviation of the observation noise, in the units of the normalized
+    /// targets.
+    pub fn noise_sigma(&self) -> f64 {
+        self.noise_sigma
+    }
+
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    /// The training inputs.
+    pub fn inputs(&self) -> &na::DVector<I> {
+        &self.x
+    }
+
+    /// The weights of the observations, all one unless given when fitting.
+    pub fn weights(&self) -> &na::DVector<f64> {
+        &self.weights
+    }
+
+    /// The training targets in their original units.
+    fn targets(&self) -> na::DVector<f64> {
+        self.y.map(|y| self.y_offset + self.y_scale * y)
+    }
+
+    /// The variance of the observation noise of a new observation with weight one, in the units
+    /// of the targets.
+    fn noise_variance(&self) -> f64 {
+        (self.noise_sigma * self.y_scale).powi(2)
     }
 
-    pub fn predict(&self, x: &na::DVector<f64>) -> (na::DVector<f64>, na::DVector<f64>) {
+    /// The covariance matrix of the normalized targets, including the observation noise.
+    fn normalized_covariance_matrix(&self) -> na::DMatrix<f64> {
+        builder::covariance_matrix(
+            &self.kernel,
+            &self.x,
+            self.noise_sigma,
+            self.jitter,
+            &self.weights,
+        )
+    }
+
+    /// The covariance matrix of the training data, including the observation noise.
+    pub fn covariance_matrix(&self) -> na::DMatrix<f64> {
+        self.normalized_covariance_matrix() * self.y_scale.powi(2)
+    }
+
+    /// The eigenvalues of the covariance matrix of the normalized targets, including the
+    /// noise and the jitter, from the largest to the smallest. None is smaller than the noise
+    /// variance plus the jitter, and the ratio of the largest to the smallest is the condition
+    /// number, which limits the accuracy of the fit.
+    pub fn covariance_spectrum(&self) -> Vec<f64> {
+        let mut eigenvalues = self
+            .normalized_covariance_matrix()
+            .symmetric_eigenvalues()
+            .as_slice()
+            .to_vec();
+        eigenvalues.sort_by(|a, b| b.total_cmp(a));
+        eigenvalues
+    }
+
+    /// Residuals `y - mean` of the posterior at the training points, together with the
+    /// residuals standardized by the predictive standard deviation (including noise).
+    pub fn residuals(&self) -> (na::DVector<f64>, na::DVector<f64>) {
+        let (mean, variance) = self.predict(&self.x);
+        let residuals = self.targets() - mean;
+        let noise = self.weights.map(|w| self.noise_variance() / w);
+        let variance = variance + noise;
+        let standardized = residuals.zip_map(&variance, |r, v| r / v.sqrt());
+        (residuals, standardized)
+    }
+
+    /// The log marginal likelihood `log p(y | x)` of the training data under the model, which
+    /// is the usual objective for choosing hyperparameters.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        let Some(cholesky) = na::Cholesky::new(self.normalized_covariance_matrix()) else {
+            return f64::NEG_INFINITY;
+        };
+
+        let data_fit = -0.5 * self.y.dot(&(&self.input_cov_matrix_inv * &self.y));
+        let log_det = 2.0 * cholesky.l().diagonal().map(f64::ln).sum();
+        let n = self.y.len() as f64;
+
+        // the change of variables from the normalized targets scales the density
+        data_fit
+            - 0.5 * log_det
+            - 0.5 * n * (2.0 * std::f64::consts::PI).ln()
+            - n * self.y_scale.ln()
+    }
+
+    /// Analytic leave-one-out predictions at each training point, i.e. the predictive mean and
+    /// variance (including noise) at `x[i]` of the GP trained on all points except `i`.
+    ///
+    /// See Rasmussen & Williams, Gaussian Processes for Machine Learning, eq. 5.12.
+    pub fn leave_one_out(&self) -> (na::DVector<f64>, na::DVector<f64>) {
+        let alpha = &self.input_cov_matrix_inv * &self.y;
+        let diagonal = self.input_cov_matrix_inv.diagonal();
+
+        let mean = na::DVector::from_fn(self.y.len(), |i, _| {
+            self.y_offset + self.y_scale * (self.y[i] - alpha[i] / diagonal[i])
+        });
+        let variance = diagonal.map(|d| self.y_scale.powi(2) / d);
+        (mean, variance)
+    }
+
+    /// The effective degrees of freedom `tr(K (K + noise)^-1)` of the fit, i.e. the trace of
+    /// the matrix mapping the targets to the fitted mean at the training points.
+    ///
+    /// It is between zero and the number of observations: close to zero when the noise
+    /// explains most of the data and the fit is smooth, and close to the number of observations
+    /// when the fit interpolates every point.
+    pub fn effective_degrees_of_freedom(&self) -> f64 {
+        // tr(K (K + S)^-1) = n - tr(S (K + S)^-1) for the diagonal noise S
+        let noise_trace: f64 = (0..self.x.len())
+            .map(|i| {
+                (self.noise_sigma.powi(2) / self.weights[i] + self.jitter)
+                    * self.input_cov_matrix_inv[(i, i)]
+            })
+            .sum();
+        self.x.len() as f64 - noise_trace
+    }
+
+    /// Predict the mean and variance at the given points. Unlike [`Self::predict_covariance`]
+    /// this only computes the diagonal of the covariance, so it is cheap for many points.
+    pub fn predict(&self, x: &na::DVector<I>) -> (na::DVector<f64>, na::DVector<f64>) {
+        let k_star = self.kernel.compute_matrix(&self.x, x);
+        let mean = (k_star.transpose() * &self.input_cov_matrix_inv * &self.y)
+            .map(|mean| self.y_offset + self.y_scale * mean);
+
+        let weighted = &self.input_cov_matrix_inv * &k_star;
+        let variance = na::DVector::from_fn(x.len(), |i, _| {
+            (self.kernel.compute(x[i], x[i]) - k_star.column(i).dot(&weighted.column(i))
+                + self.jitter)
+                * self.y_scale.powi(2)
+        });
+
+        (mean, variance)
+    }
+
+    /// Like [`Self::predict`], but taking and returning plain vectors.
+    pub fn predict_slice(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        let (mean, variance) = self.predict(&na::DVector::from_column_slice(x));
+        (mean.data.into(), variance.data.into())
+    }
+
+    /// Predict the mean and the full covariance matrix at the given points.
+    pub fn predict_covariance(&self, x: &na::DVector<I>) -> (na::DVector<f64>, na::DMatrix<f64>) {
         // Compute the covariance matrix between the input and the training data (lower left)
         let k_star = self.kernel.compute_matrix(&self.x, x);
         // Compute the covariance matrix between the input and itself (lower right)
@@ -67,60 +462,292 @@ impl<K: GpKernel> GaussianProcess<K> {
         // println!("Y: {:?}", self.y);
 
         // TODO: figure out the issue with this, why do we need the additional transpose for k_star?
-        let mean = &k_star.transpose() * &self.input_cov_matrix_inv * &self.y;
+        let mean = (&k_star.transpose() * &self.input_cov_matrix_inv * &self.y)
+            .map(|mean| self.y_offset + self.y_scale * mean);
         // println!("Mean; {:?}", mean);
 
         let covariance = k_star_star - k_star.transpose() * &self.input_cov_matrix_inv * &k_star;
-        let covariance =
-            &covariance + na::DMatrix::identity(covariance.nrows(), covariance.ncols()) * EPS;
+        let covariance = (&covariance
+            + na::DMatrix::identity(covariance.nrows(), covariance.ncols()) * self.jitter)
+            * self.y_scale.powi(2);
+
+        (mean, covariance)
+    }
+
+    /// Draw `n` function samples from the posterior evaluated at the given points.
+    pub fn sample<R: rand::Rng>(
+        &self,
+        x: &na::DVector<I>,
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<na::DVector<f64>> {
+        let Some((mean, l)) = self.sample_factor(x) else {
+            return Vec::new();
+        };
+        (0..n)
+            .map(|_| &mean + &l * standard_normal(x.len(), rng))
+            .collect()
+    }
 
-        let variance = covariance.diagonal();
+    /// Draw `n` function samples that move smoothly through the `frames` steps of an animation
+    /// and end next to where they started, so the animation can loop. Every frame holds valid
+    /// samples from the posterior, as each sample follows the circle `z1 cos t + z2 sin t`
+    /// through two independent standard normal draws.
+    pub fn sample_loop<R: rand::Rng>(
+        &self,
+        x: &na::DVector<I>,
+        n: usize,
+        frames: usize,
+        rng: &mut R,
+    ) -> Vec<Vec<na::DVector<f64>>> {
+        let Some((mean, l)) = self.sample_factor(x) else {
+            return Vec::new();
+        };
+        let circles: Vec<_> = (0..n)
+            .map(|_| (standard_normal(x.len(), rng), standard_normal(x.len(), rng)))
+            .collect();
+        (0..frames)
+            .map(|frame| {
+                let t = std::f64::consts::TAU * frame as f64 / frames as f64;
+                circles
+                    .iter()
+                    .map(|(z1, z2)| &mean + &l * (z1 * t.cos() + z2 * t.sin()))
+                    .collect()
+            })
+            .collect()
+    }
 
-        // println!("Variance: {:?}", variance);
+    /// The mean and the Cholesky factor of the covariance of the posterior at the points, which
+    /// turn standard normal draws into samples.
+    fn sample_factor(&self, x: &na::DVector<I>) -> Option<(na::DVector<f64>, na::DMatrix<f64>)> {
+        let (mean, covariance) = self.predict_covariance(x);
 
-        (mean, variance)
+        // densely sampled covariance matrices are close to singular, so keep adding jitter
+        // until the decomposition succeeds (or give up if it is hopeless)
+        let cholesky = (0..10).find_map(|i| {
+            let jitter = if i == 0 { 0.0 } else { EPS * 10f64.powi(i) };
+            na::Cholesky::new(&covariance + na::DMatrix::identity(x.len(), x.len()) * jitter)
+        })?;
+        Some((mean, cholesky.l()))
     }
 }
 
+fn standard_normal<R: rand::Rng>(n: usize, rng: &mut R) -> na::DVector<f64> {
+    na::DVector::from_fn(n, |_, _| rng.sample(rand_distr::StandardNormal))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use na::DVector;
 
     #[test]
-    fn test_rbf_kernel_compute() {
+    fn test_gaussian_process_new() {
+        let x = DVector::from_vec(vec![1.0, 2.0]);
+        let y = DVector::from_vec(vec![3.0, 4.0]);
         let kernel = RbfKernel {
             sigma: 1.0,
             length_scale: 1.0,
         };
-        let result = kernel.compute(1.0, 2.0);
-        assert!((result - 0.60653066).abs() < 1e-6);
+        let gp = GaussianProcess::new(&x, &y, kernel, 0.1);
+        assert_eq!(gp.x, x);
+        assert_eq!(gp.y, y);
     }
 
     #[test]
-    fn test_rbf_kernel_compute_matrix() {
+    fn test_gaussian_process_covariance_matrix() {
+        let x = DVector::from_vec(vec![1.0, 2.0]);
+        let y = DVector::from_vec(vec![3.0, 4.0]);
         let kernel = RbfKernel {
             sigma: 1.0,
             length_scale: 1.0,
         };
-        let x = DVector::from_vec(vec![1.0, 2.0]);
-        let x2 = DVector::from_vec(vec![1.0, 2.0]);
-        let result = kernel.compute_matrix(&x, &x2);
-        let expected = na::DMatrix::from_vec(2, 2, vec![1.0, 0.60653066, 0.60653066, 1.0]);
-        assert!((result - expected).abs().max() < 1e-6);
+        let gp = GaussianProcess::new(&x, &y, kernel, 0.1f64.sqrt());
+
+        let expected = na::DMatrix::from_vec(2, 2, vec![1.1, 0.60653066, 0.60653066, 1.1]);
+        assert!((gp.covariance_matrix() - expected).abs().max() < 1e-5);
+        assert!(
+            (gp.covariance_matrix() * &gp.input_cov_matrix_inv - na::DMatrix::identity(2, 2))
+                .abs()
+                .max()
+                < 1e-9
+        );
+
+        // the eigenvalues of [[a, b], [b, a]] are a + b and a - b
+        let spectrum = gp.covariance_spectrum();
+        assert!((spectrum[0] - 1.70653066).abs() < 1e-5);
+        assert!((spectrum[1] - 0.49346934).abs() < 1e-5);
     }
 
     #[test]
-    fn test_gaussian_process_new() {
-        let x = DVector::from_vec(vec![1.0, 2.0]);
-        let y = DVector::from_vec(vec![3.0, 4.0]);
+    fn test_gaussian_process_residuals() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0]);
+        let y = DVector::from_vec(vec![3.0, 4.0, 3.5]);
         let kernel = RbfKernel {
             sigma: 1.0,
             length_scale: 1.0,
         };
-        let gp = GaussianProcess::new(&x, &y, kernel, 0.1);
-        assert_eq!(gp.x, x);
-        assert_eq!(gp.y, y);
+
+        // without noise the posterior interpolates the data
+        let gp = GaussianProcess::new(&x, &y, kernel, 0.0);
+        let (residuals, _) = gp.residuals();
+        assert!(residuals.abs().max() < 1e-3);
+
+        // with noise the mean is pulled towards zero, giving positive residuals
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::new(&x, &y, kernel, 1.0);
+        let (residuals, standardized) = gp.residuals();
+        assert!(residuals.iter().all(|r| *r > 0.0));
+        assert!(standardized
+            .iter()
+            .zip(residuals.iter())
+            .all(|(s, r)| s.abs() < r.abs()));
+    }
+
+    #[test]
+    fn test_gaussian_process_log_marginal_likelihood() {
+        // a single point is just a normal distribution with variance sigma + noise^2
+        let kernel = RbfKernel {
+            sigma: 2.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::new(
+            &DVector::from_vec(vec![1.0]),
+            &DVector::from_vec(vec![3.0]),
+            kernel,
+            0.5f64.sqrt(),
+        );
+        let variance: f64 = 2.5;
+        let expected =
+            -0.5 * 9.0 / variance - 0.5 * variance.ln() - 0.5 * (2.0 * std::f64::consts::PI).ln();
+        assert!((gp.log_marginal_likelihood() - expected).abs() < 1e-5);
+
+        // a length scale matching the data should be more likely than a tiny one
+        let x = DVector::from_vec(vec![0.0, 0.5, 1.0, 1.5, 2.0]);
+        let y = x.map(f64::sin);
+        let lml = |length_scale| {
+            let kernel = RbfKernel {
+                sigma: 1.0,
+                length_scale,
+            };
+            GaussianProcess::new(&x, &y, kernel, 0.01).log_marginal_likelihood()
+        };
+        assert!(lml(1.0) > lml(0.01));
+    }
+
+    #[test]
+    fn test_gaussian_process_add_observation() {
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0]);
+        let y = DVector::from_vec(vec![3.0, 4.0, 3.5]);
+        let expected = GaussianProcess::new(&x, &y, kernel.clone(), 0.1);
+
+        let mut gp = GaussianProcess::prior(kernel, 0.1);
+        for (x, y) in x.iter().zip(y.iter()) {
+            gp.add_observation(*x, *y);
+        }
+        assert_eq!(gp.x, expected.x);
+        assert_eq!(gp.y, expected.y);
+        assert!(
+            (gp.input_cov_matrix_inv - &expected.input_cov_matrix_inv)
+                .abs()
+                .max()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_gaussian_process_condition_on() {
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0, 4.5, 5.0]);
+        let y = DVector::from_vec(vec![3.0, 4.0, 3.5, 2.0, 2.5]);
+        let expected = GaussianProcess::new(&x, &y, kernel.clone(), 0.1);
+
+        let gp = GaussianProcess::from_slices(&[1.0, 2.0], &[3.0, 4.0], kernel, 0.1).unwrap();
+        let conditioned = gp.condition_on(&[3.0, 4.5, 5.0], &[3.5, 2.0, 2.5]).unwrap();
+        assert_eq!(conditioned.x, expected.x);
+        assert_eq!(conditioned.y, expected.y);
+        assert!(
+            (&conditioned.input_cov_matrix_inv - &expected.input_cov_matrix_inv)
+                .abs()
+                .max()
+                < 1e-9
+        );
+        // the original is left as it was
+        assert_eq!(gp.x.len(), 2);
+        assert_eq!(gp.condition_on(&[], &[]).unwrap().x, gp.x);
+        assert!(gp.condition_on(&[1.0], &[]).is_err());
+    }
+
+    #[test]
+    fn test_gaussian_process_effective_degrees_of_freedom() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0, 4.0]);
+        let y = DVector::from_vec(vec![3.0, 4.0, 3.5, 2.0]);
+        let dof = |length_scale, noise| {
+            let kernel = RbfKernel {
+                sigma: 1.0,
+                length_scale,
+            };
+            GaussianProcess::new(&x, &y, kernel, noise).effective_degrees_of_freedom()
+        };
+        // a wiggly noise free fit uses a degree of freedom per point, a noisy smooth one few
+        assert!((dof(0.01, 1e-3) - 4.0).abs() < 1e-3);
+        assert!(dof(10.0, 1.0) < 1.0);
+        assert!(dof(1.0, 0.3) < dof(1.0, 0.1));
+
+        // the trace of K (K + noise)^-1 computed directly
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let k = kernel.compute_matrix(&x, &x);
+        let inverse = (&k + na::DMatrix::identity(4, 4) * (0.3f64.powi(2) + EPS))
+            .try_inverse()
+            .unwrap();
+        assert!((dof(1.0, 0.3) - (k * inverse).trace()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gaussian_process_leave_one_out() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0]);
+        let y = DVector::from_vec(vec![3.0, 4.0, 3.5]);
+        let (loo_mean, loo_variance) = GaussianProcess::new(
+            &x,
+            &y,
+            RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            },
+            0.1,
+        )
+        .leave_one_out();
+
+        // compare against explicitly refitting without each point
+        for i in 0..x.len() {
+            let x_rest = x.clone().remove_row(i);
+            let y_rest = y.clone().remove_row(i);
+            let gp = GaussianProcess::new(
+                &x_rest,
+                &y_rest,
+                RbfKernel {
+                    sigma: 1.0,
+                    length_scale: 1.0,
+                },
+                0.1,
+            );
+            let (mean, variance) = gp.predict(&DVector::from_element(1, x[i]));
+            assert!((loo_mean[i] - mean[0]).abs() < 1e-4);
+            assert!((loo_variance[i] - (variance[0] + 0.01)).abs() < 1e-4);
+        }
     }
 
     #[test]
@@ -139,4 +766,90 @@ mod test {
         assert!((mean[0] - 3.0).abs() < 1e-1);
         assert!(variance[0].abs() < 1e-1);
     }
+
+    #[test]
+    fn test_gaussian_process_prior() {
+        let kernel = RbfKernel {
+            sigma: 2.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::prior(kernel, 0.1);
+
+        let (mean, variance) = gp.predict(&DVector::from_vec(vec![0.0, 5.0]));
+        assert!(mean.abs().max() < 1e-9);
+        assert!((variance[0] - 2.0).abs() < 1e-3);
+        assert!((variance[1] - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_gaussian_process_sample() {
+        use rand::SeedableRng;
+
+        let x_train = DVector::from_vec(vec![1.0, 2.0]);
+        let y_train = DVector::from_vec(vec![3.0, 4.0]);
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::new(&x_train, &y_train, kernel, 0.0);
+
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+        let samples = gp.sample(&x_train, 5, &mut rng);
+
+        assert_eq!(samples.len(), 5);
+        for sample in samples {
+            assert_eq!(sample.len(), 2);
+            assert!((sample - &y_train).abs().max() < 1e-1);
+        }
+    }
+
+    #[test]
+    fn test_gaussian_process_sample_loop() {
+        use rand::SeedableRng;
+
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::prior(kernel, 0.1);
+        let x = DVector::from_fn(20, |i, _| i as f64 * 0.5);
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+        let frames = gp.sample_loop(&x, 3, 40, &mut rng);
+        assert_eq!(frames.len(), 40);
+        assert!(frames.iter().all(|samples| samples.len() == 3));
+
+        // consecutive frames, including the last and the first, are close
+        let step = |a: usize, b: usize| (&frames[a][0] - &frames[b][0]).abs().max();
+        for frame in 0..40 {
+            assert!(step(frame, (frame + 1) % 40) < 0.5);
+        }
+        assert!(step(0, 20) > 1.0);
+    }
+
+    #[test]
+    fn test_gaussian_process_slices() {
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp =
+            GaussianProcess::from_slices(&[1.0, 2.0], &[3.0, 4.0], kernel.clone(), 0.1).unwrap();
+        let (mean, variance) = gp.predict_slice(&[1.5, 5.0]);
+
+        let reference = GaussianProcess::new(
+            &DVector::from_vec(vec![1.0, 2.0]),
+            &DVector::from_vec(vec![3.0, 4.0]),
+            kernel.clone(),
+            0.1,
+        );
+        let (expected_mean, expected_variance) =
+            reference.predict(&DVector::from_vec(vec![1.5, 5.0]));
+        assert_eq!(mean, expected_mean.as_slice());
+        assert_eq!(variance, expected_variance.as_slice());
+
+        assert_eq!(
+            GaussianProcess::from_slices(&[1.0], &[], kernel, 0.1).err(),
+            Some(GpError::LengthMismatch { x: 1, y: 0 })
+        );
+    }
 }
@@ -0,0 +1,228 @@
+use nalgebra as na;
+
+use super::{GaussianProcess, GpInput, GpKernel};
+
+/// The error function, using the approximation 7.1.26 from Abramowitz & Stegun, Handbook of
+/// Mathematical Functions (maximum error 1.5e-7).
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let polynomial = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let value = 1.0 - polynomial * (-x * x).exp();
+    value.copysign(x)
+}
+
+/// Probability density function of the standard normal distribution.
+pub fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+pub fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Criteria for choosing where to sample next to learn the most about the function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ActiveLearningCriterion {
+    /// Where the posterior variance of the function is largest.
+    MaxVariance,
+    /// Where an observation carries the most information about the function, i.e. the mutual
+    /// information `0.5 * ln(1 + variance / noise)`.
+    MaxInformationGain,
+}
+
+impl ActiveLearningCriterion {
+    /// Human readable name of the criterion.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActiveLearningCriterion::MaxVariance => "Max variance",
+            ActiveLearningCriterion::MaxInformationGain => "Max information gain",
+        }
+    }
+}
+
+impl<K: GpKernel<I>, I: GpInput> GaussianProcess<K, I> {
+    /// Score the candidate points by how valuable observing them would be, higher is better.
+    pub fn active_learning_scores(
+        &self,
+        candidates: &na::DVector<I>,
+        criterion: ActiveLearningCriterion,
+    ) -> na::DVector<f64> {
+        let (_, variance) = self.predict(candidates);
+        match criterion {
+            ActiveLearningCriterion::MaxVariance => variance,
+            ActiveLearningCriterion::MaxInformationGain => {
+                let noise = self.noise_variance() + self.jitter;
+                variance.map(|v| 0.5 * (1.0 + v.max(0.0) / noise).ln())
+            }
+        }
+    }
+
+    /// The candidate point that is most valuable to observe next, if there are any.
+    pub fn suggest_next(
+        &self,
+        candidates: &na::DVector<I>,
+        criterion: ActiveLearningCriterion,
+    ) -> Option<I> {
+        if candidates.is_empty() {
+            return None;
+        }
+        let (index, _) = self.active_learning_scores(candidates, criterion).argmax();
+        Some(candidates[index])
+    }
+
+    /// The differential entropy of the predictive distribution of a new observation at each of
+    /// the points, `0.5 * ln(2 pi e (variance + noise))`, in nats.
+    pub fn predictive_entropy(&self, x: &na::DVector<I>) -> na::DVector<f64> {
+        let (_, variance) = self.predict(x);
+        let noise = self.noise_variance();
+        variance.map(|v| {
+            0.5 * (2.0 * std::f64::consts::PI * std::f64::consts::E * (v.max(0.0) + noise)).ln()
+        })
+    }
+
+    /// The expected information gain about the function over the `grid` from observing at each
+    /// of the candidates: the sum of how much the entropy of the function at each grid point
+    /// would shrink. For a GP this does not depend on the observed value, so it is exact.
+    pub fn information_gain(
+        &self,
+        candidates: &na::DVector<I>,
+        grid: &na::DVector<I>,
+    ) -> na::DVector<f64> {
+        // the posterior covariances in the normalized units the kernel works in
+        let k_grid = self.kernel.compute_matrix(&self.x, grid);
+        let k_candidates = self.kernel.compute_matrix(&self.x, candidates);
+        let weighted = &self.input_cov_matrix_inv * &k_candidates;
+        let covariance =
+            self.kernel.compute_matrix(grid, candidates) - k_grid.transpose() * &weighted;
+        let variance =
+            |x: I, k_star: na::DVectorView<'_, f64>, weighted: na::DVectorView<'_, f64>| {
+                (self.kernel.compute(x, x) - k_star.dot(&weighted)).max(0.0) + self.jitter
+            };
+        let weighted_grid = &self.input_cov_matrix_inv * &k_grid;
+        let grid_variance = na::DVector::from_fn(grid.len(), |i, _| {
+            variance(grid[i], k_grid.column(i), weighted_grid.column(i))
+        });
+
+        na::DVector::from_fn(candidates.len(), |j, _| {
+            let observed_variance =
+                variance(candidates[j], k_candidates.column(j), weighted.column(j))
+                    + self.noise_sigma.powi(2);
+            (0..grid.len())
+                .map(|i| {
+                    let reduction = covariance[(i, j)].powi(2) / observed_variance;
+                    -0.5 * (1.0 - reduction / grid_variance[i])
+                        .max(f64::MIN_POSITIVE)
+                        .ln()
+                })
+                .sum()
+        })
+    }
+
+    /// The expected improvement of the function over `best` at the given points, for
+    /// maximizing the function with Bayesian optimization.
+    pub fn expected_improvement(&self, x: &na::DVector<I>, best: f64) -> na::DVector<f64> {
+        let (mean, variance) = self.predict(x);
+        mean.zip_map(&variance, |mean, variance| {
+            let std = variance.max(0.0).sqrt();
+            if std < 1e-12 {
+                return (mean - best).max(0.0);
+            }
+            let z = (mean - best) / std;
+            (mean - best) * normal_cdf(z) + std * normal_pdf(z)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use na::DVector;
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.0) - 0.84134475).abs() < 1e-6);
+        assert!((normal_cdf(-1.96) - 0.0249979).abs() < 1e-6);
+        assert!((normal_pdf(0.0) - 0.39894228).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_suggest_next() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 6.0]);
+        let y = DVector::from_vec(vec![0.0, 1.0, 0.0]);
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::new(&x, &y, kernel, 0.01);
+
+        // the gap between the data is the most uncertain region
+        let candidates = DVector::from_vec(vec![1.5, 4.0, 6.0]);
+        for criterion in [
+            ActiveLearningCriterion::MaxVariance,
+            ActiveLearningCriterion::MaxInformationGain,
+        ] {
+            assert_eq!(gp.suggest_next(&candidates, criterion), Some(4.0));
+        }
+        assert_eq!(
+            gp.suggest_next(
+                &DVector::from_vec(Vec::new()),
+                ActiveLearningCriterion::MaxVariance
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_entropy_and_information_gain() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 6.0]);
+        let y = DVector::from_vec(vec![0.0, 1.0, 0.0]);
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::new(&x, &y, kernel, 0.1);
+
+        let entropy = gp.predictive_entropy(&DVector::from_vec(vec![1.5, 4.0, 100.0]));
+        assert!(entropy[0] < entropy[1] && entropy[1] < entropy[2]);
+        // far away the predictive distribution is the prior plus the noise
+        let expected = 0.5 * (2.0 * std::f64::consts::PI * std::f64::consts::E * 1.01f64).ln();
+        assert!((entropy[2] - expected).abs() < 1e-4);
+
+        // observing in the gap teaches the most about the function over the whole range
+        let grid = DVector::from_fn(50, |i, _| i as f64 * 0.15);
+        let candidates = DVector::from_vec(vec![1.5, 4.0, 20.0]);
+        let gain = gp.information_gain(&candidates, &grid);
+        assert!(gain[1] > gain[0] && gain[0] > gain[2]);
+        assert!(gain[2] < 1e-6);
+
+        // about the candidate itself, it is the mutual information of the observation
+        let gain = gp.information_gain(&candidates, &DVector::from_element(1, 4.0));
+        let scores =
+            gp.active_learning_scores(&candidates, ActiveLearningCriterion::MaxInformationGain);
+        assert!((gain[1] - scores[1]).abs() < 1e-3, "{gain} != {scores}");
+    }
+
+    #[test]
+    fn test_expected_improvement() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0]);
+        let y = DVector::from_vec(vec![0.0, 1.0, 0.0]);
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::new(&x, &y, kernel, 0.01);
+
+        let ei = gp.expected_improvement(&DVector::from_vec(vec![1.0, 2.2, 8.0]), 1.0);
+        assert!(ei.iter().all(|ei| *ei >= 0.0));
+        // close to the best point is more promising than next to a low one
+        assert!(ei[1] > ei[0]);
+        // far away from the data the prior (mean 0, std 1) gives E[max(f - 1, 0)]
+        let expected = normal_pdf(1.0) - (1.0 - normal_cdf(1.0));
+        assert!((ei[2] - expected).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,120 @@
+use nalgebra as na;
+
+/// A polynomial fit by least squares, as a simple baseline to compare a GP with.
+///
+/// ```
+/// use gaussian_processes::gp::Polynomial;
+///
+/// let x = [0.0, 1.0, 2.0, 3.0];
+/// let y = [1.0, 3.0, 5.0, 7.0];
+/// let line = Polynomial::fit(&x, &y, 1).unwrap();
+/// assert!((line.eval(10.0) - 21.0).abs() < 1e-9);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    /// The coefficients of increasing powers of the scaled input.
+    coefficients: Vec<f64>,
+    /// The inputs are centered and scaled to keep the powers of similar size.
+    x_offset: f64,
+    x_scale: f64,
+}
+
+impl Polynomial {
+    /// Fit a polynomial of the given degree, lowered to one less than the number of points if
+    /// there are too few of them. `None` without points.
+    pub fn fit(x: &[f64], y: &[f64], degree: usize) -> Option<Self> {
+        if x.is_empty() || x.len() != y.len() {
+            return None;
+        }
+        let degree = degree.min(x.len() - 1);
+        let min = x.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let x_offset = 0.5 * (min + max);
+        let x_scale = if max > min { 0.5 * (max - min) } else { 1.0 };
+
+        let vandermonde = na::DMatrix::from_fn(x.len(), degree + 1, |i, j| {
+            ((x[i] - x_offset) / x_scale).powi(j as i32)
+        });
+        let coefficients = vandermonde
+            .svd(true, true)
+            .solve(&na::DVector::from_column_slice(y), 1e-12)
+            .ok()?;
+        Some(Self {
+            coefficients: coefficients.as_slice().to_vec(),
+            x_offset,
+            x_scale,
+        })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+
+    pub fn eval(&self, x: f64) -> f64 {
+        let x = (x - self.x_offset) / self.x_scale;
+        // Horner's method
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |value, coefficient| value * x + coefficient)
+    }
+}
+
+/// The mean of the targets with an input within half the `window` of each of the inputs `at`,
+/// NaN where there are none.
+pub fn moving_average(x: &[f64], y: &[f64], window: f64, at: &[f64]) -> Vec<f64> {
+    at.iter()
+        .map(|at| {
+            let (sum, count) = x
+                .iter()
+                .zip(y)
+                .filter(|(x, _)| (*x - at).abs() <= 0.5 * window)
+                .fold((0.0, 0), |(sum, count), (_, y)| (sum + y, count + 1));
+            if count > 0 {
+                sum / count as f64
+            } else {
+                f64::NAN
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_polynomial() {
+        let x: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+        let y: Vec<f64> = x.iter().map(|x| 2.0 - x + 0.5 * x * x).collect();
+        let quadratic = Polynomial::fit(&x, &y, 2).unwrap();
+        assert_eq!(quadratic.degree(), 2);
+        for (x, y) in x.iter().zip(&y) {
+            assert!((quadratic.eval(*x) - y).abs() < 1e-6);
+        }
+
+        // a line through the quadratic has the slope of the secant
+        let line = Polynomial::fit(&x, &y, 1).unwrap();
+        let slope = line.eval(109.0) - line.eval(108.0);
+        assert!((slope - (y[9] - y[0]) / 9.0).abs() < 1e-6);
+
+        // too few points for the degree
+        assert_eq!(
+            Polynomial::fit(&[1.0, 2.0], &[1.0, 2.0], 5)
+                .unwrap()
+                .degree(),
+            1
+        );
+        assert_eq!(Polynomial::fit(&[3.0], &[4.0], 2).unwrap().eval(0.0), 4.0);
+        assert_eq!(Polynomial::fit(&[], &[], 2), None);
+    }
+
+    #[test]
+    fn test_moving_average() {
+        let x = [0.0, 1.0, 2.0, 10.0];
+        let y = [1.0, 2.0, 6.0, 0.0];
+        let average = moving_average(&x, &y, 2.0, &[1.0, 0.0, 5.0]);
+        assert_eq!(average[..2], [3.0, 1.5]);
+        assert!(average[2].is_nan());
+    }
+}
@@ -0,0 +1,371 @@
+use std::marker::PhantomData;
+
+use nalgebra as na;
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel, EPS};
+
+/// Options for fitting a [`GaussianProcess`], created with [`GaussianProcess::builder`].
+///
+/// ```
+/// use gaussian_processes::gp::{GaussianProcess, RbfKernel};
+/// use nalgebra::DVector;
+///
+/// let x = DVector::from_vec(vec![1.0, 2.0, 6.0]);
+/// let y = DVector::from_vec(vec![101.0, 103.0, 98.0]);
+/// let gp = GaussianProcess::builder()
+///     .kernel(RbfKernel {
+///         sigma: 1.0,
+///         length_scale: 1.0,
+///     })
+///     .noise(0.1)
+///     .normalize(true)
+///     .build(&x, &y)?;
+/// let (mean, _) = gp.predict(&DVector::from_vec(vec![20.0]));
+/// // far from the data the prediction reverts to the mean of the targets
+/// assert!((mean[0] - 100.667).abs() < 1e-3);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct GaussianProcessBuilder<K, I = f64> {
+    kernel: Option<K>,
+    noise_sigma: f64,
+    jitter: f64,
+    weights: Option<na::DVector<f64>>,
+    mean: f64,
+    normalize: bool,
+    aggregate_replicates: bool,
+    input: PhantomData<I>,
+}
+
+impl<K, I> Default for GaussianProcessBuilder<K, I> {
+    fn default() -> Self {
+        Self {
+            kernel: None,
+            noise_sigma: 0.0,
+            jitter: EPS,
+            weights: None,
+            mean: 0.0,
+            normalize: false,
+            aggregate_replicates: false,
+            input: PhantomData,
+        }
+    }
+}
+
+impl<K: GpKernel<I>, I: GpInput> GaussianProcessBuilder<K, I> {
+    /// The covariance function, which is required.
+    pub fn kernel(mut self, kernel: K) -> Self {
+        self.kernel = Some(kernel);
+        self
+    }
+
+    /// The standard deviation of the observation noise, zero by default. Its square is added to
+    /// the diagonal of the covariance matrix.
+    pub fn noise(mut self, noise_sigma: f64) -> Self {
+        self.noise_sigma = noise_sigma;
+        self
+    }
+
+    /// A small constant added to the diagonal of the covariance matrices so that they stay
+    /// numerically positive definite, 1e-6 by default. Unlike the noise it is not part of the
+    /// model, so it only needs to be raised if fitting fails for (nearly) duplicate inputs.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// A positive weight for each observation, dividing the noise variance of that observation.
+    /// For example the number of measurements averaged into each target, whose noise shrinks
+    /// accordingly. All one by default.
+    pub fn weights(mut self, weights: na::DVector<f64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// A constant prior mean, which the predictions revert to away from the data. Zero by
+    /// default.
+    pub fn mean(mut self, mean: f64) -> Self {
+        self.mean = mean;
+        self
+    }
+
+    /// Standardize the targets to zero mean and unit variance before fitting, so the kernel and
+    /// noise hyperparameters do not depend on the units of the data. Replaces the constant
+    /// [`Self::mean`] with the mean of the targets.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Replace the observations at the same input with their (weighted) mean, weighted by the
+    /// number of observations. This gives the same posterior as fitting all the replicates,
+    /// but with a smaller and better conditioned covariance matrix.
+    pub fn aggregate_replicates(mut self, aggregate_replicates: bool) -> Self {
+        self.aggregate_replicates = aggregate_replicates;
+        self
+    }
+
+    /// Fit the Gaussian process to the training data.
+    pub fn build(
+        self,
+        x: &na::DVector<I>,
+        y: &na::DVector<f64>,
+    ) -> Result<GaussianProcess<K, I>, GpError> {
+        let kernel = self.kernel.ok_or(GpError::MissingKernel)?;
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+
+        let weights = self
+            .weights
+            .unwrap_or_else(|| na::DVector::from_element(x.len(), 1.0));
+        if weights.len() != x.len() || !weights.iter().all(|w| *w > 0.0) {
+            return Err(GpError::InvalidWeights);
+        }
+
+        let (y_offset, y_scale) = if self.normalize && !y.is_empty() {
+            let mean = y.mean();
+            let std = y.map(|y| (y - mean).powi(2)).mean().sqrt();
+            (mean, if std > 0.0 { std } else { 1.0 })
+        } else {
+            (self.mean, 1.0)
+        };
+
+        let (x, y, weights) = if self.aggregate_replicates {
+            aggregate_replicates(x, y, &weights)
+        } else {
+            (x.clone(), y.clone(), weights)
+        };
+
+        Ok(GaussianProcess {
+            input_cov_matrix_inv: covariance_inverse(
+                &kernel,
+                &x,
+                self.noise_sigma,
+                self.jitter,
+                &weights,
+            )?,
+            kernel,
+            x,
+            y: y.map(|y| (y - y_offset) / y_scale),
+            noise_sigma: self.noise_sigma,
+            jitter: self.jitter,
+            weights,
+            y_offset,
+            y_scale,
+        })
+    }
+
+    /// Like [`Self::build`], but taking the training data as slices.
+    pub fn build_from_slices(self, x: &[I], y: &[f64]) -> Result<GaussianProcess<K, I>, GpError> {
+        self.build(
+            &na::DVector::from_column_slice(x),
+            &na::DVector::from_column_slice(y),
+        )
+    }
+}
+
+/// The distinct inputs with the weighted mean of their targets and the sum of their weights,
+/// in the order they first appear.
+fn aggregate_replicates<I: GpInput>(
+    x: &na::DVector<I>,
+    y: &na::DVector<f64>,
+    weights: &na::DVector<f64>,
+) -> (na::DVector<I>, na::DVector<f64>, na::DVector<f64>) {
+    let mut inputs: Vec<I> = Vec::new();
+    let mut sums: Vec<(f64, f64)> = Vec::new();
+    for ((x, y), weight) in x.iter().zip(y.iter()).zip(weights.iter()) {
+        match inputs.iter().position(|input| input == x) {
+            Some(i) => {
+                sums[i].0 += weight * y;
+                sums[i].1 += weight;
+            }
+            None => {
+                inputs.push(*x);
+                sums.push((weight * y, *weight));
+            }
+        }
+    }
+    let (targets, weights): (Vec<f64>, Vec<f64>) = sums
+        .into_iter()
+        .map(|(sum, weight)| (sum / weight, weight))
+        .unzip();
+    (
+        na::DVector::from_vec(inputs),
+        na::DVector::from_vec(targets),
+        na::DVector::from_vec(weights),
+    )
+}
+
+/// The covariance matrix of the training inputs, including the noise of each observation.
+pub(super) fn covariance_matrix<K: GpKernel<I>, I: GpInput>(
+    kernel: &K,
+    x: &na::DVector<I>,
+    noise_sigma: f64,
+    jitter: f64,
+    weights: &na::DVector<f64>,
+) -> na::DMatrix<f64> {
+    let noise = weights.map(|w| noise_sigma.powi(2) / w + jitter);
+    kernel.compute_matrix(x, x) + na::DMatrix::from_diagonal(&noise)
+}
+
+/// The inverse of [`covariance_matrix`].
+pub(super) fn covariance_inverse<K: GpKernel<I>, I: GpInput>(
+    kernel: &K,
+    x: &na::DVector<I>,
+    noise_sigma: f64,
+    jitter: f64,
+    weights: &na::DVector<f64>,
+) -> Result<na::DMatrix<f64>, GpError> {
+    covariance_matrix(kernel, x, noise_sigma, jitter, weights)
+        .try_inverse()
+        .ok_or(GpError::NotInvertible)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use na::DVector;
+
+    fn kernel() -> RbfKernel {
+        RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let x = DVector::from_vec(vec![1.0, 2.0]);
+        let y = DVector::from_vec(vec![1.0]);
+        assert_eq!(
+            GaussianProcess::<RbfKernel>::builder().build(&x, &x).err(),
+            Some(GpError::MissingKernel)
+        );
+        assert_eq!(
+            GaussianProcess::builder()
+                .kernel(kernel())
+                .build(&x, &y)
+                .err(),
+            Some(GpError::LengthMismatch { x: 2, y: 1 })
+        );
+    }
+
+    #[test]
+    fn test_builder_weights() {
+        let x = DVector::from_vec(vec![1.0, 2.0]);
+        let y = DVector::from_vec(vec![1.0, -1.0]);
+
+        // a point with weight 4 is the mean of 4 replicates, with a quarter of the noise
+        let weighted = GaussianProcess::new_weighted(
+            &x,
+            &y,
+            &DVector::from_vec(vec![4.0, 1.0]),
+            kernel(),
+            0.4,
+        )
+        .unwrap();
+        let replicated = GaussianProcess::new(
+            &DVector::from_vec(vec![1.0, 1.0, 1.0, 1.0, 2.0]),
+            &DVector::from_vec(vec![1.0, 1.0, 1.0, 1.0, -1.0]),
+            kernel(),
+            0.4,
+        );
+        let test = DVector::from_vec(vec![0.0, 1.5, 3.0]);
+        let (mean, variance) = weighted.predict(&test);
+        let (expected_mean, expected_variance) = replicated.predict(&test);
+        assert!((mean - expected_mean).abs().max() < 1e-4);
+        assert!((variance - expected_variance).abs().max() < 1e-4);
+
+        for weights in [vec![1.0], vec![1.0, 0.0], vec![1.0, f64::NAN]] {
+            assert_eq!(
+                GaussianProcess::builder()
+                    .kernel(kernel())
+                    .weights(DVector::from_vec(weights))
+                    .build(&x, &y)
+                    .err(),
+                Some(GpError::InvalidWeights)
+            );
+        }
+    }
+
+    #[test]
+    fn test_builder_aggregate_replicates() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 1.0, 3.0, 1.0]);
+        let y = DVector::from_vec(vec![1.0, 0.5, 2.0, -1.0, 3.0]);
+        let fit = |aggregate| {
+            GaussianProcess::builder()
+                .kernel(kernel())
+                .noise(0.3)
+                .aggregate_replicates(aggregate)
+                .build(&x, &y)
+                .unwrap()
+        };
+        let aggregated = fit(true);
+        assert_eq!(aggregated.inputs().as_slice(), &[1.0, 2.0, 3.0]);
+        assert_eq!(aggregated.weights().as_slice(), &[3.0, 1.0, 1.0]);
+
+        let test = DVector::from_vec(vec![0.0, 1.0, 2.5]);
+        let (mean, variance) = aggregated.predict(&test);
+        let (expected_mean, expected_variance) = fit(false).predict(&test);
+        assert!((mean - expected_mean).abs().max() < 1e-4);
+        assert!((variance - expected_variance).abs().max() < 1e-4);
+    }
+
+    #[test]
+    fn test_builder_mean_and_normalize() {
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0]);
+        let y = DVector::from_vec(vec![10.0, 14.0, 12.0]);
+        let far = DVector::from_vec(vec![50.0]);
+
+        let gp = GaussianProcess::builder()
+            .kernel(kernel())
+            .noise(0.01)
+            .mean(5.0)
+            .build(&x, &y)
+            .unwrap();
+        assert!((gp.predict(&far).0[0] - 5.0).abs() < 1e-9);
+
+        let gp = GaussianProcess::builder()
+            .kernel(kernel())
+            .noise(0.01)
+            .normalize(true)
+            .build(&x, &y)
+            .unwrap();
+        let (mean, variance) = gp.predict(&far);
+        assert!((mean[0] - 12.0).abs() < 1e-9);
+        // the prior variance is in units of the standardized targets
+        let std = (8.0f64 / 3.0).sqrt();
+        assert!((variance[0] - std * std).abs() < 1e-4);
+        // the posterior still passes close to the data
+        let (mean, _) = gp.predict(&x);
+        assert!((mean - &y).abs().max() < 0.1);
+
+        let (residuals, _) = gp.residuals();
+        assert!(residuals.abs().max() < 0.1);
+        let (loo_mean, _) = gp.leave_one_out();
+        assert!(loo_mean.iter().all(|m| (9.0..15.0).contains(m)));
+    }
+
+    #[test]
+    fn test_normalized_log_marginal_likelihood() {
+        // scaling the data and the kernel together only shifts the likelihood by the Jacobian
+        let x = DVector::from_vec(vec![1.0, 2.0, 4.0]);
+        let y = DVector::from_vec(vec![1.0, -1.0, 0.5]);
+        let scaled = GaussianProcess::builder()
+            .kernel(kernel())
+            .noise(0.1)
+            .normalize(true)
+            .build(&x, &(&y * 3.0))
+            .unwrap();
+
+        let mean = y.mean();
+        let std = y.map(|y| (y - mean).powi(2)).mean().sqrt();
+        let shifted = GaussianProcess::new(&x, &y.map(|y| (y - mean) / std), kernel(), 0.1);
+        let expected = shifted.log_marginal_likelihood() - 3.0 * (3.0 * std).ln();
+        assert!((scaled.log_marginal_likelihood() - expected).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,110 @@
+use nalgebra as na;
+
+use super::{normal_cdf, GaussianProcess, GpInput, GpKernel};
+
+/// The empirical coverage of the central predictive intervals of one nominal level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CalibrationPoint {
+    /// The probability the intervals should contain an observation.
+    pub nominal: f64,
+    /// The fraction of the observations they did contain.
+    pub empirical: f64,
+}
+
+/// The reliability curve of Gaussian predictions of held-out observations: for each nominal
+/// level, the fraction of the observations inside the central interval of that probability.
+///
+/// Honest uncertainties give a curve along the diagonal. Below it the intervals are too narrow,
+/// e.g. from too little noise or too short a length scale, and above it they are too wide.
+pub fn calibration_curve(
+    mean: &[f64],
+    variance: &[f64],
+    y: &[f64],
+    levels: &[f64],
+) -> Vec<CalibrationPoint> {
+    // the level of the narrowest central interval containing each observation
+    let contained_at: Vec<f64> = y
+        .iter()
+        .zip(mean.iter().zip(variance))
+        .map(|(y, (mean, variance))| 2.0 * normal_cdf((y - mean).abs() / variance.sqrt()) - 1.0)
+        .collect();
+    levels
+        .iter()
+        .map(|&nominal| {
+            let inside = contained_at
+                .iter()
+                .filter(|level| **level <= nominal)
+                .count();
+            CalibrationPoint {
+                nominal,
+                empirical: inside as f64 / contained_at.len().max(1) as f64,
+            }
+        })
+        .collect()
+}
+
+/// The mean absolute difference between the nominal and the empirical coverage, zero for a
+/// perfectly calibrated model.
+pub fn calibration_error(curve: &[CalibrationPoint]) -> f64 {
+    let total: f64 = curve
+        .iter()
+        .map(|point| (point.empirical - point.nominal).abs())
+        .sum();
+    total / curve.len().max(1) as f64
+}
+
+impl<K: GpKernel<I>, I: GpInput> GaussianProcess<K, I> {
+    /// The reliability curve of the predictions of held-out observations at the nominal levels,
+    /// see [`calibration_curve`]. The predictive intervals include the noise.
+    pub fn calibration(&self, x: &[I], y: &[f64], levels: &[f64]) -> Vec<CalibrationPoint> {
+        let (mean, variance) = self.predict(&na::DVector::from_column_slice(x));
+        let noise = self.noise_variance();
+        let variance: Vec<f64> = variance.iter().map(|v| v + noise).collect();
+        calibration_curve(mean.as_slice(), &variance, y, levels)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_calibration_curve() {
+        // standard normal observations of a zero mean prediction
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+        let y: Vec<f64> = (0..4000)
+            .map(|_| rng.sample::<f64, _>(rand_distr::StandardNormal))
+            .collect();
+        let levels = [0.1, 0.5, 0.9];
+        let mean = vec![0.0; y.len()];
+
+        let honest = calibration_curve(&mean, &vec![1.0; y.len()], &y, &levels);
+        for point in &honest {
+            assert!((point.empirical - point.nominal).abs() < 0.03, "{honest:?}");
+        }
+        assert!(calibration_error(&honest) < 0.03);
+
+        let overconfident = calibration_curve(&mean, &vec![0.25; y.len()], &y, &levels);
+        assert!(overconfident.iter().all(|p| p.empirical < p.nominal));
+        let underconfident = calibration_curve(&mean, &vec![4.0; y.len()], &y, &levels);
+        assert!(underconfident.iter().all(|p| p.empirical > p.nominal));
+        assert!(calibration_error(&overconfident) > 0.1);
+    }
+
+    #[test]
+    fn test_gaussian_process_calibration() {
+        let x = [0.0, 1.0, 2.0, 3.0];
+        let y = [0.0, 1.0, 0.0, -1.0];
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::from_slices(&x, &y, kernel, 0.1).unwrap();
+        // the held-out observations are far from the predictions
+        let curve = gp.calibration(&[0.5, 1.5], &[10.0, -10.0], &[0.5, 0.99]);
+        assert_eq!(curve.len(), 2);
+        assert!(curve.iter().all(|point| point.empirical == 0.0));
+    }
+}
@@ -0,0 +1,209 @@
+//! A C interface to one dimensional Gaussian process regression, declared in
+//! `include/gaussian_processes.h`. Build it as a C library with
+//! `cargo rustc --release --lib --features capi --crate-type cdylib` (or `staticlib`).
+
+use std::ffi::{c_char, CStr};
+
+use super::{GaussianProcess, GpError, Kernel};
+
+/// The result of a call through the C interface.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpStatus {
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// The kernel is not valid UTF-8 or not a valid kernel in RON.
+    InvalidKernel = 2,
+    /// The covariance matrix of the training data could not be inverted.
+    NotInvertible = 3,
+    /// The training data does not fit the model.
+    InvalidInput = 4,
+}
+
+impl From<GpError> for GpStatus {
+    fn from(error: GpError) -> Self {
+        match error {
+            GpError::NotInvertible => GpStatus::NotInvertible,
+            GpError::MissingKernel
+            | GpError::LengthMismatch { .. }
+            | GpError::DimensionMismatch { .. }
+            | GpError::InvalidWeights => GpStatus::InvalidInput,
+        }
+    }
+}
+
+/// Opaque handle to a fitted Gaussian process.
+pub struct GpHandle(GaussianProcess<Kernel>);
+
+/// A slice from a pointer and length, allowing a null pointer for an empty slice.
+unsafe fn slice<'a>(data: *const f64, len: usize) -> Option<&'a [f64]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(data, len))
+    }
+}
+
+/// Fit a Gaussian process to `n` training points, with the kernel given in RON as a null
+/// terminated string, e.g. `Rbf((sigma: 1.0, length_scale: 1.0))`. On success the handle is
+/// written to `out` and must be released with [`gp_free`].
+///
+/// # Safety
+///
+/// `x` and `y` must point to `n` values each, `kernel` to a null terminated string and `out`
+/// to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gp_fit(
+    x: *const f64,
+    y: *const f64,
+    n: usize,
+    kernel: *const c_char,
+    noise_sigma: f64,
+    out: *mut *mut GpHandle,
+) -> GpStatus {
+    let (Some(x), Some(y)) = (slice(x, n), slice(y, n)) else {
+        return GpStatus::NullPointer;
+    };
+    if kernel.is_null() || out.is_null() {
+        return GpStatus::NullPointer;
+    }
+    let Some(kernel) = CStr::from_ptr(kernel)
+        .to_str()
+        .ok()
+        .and_then(|kernel| ron::from_str::<Kernel>(kernel).ok())
+    else {
+        return GpStatus::InvalidKernel;
+    };
+
+    match GaussianProcess::from_slices(x, y, kernel, noise_sigma) {
+        Ok(gp) => {
+            *out = Box::into_raw(Box::new(GpHandle(gp)));
+            GpStatus::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// Predict the mean and variance at `n` inputs, writing `n` values to each of `mean` and
+/// `variance`.
+///
+/// # Safety
+///
+/// `gp` must be a handle from [`gp_fit`] that has not been freed, `x` must point to `n` values
+/// and `mean` and `variance` to writable memory for `n` values each.
+#[no_mangle]
+pub unsafe extern "C" fn gp_predict(
+    gp: *const GpHandle,
+    x: *const f64,
+    n: usize,
+    mean: *mut f64,
+    variance: *mut f64,
+) -> GpStatus {
+    let (Some(gp), Some(x)) = (gp.as_ref(), slice(x, n)) else {
+        return GpStatus::NullPointer;
+    };
+    if n > 0 && (mean.is_null() || variance.is_null()) {
+        return GpStatus::NullPointer;
+    }
+
+    let (m, v) = gp.0.predict_slice(x);
+    for (i, (m, v)) in m.into_iter().zip(v).enumerate() {
+        *mean.add(i) = m;
+        *variance.add(i) = v;
+    }
+    GpStatus::Ok
+}
+
+/// The log marginal likelihood of the training data.
+///
+/// # Safety
+///
+/// `gp` must be a handle from [`gp_fit`] that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gp_log_marginal_likelihood(gp: *const GpHandle) -> f64 {
+    gp.as_ref()
+        .map_or(f64::NAN, |gp| gp.0.log_marginal_likelihood())
+}
+
+/// Release a handle from [`gp_fit`]. Does nothing for a null pointer.
+///
+/// # Safety
+///
+/// `gp` must be null or a handle from [`gp_fit`] that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn gp_free(gp: *mut GpHandle) {
+    if !gp.is_null() {
+        drop(Box::from_raw(gp));
+    }
+}
+
+/// A description of a status, as a static null terminated string.
+#[no_mangle]
+pub extern "C" fn gp_status_message(status: GpStatus) -> *const c_char {
+    let message: &'static [u8] = match status {
+        GpStatus::Ok => b"ok\0",
+        GpStatus::NullPointer => b"a required pointer was null\0",
+        GpStatus::InvalidKernel => b"the kernel is not valid\0",
+        GpStatus::NotInvertible => b"the covariance matrix is not invertible\0",
+        GpStatus::InvalidInput => b"the training data does not fit the model\0",
+    };
+    message.as_ptr().cast()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::CString;
+    use std::ptr;
+
+    #[test]
+    fn test_capi() {
+        let x = [1.0, 2.0, 4.0];
+        let y = [0.5, -1.0, 0.2];
+        let kernel = CString::new("Rbf((sigma: 1.0, length_scale: 1.0))").unwrap();
+        let invalid = CString::new("Rbf(").unwrap();
+        let mut gp = ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                gp_fit(x.as_ptr(), y.as_ptr(), 3, invalid.as_ptr(), 0.1, &mut gp),
+                GpStatus::InvalidKernel
+            );
+            assert_eq!(
+                gp_fit(ptr::null(), y.as_ptr(), 3, kernel.as_ptr(), 0.1, &mut gp),
+                GpStatus::NullPointer
+            );
+            assert_eq!(
+                gp_fit(x.as_ptr(), y.as_ptr(), 3, kernel.as_ptr(), 0.1, &mut gp),
+                GpStatus::Ok
+            );
+
+            let test = [1.0, 3.0];
+            let mut mean = [0.0; 2];
+            let mut variance = [0.0; 2];
+            assert_eq!(
+                gp_predict(
+                    gp,
+                    test.as_ptr(),
+                    2,
+                    mean.as_mut_ptr(),
+                    variance.as_mut_ptr()
+                ),
+                GpStatus::Ok
+            );
+            let expected = GaussianProcess::from_slices(
+                &x,
+                &y,
+                ron::from_str::<Kernel>(kernel.to_str().unwrap()).unwrap(),
+                0.1,
+            )
+            .unwrap()
+            .predict_slice(&test);
+            assert_eq!((mean.to_vec(), variance.to_vec()), expected);
+            assert!(gp_log_marginal_likelihood(gp).is_finite());
+            gp_free(gp);
+        }
+    }
+}
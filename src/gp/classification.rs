@@ -0,0 +1,170 @@
+use nalgebra as na;
+
+use super::{GpInput, GpKernel};
+
+/// Maximum number of Newton iterations when finding the mode of the posterior.
+const MAX_ITERATIONS: usize = 100;
+
+/// Newton iterations stop when the objective improves less than this.
+const TOLERANCE: f64 = 1e-9;
+
+fn sigmoid(f: f64) -> f64 {
+    1.0 / (1.0 + (-f).exp())
+}
+
+/// Binary Gaussian process classifier with a logistic likelihood, using the Laplace
+/// approximation of the posterior over the latent function.
+///
+/// See Rasmussen & Williams, Gaussian Processes for Machine Learning, algorithms 3.1 and 3.2.
+pub struct GaussianProcessClassifier<K: GpKernel<I>, I: GpInput = f64> {
+    kernel: K,
+    x: na::DVector<I>,
+    /// Gradient of the log likelihood at the mode, `t - sigmoid(f)` with targets `t` in {0, 1}.
+    gradient: na::DVector<f64>,
+    /// Square root of the negative Hessian of the log likelihood at the mode.
+    sqrt_w: na::DVector<f64>,
+    /// Cholesky factor of `I + sqrt(W) K sqrt(W)`.
+    l: na::DMatrix<f64>,
+    log_marginal_likelihood: f64,
+}
+
+impl<K: GpKernel<I>, I: GpInput> GaussianProcessClassifier<K, I> {
+    /// Fit the classifier to points `x` with labels `y`, where `true` is the positive class.
+    pub fn new(x: &na::DVector<I>, y: &[bool], kernel: K) -> GaussianProcessClassifier<K, I> {
+        let n = x.len();
+        let k = kernel.compute_matrix(x, x);
+        let t = na::DVector::from_iterator(n, y.iter().map(|&y| if y { 1.0 } else { 0.0 }));
+        let signs = t.map(|t| 2.0 * t - 1.0);
+        let log_likelihood =
+            |f: &na::DVector<f64>| f.zip_map(&signs, |f, s| sigmoid(s * f).ln()).sum();
+
+        let mut f = na::DVector::zeros(n);
+        let mut objective = f64::NEG_INFINITY;
+        for _ in 0..MAX_ITERATIONS {
+            let pi = f.map(sigmoid);
+            let w = pi.map(|p| p * (1.0 - p));
+            let sqrt_w = w.map(f64::sqrt);
+            let gradient = &t - &pi;
+
+            let b_matrix = na::DMatrix::identity(n, n)
+                + na::DMatrix::from_diagonal(&sqrt_w) * &k * na::DMatrix::from_diagonal(&sqrt_w);
+            let cholesky = na::Cholesky::new(b_matrix).expect("should be positive definite");
+            let b = w.component_mul(&f) + &gradient;
+            let a = &b - sqrt_w.component_mul(&cholesky.solve(&sqrt_w.component_mul(&(&k * &b))));
+            f = &k * &a;
+
+            let new_objective = -0.5 * a.dot(&f) + log_likelihood(&f);
+            let converged = (new_objective - objective).abs() < TOLERANCE;
+            objective = new_objective;
+            if converged {
+                break;
+            }
+        }
+
+        // recompute the quantities needed for prediction at the final mode
+        let pi = f.map(sigmoid);
+        let sqrt_w = pi.map(|p| (p * (1.0 - p)).sqrt());
+        let gradient = &t - &pi;
+        let l = na::Cholesky::new(
+            na::DMatrix::identity(n, n)
+                + na::DMatrix::from_diagonal(&sqrt_w) * &k * na::DMatrix::from_diagonal(&sqrt_w),
+        )
+        .expect("should be positive definite")
+        .l();
+        let log_marginal_likelihood = objective - l.diagonal().map(f64::ln).sum();
+
+        GaussianProcessClassifier {
+            kernel,
+            x: x.clone(),
+            gradient,
+            sqrt_w,
+            l,
+            log_marginal_likelihood,
+        }
+    }
+
+    /// The Laplace approximation of the log marginal likelihood `log p(y | x)`.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.log_marginal_likelihood
+    }
+
+    /// Predict the mean and variance of the latent function at the given points.
+    pub fn predict_latent(&self, x: &na::DVector<I>) -> (na::DVector<f64>, na::DVector<f64>) {
+        let k_star = self.kernel.compute_matrix(&self.x, x);
+        let mean = k_star.transpose() * &self.gradient;
+
+        let mut scaled = k_star;
+        for mut column in scaled.column_iter_mut() {
+            column.component_mul_assign(&self.sqrt_w);
+        }
+        let v = self
+            .l
+            .solve_lower_triangular(&scaled)
+            .expect("should be invertible");
+        let variance = na::DVector::from_fn(x.len(), |i, _| {
+            (self.kernel.compute(x[i], x[i]) - v.column(i).norm_squared()).max(0.0)
+        });
+
+        (mean, variance)
+    }
+
+    /// Predict the probability of the positive class at the given points, averaged over the
+    /// uncertainty of the latent function (using the probit approximation of the logistic).
+    pub fn predict_probability(&self, x: &na::DVector<I>) -> na::DVector<f64> {
+        let (mean, variance) = self.predict_latent(x);
+        mean.zip_map(&variance, |m, v| {
+            sigmoid(m / (1.0 + std::f64::consts::PI * v / 8.0).sqrt())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use na::DVector;
+
+    fn classifier() -> GaussianProcessClassifier<RbfKernel> {
+        let x = DVector::from_vec(vec![1.0, 2.0, 3.0, 7.0, 8.0, 9.0]);
+        let y = [false, false, false, true, true, true];
+        let kernel = RbfKernel {
+            sigma: 4.0,
+            length_scale: 2.0,
+        };
+        GaussianProcessClassifier::new(&x, &y, kernel)
+    }
+
+    #[test]
+    fn test_classifier_predict_probability() {
+        let gp = classifier();
+        let p = gp.predict_probability(&DVector::from_vec(vec![2.0, 5.0, 8.0]));
+        assert!(p[0] < 0.25);
+        assert!((p[1] - 0.5).abs() < 1e-6);
+        assert!(p[2] > 0.75);
+        assert!((p[0] + p[2] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_classifier_latent_variance() {
+        let gp = classifier();
+        let (_, variance) = gp.predict_latent(&DVector::from_vec(vec![8.0, 100.0]));
+        // far from the data the latent variance returns to the prior variance
+        assert!(variance[0] < variance[1]);
+        assert!((variance[1] - 4.0).abs() < 1e-6);
+        assert!(gp.log_marginal_likelihood() < 0.0);
+    }
+
+    #[test]
+    fn test_classifier_prior() {
+        let gp = GaussianProcessClassifier::new(
+            &DVector::from_vec(Vec::new()),
+            &[],
+            RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            },
+        );
+        let p = gp.predict_probability(&DVector::from_vec(vec![0.0]));
+        assert!((p[0] - 0.5).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,155 @@
+use rand::seq::SliceRandom;
+
+use super::parallel::parallel_map;
+use super::{GaussianProcess, GpError, GpInput, GpKernel};
+
+/// A robust Bayesian committee machine (Deisenroth & Ng, 2015, Distributed Gaussian
+/// processes): independent GPs fit to random shards of the observations, whose predictions are
+/// combined by weighting each expert by how much it learned at the input.
+///
+/// Each expert contributes with the weight `beta = 0.5 * (ln prior variance - ln variance)`,
+/// the information it gained over the prior, so experts without data near the input do not
+/// dilute the others, and where no expert knows anything the prediction falls back to the
+/// prior. Fitting `k` shards costs `O(n^3 / k^2)`, and with the `rayon` feature the shards are
+/// fit and queried in parallel on native targets.
+///
+/// ```
+/// use gaussian_processes::gp::{CommitteeGaussianProcess, RbfKernel};
+/// use rand::SeedableRng;
+///
+/// let x: Vec<f64> = (0..400).map(|i| i as f64 * 0.025).collect();
+/// let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+/// let kernel = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// };
+/// let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+/// let gp = CommitteeGaussianProcess::new(&x, &y, kernel, 0.01, 4, &mut rng)?;
+/// let (mean, _) = gp.predict(&[5.01]);
+/// assert!((mean[0] - 5.01f64.sin()).abs() < 0.05);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct CommitteeGaussianProcess<K: GpKernel<I>, I: GpInput = f64> {
+    experts: Vec<GaussianProcess<K, I>>,
+    kernel: K,
+}
+
+impl<K, I> CommitteeGaussianProcess<K, I>
+where
+    K: GpKernel<I> + Clone + Send + Sync,
+    I: GpInput + Send + Sync,
+{
+    /// Fit a GP to each of `shards` random, equally large parts of the observations.
+    pub fn new(
+        x: &[I],
+        y: &[f64],
+        kernel: K,
+        noise_sigma: f64,
+        shards: usize,
+        rng: &mut impl rand::Rng,
+    ) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        let mut order: Vec<usize> = (0..x.len()).collect();
+        order.shuffle(rng);
+        let shards = shards.clamp(1, x.len().max(1));
+        let parts: Vec<(Vec<I>, Vec<f64>)> = (0..shards)
+            .map(|shard| {
+                order
+                    .iter()
+                    .skip(shard)
+                    .step_by(shards)
+                    .map(|i| (x[*i], y[*i]))
+                    .unzip()
+            })
+            .collect();
+        let experts = parallel_map(parts, |(x, y)| {
+            GaussianProcess::from_slices(&x, &y, kernel.clone(), noise_sigma)
+        })
+        .into_iter()
+        .collect::<Result<_, _>>()?;
+        Ok(Self { experts, kernel })
+    }
+
+    /// The mean and variance of the latent function at the inputs, combining the experts.
+    pub fn predict(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        let predictions = parallel_map(self.experts.iter().collect(), |expert| {
+            expert.predict_slice(x)
+        });
+        (0..x.len())
+            .map(|i| {
+                let prior_variance = self.kernel.compute(x[i], x[i]);
+                let mut precision = 1.0 / prior_variance;
+                let mut weighted_mean = 0.0;
+                for (mean, variance) in &predictions {
+                    let beta = 0.5 * (prior_variance.ln() - variance[i].ln());
+                    precision += beta * (1.0 / variance[i] - 1.0 / prior_variance);
+                    weighted_mean += beta * mean[i] / variance[i];
+                }
+                let variance = 1.0 / precision;
+                (weighted_mean * variance, variance)
+            })
+            .unzip()
+    }
+
+    /// The sum of the log marginal likelihoods of the experts, an approximation of the log
+    /// marginal likelihood of all observations that treats the shards as independent.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.experts
+            .iter()
+            .map(GaussianProcess::log_marginal_likelihood)
+            .sum()
+    }
+
+    /// The GP fit to each shard.
+    pub fn experts(&self) -> &[GaussianProcess<K, I>] {
+        &self.experts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_committee_matches_full_gp() {
+        let x: Vec<f64> = (0..120).map(|i| i as f64 * 0.05).collect();
+        let y: Vec<f64> = x.iter().map(|x| (1.5 * x).sin()).collect();
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 0.8,
+        };
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(3);
+        let committee =
+            CommitteeGaussianProcess::new(&x, &y, kernel.clone(), 0.01, 4, &mut rng).unwrap();
+        assert_eq!(committee.experts().len(), 4);
+        assert!(committee.experts().iter().all(|gp| gp.inputs().len() == 30));
+
+        let full = GaussianProcess::from_slices(&x, &y, kernel.clone(), 0.01).unwrap();
+        let test = [0.52, 3.1, 5.3];
+        let (mean, variance) = committee.predict(&test);
+        let (full_mean, _) = full.predict_slice(&test);
+        for i in 0..test.len() {
+            assert!(
+                (mean[i] - full_mean[i]).abs() < 0.01,
+                "{mean:?} {full_mean:?}"
+            );
+            assert!(variance[i] > 0.0 && variance[i] < 0.01);
+        }
+
+        // far from the data it falls back to the prior
+        let (mean, variance) = committee.predict(&[100.0]);
+        assert!(mean[0].abs() < 1e-6);
+        assert!((variance[0] - 1.0).abs() < 1e-3);
+
+        // a single shard is the full GP
+        let single = CommitteeGaussianProcess::new(&x, &y, kernel, 0.01, 1, &mut rng).unwrap();
+        assert!((single.log_marginal_likelihood() - full.log_marginal_likelihood()).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,163 @@
+use nalgebra as na;
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel};
+
+/// Split conformal prediction intervals around the predictions of a GP (Papadopoulos et al.,
+/// 2002; Lei et al., 2018, Distribution-free predictive inference for regression).
+///
+/// The GP is fit to one part of the data and its predictive intervals are rescaled to reach the
+/// asked coverage on a separate calibration set. For exchangeable data, a new observation is
+/// inside the interval with at least the asked probability, however wrong the kernel or the
+/// noise are: a misspecified model only gives wider intervals. The nonconformity score is the
+/// residual in predictive standard deviations, so the intervals keep the shape of the Bayesian
+/// ones and are still narrow near the data.
+///
+/// ```
+/// use gaussian_processes::gp::{ConformalGaussianProcess, GaussianProcess, RbfKernel};
+///
+/// let kernel = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// };
+/// let gp = GaussianProcess::from_slices(&[0.0, 2.0, 4.0], &[0.0, 0.9, -0.8], kernel, 0.01)?;
+/// let x: Vec<f64> = (0..19).map(|i| i as f64 * 0.2 + 0.1).collect();
+/// let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+/// let conformal = ConformalGaussianProcess::new(gp, &x, &y)?;
+/// let (lower, upper) = conformal.predict_interval(&[1.0], 0.9);
+/// assert!(lower[0] < 1f64.sin() && 1f64.sin() < upper[0]);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct ConformalGaussianProcess<K: GpKernel<I>, I: GpInput = f64> {
+    gp: GaussianProcess<K, I>,
+    /// The nonconformity scores of the calibration set, in increasing order.
+    scores: Vec<f64>,
+}
+
+impl<K: GpKernel<I>, I: GpInput> ConformalGaussianProcess<K, I> {
+    /// Calibrate the intervals of a fitted GP on held-out observations, which must not be
+    /// among those it was fit to.
+    pub fn new(gp: GaussianProcess<K, I>, x: &[I], y: &[f64]) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        let (mean, std) = Self::predictive(&gp, x);
+        let mut scores: Vec<f64> = y
+            .iter()
+            .zip(mean.iter().zip(&std))
+            .map(|(y, (mean, std))| (y - mean).abs() / std)
+            .collect();
+        scores.sort_by(f64::total_cmp);
+        Ok(Self { gp, scores })
+    }
+
+    /// The mean and standard deviation of new observations, including the noise.
+    fn predictive(gp: &GaussianProcess<K, I>, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        let (mean, variance) = gp.predict(&na::DVector::from_column_slice(x));
+        let noise = gp.noise_variance();
+        let std = variance.iter().map(|v| (v + noise).sqrt()).collect();
+        (mean.data.into(), std)
+    }
+
+    /// How many predictive standard deviations the intervals of the given coverage extend from
+    /// the mean: the `ceil((n + 1) * coverage)`-th smallest of the `n` calibration scores. It is
+    /// infinite when there are too few calibration points to guarantee the coverage.
+    pub fn scale(&self, coverage: f64) -> f64 {
+        let n = self.scores.len();
+        let rank = ((n + 1) as f64 * coverage).ceil() as usize;
+        match rank {
+            0 => 0.0,
+            rank if rank <= n => self.scores[rank - 1],
+            _ => f64::INFINITY,
+        }
+    }
+
+    /// The lower and upper bounds of the intervals that contain a new observation at each
+    /// input with at least the given probability, e.g. 0.9.
+    pub fn predict_interval(&self, x: &[I], coverage: f64) -> (Vec<f64>, Vec<f64>) {
+        let scale = self.scale(coverage);
+        let (mean, std) = Self::predictive(&self.gp, x);
+        mean.iter()
+            .zip(&std)
+            .map(|(mean, std)| (mean - scale * std, mean + scale * std))
+            .unzip()
+    }
+
+    /// The wrapped GP.
+    pub fn gp(&self) -> &GaussianProcess<K, I> {
+        &self.gp
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_conformal_scale() {
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::<_, f64>::prior(kernel, 0.0);
+        // the prior predicts zero with a unit variance, so the scores are the |y|
+        let y = [0.1, -0.4, 0.3, 0.2];
+        let conformal = ConformalGaussianProcess::new(gp, &[0.0; 4], &y).unwrap();
+        let scale = |coverage| conformal.scale(coverage);
+        assert!((scale(0.5) - 0.3).abs() < 1e-4);
+        assert!((scale(0.8) - 0.4).abs() < 1e-4);
+        assert_eq!(scale(0.9), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_conformal_coverage_of_misspecified_model() {
+        // the GP assumes far too little noise
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+        let mut sample = |n: usize| -> (Vec<f64>, Vec<f64>) {
+            (0..n)
+                .map(|_| {
+                    let x: f64 = rng.gen_range(0.0..10.0);
+                    (
+                        x,
+                        x.sin() + 0.5 * rng.sample::<f64, _>(rand_distr::StandardNormal),
+                    )
+                })
+                .unzip()
+        };
+        let (x_train, y_train) = sample(40);
+        let (x_calibration, y_calibration) = sample(200);
+        let (x_test, y_test) = sample(2000);
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::from_slices(&x_train, &y_train, kernel, 0.01).unwrap();
+        let coverage = |lower: &[f64], upper: &[f64]| {
+            let inside = (0..y_test.len())
+                .filter(|i| lower[*i] <= y_test[*i] && y_test[*i] <= upper[*i])
+                .count();
+            inside as f64 / y_test.len() as f64
+        };
+
+        // the Bayesian 90% intervals are far too narrow
+        let (mean, variance) = gp.predict_slice(&x_test);
+        let (lower, upper): (Vec<f64>, Vec<f64>) = mean
+            .iter()
+            .zip(&variance)
+            .map(|(m, v)| (m - 1.645 * (v + 0.01).sqrt(), m + 1.645 * (v + 0.01).sqrt()))
+            .unzip();
+        assert!(coverage(&lower, &upper) < 0.6);
+
+        let conformal = ConformalGaussianProcess::new(gp, &x_calibration, &y_calibration).unwrap();
+        let (lower, upper) = conformal.predict_interval(&x_test, 0.9);
+        let conformal_coverage = coverage(&lower, &upper);
+        assert!(
+            (conformal_coverage - 0.9).abs() < 0.04,
+            "{conformal_coverage}"
+        );
+    }
+}
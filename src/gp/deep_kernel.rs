@@ -0,0 +1,378 @@
+//! Experimental deep kernel learning (Wilson et al., 2016): the inputs pass through a small
+//! neural network before an RBF kernel, and the weights of the network are trained together
+//! with the hyperparameters by maximizing the log marginal likelihood.
+
+use nalgebra as na;
+
+use super::optimize::Adam;
+use super::{FitEvent, GaussianProcess, GpError, GpKernel, RbfKernel};
+
+/// The optimization has converged when an iteration improves the log marginal likelihood by
+/// less than this.
+const TOLERANCE: f64 = 1e-8;
+
+/// A fully connected layer, `tanh(weights * x + bias)` or without the `tanh` for the last one.
+#[derive(Clone, Debug, PartialEq)]
+struct Layer {
+    weights: na::DMatrix<f64>,
+    bias: na::DVector<f64>,
+}
+
+/// An RBF kernel on the output of a feedforward network, which can learn which directions
+/// and regions of the input space matter.
+///
+/// ```
+/// use gaussian_processes::gp::{DeepKernel, DeepKernelOptimizer, RbfKernel};
+/// use rand::SeedableRng;
+///
+/// // the target only depends on the sum of the inputs
+/// let x: Vec<[f64; 2]> = (0..30).map(|i| [(i % 6) as f64, (i / 6) as f64]).collect();
+/// let y: Vec<f64> = x.iter().map(|[a, b]| ((a + b) / 2.0).sin()).collect();
+///
+/// let rbf = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// };
+/// let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+/// let kernel = DeepKernel::new(&[8], 2, rbf, &mut rng);
+/// let mut optimizer = DeepKernelOptimizer::new(&x, &y, kernel, 0.1)?;
+/// let start = optimizer.log_marginal_likelihood();
+/// optimizer.run(100, |_| true);
+/// assert!(optimizer.log_marginal_likelihood() > start);
+/// let gp = optimizer.gaussian_process()?;
+/// let (mean, _) = gp.predict_slice(&[[1.0, 3.0], [3.0, 1.0]]);
+/// assert!((mean[0] - mean[1]).abs() < 0.1);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeepKernel<const N: usize> {
+    layers: Vec<Layer>,
+    pub rbf: RbfKernel,
+}
+
+impl<const N: usize> DeepKernel<N> {
+    /// A network with `tanh` hidden layers of the given sizes and a linear output layer of
+    /// `output` features, with random (Glorot) initial weights.
+    pub fn new<R: rand::Rng>(hidden: &[usize], output: usize, rbf: RbfKernel, rng: &mut R) -> Self {
+        let sizes: Vec<usize> = std::iter::once(N)
+            .chain(hidden.iter().copied())
+            .chain(std::iter::once(output))
+            .collect();
+        let layers = sizes
+            .windows(2)
+            .map(|size| {
+                let limit = (6.0 / (size[0] + size[1]) as f64).sqrt();
+                Layer {
+                    weights: na::DMatrix::from_fn(size[1], size[0], |_, _| {
+                        rng.gen_range(-limit..limit)
+                    }),
+                    bias: na::DVector::zeros(size[1]),
+                }
+            })
+            .collect();
+        Self { layers, rbf }
+    }
+
+    /// The features the RBF kernel is evaluated on.
+    pub fn embed(&self, x: [f64; N]) -> na::DVector<f64> {
+        self.activations(x)
+            .pop()
+            .expect("there is at least one layer")
+    }
+
+    /// The input followed by the output of every layer.
+    fn activations(&self, x: [f64; N]) -> Vec<na::DVector<f64>> {
+        let mut activations = vec![na::DVector::from_column_slice(&x)];
+        for (i, layer) in self.layers.iter().enumerate() {
+            let h = &layer.weights * &activations[i] + &layer.bias;
+            let last = i == self.layers.len() - 1;
+            activations.push(if last { h } else { h.map(f64::tanh) });
+        }
+        activations
+    }
+
+    fn rbf_of_embeddings(&self, a: &na::DVector<f64>, b: &na::DVector<f64>) -> f64 {
+        self.rbf.sigma * (-0.5 * (a - b).norm_squared() / self.rbf.length_scale.powi(2)).exp()
+    }
+
+    /// All weights and biases, layer by layer.
+    fn weights(&self) -> Vec<f64> {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.weights.iter().chain(layer.bias.iter()))
+            .copied()
+            .collect()
+    }
+
+    fn set_weights(&mut self, values: &[f64]) {
+        let mut values = values.iter();
+        for layer in &mut self.layers {
+            for w in layer.weights.iter_mut().chain(layer.bias.iter_mut()) {
+                *w = *values.next().expect("one value for every weight");
+            }
+        }
+    }
+}
+
+impl<const N: usize> GpKernel<[f64; N]> for DeepKernel<N> {
+    fn compute(&self, x: [f64; N], x2: [f64; N]) -> f64 {
+        self.rbf_of_embeddings(&self.embed(x), &self.embed(x2))
+    }
+
+    /// Embeds every input once instead of once per pair.
+    fn compute_matrix(
+        &self,
+        x: &na::DVector<[f64; N]>,
+        x2: &na::DVector<[f64; N]>,
+    ) -> na::DMatrix<f64> {
+        let a: Vec<_> = x.iter().map(|x| self.embed(*x)).collect();
+        let b: Vec<_> = x2.iter().map(|x| self.embed(*x)).collect();
+        na::DMatrix::from_fn(a.len(), b.len(), |i, j| {
+            self.rbf_of_embeddings(&a[i], &b[j])
+        })
+    }
+}
+
+/// Trains the weights of a [`DeepKernel`] together with its RBF hyperparameters and the noise,
+/// by gradient ascent (Adam) on the log marginal likelihood.
+///
+/// Like [`super::HyperparameterOptimizer`], it is advanced one iteration at a time so the
+/// caller can show the progress and stop at any point.
+pub struct DeepKernelOptimizer<const N: usize> {
+    kernel: DeepKernel<N>,
+    x: na::DVector<[f64; N]>,
+    y: na::DVector<f64>,
+    /// The parameters `[weights.., log sigma, log length scale, log noise]`.
+    params: Vec<f64>,
+    adam: Adam,
+    value: f64,
+    improvement: f64,
+    iteration: usize,
+}
+
+impl<const N: usize> DeepKernelOptimizer<N> {
+    pub fn new(
+        x: &[[f64; N]],
+        y: &[f64],
+        kernel: DeepKernel<N>,
+        noise_sigma: f64,
+    ) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        let mut params = kernel.weights();
+        params.extend([
+            kernel.rbf.sigma.ln(),
+            kernel.rbf.length_scale.ln(),
+            noise_sigma.ln(),
+        ]);
+        let optimizer = Self {
+            kernel,
+            x: na::DVector::from_column_slice(x),
+            y: na::DVector::from_column_slice(y),
+            adam: Adam::new(0.01, params.len()),
+            params,
+            value: f64::NEG_INFINITY,
+            improvement: f64::INFINITY,
+            iteration: 0,
+        };
+        // fail early if the covariance matrix of the initial kernel is singular
+        optimizer.gradient()?;
+        Ok(optimizer)
+    }
+
+    /// The step size of the gradient ascent, 0.01 by default.
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.adam.set_learning_rate(learning_rate);
+        self
+    }
+
+    /// The kernel and noise with the current parameters.
+    fn hyperparameters(&self) -> (DeepKernel<N>, f64) {
+        let n = self.params.len();
+        let mut kernel = self.kernel.clone();
+        kernel.set_weights(&self.params[..n - 3]);
+        kernel.rbf.sigma = self.params[n - 3].exp();
+        kernel.rbf.length_scale = self.params[n - 2].exp();
+        (kernel, self.params[n - 1].exp())
+    }
+
+    /// The log marginal likelihood and its gradient with respect to the parameters.
+    fn gradient(&self) -> Result<(f64, Vec<f64>), GpError> {
+        let (kernel, noise) = self.hyperparameters();
+        let gp = GaussianProcess::builder()
+            .kernel(kernel.clone())
+            .noise(noise)
+            .build(&self.x, &self.y)?;
+        let value = gp.log_marginal_likelihood();
+
+        // dL/dK = (alpha alpha^T - K^-1) / 2
+        let alpha = &gp.input_cov_matrix_inv * &self.y;
+        let g = (&alpha * alpha.transpose() - &gp.input_cov_matrix_inv) * 0.5;
+
+        let activations: Vec<_> = self.x.iter().map(|x| kernel.activations(*x)).collect();
+        let z: Vec<_> = activations.iter().map(|a| &a[a.len() - 1]).collect();
+        let n = z.len();
+        let length_scale2 = kernel.rbf.length_scale.powi(2);
+        let mut d_log_sigma = 0.0;
+        let mut d_log_length_scale = 0.0;
+        let mut d_z = vec![na::DVector::zeros(z.first().map_or(0, |z| z.len())); n];
+        for i in 0..n {
+            for j in 0..n {
+                let k = kernel.rbf_of_embeddings(z[i], z[j]);
+                let difference = z[i] - z[j];
+                d_log_sigma += g[(i, j)] * k;
+                d_log_length_scale += g[(i, j)] * k * difference.norm_squared() / length_scale2;
+                // K[(i, j)] and K[(j, i)] both depend on z[i]
+                d_z[i] -= difference * (2.0 * g[(i, j)] * k / length_scale2);
+            }
+        }
+        let d_log_noise = g.trace() * 2.0 * noise.powi(2);
+
+        // backpropagate the gradients of the features through the network
+        let mut d_weights: Vec<Layer> = kernel
+            .layers
+            .iter()
+            .map(|layer| Layer {
+                weights: na::DMatrix::zeros(layer.weights.nrows(), layer.weights.ncols()),
+                bias: na::DVector::zeros(layer.bias.len()),
+            })
+            .collect();
+        for (activations, d_z) in activations.iter().zip(d_z) {
+            let mut delta = d_z;
+            for l in (0..kernel.layers.len()).rev() {
+                d_weights[l].weights += &delta * activations[l].transpose();
+                d_weights[l].bias += &delta;
+                if l > 0 {
+                    delta = (kernel.layers[l].weights.transpose() * &delta)
+                        .component_mul(&activations[l].map(|a| 1.0 - a * a));
+                }
+            }
+        }
+
+        let mut gradient: Vec<f64> = d_weights
+            .iter()
+            .flat_map(|layer| layer.weights.iter().chain(layer.bias.iter()))
+            .copied()
+            .collect();
+        gradient.extend([d_log_sigma, d_log_length_scale, d_log_noise]);
+        Ok((value, gradient))
+    }
+
+    /// Perform one step of gradient ascent, returning the log marginal likelihood before it.
+    pub fn step(&mut self) -> f64 {
+        let Ok((value, gradient)) = self.gradient() else {
+            // the last step made the covariance matrix singular, so stop here
+            self.improvement = 0.0;
+            return self.value;
+        };
+        self.improvement = value - self.value;
+        self.value = value;
+        self.iteration += 1;
+        self.adam.step(&mut self.params, &gradient);
+        value
+    }
+
+    /// Iterate until converged or `max_iterations` is reached, calling `callback` after every
+    /// iteration with the RBF hyperparameters and the noise. Returning false from the callback
+    /// stops early.
+    pub fn run(&mut self, max_iterations: usize, mut callback: impl FnMut(FitEvent<'_>) -> bool) {
+        while !self.converged() && self.iteration < max_iterations {
+            let value = self.step();
+            let n = self.params.len();
+            let hyperparameters: Vec<f64> = self.params[n - 3..].iter().map(|p| p.exp()).collect();
+            let event = FitEvent {
+                iteration: self.iteration,
+                log_marginal_likelihood: value,
+                hyperparameters: &hyperparameters,
+            };
+            if !callback(event) {
+                break;
+            }
+        }
+    }
+
+    /// Whether the last iteration did not improve the result noticeably.
+    pub fn converged(&self) -> bool {
+        self.improvement.abs() < TOLERANCE
+    }
+
+    /// The log marginal likelihood with the current parameters.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.gradient()
+            .map_or(f64::NEG_INFINITY, |(value, _)| value)
+    }
+
+    /// The trained kernel and noise.
+    pub fn best(&self) -> (DeepKernel<N>, f64) {
+        self.hyperparameters()
+    }
+
+    /// The Gaussian process with the trained kernel and noise.
+    pub fn gaussian_process(&self) -> Result<GaussianProcess<DeepKernel<N>, [f64; N]>, GpError> {
+        let (kernel, noise) = self.hyperparameters();
+        GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(noise)
+            .build(&self.x, &self.y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn optimizer() -> DeepKernelOptimizer<2> {
+        let x: Vec<[f64; 2]> = (0..12)
+            .map(|i| [(i % 4) as f64 * 0.7, (i / 4) as f64 * 1.1])
+            .collect();
+        let y: Vec<f64> = x.iter().map(|[a, b]| (a - 0.5 * b).sin()).collect();
+        let rbf = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+        let kernel = DeepKernel::new(&[4], 2, rbf, &mut rng);
+        DeepKernelOptimizer::new(&x, &y, kernel, 0.05).unwrap()
+    }
+
+    #[test]
+    fn test_deep_kernel_gradient() {
+        let mut optimizer = optimizer();
+        let (value, gradient) = optimizer.gradient().unwrap();
+        let h = 1e-6;
+        for (i, expected) in gradient.iter().enumerate() {
+            optimizer.params[i] += h;
+            let (shifted, _) = optimizer.gradient().unwrap();
+            optimizer.params[i] -= h;
+            let numeric = (shifted - value) / h;
+            assert!(
+                (numeric - expected).abs() < 1e-3 * (1.0 + numeric.abs()),
+                "parameter {i}: {numeric} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_deep_kernel_training() {
+        let mut optimizer = optimizer();
+        let start = optimizer.log_marginal_likelihood();
+        let mut iterations = 0;
+        optimizer.run(200, |event| {
+            iterations = event.iteration;
+            event.hyperparameters.len() == 3
+        });
+        assert_eq!(iterations, 200);
+        assert!(optimizer.log_marginal_likelihood() > start + 1.0);
+
+        // the kernel matrix of the trained GP matches the embeddings
+        let (kernel, _) = optimizer.best();
+        let k = kernel.compute_matrix(&optimizer.x, &optimizer.x);
+        assert!((k[(0, 1)] - kernel.compute(optimizer.x[0], optimizer.x[1])).abs() < 1e-12);
+        assert!(optimizer.gaussian_process().is_ok());
+    }
+}
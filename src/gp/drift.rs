@@ -0,0 +1,177 @@
+use nalgebra as na;
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel};
+
+/// Regression on known explanatory variables plus a GP, `y = H beta + f(x) + noise`, where each
+/// row of the covariate matrix `H` holds the covariates of one observation (universal kriging
+/// with an external drift).
+///
+/// The coefficients `beta` have a flat prior, so they are the generalized least squares
+/// estimate under the GP covariance, and their uncertainty is included in the predictive
+/// variance. See Rasmussen & Williams, Gaussian Processes for Machine Learning, section 2.7.
+/// Include a column of ones in `H` to estimate a constant mean as well.
+///
+/// ```
+/// use gaussian_processes::gp::{ExternalDriftGaussianProcess, RbfKernel};
+/// use nalgebra::DMatrix;
+///
+/// // the temperature depends on the altitude as well as smoothly on the position
+/// let x = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+/// let altitude = [100.0, 500.0, 200.0, 800.0, 300.0, 600.0];
+/// let temperature: Vec<f64> = x
+///     .iter()
+///     .zip(&altitude)
+///     .map(|(x, altitude)| 20.0 - 0.01 * altitude + (x / 2.0f64).sin())
+///     .collect();
+/// let covariates = DMatrix::from_fn(6, 2, |i, j| if j == 0 { 1.0 } else { altitude[i] });
+///
+/// let kernel = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 2.0,
+/// };
+/// let gp = ExternalDriftGaussianProcess::new(&x, &covariates, &temperature, kernel, 1e-4)?;
+/// assert!((gp.coefficients()[1] + 0.01).abs() < 1e-3);
+/// let (mean, _) = gp.predict(&[2.5], &DMatrix::from_row_slice(1, 2, &[1.0, 400.0]))?;
+/// assert!((mean[0] - (16.0 + 1.25f64.sin())).abs() < 0.1);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct ExternalDriftGaussianProcess<K: GpKernel<I>, I: GpInput = f64> {
+    /// The GP of the residuals `y - H beta`.
+    gp: GaussianProcess<K, I>,
+    covariates: na::DMatrix<f64>,
+    coefficients: na::DVector<f64>,
+    /// The covariance of the coefficients, `(H^T K^-1 H)^-1`.
+    coefficient_covariance: na::DMatrix<f64>,
+}
+
+impl<K: GpKernel<I>, I: GpInput> ExternalDriftGaussianProcess<K, I> {
+    pub fn new(
+        x: &[I],
+        covariates: &na::DMatrix<f64>,
+        y: &[f64],
+        kernel: K,
+        noise_sigma: f64,
+    ) -> Result<Self, GpError> {
+        if covariates.nrows() != x.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: covariates.nrows(),
+            });
+        }
+        let mut gp = GaussianProcess::from_slices(x, y, kernel, noise_sigma)?;
+
+        // generalized least squares, beta = (H^T K^-1 H)^-1 H^T K^-1 y
+        let weighted = covariates.transpose() * &gp.input_cov_matrix_inv;
+        let coefficient_covariance = (&weighted * covariates)
+            .try_inverse()
+            .ok_or(GpError::NotInvertible)?;
+        let coefficients = &coefficient_covariance * (&weighted * &gp.y);
+
+        gp.y = &gp.y - covariates * &coefficients;
+        Ok(Self {
+            gp,
+            covariates: covariates.clone(),
+            coefficients,
+            coefficient_covariance,
+        })
+    }
+
+    /// The estimated coefficients `beta` of the covariates.
+    pub fn coefficients(&self) -> &na::DVector<f64> {
+        &self.coefficients
+    }
+
+    /// The covariance of the estimated coefficients, whose diagonal holds the squares of their
+    /// standard errors.
+    pub fn coefficient_covariance(&self) -> &na::DMatrix<f64> {
+        &self.coefficient_covariance
+    }
+
+    /// The mean and variance at the inputs, with one row of covariates for each input.
+    pub fn predict(
+        &self,
+        x: &[I],
+        covariates: &na::DMatrix<f64>,
+    ) -> Result<(Vec<f64>, Vec<f64>), GpError> {
+        if covariates.ncols() != self.covariates.ncols() {
+            return Err(GpError::DimensionMismatch {
+                expected: self.covariates.ncols(),
+                got: covariates.ncols(),
+            });
+        }
+        if covariates.nrows() != x.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: covariates.nrows(),
+            });
+        }
+
+        let x_vector = na::DVector::from_column_slice(x);
+        let (mean, variance) = self.gp.predict(&x_vector);
+        let mean = mean + covariates * &self.coefficients;
+
+        // the uncertainty of the coefficients, R^T cov(beta) R with R = H*^T - H^T K^-1 K*
+        let k_star = self.gp.kernel.compute_matrix(&self.gp.x, &x_vector);
+        let r = covariates.transpose()
+            - self.covariates.transpose() * &self.gp.input_cov_matrix_inv * k_star;
+        let spread = &self.coefficient_covariance * &r;
+        let variance = na::DVector::from_fn(x.len(), |i, _| {
+            variance[i] + r.column(i).dot(&spread.column(i))
+        });
+        Ok((mean.data.into(), variance.data.into()))
+    }
+
+    /// The GP of what the covariates do not explain.
+    pub fn gp(&self) -> &GaussianProcess<K, I> {
+        &self.gp
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+
+    #[test]
+    fn test_external_drift() {
+        let x: Vec<f64> = (0..15).map(|i| i as f64 * 0.5).collect();
+        let covariate: Vec<f64> = x.iter().map(|x| (x * 3.1).cos() * 4.0).collect();
+        let y: Vec<f64> = x
+            .iter()
+            .zip(&covariate)
+            .map(|(x, c)| 1.0 + 2.0 * c + x.sin())
+            .collect();
+        let covariates = na::DMatrix::from_fn(x.len(), 2, |i, j| [1.0, covariate[i]][j]);
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = ExternalDriftGaussianProcess::new(&x, &covariates, &y, kernel, 1e-4).unwrap();
+        assert!((gp.coefficients()[1] - 2.0).abs() < 0.01);
+        assert!(gp.coefficient_covariance()[(1, 1)] > 0.0);
+
+        // the fast changing covariate is not soaked up by the smooth kernel
+        let test = [1.3, 4.8];
+        let test_covariates = na::DMatrix::from_row_slice(2, 2, &[1.0, -1.0, 1.0, 3.0]);
+        let (mean, variance) = gp.predict(&test, &test_covariates).unwrap();
+        for i in 0..2 {
+            let expected = 1.0 + 2.0 * test_covariates[(i, 1)] + test[i].sin();
+            assert!(
+                (mean[i] - expected).abs() < 0.1,
+                "{} != {expected}",
+                mean[i]
+            );
+        }
+        // the uncertain coefficients add to the variance of the GP alone
+        let (_, gp_variance) = gp.gp().predict_slice(&test);
+        assert!(variance[0] >= gp_variance[0] && variance[1] >= gp_variance[1]);
+
+        assert_eq!(
+            gp.predict(&test, &na::DMatrix::zeros(2, 3)).err(),
+            Some(GpError::DimensionMismatch {
+                expected: 2,
+                got: 3
+            })
+        );
+    }
+}
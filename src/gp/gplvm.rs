@@ -0,0 +1,285 @@
+use nalgebra as na;
+
+use super::optimize::Adam;
+use super::{FitEvent, GaussianProcess, GpError, GpKernel, RbfKernel, EPS};
+
+/// The optimization has converged when an iteration improves the objective by less than this.
+const TOLERANCE: f64 = 1e-8;
+
+/// A Gaussian process latent variable model (Lawrence, 2005): unsupervised dimensionality
+/// reduction that finds a position in a `Q` dimensional latent space for every observed
+/// vector, such that a GP from the latent space explains the observations well.
+///
+/// Each column of the observations is modelled by an independent GP with a shared RBF kernel.
+/// The latent positions start from principal component analysis and are moved by gradient
+/// ascent (Adam) on the log likelihood together with the kernel hyperparameters and the
+/// noise. A unit Gaussian prior on the positions keeps them from drifting apart.
+///
+/// ```
+/// use gaussian_processes::gp::{Gplvm, RbfKernel};
+/// use nalgebra::DMatrix;
+///
+/// // points on a curve in three dimensions
+/// let y = DMatrix::from_fn(20, 3, |i, j| {
+///     let t = i as f64 / 5.0;
+///     [t.cos(), t.sin(), t / 2.0][j]
+/// });
+/// let kernel = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// };
+/// let mut gplvm = Gplvm::<1>::new(&y, kernel, 0.1)?;
+/// let start = gplvm.log_likelihood();
+/// gplvm.run(50, |_| true);
+/// assert!(gplvm.log_likelihood() > start);
+/// let latent = gplvm.latent();
+/// let reconstructed = gplvm.reconstruct(&latent[..1])?;
+/// assert_eq!(reconstructed.shape(), (1, 3));
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct Gplvm<const Q: usize> {
+    /// The observations with the mean of each column subtracted.
+    y: na::DMatrix<f64>,
+    y_mean: na::RowDVector<f64>,
+    /// The parameters `[latent positions.., log sigma, log length scale, log noise]`, with the
+    /// positions stored point by point.
+    params: Vec<f64>,
+    adam: Adam,
+    value: f64,
+    improvement: f64,
+    iteration: usize,
+}
+
+impl<const Q: usize> Gplvm<Q> {
+    /// Start from the principal components of the observations, one row per observation.
+    pub fn new(y: &na::DMatrix<f64>, kernel: RbfKernel, noise_sigma: f64) -> Result<Self, GpError> {
+        if y.ncols() < Q {
+            return Err(GpError::DimensionMismatch {
+                expected: Q,
+                got: y.ncols(),
+            });
+        }
+        let y_mean = y.row_mean();
+        let centered = na::DMatrix::from_fn(y.nrows(), y.ncols(), |i, j| y[(i, j)] - y_mean[j]);
+
+        // the projections on the first principal components, scaled to unit variance
+        let svd = centered.clone().svd(true, false);
+        let u = svd
+            .u
+            .as_ref()
+            .expect("the left singular vectors were computed");
+        let mut order: Vec<usize> = (0..svd.singular_values.len()).collect();
+        order.sort_by(|a, b| svd.singular_values[*b].total_cmp(&svd.singular_values[*a]));
+        let scale = (y.nrows() as f64).sqrt();
+        let mut params: Vec<f64> = (0..y.nrows())
+            .flat_map(|i| order[..Q].iter().map(move |c| u[(i, *c)] * scale))
+            .collect();
+        params.extend([
+            kernel.sigma.ln(),
+            kernel.length_scale.ln(),
+            noise_sigma.ln(),
+        ]);
+
+        let gplvm = Self {
+            y: centered,
+            y_mean,
+            adam: Adam::new(0.01, params.len()),
+            params,
+            value: f64::NEG_INFINITY,
+            improvement: f64::INFINITY,
+            iteration: 0,
+        };
+        // fail early if the covariance matrix of the initial positions is singular
+        gplvm.gradient(&gplvm.params)?;
+        Ok(gplvm)
+    }
+
+    /// The step size of the gradient ascent, 0.01 by default.
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.adam.set_learning_rate(learning_rate);
+        self
+    }
+
+    fn unpack(&self, params: &[f64]) -> (Vec<[f64; Q]>, RbfKernel, f64) {
+        let n = self.y.nrows();
+        let latent = (0..n)
+            .map(|i| std::array::from_fn(|q| params[i * Q + q]))
+            .collect();
+        let kernel = RbfKernel {
+            sigma: params[n * Q].exp(),
+            length_scale: params[n * Q + 1].exp(),
+        };
+        (latent, kernel, params[n * Q + 2].exp())
+    }
+
+    /// The log likelihood (including the prior of the positions) and its gradient.
+    fn gradient(&self, params: &[f64]) -> Result<(f64, Vec<f64>), GpError> {
+        let (latent, kernel, noise) = self.unpack(params);
+        let (n, d) = self.y.shape();
+        let latent_vector = na::DVector::from_vec(latent.clone());
+        let k_f = kernel.compute_matrix(&latent_vector, &latent_vector);
+        let k = &k_f + na::DMatrix::identity(n, n) * (noise.powi(2) + EPS);
+        let cholesky = na::Cholesky::new(k).ok_or(GpError::NotInvertible)?;
+        let k_inv = cholesky.inverse();
+        let alpha = &k_inv * &self.y;
+
+        let log_det = 2.0 * cholesky.l().diagonal().map(f64::ln).sum();
+        let prior: f64 = params[..n * Q].iter().map(|x| x * x).sum();
+        let value = -0.5 * d as f64 * log_det
+            - 0.5 * self.y.dot(&alpha)
+            - 0.5 * (n * d) as f64 * (2.0 * std::f64::consts::PI).ln()
+            - 0.5 * prior;
+
+        // dL/dK = (K^-1 Y Y^T K^-1 - D K^-1) / 2
+        let g = (&alpha * alpha.transpose() - &k_inv * d as f64) * 0.5;
+        let length_scale2 = kernel.length_scale.powi(2);
+        let mut gradient = vec![0.0; params.len()];
+        let mut d_log_sigma = 0.0;
+        let mut d_log_length_scale = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let distance2: f64 = (0..Q).map(|q| (latent[i][q] - latent[j][q]).powi(2)).sum();
+                d_log_sigma += g[(i, j)] * k_f[(i, j)];
+                d_log_length_scale += g[(i, j)] * k_f[(i, j)] * distance2 / length_scale2;
+                // K[(i, j)] and K[(j, i)] both depend on the position of i
+                for q in 0..Q {
+                    gradient[i * Q + q] -=
+                        2.0 * g[(i, j)] * k_f[(i, j)] * (latent[i][q] - latent[j][q])
+                            / length_scale2;
+                }
+            }
+        }
+        for (gradient, x) in gradient.iter_mut().zip(&params[..n * Q]) {
+            *gradient -= x;
+        }
+        gradient[n * Q] = d_log_sigma;
+        gradient[n * Q + 1] = d_log_length_scale;
+        gradient[n * Q + 2] = g.trace() * 2.0 * noise.powi(2);
+        Ok((value, gradient))
+    }
+
+    /// Perform one step of gradient ascent, returning the log likelihood before it.
+    pub fn step(&mut self) -> f64 {
+        let Ok((value, gradient)) = self.gradient(&self.params) else {
+            // the last step made the covariance matrix singular, so stop here
+            self.improvement = 0.0;
+            return self.value;
+        };
+        self.improvement = value - self.value;
+        self.value = value;
+        self.iteration += 1;
+        self.adam.step(&mut self.params, &gradient);
+        value
+    }
+
+    /// Iterate until converged or `max_iterations` is reached, calling `callback` after every
+    /// iteration with the hyperparameters of the kernel and the noise. Returning false from
+    /// the callback stops early.
+    pub fn run(&mut self, max_iterations: usize, mut callback: impl FnMut(FitEvent<'_>) -> bool) {
+        while !self.converged() && self.iteration < max_iterations {
+            let value = self.step();
+            let n = self.params.len();
+            let hyperparameters: Vec<f64> = self.params[n - 3..].iter().map(|p| p.exp()).collect();
+            let event = FitEvent {
+                iteration: self.iteration,
+                log_marginal_likelihood: value,
+                hyperparameters: &hyperparameters,
+            };
+            if !callback(event) {
+                break;
+            }
+        }
+    }
+
+    /// Whether the last iteration did not improve the result noticeably.
+    pub fn converged(&self) -> bool {
+        self.improvement.abs() < TOLERANCE
+    }
+
+    /// The log likelihood of the observations at the current positions, plus the log prior of
+    /// the positions.
+    pub fn log_likelihood(&self) -> f64 {
+        self.gradient(&self.params)
+            .map_or(f64::NEG_INFINITY, |(value, _)| value)
+    }
+
+    /// The latent position of each observation.
+    pub fn latent(&self) -> Vec<[f64; Q]> {
+        self.unpack(&self.params).0
+    }
+
+    /// The kernel and noise with the current hyperparameters.
+    pub fn hyperparameters(&self) -> (RbfKernel, f64) {
+        let (_, kernel, noise) = self.unpack(&self.params);
+        (kernel, noise)
+    }
+
+    /// The predicted observations at latent positions, one row per position.
+    pub fn reconstruct(&self, latent: &[[f64; Q]]) -> Result<na::DMatrix<f64>, GpError> {
+        let (positions, kernel, noise) = self.unpack(&self.params);
+        let mut reconstructed = na::DMatrix::zeros(latent.len(), self.y.ncols());
+        for (j, column) in self.y.column_iter().enumerate() {
+            let gp =
+                GaussianProcess::from_slices(&positions, column.as_slice(), kernel.clone(), noise)?;
+            let (mean, _) = gp.predict_slice(latent);
+            for (i, mean) in mean.into_iter().enumerate() {
+                reconstructed[(i, j)] = mean + self.y_mean[j];
+            }
+        }
+        Ok(reconstructed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spiral() -> na::DMatrix<f64> {
+        na::DMatrix::from_fn(15, 4, |i, j| {
+            let t = i as f64 / 4.0;
+            [t.cos(), t.sin(), 0.5 * t, (2.0 * t).sin()][j]
+        })
+    }
+
+    fn kernel() -> RbfKernel {
+        RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_gplvm_gradient() {
+        let gplvm = Gplvm::<2>::new(&spiral(), kernel(), 0.1).unwrap();
+        let (value, gradient) = gplvm.gradient(&gplvm.params).unwrap();
+        let h = 1e-6;
+        let mut shifted = gplvm.params.clone();
+        for (i, expected) in gradient.iter().enumerate() {
+            shifted[i] += h;
+            let (value_shifted, _) = gplvm.gradient(&shifted).unwrap();
+            shifted[i] = gplvm.params[i];
+            let numeric = (value_shifted - value) / h;
+            assert!(
+                (numeric - expected).abs() < 1e-3 * (1.0 + numeric.abs()),
+                "parameter {i}: {numeric} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_gplvm_training() {
+        let y = spiral();
+        let mut gplvm = Gplvm::<1>::new(&y, kernel(), 0.1)
+            .unwrap()
+            .learning_rate(0.05);
+        let start = gplvm.log_likelihood();
+        gplvm.run(300, |_| true);
+        assert!(gplvm.log_likelihood() > start + 10.0);
+
+        // the observations are recovered from their latent positions
+        let reconstructed = gplvm.reconstruct(&gplvm.latent()).unwrap();
+        assert!((reconstructed - &y).abs().max() < 0.3);
+
+        assert!(Gplvm::<5>::new(&y, kernel(), 0.1).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+use nalgebra as na;
+use rand::{Rng, SeedableRng};
+
+use super::{
+    ArdKernel, GaussianProcess, GpInput, GpKernel, Kernel, KernelParams, MaternKernel,
+    MaternSmoothness, PeriodicKernel, RbfKernel,
+};
+
+/// Kernels with analytic derivatives with respect to their hyperparameters, for gradient based
+/// fitting. Check new implementations with [`check_gradients`].
+pub trait KernelGradient<I: GpInput = f64>: GpKernel<I> + KernelParams {
+    /// The derivatives of `compute(x, x2)` with respect to the hyperparameters, in the order of
+    /// [`KernelParams::params_mut`].
+    fn param_gradient(&self, x: I, x2: I) -> Vec<f64>;
+}
+
+impl<I: GpInput> KernelGradient<I> for RbfKernel {
+    fn param_gradient(&self, x: I, x2: I) -> Vec<f64> {
+        let distance2 = x.distance(&x2).powi(2);
+        let shape = (-0.5 * distance2 / self.length_scale.powi(2)).exp();
+        vec![
+            self.sigma * shape * distance2 / self.length_scale.powi(3),
+            shape,
+        ]
+    }
+}
+
+impl<const N: usize> KernelGradient<[f64; N]> for ArdKernel<N> {
+    fn param_gradient(&self, x: [f64; N], x2: [f64; N]) -> Vec<f64> {
+        let scaled2: Vec<f64> = (0..N)
+            .map(|d| ((x[d] - x2[d]) / self.length_scales[d]).powi(2))
+            .collect();
+        let shape = (-0.5 * scaled2.iter().sum::<f64>()).exp();
+        let mut gradient: Vec<f64> = (0..N)
+            .map(|d| self.sigma * shape * scaled2[d] / self.length_scales[d])
+            .collect();
+        gradient.push(shape);
+        gradient
+    }
+}
+
+impl<I: GpInput> KernelGradient<I> for MaternKernel {
+    fn param_gradient(&self, x: I, x2: I) -> Vec<f64> {
+        let r = x.distance(&x2) / self.length_scale;
+        // the shape and its derivative with respect to the length scale, times the length scale
+        let (shape, d_shape) = match self.smoothness {
+            MaternSmoothness::Half => ((-r).exp(), r * (-r).exp()),
+            MaternSmoothness::ThreeHalves => {
+                let r = 3f64.sqrt() * r;
+                ((1.0 + r) * (-r).exp(), r * r * (-r).exp())
+            }
+            MaternSmoothness::FiveHalves => {
+                let r = 5f64.sqrt() * r;
+                (
+                    (1.0 + r + r * r / 3.0) * (-r).exp(),
+                    r * r * (1.0 + r) / 3.0 * (-r).exp(),
+                )
+            }
+        };
+        vec![self.sigma * d_shape / self.length_scale, shape]
+    }
+}
+
+impl<I: GpInput> KernelGradient<I> for PeriodicKernel {
+    fn param_gradient(&self, x: I, x2: I) -> Vec<f64> {
+        let angle = std::f64::consts::PI * x.distance(&x2) / self.period;
+        let (sin, cos) = angle.sin_cos();
+        let length_scale2 = self.length_scale.powi(2);
+        let shape = (-2.0 * sin * sin / length_scale2).exp();
+        let k = self.sigma * shape;
+        vec![
+            k * 4.0 * sin * sin / (length_scale2 * self.length_scale),
+            shape,
+            k * 4.0 * sin * cos * angle / (length_scale2 * self.period),
+        ]
+    }
+}
+
+/// The gradients of the terms are concatenated, with the product rule for products.
+impl<I: GpInput> KernelGradient<I> for Kernel {
+    fn param_gradient(&self, x: I, x2: I) -> Vec<f64> {
+        match self {
+            Kernel::Rbf(k) => k.param_gradient(x, x2),
+            Kernel::Matern(k) => k.param_gradient(x, x2),
+            Kernel::Periodic(k) => k.param_gradient(x, x2),
+            Kernel::Sum(kernels) => kernels
+                .iter()
+                .flat_map(|k| k.param_gradient(x, x2))
+                .collect(),
+            Kernel::Product(kernels) => {
+                let values: Vec<f64> = kernels.iter().map(|k| k.compute(x, x2)).collect();
+                kernels
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, k)| {
+                        let others: f64 = (0..values.len())
+                            .filter(|j| *j != i)
+                            .map(|j| values[j])
+                            .product();
+                        k.param_gradient(x, x2).into_iter().map(move |g| g * others)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl<K: KernelGradient<I>, I: GpInput> GaussianProcess<K, I> {
+    /// The derivatives of the log marginal likelihood of the normalized targets with respect to
+    /// the hyperparameters `[kernel params.., noise]` (Rasmussen & Williams, eq. 5.9):
+    /// `tr((alpha alpha^T - K^-1) dK/dtheta) / 2` with `alpha = K^-1 y`.
+    pub fn log_marginal_likelihood_gradient(&self) -> Vec<f64> {
+        let alpha = &self.input_cov_matrix_inv * &self.y;
+        let g = (&alpha * alpha.transpose() - &self.input_cov_matrix_inv) * 0.5;
+        let n = self.x.len();
+        let mut gradient = vec![0.0; self.kernel.param_names().len()];
+        for i in 0..n {
+            for j in 0..n {
+                let d_k = self.kernel.param_gradient(self.x[i], self.x[j]);
+                for (gradient, d_k) in gradient.iter_mut().zip(d_k) {
+                    *gradient += g[(i, j)] * d_k;
+                }
+            }
+        }
+        // the noise enters the diagonal as its square
+        gradient.push(
+            (0..n)
+                .map(|i| 2.0 * self.noise_sigma * g[(i, i)] / self.weights[i])
+                .sum(),
+        );
+        gradient
+    }
+}
+
+/// A hyperparameter whose analytic derivative disagrees with the finite differences, see
+/// [`check_gradients`]. The errors are relative, `|analytic - numeric| / (1 + |numeric|)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientDiscrepancy {
+    /// The name of the hyperparameter, "noise" for the noise.
+    pub name: &'static str,
+    /// The largest error of the derivative of the kernel over the pairs of inputs.
+    pub kernel_error: f64,
+    /// The error of the derivative of the log marginal likelihood.
+    pub likelihood_error: f64,
+}
+
+/// The number of random inputs the gradients are checked at.
+const CHECK_POINTS: usize = 12;
+
+/// Compare the analytic derivatives of a kernel with [`KernelGradient`], and of the log
+/// marginal likelihood of a GP using it, with central finite differences. The inputs and
+/// targets are random with a fixed seed, and the noise is 0.1.
+///
+/// Returns the hyperparameters with an error above `tolerance`, nothing when all derivatives
+/// agree. Useful in the tests of new kernels.
+///
+/// ```
+/// use gaussian_processes::gp::{check_gradients, PeriodicKernel};
+///
+/// let kernel = PeriodicKernel {
+///     sigma: 1.5,
+///     length_scale: 0.8,
+///     period: 2.0,
+/// };
+/// assert_eq!(check_gradients(&kernel, 1e-5), vec![]);
+/// ```
+pub fn check_gradients<K: KernelGradient<f64> + Clone>(
+    kernel: &K,
+    tolerance: f64,
+) -> Vec<GradientDiscrepancy> {
+    let noise_sigma = 0.1;
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+    let x: Vec<f64> = (0..CHECK_POINTS).map(|_| rng.gen_range(0.0..5.0)).collect();
+    let y = na::DVector::from_fn(CHECK_POINTS, |_, _| {
+        rng.sample::<f64, _>(rand_distr::StandardNormal)
+    });
+    let x = na::DVector::from_vec(x);
+
+    let mut params: Vec<f64> = kernel
+        .clone()
+        .params_mut()
+        .into_iter()
+        .map(|p| *p)
+        .collect();
+    params.push(noise_sigma);
+    let with_params = |params: &[f64]| {
+        let mut kernel = kernel.clone();
+        for (param, value) in kernel.params_mut().into_iter().zip(params) {
+            *param = *value;
+        }
+        kernel
+    };
+    let log_marginal_likelihood = |params: &[f64]| {
+        let noise = params[params.len() - 1];
+        GaussianProcess::new(&x, &y, with_params(params), noise).log_marginal_likelihood()
+    };
+    let error = |analytic: f64, numeric: f64| (analytic - numeric).abs() / (1.0 + numeric.abs());
+
+    let analytic = GaussianProcess::new(&x, &y, kernel.clone(), noise_sigma)
+        .log_marginal_likelihood_gradient();
+    let mut names = kernel.param_names();
+    names.push("noise");
+    let mut discrepancies = Vec::new();
+    for (p, name) in names.into_iter().enumerate() {
+        let h = 1e-6 * (1.0 + params[p].abs());
+        let (mut above, mut below) = (params.clone(), params.clone());
+        above[p] += h;
+        below[p] -= h;
+
+        let mut kernel_error: f64 = 0.0;
+        if p < params.len() - 1 {
+            let (above, below) = (with_params(&above), with_params(&below));
+            for i in 0..CHECK_POINTS {
+                for j in 0..CHECK_POINTS {
+                    let numeric =
+                        (above.compute(x[i], x[j]) - below.compute(x[i], x[j])) / (2.0 * h);
+                    let analytic = kernel.param_gradient(x[i], x[j])[p];
+                    kernel_error = kernel_error.max(error(analytic, numeric));
+                }
+            }
+        }
+        let numeric =
+            (log_marginal_likelihood(&above) - log_marginal_likelihood(&below)) / (2.0 * h);
+        let likelihood_error = error(analytic[p], numeric);
+
+        if kernel_error > tolerance || likelihood_error > tolerance {
+            discrepancies.push(GradientDiscrepancy {
+                name,
+                kernel_error,
+                likelihood_error,
+            });
+        }
+    }
+    discrepancies
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An RBF kernel with the derivative of the length scale off by a factor of two.
+    #[derive(Clone)]
+    struct WrongRbfKernel(RbfKernel);
+
+    impl GpKernel for WrongRbfKernel {
+        fn compute(&self, x: f64, x2: f64) -> f64 {
+            self.0.compute(x, x2)
+        }
+    }
+
+    impl KernelParams for WrongRbfKernel {
+        fn param_names(&self) -> Vec<&'static str> {
+            self.0.param_names()
+        }
+
+        fn param_bounds(&self) -> Vec<(f64, f64)> {
+            self.0.param_bounds()
+        }
+
+        fn params_mut(&mut self) -> Vec<&mut f64> {
+            self.0.params_mut()
+        }
+    }
+
+    impl KernelGradient for WrongRbfKernel {
+        fn param_gradient(&self, x: f64, x2: f64) -> Vec<f64> {
+            let mut gradient = self.0.param_gradient(x, x2);
+            gradient[0] *= 2.0;
+            gradient
+        }
+    }
+
+    #[test]
+    fn test_check_gradients() {
+        let rbf = RbfKernel {
+            sigma: 2.0,
+            length_scale: 0.7,
+        };
+        let periodic = PeriodicKernel {
+            sigma: 1.0,
+            length_scale: 1.2,
+            period: 1.7,
+        };
+        let kernels = [
+            Kernel::Rbf(rbf.clone()),
+            Kernel::Periodic(periodic.clone()),
+            Kernel::Sum(vec![Kernel::Rbf(rbf.clone()), Kernel::Periodic(periodic)]),
+        ];
+        for kernel in kernels.iter().chain([&Kernel::Product(kernels.to_vec())]) {
+            assert_eq!(check_gradients(kernel, 1e-5), vec![], "{kernel:?}");
+        }
+        for smoothness in [
+            MaternSmoothness::Half,
+            MaternSmoothness::ThreeHalves,
+            MaternSmoothness::FiveHalves,
+        ] {
+            let matern = MaternKernel {
+                smoothness,
+                sigma: 1.3,
+                length_scale: 0.9,
+            };
+            assert_eq!(check_gradients(&matern, 1e-5), vec![]);
+        }
+
+        let discrepancies = check_gradients(&WrongRbfKernel(rbf), 1e-5);
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(discrepancies[0].name, "length scale");
+        assert!(discrepancies[0].kernel_error > 0.01 && discrepancies[0].likelihood_error > 0.01);
+    }
+
+    #[test]
+    fn test_ard_kernel_gradient() {
+        let kernel = ArdKernel {
+            sigma: 1.5,
+            length_scales: [0.5, 2.0],
+        };
+        let (x, x2) = ([0.3, -1.0], [1.1, 0.4]);
+        let gradient = kernel.param_gradient(x, x2);
+        let mut params = [0.5, 2.0, 1.5];
+        for (p, analytic) in gradient.iter().enumerate() {
+            let h = 1e-6;
+            let mut shifted = kernel.clone();
+            params[p] += h;
+            for (param, value) in shifted.params_mut().into_iter().zip(params) {
+                *param = value;
+            }
+            params[p] -= h;
+            let numeric = (shifted.compute(x, x2) - kernel.compute(x, x2)) / h;
+            assert!(
+                (numeric - analytic).abs() < 1e-5,
+                "{p}: {numeric} != {analytic}"
+            );
+        }
+    }
+}
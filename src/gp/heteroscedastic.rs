@@ -0,0 +1,179 @@
+use nalgebra as na;
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel, RegressionModel};
+
+/// How many times the noise estimate and the fit are improved in turn.
+const ITERATIONS: usize = 5;
+
+/// The mean of the log of a squared standard normal sample (`-gamma - ln 2`), by which the log
+/// of a squared residual underestimates the log noise variance.
+const LOG_SQUARED_NORMAL_MEAN: f64 = -0.5772156649015329 - std::f64::consts::LN_2;
+
+/// The variance of the log of a squared standard normal sample (`pi^2 / 2`), i.e. how noisy
+/// each estimate of the log noise variance is.
+const LOG_SQUARED_NORMAL_VARIANCE: f64 = std::f64::consts::PI * std::f64::consts::PI / 2.0;
+
+/// A Gaussian process whose observation noise changes with the input, modelled by a second GP
+/// of the log noise variance.
+///
+/// Fit with the "most likely heteroscedastic GP" scheme of Kersting et al. (2007): starting
+/// from a GP with constant noise, the expected squared residual at each training point gives
+/// an estimate of the noise there, the noise GP is fit to the logs of these estimates, and the
+/// GP is refit with the noise the noise GP predicts. The two steps are repeated a few times.
+pub struct HeteroscedasticGaussianProcess<K: GpKernel<I>, L: GpKernel<I> = K, I: GpInput = f64> {
+    gp: GaussianProcess<K, I>,
+    noise: GaussianProcess<L, I>,
+    /// The standard deviation of the constant noise the fit started from.
+    initial_noise_sigma: f64,
+}
+
+impl<K, L, I> HeteroscedasticGaussianProcess<K, L, I>
+where
+    K: GpKernel<I> + Clone,
+    L: GpKernel<I> + Clone,
+    I: GpInput,
+{
+    /// Fit to the training data, with `kernel` for the function, `noise_kernel` for the log
+    /// noise variance and `noise_sigma` as the standard deviation of the constant noise to start
+    /// from. Both GPs add `jitter` to their diagonal (see [`super::GaussianProcessBuilder::jitter`]).
+    pub fn new(
+        x: &[I],
+        y: &[f64],
+        kernel: K,
+        noise_kernel: L,
+        noise_sigma: f64,
+        jitter: f64,
+    ) -> Result<Self, GpError> {
+        let mut gp = GaussianProcess::builder()
+            .kernel(kernel.clone())
+            .noise(noise_sigma)
+            .jitter(jitter)
+            .build_from_slices(x, y)?;
+        let mut noise = GaussianProcess::builder()
+            .kernel(noise_kernel.clone())
+            .noise(LOG_SQUARED_NORMAL_VARIANCE.sqrt())
+            .jitter(jitter)
+            .build_from_slices(&[], &[])?;
+        for _ in 0..ITERATIONS {
+            let (mean, variance) = gp.predict_slice(x);
+            let log_noise: Vec<f64> = y
+                .iter()
+                .zip(mean.iter().zip(&variance))
+                .map(|(y, (mean, variance))| {
+                    ((y - mean).powi(2) + variance).ln() - LOG_SQUARED_NORMAL_MEAN
+                })
+                .collect();
+            noise = GaussianProcess::builder()
+                .kernel(noise_kernel.clone())
+                .noise(LOG_SQUARED_NORMAL_VARIANCE.sqrt())
+                .jitter(jitter)
+                .normalize(true)
+                .build_from_slices(x, &log_noise)?;
+
+            // the noise variance of each point is one over its weight
+            let (log_noise, _) = noise.predict_slice(x);
+            gp = GaussianProcess::builder()
+                .kernel(kernel.clone())
+                .noise(1.0)
+                .jitter(jitter)
+                .weights(na::DVector::from_iterator(
+                    x.len(),
+                    log_noise.iter().map(|log_noise| (-log_noise).exp()),
+                ))
+                .build_from_slices(x, y)?;
+        }
+        Ok(Self {
+            gp,
+            noise,
+            initial_noise_sigma: noise_sigma,
+        })
+    }
+
+    /// The GP of the function, with the noise of each training point fixed to its estimate.
+    pub fn gp(&self) -> &GaussianProcess<K, I> {
+        &self.gp
+    }
+
+    /// The predicted variance of the observation noise at the inputs.
+    pub fn noise_variance(&self, x: &[I]) -> Vec<f64> {
+        let (log_noise, _) = self.noise.predict_slice(x);
+        log_noise.into_iter().map(f64::exp).collect()
+    }
+}
+
+impl<K, L, I> RegressionModel<I> for HeteroscedasticGaussianProcess<K, L, I>
+where
+    K: GpKernel<I> + Clone,
+    L: GpKernel<I> + Clone,
+    I: GpInput,
+{
+    fn fit(&mut self, x: &[I], y: &[f64]) -> Result<(), GpError> {
+        *self = Self::new(
+            x,
+            y,
+            self.gp.kernel.clone(),
+            self.noise.kernel.clone(),
+            self.initial_noise_sigma,
+            self.gp.jitter(),
+        )?;
+        Ok(())
+    }
+
+    fn predict_mean_std(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        self.gp.predict_mean_std(x)
+    }
+
+    /// The evidence of the function GP, given the estimated noise.
+    fn log_evidence(&self) -> f64 {
+        self.gp.log_marginal_likelihood()
+    }
+
+    fn noise_variance(&self, x: &[I]) -> Vec<f64> {
+        HeteroscedasticGaussianProcess::noise_variance(self, x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{RbfKernel, EPS};
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_heteroscedastic_noise() {
+        // a sine wave whose noise grows from almost nothing to a standard deviation of 1
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+        let x: Vec<f64> = (0..100).map(|i| i as f64 / 10.0).collect();
+        let y: Vec<f64> = x
+            .iter()
+            .map(|x| x.sin() + x / 10.0 * rng.sample::<f64, _>(rand_distr::StandardNormal))
+            .collect();
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let noise_kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 3.0,
+        };
+        let mut gp =
+            HeteroscedasticGaussianProcess::new(&x, &y, kernel, noise_kernel, 0.1, EPS).unwrap();
+
+        let noise = gp.noise_variance(&[1.0, 5.0, 9.0]);
+        assert!(noise[0] < noise[1] && noise[1] < noise[2], "{noise:?}");
+        assert!(
+            noise[2] > 5.0 * noise[0] && (0.3..2.0).contains(&noise[2]),
+            "{noise:?}"
+        );
+
+        // the mean still follows the sine wave
+        let (mean, _) = gp.predict_mean_std(&[1.5, 4.5]);
+        assert!((mean[0] - 1.5f64.sin()).abs() < 0.2);
+        assert!((mean[1] - 4.5f64.sin()).abs() < 0.3);
+
+        // refitting to the same data gives the same model
+        let before = gp.log_evidence();
+        gp.fit(&x, &y).unwrap();
+        assert!((gp.log_evidence() - before).abs() < 1e-9);
+    }
+}
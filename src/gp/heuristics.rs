@@ -0,0 +1,183 @@
+use super::Kernel;
+
+/// At most this many points are used, evenly spread over the data, as the heuristics take
+/// quadratic time.
+const MAX_POINTS: usize = 1000;
+
+/// Starting values of the hyperparameters guessed from the data, see [`init_heuristics`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InitialHyperparameters {
+    pub length_scale: f64,
+    /// The signal variance.
+    pub sigma: f64,
+    /// The standard deviation of the noise.
+    pub noise_sigma: f64,
+}
+
+/// Guess reasonable starting values of the hyperparameters from the data, for the optimizer or
+/// for a first fit:
+///
+/// - the length scale is the median distance between the inputs,
+/// - the signal variance is the variance of the targets,
+/// - the noise variance is the mean squared difference between each target and the straight
+///   line through its neighbors on either side (Gasser et al., 1986, Residual variance and
+///   residual pattern in nonlinear regression), which removes the smooth part of the function
+///   and leaves the high frequency noise. It is at most half the signal variance.
+///
+/// ```
+/// use gaussian_processes::gp::init_heuristics;
+///
+/// let x: Vec<f64> = (0..100).map(|i| i as f64 * 0.1).collect();
+/// let y: Vec<f64> = x.iter().map(|x| 2.0 * x.sin()).collect();
+/// let initial = init_heuristics(&x, &y);
+/// assert!((initial.length_scale - 2.9).abs() < 0.2);
+/// assert!(initial.noise_sigma < 0.1);
+/// ```
+pub fn init_heuristics(x: &[f64], y: &[f64]) -> InitialHyperparameters {
+    let n = x.len().min(y.len());
+    let mut points: Vec<(f64, f64)> = x.iter().copied().zip(y.iter().copied()).collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let stride = n.div_ceil(MAX_POINTS).max(1);
+    let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().step_by(stride).unzip();
+    let n = x.len();
+    if n < 2 {
+        return InitialHyperparameters {
+            length_scale: 1.0,
+            sigma: 1.0,
+            noise_sigma: 0.3,
+        };
+    }
+
+    let mut distances: Vec<f64> = (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .map(|(i, j)| (x[j] - x[i]).abs())
+        .collect();
+    let middle = distances.len() / 2;
+    let (_, median, _) = distances.select_nth_unstable_by(middle, f64::total_cmp);
+    let length_scale = if *median > 0.0 { *median } else { 1.0 };
+
+    let mean = y.iter().sum::<f64>() / n as f64;
+    let variance = y.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / n as f64;
+    let sigma = if variance > 0.0 { variance } else { 1.0 };
+
+    // the residuals of the lines through the neighbors, scaled to the variance of the noise
+    let residuals: Vec<f64> = (1..n - 1)
+        .filter(|i| x[i + 1] > x[i - 1])
+        .map(|i| {
+            let a = (x[i + 1] - x[i]) / (x[i + 1] - x[i - 1]);
+            let b = 1.0 - a;
+            let residual = a * y[i - 1] + b * y[i + 1] - y[i];
+            residual * residual / (a * a + b * b + 1.0)
+        })
+        .collect();
+    let noise_variance = if residuals.is_empty() {
+        0.1 * sigma
+    } else {
+        residuals.iter().sum::<f64>() / residuals.len() as f64
+    };
+    let noise_sigma = noise_variance.min(0.5 * sigma).sqrt();
+
+    InitialHyperparameters {
+        length_scale,
+        sigma,
+        noise_sigma,
+    }
+}
+
+impl Kernel {
+    /// Set the hyperparameters to the guessed starting values: every term gets the length
+    /// scale, and the signal variance is split evenly between the terms of sums. The periods and
+    /// the length scales of periodic terms, which are relative to the period, are kept.
+    pub fn init_from(&mut self, initial: &InitialHyperparameters) {
+        self.init_with_sigma(initial.length_scale, initial.sigma);
+    }
+
+    fn init_with_sigma(&mut self, length_scale: f64, sigma: f64) {
+        match self {
+            Kernel::Rbf(k) => (k.length_scale, k.sigma) = (length_scale, sigma),
+            Kernel::Matern(k) => (k.length_scale, k.sigma) = (length_scale, sigma),
+            Kernel::Periodic(k) => k.sigma = sigma,
+            Kernel::Sum(kernels) => {
+                let share = sigma / kernels.len() as f64;
+                for kernel in kernels {
+                    kernel.init_with_sigma(length_scale, share);
+                }
+            }
+            // the product of the terms has the signal variance of the first
+            Kernel::Product(kernels) => {
+                for (i, kernel) in kernels.iter_mut().enumerate() {
+                    kernel.init_with_sigma(length_scale, if i == 0 { sigma } else { 1.0 });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{PeriodicKernel, RbfKernel};
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_init_heuristics() {
+        // evenly spaced, with noise of variance 0.01
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(4);
+        let x: Vec<f64> = (0..=100).map(|i| i as f64 * 0.1).collect();
+        let y: Vec<f64> = x
+            .iter()
+            .map(|x| 3.0 + (0.5 * x).sin() + 0.1 * rng.sample::<f64, _>(rand_distr::StandardNormal))
+            .collect();
+        let initial = init_heuristics(&x, &y);
+        assert!((initial.length_scale - 3.0).abs() < 0.1, "{initial:?}");
+        let mean = y.iter().sum::<f64>() / y.len() as f64;
+        let variance = y.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / y.len() as f64;
+        assert_eq!(initial.sigma, variance);
+        assert!((initial.noise_sigma - 0.1).abs() < 0.015, "{initial:?}");
+
+        // the order of the points does not matter
+        let (x, y): (Vec<f64>, Vec<f64>) = x.iter().zip(&y).rev().unzip();
+        assert_eq!(init_heuristics(&x, &y), initial);
+
+        let fallback = init_heuristics(&[1.0], &[2.0]);
+        assert_eq!(fallback.length_scale, 1.0);
+    }
+
+    #[test]
+    fn test_kernel_init_from() {
+        let initial = InitialHyperparameters {
+            length_scale: 2.0,
+            sigma: 4.0,
+            noise_sigma: 0.1,
+        };
+        let rbf = Kernel::Rbf(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        });
+        let periodic = Kernel::Periodic(PeriodicKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+            period: 3.0,
+        });
+        let mut kernel = Kernel::Sum(vec![rbf.clone(), periodic]);
+        kernel.init_from(&initial);
+        let Kernel::Sum(terms) = &kernel else {
+            unreachable!()
+        };
+        assert_eq!(
+            terms[0],
+            Kernel::Rbf(RbfKernel {
+                sigma: 2.0,
+                length_scale: 2.0
+            })
+        );
+        assert_eq!(
+            terms[1],
+            Kernel::Periodic(PeriodicKernel {
+                sigma: 2.0,
+                length_scale: 1.0,
+                period: 3.0
+            })
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::{GpInput, RegressionModel};
+
+/// How well a model predicts observations it was not trained on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HoldoutMetrics {
+    /// The root mean squared error of the predictive means.
+    pub rmse: f64,
+    /// The mean negative log predictive density of the observations, lower is better. Unlike the
+    /// error it also punishes uncertainties that are too small or too large.
+    pub nlpd: f64,
+    /// The fraction of the observations within two standard deviations of the mean, about 0.95
+    /// for honest uncertainties.
+    pub coverage: f64,
+}
+
+/// Choose `fraction` of `n` points at random to hold out as a test set, returning for each point
+/// whether it is held out. At least one point is kept for training.
+pub fn holdout_split(n: usize, fraction: f64, seed: u64) -> Vec<bool> {
+    let count = ((fraction.clamp(0.0, 1.0) * n as f64).round() as usize).min(n.saturating_sub(1));
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(seed);
+    let mut indices: Vec<usize> = (0..n).collect();
+    indices.shuffle(&mut rng);
+    let mut held_out = vec![false; n];
+    for i in &indices[..count] {
+        held_out[*i] = true;
+    }
+    held_out
+}
+
+/// The metrics of the predictions of the model at the held-out points, with the noise included
+/// in the predictive distributions. `None` without test points.
+pub fn holdout_metrics<I: GpInput>(
+    model: &(impl RegressionModel<I> + ?Sized),
+    x: &[I],
+    y: &[f64],
+) -> Option<HoldoutMetrics> {
+    if x.is_empty() || x.len() != y.len() {
+        return None;
+    }
+    let (mean, std) = model.predict_mean_std(x);
+    let noise = model.noise_variance(x);
+    let (mut squared_error, mut nlpd, mut covered) = (0.0, 0.0, 0);
+    for ((y, mean), (std, noise)) in y.iter().zip(&mean).zip(std.iter().zip(&noise)) {
+        let variance = (std * std + noise).max(f64::MIN_POSITIVE);
+        let error = y - mean;
+        squared_error += error * error;
+        nlpd += 0.5 * (2.0 * std::f64::consts::PI * variance).ln() + 0.5 * error * error / variance;
+        if error.abs() <= 2.0 * variance.sqrt() {
+            covered += 1;
+        }
+    }
+    let n = x.len() as f64;
+    Some(HoldoutMetrics {
+        rmse: (squared_error / n).sqrt(),
+        nlpd: nlpd / n,
+        coverage: covered as f64 / n,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{GaussianProcess, RbfKernel};
+
+    #[test]
+    fn test_holdout_split() {
+        let held_out = holdout_split(10, 0.3, 0);
+        assert_eq!(held_out.iter().filter(|h| **h).count(), 3);
+        assert_eq!(holdout_split(10, 0.3, 0), held_out);
+        assert_ne!(holdout_split(10, 0.3, 1), held_out);
+        // one point is always kept for training
+        assert_eq!(holdout_split(4, 1.0, 0).iter().filter(|h| **h).count(), 3);
+        assert!(holdout_split(0, 0.5, 0).is_empty());
+    }
+
+    #[test]
+    fn test_holdout_metrics() {
+        let x: Vec<f64> = (0..20).map(|i| i as f64 * 0.5).collect();
+        let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::from_slices(&x, &y, kernel, 0.01).unwrap();
+
+        // points between the training points are predicted well
+        let test_x: Vec<f64> = x.iter().map(|x| x + 0.25).collect();
+        let test_y: Vec<f64> = test_x.iter().map(|x| x.sin()).collect();
+        let good = holdout_metrics(&gp, &test_x, &test_y).unwrap();
+        assert!(good.rmse < 0.05, "{good:?}");
+        assert_eq!(good.coverage, 1.0);
+
+        let shifted: Vec<f64> = test_y.iter().map(|y| y + 1.0).collect();
+        let bad = holdout_metrics(&gp, &test_x, &shifted).unwrap();
+        assert!((bad.rmse - 1.0).abs() < 0.05, "{bad:?}");
+        assert!(bad.nlpd > good.nlpd);
+        assert_eq!(bad.coverage, 0.0);
+
+        assert_eq!(holdout_metrics(&gp, &[], &[]), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Properties every fit should have, checked on random datasets and hyperparameters.
+
+use nalgebra as na;
+use proptest::prelude::*;
+
+use super::{
+    GaussianProcess, GpKernel, Kernel, MaternKernel, MaternSmoothness, PeriodicKernel, RbfKernel,
+};
+
+fn stationary_kernel(length_scale: std::ops::Range<f64>) -> impl Strategy<Value = Kernel> {
+    let smoothness = prop_oneof![
+        Just(MaternSmoothness::Half),
+        Just(MaternSmoothness::ThreeHalves),
+        Just(MaternSmoothness::FiveHalves),
+    ];
+    prop_oneof![
+        (0.1..5.0, length_scale.clone()).prop_map(|(sigma, length_scale)| Kernel::Rbf(RbfKernel {
+            sigma,
+            length_scale
+        })),
+        (smoothness, 0.1..5.0, length_scale).prop_map(|(smoothness, sigma, length_scale)| {
+            Kernel::Matern(MaternKernel {
+                smoothness,
+                sigma,
+                length_scale,
+            })
+        }),
+    ]
+}
+
+fn kernel() -> impl Strategy<Value = Kernel> {
+    let periodic = (0.1..5.0, 0.2..3.0, 0.5..5.0).prop_map(|(sigma, length_scale, period)| {
+        Kernel::Periodic(PeriodicKernel {
+            sigma,
+            length_scale,
+            period,
+        })
+    });
+    let base = prop_oneof![stationary_kernel(0.2..1.5), periodic];
+    prop_oneof![
+        base.clone(),
+        prop::collection::vec(base.clone(), 2..4).prop_map(Kernel::Sum),
+        prop::collection::vec(base, 2..4).prop_map(Kernel::Product),
+    ]
+}
+
+/// Training data on a jittered grid, so no two inputs are closer than half a unit and the
+/// covariance matrices stay well conditioned.
+fn dataset() -> impl Strategy<Value = (Vec<f64>, Vec<f64>)> {
+    prop::collection::vec((0.0..0.5, -5.0..5.0), 1..12).prop_map(|points| {
+        points
+            .into_iter()
+            .enumerate()
+            .map(|(i, (jitter, y))| (i as f64 + jitter, y))
+            .unzip()
+    })
+}
+
+proptest! {
+    #[test]
+    fn variance_is_non_negative(
+        kernel in kernel(),
+        (x, y) in dataset(),
+        noise in 0.0..1.0,
+        test in prop::collection::vec(-5.0..20.0, 1..20),
+    ) {
+        let gp = GaussianProcess::from_slices(&x, &y, kernel, noise).unwrap();
+        let (mean, variance) = gp.predict_slice(&test);
+        for (mean, variance) in mean.iter().zip(&variance) {
+            prop_assert!(mean.is_finite());
+            prop_assert!(*variance >= 0.0, "variance {variance}");
+        }
+    }
+
+    /// Only short length scales, as with long ones the jitter added to the diagonal of the
+    /// nearly singular covariance matrix visibly smooths the mean.
+    #[test]
+    fn noise_free_mean_interpolates(kernel in stationary_kernel(0.1..0.4), (x, y) in dataset()) {
+        let gp = GaussianProcess::from_slices(&x, &y, kernel, 1e-8).unwrap();
+        let (mean, _) = gp.predict_slice(&x);
+        for (mean, y) in mean.iter().zip(&y) {
+            prop_assert!((mean - y).abs() < 1e-3 * (1.0 + y.abs()), "{mean} != {y}");
+        }
+    }
+
+    #[test]
+    fn training_points_reduce_variance(
+        kernel in kernel(),
+        (x, y) in dataset(),
+        noise in 0.0..1.0,
+    ) {
+        let gp = GaussianProcess::from_slices(&x, &y, kernel.clone(), noise).unwrap();
+        let (_, variance) = gp.predict_slice(&x);
+        for (x, variance) in x.iter().zip(&variance) {
+            let prior = kernel.compute(*x, *x);
+            prop_assert!(*variance <= prior + 1e-9, "{variance} > {prior}");
+        }
+    }
+
+    #[test]
+    fn kernel_matrix_is_positive_semi_definite(
+        kernel in kernel(),
+        x in prop::collection::vec(-10.0..10.0, 1..15),
+    ) {
+        let x = na::DVector::from_vec(x);
+        let matrix = kernel.compute_matrix(&x, &x);
+        prop_assert!((&matrix - matrix.transpose()).abs().max() < 1e-12);
+        let scale = matrix.abs().max().max(1.0);
+        let smallest = matrix.symmetric_eigenvalues().min();
+        prop_assert!(smallest > -1e-9 * scale, "eigenvalue {smallest}");
+    }
+}
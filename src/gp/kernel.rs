@@ -0,0 +1,644 @@
+use nalgebra as na;
+
+/// Input points that kernels can be evaluated on, i.e. points with a distance between them.
+pub trait GpInput: na::Scalar + Copy {
+    fn distance(&self, other: &Self) -> f64;
+}
+
+impl GpInput for f64 {
+    fn distance(&self, other: &Self) -> f64 {
+        (self - other).abs()
+    }
+}
+
+/// Points in `N` dimensions, using the euclidean distance.
+impl<const N: usize> GpInput for [f64; N] {
+    fn distance(&self, other: &Self) -> f64 {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+pub trait GpKernel<I: GpInput = f64> {
+    fn compute(&self, x: I, x2: I) -> f64;
+
+    fn compute_matrix(&self, x: &na::DVector<I>, x2: &na::DVector<I>) -> na::DMatrix<f64> {
+        let mut matrix = na::DMatrix::zeros(x.len(), x2.len());
+        for i in 0..x.len() {
+            for j in 0..x2.len() {
+                matrix[(i, j)] = self.compute(x[i], x2[j]);
+            }
+        }
+        matrix //.transpose() // TOD, correct?
+    }
+}
+
+/// Kernels behind a pointer, e.g. a `Box<dyn GpKernel + Send + Sync>` chosen at runtime or a
+/// kernel shared between models with an `Arc`.
+impl<I: GpInput, K: GpKernel<I> + ?Sized> GpKernel<I> for Box<K> {
+    fn compute(&self, x: I, x2: I) -> f64 {
+        (**self).compute(x, x2)
+    }
+
+    fn compute_matrix(&self, x: &na::DVector<I>, x2: &na::DVector<I>) -> na::DMatrix<f64> {
+        (**self).compute_matrix(x, x2)
+    }
+}
+
+impl<I: GpInput, K: GpKernel<I> + ?Sized> GpKernel<I> for std::sync::Arc<K> {
+    fn compute(&self, x: I, x2: I) -> f64 {
+        (**self).compute(x, x2)
+    }
+
+    fn compute_matrix(&self, x: &na::DVector<I>, x2: &na::DVector<I>) -> na::DMatrix<f64> {
+        (**self).compute_matrix(x, x2)
+    }
+}
+
+/// Introspection of the hyperparameters of a kernel, so that controls and optimizers can work
+/// with any kernel without knowing its type.
+pub trait KernelParams {
+    /// Human readable names of the hyperparameters, in the order of [`KernelParams::params_mut`].
+    fn param_names(&self) -> Vec<&'static str>;
+
+    /// The range each hyperparameter is sensibly chosen from.
+    fn param_bounds(&self) -> Vec<(f64, f64)>;
+
+    /// Mutable references to the hyperparameters.
+    fn params_mut(&mut self) -> Vec<&mut f64>;
+}
+
+const LENGTH_SCALE_BOUNDS: (f64, f64) = (0.0, 10.0);
+const SIGMA_BOUNDS: (f64, f64) = (0.0, 10.0);
+const PERIOD_BOUNDS: (f64, f64) = (0.1, 10.0);
+
+/// Radial basis function kernel
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RbfKernel {
+    pub sigma: f64,
+    pub length_scale: f64,
+}
+
+impl<I: GpInput> GpKernel<I> for RbfKernel {
+    fn compute(&self, x: I, x2: I) -> f64 {
+        self.sigma * (-0.5 * x.distance(&x2).powi(2) / self.length_scale.powi(2)).exp()
+    }
+}
+
+impl KernelParams for RbfKernel {
+    fn param_names(&self) -> Vec<&'static str> {
+        vec!["length scale", "sigma"]
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        vec![LENGTH_SCALE_BOUNDS, SIGMA_BOUNDS]
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        vec![&mut self.length_scale, &mut self.sigma]
+    }
+}
+
+/// Names of the length scales of an [`ArdKernel`], one per input dimension.
+const ARD_LENGTH_SCALE_NAMES: [&str; 8] = [
+    "length scale 1",
+    "length scale 2",
+    "length scale 3",
+    "length scale 4",
+    "length scale 5",
+    "length scale 6",
+    "length scale 7",
+    "length scale 8",
+];
+
+/// Radial basis function kernel with automatic relevance determination (ARD): a separate
+/// length scale for each of the `N` input dimensions.
+///
+/// After fitting the length scales, [`ArdKernel::relevance`] tells which inputs the function
+/// depends on; a dimension with a very long length scale is effectively ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArdKernel<const N: usize> {
+    pub sigma: f64,
+    pub length_scales: [f64; N],
+}
+
+impl<const N: usize> ArdKernel<N> {
+    /// The same length scale in all dimensions, a starting point for fitting them.
+    pub fn isotropic(sigma: f64, length_scale: f64) -> Self {
+        Self {
+            sigma,
+            length_scales: [length_scale; N],
+        }
+    }
+
+    /// How much the function depends on each input dimension: the inverse length scales,
+    /// normalized to sum to one.
+    pub fn relevance(&self) -> [f64; N] {
+        let total: f64 = self.length_scales.iter().map(|l| 1.0 / l).sum();
+        self.length_scales.map(|l| 1.0 / l / total)
+    }
+}
+
+impl<const N: usize> GpKernel<[f64; N]> for ArdKernel<N> {
+    fn compute(&self, x: [f64; N], x2: [f64; N]) -> f64 {
+        let distance2: f64 = (0..N)
+            .map(|d| ((x[d] - x2[d]) / self.length_scales[d]).powi(2))
+            .sum();
+        self.sigma * (-0.5 * distance2).exp()
+    }
+}
+
+impl<const N: usize> KernelParams for ArdKernel<N> {
+    fn param_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = (0..N)
+            .map(|d| {
+                ARD_LENGTH_SCALE_NAMES
+                    .get(d)
+                    .copied()
+                    .unwrap_or("length scale")
+            })
+            .collect();
+        names.push("sigma");
+        names
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        let mut bounds = vec![LENGTH_SCALE_BOUNDS; N];
+        bounds.push(SIGMA_BOUNDS);
+        bounds
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        let mut params: Vec<&mut f64> = self.length_scales.iter_mut().collect();
+        params.push(&mut self.sigma);
+        params
+    }
+}
+
+/// RBF kernel on the plane whose correlation falls off at different rates along two
+/// perpendicular axes, rotated by `angle` (in radians, counterclockwise from the first input
+/// axis to the axis of the first length scale).
+///
+/// Lines of equal correlation are ellipses instead of circles, for structures that are
+/// elongated in a direction other than along the inputs, e.g. along a coastline or a ridge. With
+/// a zero angle this is an [`ArdKernel`].
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AnisotropicRbfKernel {
+    pub sigma: f64,
+    pub length_scales: [f64; 2],
+    pub angle: f64,
+}
+
+impl GpKernel<[f64; 2]> for AnisotropicRbfKernel {
+    fn compute(&self, x: [f64; 2], x2: [f64; 2]) -> f64 {
+        let (sin, cos) = self.angle.sin_cos();
+        let (dx, dy) = (x[0] - x2[0], x[1] - x2[1]);
+        // the difference in the rotated axes
+        let along = cos * dx + sin * dy;
+        let across = -sin * dx + cos * dy;
+        let distance2 =
+            (along / self.length_scales[0]).powi(2) + (across / self.length_scales[1]).powi(2);
+        self.sigma * (-0.5 * distance2).exp()
+    }
+}
+
+impl KernelParams for AnisotropicRbfKernel {
+    fn param_names(&self) -> Vec<&'static str> {
+        vec!["length scale 1", "length scale 2", "angle", "sigma"]
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        vec![
+            LENGTH_SCALE_BOUNDS,
+            LENGTH_SCALE_BOUNDS,
+            (0.0, std::f64::consts::PI),
+            SIGMA_BOUNDS,
+        ]
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        let [along, across] = &mut self.length_scales;
+        vec![along, across, &mut self.angle, &mut self.sigma]
+    }
+}
+
+/// The smoothness parameter `nu` of a Matérn kernel. Samples are `ceil(nu) - 1` times
+/// differentiable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum MaternSmoothness {
+    /// `nu = 1/2`, equivalent to the exponential (Ornstein-Uhlenbeck) kernel
+    Half,
+    /// `nu = 3/2`
+    ThreeHalves,
+    /// `nu = 5/2`
+    FiveHalves,
+}
+
+/// Matérn kernel, a rougher alternative to the RBF kernel (which is the limit `nu -> inf`)
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct MaternKernel {
+    pub smoothness: MaternSmoothness,
+    pub sigma: f64,
+    pub length_scale: f64,
+}
+
+impl<I: GpInput> GpKernel<I> for MaternKernel {
+    fn compute(&self, x: I, x2: I) -> f64 {
+        let r = x.distance(&x2) / self.length_scale;
+        let shape = match self.smoothness {
+            MaternSmoothness::Half => (-r).exp(),
+            MaternSmoothness::ThreeHalves => {
+                let r = 3f64.sqrt() * r;
+                (1.0 + r) * (-r).exp()
+            }
+            MaternSmoothness::FiveHalves => {
+                let r = 5f64.sqrt() * r;
+                (1.0 + r + r * r / 3.0) * (-r).exp()
+            }
+        };
+        self.sigma * shape
+    }
+}
+
+impl KernelParams for MaternKernel {
+    fn param_names(&self) -> Vec<&'static str> {
+        vec!["length scale", "sigma"]
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        vec![LENGTH_SCALE_BOUNDS, SIGMA_BOUNDS]
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        vec![&mut self.length_scale, &mut self.sigma]
+    }
+}
+
+/// Periodic (exp-sine-squared) kernel for functions repeating with the given period
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PeriodicKernel {
+    pub sigma: f64,
+    pub length_scale: f64,
+    pub period: f64,
+}
+
+impl<I: GpInput> GpKernel<I> for PeriodicKernel {
+    fn compute(&self, x: I, x2: I) -> f64 {
+        let sin = (std::f64::consts::PI * x.distance(&x2) / self.period).sin();
+        self.sigma * (-2.0 * sin * sin / self.length_scale.powi(2)).exp()
+    }
+}
+
+impl KernelParams for PeriodicKernel {
+    fn param_names(&self) -> Vec<&'static str> {
+        vec!["length scale", "sigma", "period"]
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        vec![LENGTH_SCALE_BOUNDS, SIGMA_BOUNDS, PERIOD_BOUNDS]
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        vec![&mut self.length_scale, &mut self.sigma, &mut self.period]
+    }
+}
+
+/// A kernel on a circular domain such as angles or times of day, where `x` and `x + period`
+/// are the same point. The inputs are placed on a circle of circumference `period` and the
+/// wrapped kernel is evaluated on the plane, so distances across the boundary are short and
+/// the covariance stays positive definite for any kernel of the distance.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CircularKernel<K> {
+    pub kernel: K,
+    pub period: f64,
+}
+
+impl<K> CircularKernel<K> {
+    /// The point on the circle, with the same distance as `x` along the circle to zero.
+    fn embed(&self, x: f64) -> [f64; 2] {
+        let radius = self.period / std::f64::consts::TAU;
+        let angle = x / radius;
+        [radius * angle.cos(), radius * angle.sin()]
+    }
+}
+
+impl<K: GpKernel<[f64; 2]>> GpKernel<f64> for CircularKernel<K> {
+    fn compute(&self, x: f64, x2: f64) -> f64 {
+        self.kernel.compute(self.embed(x), self.embed(x2))
+    }
+}
+
+/// The period is a property of the domain, not a hyperparameter.
+impl<K: KernelParams> KernelParams for CircularKernel<K> {
+    fn param_names(&self) -> Vec<&'static str> {
+        self.kernel.param_names()
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        self.kernel.param_bounds()
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        self.kernel.params_mut()
+    }
+}
+
+/// Any of the available kernels, for choosing the kernel at runtime.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Kernel {
+    Rbf(RbfKernel),
+    Matern(MaternKernel),
+    Periodic(PeriodicKernel),
+    /// The sum of the kernels, modelling a function as the sum of independent functions.
+    Sum(Vec<Kernel>),
+    /// The product of the kernels, e.g. a periodic pattern whose shape changes over time.
+    Product(Vec<Kernel>),
+}
+
+impl Kernel {
+    /// Human readable name of the kernel type.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kernel::Rbf(_) => "RBF",
+            Kernel::Matern(k) => match k.smoothness {
+                MaternSmoothness::Half => "Matérn 1/2",
+                MaternSmoothness::ThreeHalves => "Matérn 3/2",
+                MaternSmoothness::FiveHalves => "Matérn 5/2",
+            },
+            Kernel::Periodic(_) => "Periodic",
+            Kernel::Sum(_) => "Sum",
+            Kernel::Product(_) => "Product",
+        }
+    }
+
+    /// Human readable description of the kernel, spelling out the terms of composite kernels.
+    pub fn description(&self) -> String {
+        let join = |kernels: &[Kernel], operator: &str| {
+            kernels
+                .iter()
+                .map(|kernel| match kernel {
+                    Kernel::Sum(_) | Kernel::Product(_) => format!("({})", kernel.description()),
+                    _ => kernel.description(),
+                })
+                .collect::<Vec<_>>()
+                .join(operator)
+        };
+        match self {
+            Kernel::Sum(kernels) => join(kernels, " + "),
+            Kernel::Product(kernels) => join(kernels, " × "),
+            _ => self.name().to_owned(),
+        }
+    }
+}
+
+impl<I: GpInput> GpKernel<I> for Kernel {
+    fn compute(&self, x: I, x2: I) -> f64 {
+        match self {
+            Kernel::Rbf(k) => k.compute(x, x2),
+            Kernel::Matern(k) => k.compute(x, x2),
+            Kernel::Periodic(k) => k.compute(x, x2),
+            Kernel::Sum(kernels) => kernels.iter().map(|k| k.compute(x, x2)).sum(),
+            Kernel::Product(kernels) => kernels.iter().map(|k| k.compute(x, x2)).product(),
+        }
+    }
+}
+
+/// Composite kernels list the hyperparameters of all their terms in order.
+impl KernelParams for Kernel {
+    fn param_names(&self) -> Vec<&'static str> {
+        match self {
+            Kernel::Rbf(k) => k.param_names(),
+            Kernel::Matern(k) => k.param_names(),
+            Kernel::Periodic(k) => k.param_names(),
+            Kernel::Sum(kernels) | Kernel::Product(kernels) => {
+                kernels.iter().flat_map(|k| k.param_names()).collect()
+            }
+        }
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        match self {
+            Kernel::Rbf(k) => k.param_bounds(),
+            Kernel::Matern(k) => k.param_bounds(),
+            Kernel::Periodic(k) => k.param_bounds(),
+            Kernel::Sum(kernels) | Kernel::Product(kernels) => {
+                kernels.iter().flat_map(|k| k.param_bounds()).collect()
+            }
+        }
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        match self {
+            Kernel::Rbf(k) => k.params_mut(),
+            Kernel::Matern(k) => k.params_mut(),
+            Kernel::Periodic(k) => k.params_mut(),
+            Kernel::Sum(kernels) | Kernel::Product(kernels) => {
+                kernels.iter_mut().flat_map(|k| k.params_mut()).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use na::DVector;
+
+    #[test]
+    fn test_rbf_kernel_compute() {
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let result = kernel.compute(1.0, 2.0);
+        assert!((result - 0.60653066).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rbf_kernel_compute_matrix() {
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let x = DVector::from_vec(vec![1.0, 2.0]);
+        let x2 = DVector::from_vec(vec![1.0, 2.0]);
+        let result = kernel.compute_matrix(&x, &x2);
+        let expected = na::DMatrix::from_vec(2, 2, vec![1.0, 0.60653066, 0.60653066, 1.0]);
+        assert!((result - expected).abs().max() < 1e-6);
+    }
+
+    #[test]
+    fn test_rbf_kernel_compute_2d() {
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        // same distance as between 1.0 and 2.0 in one dimension
+        let result = kernel.compute([0.0, 0.0], [0.6, 0.8]);
+        assert!((result - 0.60653066).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_matern_kernel_compute() {
+        let matern = |smoothness| MaternKernel {
+            smoothness,
+            sigma: 2.0,
+            length_scale: 1.0,
+        };
+        for smoothness in [
+            MaternSmoothness::Half,
+            MaternSmoothness::ThreeHalves,
+            MaternSmoothness::FiveHalves,
+        ] {
+            assert!((matern(smoothness).compute(1.0, 1.0) - 2.0).abs() < 1e-12);
+        }
+
+        assert!((matern(MaternSmoothness::Half).compute(1.0, 2.0) - 0.73575888).abs() < 1e-6);
+        assert!(
+            (matern(MaternSmoothness::ThreeHalves).compute(1.0, 2.0) - 0.96671545).abs() < 1e-6
+        );
+        assert!((matern(MaternSmoothness::FiveHalves).compute(1.0, 2.0) - 1.04798822).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_composite_kernel_compute() {
+        let rbf = RbfKernel {
+            sigma: 2.0,
+            length_scale: 1.0,
+        };
+        let periodic = PeriodicKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+            period: 3.0,
+        };
+        let (a, b) = (rbf.compute(1.0, 2.0), periodic.compute(1.0, 2.0));
+
+        let sum = Kernel::Sum(vec![
+            Kernel::Rbf(rbf.clone()),
+            Kernel::Periodic(periodic.clone()),
+        ]);
+        assert!((sum.compute(1.0, 2.0) - (a + b)).abs() < 1e-12);
+
+        let product = Kernel::Product(vec![Kernel::Rbf(rbf), sum]);
+        assert!((product.compute(1.0, 2.0) - a * (a + b)).abs() < 1e-12);
+        assert_eq!(product.description(), "RBF × (RBF + Periodic)");
+    }
+
+    #[test]
+    fn test_periodic_kernel_compute() {
+        let kernel = PeriodicKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+            period: 2.0,
+        };
+        assert!((kernel.compute(0.0, 2.0) - 1.0).abs() < 1e-12);
+        assert!((kernel.compute(0.0, 1.0) - (-2f64).exp()).abs() < 1e-12);
+        assert!((kernel.compute(0.5, 1.0) - kernel.compute(2.5, 5.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_circular_kernel_compute() {
+        let kernel = CircularKernel {
+            kernel: RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            },
+            period: std::f64::consts::TAU,
+        };
+        // the same point after a full turn
+        assert!((kernel.compute(0.5, 0.5 + std::f64::consts::TAU) - 1.0).abs() < 1e-12);
+        // close across the boundary as well as within the domain
+        let across = kernel.compute(0.1, std::f64::consts::TAU - 0.1);
+        assert!((across - kernel.compute(1.0, 1.2)).abs() < 1e-12);
+        assert!((across - kernel.kernel.compute(0.0, 0.2)).abs() < 1e-3);
+        assert!(kernel.compute(0.0, std::f64::consts::PI) < across);
+    }
+
+    #[test]
+    fn test_anisotropic_rbf_kernel() {
+        let kernel = AnisotropicRbfKernel {
+            sigma: 2.0,
+            length_scales: [3.0, 1.0],
+            angle: std::f64::consts::FRAC_PI_4,
+        };
+        // three length scales along the diagonal, one across it
+        let diagonal = 3.0 / 2f64.sqrt();
+        let along = kernel.compute([1.0, 1.0], [1.0 + diagonal, 1.0 + diagonal]);
+        let across = kernel.compute([1.0, 1.0], [1.0 - 0.5f64.sqrt(), 1.0 + 0.5f64.sqrt()]);
+        assert!((along - 2.0 * (-0.5f64).exp()).abs() < 1e-12);
+        assert!((across - along).abs() < 1e-12);
+
+        // without rotation it is the ARD kernel
+        let ard = ArdKernel {
+            sigma: 2.0,
+            length_scales: [3.0, 1.0],
+        };
+        let unrotated = AnisotropicRbfKernel {
+            angle: 0.0,
+            ..kernel
+        };
+        assert_eq!(
+            unrotated.compute([0.5, 2.0], [1.5, -1.0]),
+            ard.compute([0.5, 2.0], [1.5, -1.0])
+        );
+    }
+
+    #[test]
+    fn test_ard_kernel_relevance() {
+        let kernel = ArdKernel {
+            sigma: 2.0,
+            length_scales: [1.0, 3.0],
+        };
+        assert!((kernel.compute([0.0, 0.0], [1.0, 3.0]) - 2.0 * (-1.0f64).exp()).abs() < 1e-12);
+        let relevance = kernel.relevance();
+        assert!((relevance[0] - 0.75).abs() < 1e-12 && (relevance[1] - 0.25).abs() < 1e-12);
+        assert_eq!(
+            kernel.param_names(),
+            ["length scale 1", "length scale 2", "sigma"]
+        );
+
+        // fitting the length scales finds the input the function depends on
+        let x: Vec<[f64; 2]> = (0..25)
+            .map(|i| [(i % 5) as f64 * 1.5, (i / 5) as f64 * 1.5])
+            .collect();
+        let y: Vec<f64> = x.iter().map(|x| x[1].sin()).collect();
+        let mut optimizer = crate::gp::HyperparameterOptimizer::new(
+            &DVector::from_vec(x),
+            &DVector::from_vec(y),
+            ArdKernel::<2>::isotropic(1.0, 1.0),
+            0.01,
+        );
+        optimizer.run(300, |_| true);
+        let (kernel, _) = optimizer.best();
+        assert!(kernel.relevance()[1] > 0.8, "{kernel:?}");
+    }
+
+    #[test]
+    fn test_kernel_params() {
+        let mut kernel = Kernel::Sum(vec![
+            Kernel::Rbf(RbfKernel {
+                sigma: 1.0,
+                length_scale: 2.0,
+            }),
+            Kernel::Periodic(PeriodicKernel {
+                sigma: 3.0,
+                length_scale: 4.0,
+                period: 5.0,
+            }),
+        ]);
+        assert_eq!(
+            kernel.param_names(),
+            vec!["length scale", "sigma", "length scale", "sigma", "period"]
+        );
+        assert_eq!(kernel.param_bounds().len(), 5);
+
+        for param in kernel.params_mut() {
+            *param *= 2.0;
+        }
+        let values: Vec<f64> = kernel.params_mut().into_iter().map(|p| *p).collect();
+        assert_eq!(values, vec![4.0, 2.0, 8.0, 6.0, 10.0]);
+    }
+}
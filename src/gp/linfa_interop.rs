@@ -0,0 +1,167 @@
+use std::marker::PhantomData;
+
+use linfa::dataset::AsSingleTargets;
+use linfa::traits::{Fit, PredictInplace};
+use linfa::DatasetBase;
+use ndarray::{Array1, ArrayBase, Data, Ix2};
+
+use super::{GaussianProcess, GpError, GpKernel, NdarrayInput};
+
+/// The hyperparameters of a Gaussian process, which linfa's [`Fit`] trait fits to a dataset
+/// with one row per input.
+///
+/// ```
+/// use gaussian_processes::gp::{GaussianProcessParams, RbfKernel};
+/// use linfa::prelude::*;
+/// use ndarray::array;
+///
+/// let dataset = Dataset::new(array![[1.0], [2.0], [6.0]], array![1.0, 1.0, -1.0]);
+/// let params: GaussianProcessParams<_> = GaussianProcessParams::new(RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// })
+/// .noise(0.01);
+/// let gp = params.fit(&dataset)?;
+/// // the inherent `predict` takes nalgebra vectors
+/// let prediction = Predict::predict(&gp, &array![[1.0], [6.0]]);
+/// assert!((prediction[1] + 1.0).abs() < 0.1);
+/// # Ok::<(), gaussian_processes::gp::LinfaError>(())
+/// ```
+pub struct GaussianProcessParams<K, I = f64> {
+    kernel: K,
+    noise_sigma: f64,
+    normalize: bool,
+    input: PhantomData<I>,
+}
+
+impl<K: GpKernel<I> + Clone, I: NdarrayInput> GaussianProcessParams<K, I> {
+    pub fn new(kernel: K) -> Self {
+        Self {
+            kernel,
+            noise_sigma: 0.0,
+            normalize: false,
+            input: PhantomData,
+        }
+    }
+
+    /// See [`super::GaussianProcessBuilder::noise`].
+    pub fn noise(mut self, noise_sigma: f64) -> Self {
+        self.noise_sigma = noise_sigma;
+        self
+    }
+
+    /// See [`super::GaussianProcessBuilder::normalize`].
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+}
+
+/// Errors from fitting with linfa, either from this crate or from linfa itself.
+#[derive(Debug)]
+pub enum LinfaError {
+    Gp(GpError),
+    Linfa(linfa::Error),
+}
+
+impl std::fmt::Display for LinfaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinfaError::Gp(e) => e.fmt(f),
+            LinfaError::Linfa(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LinfaError {}
+
+impl From<GpError> for LinfaError {
+    fn from(e: GpError) -> Self {
+        LinfaError::Gp(e)
+    }
+}
+
+impl From<linfa::Error> for LinfaError {
+    fn from(e: linfa::Error) -> Self {
+        LinfaError::Linfa(e)
+    }
+}
+
+impl<K, I, D, T> Fit<ArrayBase<D, Ix2>, T, LinfaError> for GaussianProcessParams<K, I>
+where
+    K: GpKernel<I> + Clone,
+    I: NdarrayInput,
+    D: Data<Elem = f64>,
+    T: AsSingleTargets<Elem = f64>,
+{
+    type Object = GaussianProcess<K, I>;
+
+    fn fit(&self, dataset: &DatasetBase<ArrayBase<D, Ix2>, T>) -> Result<Self::Object, LinfaError> {
+        let x = I::from_rows(dataset.records.view())?;
+        let y = dataset.as_single_targets().to_vec();
+        Ok(GaussianProcess::builder()
+            .kernel(self.kernel.clone())
+            .noise(self.noise_sigma)
+            .normalize(self.normalize)
+            .build_from_slices(&x, &y)?)
+    }
+}
+
+/// Predicts the posterior mean for each row of the records.
+impl<K, I, D> PredictInplace<ArrayBase<D, Ix2>, Array1<f64>> for GaussianProcess<K, I>
+where
+    K: GpKernel<I>,
+    I: NdarrayInput,
+    D: Data<Elem = f64>,
+{
+    fn predict_inplace(&self, x: &ArrayBase<D, Ix2>, y: &mut Array1<f64>) {
+        assert_eq!(
+            x.nrows(),
+            y.len(),
+            "the number of records and targets should be the same"
+        );
+        let inputs =
+            I::from_rows(x.view()).expect("the records should have one column per input dimension");
+        let (mean, _) = self.predict_slice(&inputs);
+        y.assign(&Array1::from_vec(mean));
+    }
+
+    fn default_target(&self, x: &ArrayBase<D, Ix2>) -> Array1<f64> {
+        Array1::zeros(x.nrows())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use linfa::prelude::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_linfa_fit_predict() {
+        let records = array![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+        let dataset = Dataset::new(records.clone(), array![1.0, 2.0, 3.0]);
+        let params: GaussianProcessParams<_, [f64; 2]> = GaussianProcessParams::new(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        })
+        .noise(1e-4)
+        .normalize(true);
+        let gp = params.fit(&dataset).unwrap();
+
+        let prediction = Predict::predict(&gp, &records);
+        assert!((prediction - array![1.0, 2.0, 3.0])
+            .iter()
+            .all(|e| e.abs() < 0.01));
+
+        let wrong = Dataset::new(array![[0.0], [1.0]], array![1.0, 2.0]);
+        assert!(matches!(
+            params.fit(&wrong),
+            Err(LinfaError::Gp(GpError::DimensionMismatch {
+                expected: 2,
+                got: 1
+            }))
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+use super::{
+    GaussianProcess, GpError, GpInput, GpKernel, HyperparameterOptimizer, KernelParams,
+    RegressionModel,
+};
+
+/// A local GP fit to the observations of one region of the input domain.
+struct Expert<K: GpKernel<I>, I: GpInput> {
+    center: I,
+    gp: GaussianProcess<K, I>,
+}
+
+/// Centers spread over the inputs by farthest point sampling: starting from the first input,
+/// repeatedly pick the input farthest from all centers so far.
+fn spread_centers<I: GpInput>(x: &[I], count: usize) -> Vec<I> {
+    let Some(first) = x.first() else {
+        return Vec::new();
+    };
+    let mut centers = vec![*first];
+    let mut distances: Vec<f64> = x.iter().map(|x| x.distance(first)).collect();
+    while centers.len() < count {
+        let (index, distance) = distances
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .expect("there is at least one input");
+        if *distance == 0.0 {
+            // fewer distinct inputs than experts
+            break;
+        }
+        let center = x[index];
+        for (distance, x) in distances.iter_mut().zip(x) {
+            *distance = distance.min(x.distance(&center));
+        }
+        centers.push(center);
+    }
+    centers
+}
+
+/// A mixture of local GP experts: the input domain is split into regions around centers spread
+/// over the inputs, a separate GP is fit to the observations of each region, and predictions
+/// are blended between the experts by how close the input is to each center.
+///
+/// Each expert only inverts the covariance of its own observations, so `k` experts fit `n`
+/// points in about `O(n^3 / k^2)`. Fitting the hyperparameters of each expert separately with
+/// [`Self::fit_hyperparameters`] also handles non-stationary data, e.g. a function that is
+/// smooth in one region and wiggly in another.
+///
+/// ```
+/// use gaussian_processes::gp::{LocalExpertsGaussianProcess, RbfKernel};
+///
+/// let x: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+/// let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+/// let kernel = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// };
+/// let gp = LocalExpertsGaussianProcess::new(&x, &y, kernel, 0.01, 4, 0.5)?;
+/// let (mean, _) = gp.predict(&[4.52]);
+/// assert!((mean[0] - 4.52f64.sin()).abs() < 0.05);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct LocalExpertsGaussianProcess<K: GpKernel<I>, I: GpInput = f64> {
+    experts: Vec<Expert<K, I>>,
+    kernel: K,
+    noise_sigma: f64,
+    /// The number of experts asked for, which is more than there are when there are fewer
+    /// distinct inputs.
+    expert_count: usize,
+    blend_width: f64,
+}
+
+impl<K: GpKernel<I> + Clone, I: GpInput> LocalExpertsGaussianProcess<K, I> {
+    /// Split the observations between (at most) `experts` local GPs with the same kernel and
+    /// noise. Predictions are blended with weights that fall off with the distance to the
+    /// center of each expert over about `blend_width`, so a small width switches sharply
+    /// between experts and a large one averages them.
+    pub fn new(
+        x: &[I],
+        y: &[f64],
+        kernel: K,
+        noise_sigma: f64,
+        experts: usize,
+        blend_width: f64,
+    ) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        let centers = spread_centers(x, experts.max(1));
+        let mut regions = vec![(Vec::new(), Vec::new()); centers.len()];
+        for (x, y) in x.iter().zip(y) {
+            let index = Self::nearest_center(&centers, x);
+            regions[index].0.push(*x);
+            regions[index].1.push(*y);
+        }
+        let fitted = centers
+            .into_iter()
+            .zip(regions)
+            .map(|(center, (x, y))| {
+                Ok(Expert {
+                    center,
+                    gp: GaussianProcess::from_slices(&x, &y, kernel.clone(), noise_sigma)?,
+                })
+            })
+            .collect::<Result<_, GpError>>()?;
+        Ok(Self {
+            experts: fitted,
+            kernel,
+            noise_sigma,
+            expert_count: experts,
+            blend_width,
+        })
+    }
+
+    fn nearest_center(centers: &[I], x: &I) -> usize {
+        centers
+            .iter()
+            .map(|center| center.distance(x))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// How much each expert contributes at `x`, summing to one.
+    fn blend_weights(&self, x: I) -> Vec<f64> {
+        let distances: Vec<f64> = self
+            .experts
+            .iter()
+            .map(|expert| (expert.center.distance(&x) / self.blend_width).powi(2))
+            .collect();
+        // relative to the nearest center, so the weights do not all underflow far away
+        let nearest = distances.iter().copied().fold(f64::INFINITY, f64::min);
+        let weights: Vec<f64> = distances
+            .iter()
+            .map(|d| (-0.5 * (d - nearest)).exp())
+            .collect();
+        let total: f64 = weights.iter().sum();
+        weights.into_iter().map(|w| w / total).collect()
+    }
+
+    /// The mean and variance of the mixture of the experts' predictions at the inputs.
+    pub fn predict(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        if self.experts.is_empty() {
+            let variance = x.iter().map(|x| self.kernel.compute(*x, *x)).collect();
+            return (vec![0.0; x.len()], variance);
+        }
+        let predictions: Vec<(Vec<f64>, Vec<f64>)> = self
+            .experts
+            .iter()
+            .map(|expert| expert.gp.predict_slice(x))
+            .collect();
+        (0..x.len())
+            .map(|i| {
+                let weights = self.blend_weights(x[i]);
+                let mut mean = 0.0;
+                let mut second_moment = 0.0;
+                for (weight, (m, v)) in weights.iter().zip(&predictions) {
+                    mean += weight * m[i];
+                    second_moment += weight * (v[i] + m[i] * m[i]);
+                }
+                (mean, (second_moment - mean * mean).max(0.0))
+            })
+            .unzip()
+    }
+
+    /// The sum of the log marginal likelihoods of the experts, treating the regions as
+    /// independent.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.experts
+            .iter()
+            .map(|expert| expert.gp.log_marginal_likelihood())
+            .sum()
+    }
+
+    /// The local GP of each expert, with the center of its region.
+    pub fn experts(&self) -> impl Iterator<Item = (I, &GaussianProcess<K, I>)> {
+        self.experts
+            .iter()
+            .map(|expert| (expert.center, &expert.gp))
+    }
+}
+
+impl<K: GpKernel<I> + KernelParams + Clone, I: GpInput> LocalExpertsGaussianProcess<K, I> {
+    /// Maximize the log marginal likelihood of each expert separately over its kernel
+    /// hyperparameters and noise, so each region gets its own smoothness.
+    pub fn fit_hyperparameters(&mut self, max_iterations: usize) -> Result<(), GpError> {
+        for expert in &mut self.experts {
+            let mut optimizer = HyperparameterOptimizer::new(
+                &expert.gp.x,
+                &expert.gp.targets(),
+                expert.gp.kernel.clone(),
+                expert.gp.noise_variance().sqrt(),
+            );
+            optimizer.run(max_iterations, |_| true);
+            let (kernel, noise_sigma) = optimizer.best();
+            expert.gp = GaussianProcess::new_weighted(
+                &expert.gp.x,
+                &expert.gp.targets(),
+                &expert.gp.weights,
+                kernel,
+                noise_sigma,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: GpKernel<I> + Clone, I: GpInput> RegressionModel<I> for LocalExpertsGaussianProcess<K, I> {
+    fn fit(&mut self, x: &[I], y: &[f64]) -> Result<(), GpError> {
+        *self = Self::new(
+            x,
+            y,
+            self.kernel.clone(),
+            self.noise_sigma,
+            self.expert_count,
+            self.blend_width,
+        )?;
+        Ok(())
+    }
+
+    fn predict_mean_std(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        let (mean, variance) = self.predict(x);
+        (mean, variance.into_iter().map(f64::sqrt).collect())
+    }
+
+    fn log_evidence(&self) -> f64 {
+        self.log_marginal_likelihood()
+    }
+
+    /// The noise of the experts, blended like their predictions.
+    fn noise_variance(&self, x: &[I]) -> Vec<f64> {
+        x.iter()
+            .map(|x| {
+                self.blend_weights(*x)
+                    .iter()
+                    .zip(&self.experts)
+                    .map(|(weight, expert)| weight * expert.gp.noise_variance())
+                    .sum::<f64>()
+            })
+            .map(|noise| {
+                if self.experts.is_empty() {
+                    self.noise_sigma.powi(2)
+                } else {
+                    noise
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+
+    #[test]
+    fn test_spread_centers() {
+        let x = [0.0, 1.0, 2.0, 10.0, 5.0];
+        assert_eq!(spread_centers(&x, 3), vec![0.0, 10.0, 5.0]);
+        assert_eq!(spread_centers(&[1.0, 1.0], 3), vec![1.0]);
+        assert!(spread_centers::<f64>(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_local_experts_non_stationary() {
+        // smooth on the left, wiggly on the right
+        let f = |x: f64| if x < 5.0 { 0.2 * x } else { (4.0 * x).sin() };
+        let x: Vec<f64> = (0..100).map(|i| i as f64 * 0.1).collect();
+        let y: Vec<f64> = x.iter().map(|x| f(*x)).collect();
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 2.0,
+        };
+        let mut gp = LocalExpertsGaussianProcess::new(&x, &y, kernel, 0.01, 2, 0.5).unwrap();
+        assert_eq!(gp.experts().count(), 2);
+        let before = gp.log_marginal_likelihood();
+        gp.fit_hyperparameters(200).unwrap();
+        assert!(gp.log_marginal_likelihood() > before);
+
+        // each region gets its own length scale
+        let length_scales: Vec<f64> = gp
+            .experts()
+            .map(|(_, gp)| gp.kernel().length_scale)
+            .collect();
+        assert!(
+            length_scales[0] > 2.0 * length_scales[1],
+            "{length_scales:?}"
+        );
+
+        let test = [2.05, 7.05];
+        let (mean, variance) = gp.predict(&test);
+        for i in 0..test.len() {
+            assert!((mean[i] - f(test[i])).abs() < 0.05, "{mean:?}");
+            assert!(variance[i] >= 0.0);
+        }
+
+        // the blend is smooth across the boundary between the regions
+        let (mean, _) = gp.predict(&[4.95, 4.96]);
+        assert!((mean[0] - mean[1]).abs() < 0.05);
+    }
+}
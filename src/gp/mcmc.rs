@@ -0,0 +1,222 @@
+use nalgebra as na;
+
+use super::optimize::PARAM_RANGE;
+use super::{FitEvent, GaussianProcess, GpInput, GpKernel, KernelParams};
+
+/// Standard deviation of the random walk proposals, in log units of the hyperparameters.
+const STEP_SIZE: f64 = 0.2;
+
+/// Draws samples from the posterior over the kernel hyperparameters and the noise with the
+/// random walk Metropolis algorithm, using a log-uniform prior within the range the optimizer
+/// uses.
+///
+/// Like [`super::HyperparameterOptimizer`] it advances one step at a time with [`Self::step`].
+pub struct HyperparameterSampler<K, I: GpInput = f64> {
+    kernel: K,
+    x: na::DVector<I>,
+    y: na::DVector<f64>,
+    /// The weights of the observations and the jitter on the diagonal, see
+    /// [`Self::with_observation_noise`].
+    weights: na::DVector<f64>,
+    jitter: f64,
+    /// The current state as log hyperparameters `[kernel params.., noise]` and its log
+    /// marginal likelihood.
+    current: (Vec<f64>, f64),
+    /// The visited states as hyperparameters `[kernel params.., noise]`.
+    samples: Vec<Vec<f64>>,
+    accepted: usize,
+}
+
+impl<K: GpKernel<I> + KernelParams + Clone, I: GpInput> HyperparameterSampler<K, I> {
+    /// Start the chain at the given kernel and noise.
+    pub fn new(x: &na::DVector<I>, y: &na::DVector<f64>, kernel: K, noise_sigma: f64) -> Self {
+        let mut kernel = kernel;
+        let mut start: Vec<f64> = kernel.params_mut().into_iter().map(|p| *p).collect();
+        start.push(noise_sigma);
+        let start: Vec<f64> = start
+            .into_iter()
+            .map(|p| p.clamp(PARAM_RANGE.0, PARAM_RANGE.1).ln())
+            .collect();
+
+        let mut sampler = Self {
+            kernel,
+            x: x.clone(),
+            y: y.clone(),
+            weights: na::DVector::from_element(x.len(), 1.0),
+            jitter: super::EPS,
+            current: (Vec::new(), f64::NEG_INFINITY),
+            samples: Vec::new(),
+            accepted: 0,
+        };
+        let value = sampler.evaluate(&start);
+        sampler.current = (start, value);
+        sampler
+    }
+
+    /// Weigh the observations and set the jitter added to the diagonal, like
+    /// [`super::HyperparameterOptimizer::with_observation_noise`]. The start of the chain is
+    /// evaluated again with them.
+    pub fn with_observation_noise(mut self, weights: na::DVector<f64>, jitter: f64) -> Self {
+        self.weights = weights;
+        self.jitter = jitter;
+        self.current.1 = self.evaluate(&self.current.0);
+        self
+    }
+
+    /// The kernel and noise of a state given as (not log) hyperparameters.
+    pub fn hyperparameters(&self, sample: &[f64]) -> (K, f64) {
+        let mut kernel = self.kernel.clone();
+        for (param, value) in kernel.params_mut().into_iter().zip(sample) {
+            *param = *value;
+        }
+        (kernel, sample[sample.len() - 1])
+    }
+
+    fn evaluate(&self, state: &[f64]) -> f64 {
+        let sample: Vec<f64> = state.iter().map(|p| p.exp()).collect();
+        let (kernel, noise) = self.hyperparameters(&sample);
+        // hyperparameters whose covariance matrix is not positive definite are never accepted
+        let value = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(noise)
+            .jitter(self.jitter)
+            .weights(self.weights.clone())
+            .build(&self.x, &self.y)
+            .map_or(f64::NEG_INFINITY, |gp| gp.log_marginal_likelihood());
+        if value.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            value
+        }
+    }
+
+    /// Propose a move and record the resulting state, returning its log marginal likelihood.
+    pub fn step<R: rand::Rng>(&mut self, rng: &mut R) -> f64 {
+        let (min, max) = (PARAM_RANGE.0.ln(), PARAM_RANGE.1.ln());
+        let proposal: Vec<f64> = self
+            .current
+            .0
+            .iter()
+            .map(|p| p + STEP_SIZE * rng.sample::<f64, _>(rand_distr::StandardNormal))
+            .collect();
+
+        // the prior is zero outside of the range, so such proposals are always rejected
+        if proposal.iter().all(|p| (min..=max).contains(p)) {
+            let value = self.evaluate(&proposal);
+            if rng.gen::<f64>().ln() < value - self.current.1 {
+                self.current = (proposal, value);
+                self.accepted += 1;
+            }
+        }
+
+        self.samples
+            .push(self.current.0.iter().map(|p| p.exp()).collect());
+        self.current.1
+    }
+
+    /// Take `steps` steps, calling `callback` after every step. Returning false from the
+    /// callback stops early, e.g. when out of time.
+    pub fn run<R: rand::Rng>(
+        &mut self,
+        steps: usize,
+        rng: &mut R,
+        mut callback: impl FnMut(FitEvent<'_>) -> bool,
+    ) {
+        for _ in 0..steps {
+            let value = self.step(rng);
+            let event = FitEvent {
+                iteration: self.samples.len(),
+                log_marginal_likelihood: value,
+                hyperparameters: &self.samples[self.samples.len() - 1],
+            };
+            if !callback(event) {
+                break;
+            }
+        }
+    }
+
+    /// The visited states as hyperparameters `[kernel params.., noise]`, in order.
+    pub fn samples(&self) -> &[Vec<f64>] {
+        &self.samples
+    }
+
+    /// The fraction of proposals that were accepted.
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.accepted as f64 / self.samples.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_sampler() {
+        let x = na::DVector::from_vec((0..10).map(|i| i as f64).collect());
+        let y = x.map(|x: f64| (x * 0.5).sin());
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let mut sampler = HyperparameterSampler::new(&x, &y, kernel, 0.1);
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+        for _ in 0..300 {
+            assert!(sampler.step(&mut rng).is_finite());
+        }
+
+        assert_eq!(sampler.samples().len(), 300);
+        for sample in sampler.samples() {
+            assert_eq!(sample.len(), 3);
+            assert!(sample
+                .iter()
+                .all(|p| (PARAM_RANGE.0..=PARAM_RANGE.1).contains(p)));
+        }
+        let rate = sampler.acceptance_rate();
+        assert!(rate > 0.05 && rate < 0.95, "acceptance rate {rate}");
+
+        let (kernel, noise) = sampler.hyperparameters(&sampler.samples()[299]);
+        assert_eq!(kernel.length_scale, sampler.samples()[299][0]);
+        assert_eq!(noise, sampler.samples()[299][2]);
+
+        let mut events = 0;
+        sampler.run(100, &mut rng, |event| {
+            events += 1;
+            assert_eq!(event.iteration, 300 + events);
+            assert_eq!(event.hyperparameters.len(), 3);
+            events < 10
+        });
+        assert_eq!(events, 10);
+        assert_eq!(sampler.samples().len(), 310);
+    }
+
+    #[test]
+    fn test_sampler_uses_weights_and_jitter() {
+        let x = na::DVector::from_vec((0..10).map(|i| i as f64).collect());
+        let y = x.map(|x: f64| (x * 0.5).sin());
+        let weights = na::DVector::from_vec((0..10).map(|i| 1.0 + (i % 3) as f64).collect());
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let mut sampler = HyperparameterSampler::new(&x, &y, kernel, 0.1)
+            .with_observation_noise(weights.clone(), 1e-4);
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+        let value = sampler.step(&mut rng);
+
+        // the likelihood is that of the model fitted with the same weights and jitter
+        let (kernel, noise) = sampler.hyperparameters(&sampler.samples()[0]);
+        let fitted = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(noise)
+            .jitter(1e-4)
+            .weights(weights)
+            .build(&x, &y)
+            .unwrap();
+        assert!((fitted.log_marginal_likelihood() - value).abs() < 1e-9);
+    }
+}
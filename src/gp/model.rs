@@ -0,0 +1,188 @@
+use nalgebra as na;
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel};
+
+/// A probabilistic regression model, so different methods can be fit, plotted and compared
+/// the same way.
+pub trait RegressionModel<I: GpInput = f64> {
+    /// Fit the model to the training data, keeping its settings (e.g. the kernel and noise).
+    fn fit(&mut self, x: &[I], y: &[f64]) -> Result<(), GpError>;
+
+    /// The predictive mean and standard deviation of the latent function at the inputs.
+    fn predict_mean_std(&self, x: &[I]) -> (Vec<f64>, Vec<f64>);
+
+    /// The log of the marginal likelihood of the training data, for comparing models.
+    fn log_evidence(&self) -> f64;
+
+    /// The variance of the observation noise at the inputs, which added to the latent variance
+    /// gives the variance of new observations.
+    fn noise_variance(&self, x: &[I]) -> Vec<f64>;
+}
+
+impl<K: GpKernel<I> + Clone, I: GpInput> RegressionModel<I> for GaussianProcess<K, I> {
+    fn fit(&mut self, x: &[I], y: &[f64]) -> Result<(), GpError> {
+        *self = GaussianProcess::builder()
+            .kernel(self.kernel.clone())
+            .noise(self.noise_sigma)
+            .jitter(self.jitter)
+            .build_from_slices(x, y)?;
+        Ok(())
+    }
+
+    fn predict_mean_std(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        let (mean, variance) = self.predict_slice(x);
+        (
+            mean,
+            variance.into_iter().map(|v| v.max(0.0).sqrt()).collect(),
+        )
+    }
+
+    fn log_evidence(&self) -> f64 {
+        self.log_marginal_likelihood()
+    }
+
+    fn noise_variance(&self, x: &[I]) -> Vec<f64> {
+        vec![GaussianProcess::noise_variance(self); x.len()]
+    }
+}
+
+/// Bayesian linear regression `y = w0 + w1 x + noise`, with a zero mean Gaussian prior on the
+/// weights. The same as a GP with the kernel `weight_variance * (1 + x x')`, but fit in
+/// constant time per point.
+#[derive(Clone, Debug)]
+pub struct BayesianLinearRegression {
+    /// The prior variance of each weight.
+    pub weight_variance: f64,
+    /// The standard deviation of the observation noise.
+    pub noise_sigma: f64,
+    /// The posterior mean and covariance of the weights.
+    mean: na::Vector2<f64>,
+    covariance: na::Matrix2<f64>,
+    log_evidence: f64,
+}
+
+impl BayesianLinearRegression {
+    /// The prior, without any training data.
+    pub fn new(weight_variance: f64, noise_sigma: f64) -> Self {
+        Self {
+            weight_variance,
+            noise_sigma,
+            mean: na::Vector2::zeros(),
+            covariance: na::Matrix2::identity() * weight_variance,
+            log_evidence: 0.0,
+        }
+    }
+
+    /// The posterior mean of the intercept and slope.
+    pub fn weights(&self) -> (f64, f64) {
+        (self.mean[0], self.mean[1])
+    }
+}
+
+impl RegressionModel for BayesianLinearRegression {
+    fn fit(&mut self, x: &[f64], y: &[f64]) -> Result<(), GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+
+        // precision of the posterior A = I / weight_variance + Φ^T Φ / noise
+        let alpha = 1.0 / self.weight_variance;
+        let beta = 1.0 / self.noise_sigma.powi(2);
+        let mut gram = na::Matrix2::zeros();
+        let mut projected = na::Vector2::zeros();
+        for (x, y) in x.iter().zip(y) {
+            let phi = na::Vector2::new(1.0, *x);
+            gram += phi * phi.transpose();
+            projected += phi * *y;
+        }
+        let precision = na::Matrix2::identity() * alpha + gram * beta;
+        let covariance = precision.try_inverse().ok_or(GpError::NotInvertible)?;
+        let mean = covariance * projected * beta;
+
+        // Bishop, Pattern Recognition and Machine Learning, eq. 3.86
+        let n = x.len() as f64;
+        let squared_error: f64 = x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| (y - mean[0] - mean[1] * x).powi(2))
+            .sum();
+        let energy = beta / 2.0 * squared_error + alpha / 2.0 * mean.norm_squared();
+        self.log_evidence = alpha.ln() + n / 2.0 * beta.ln()
+            - energy
+            - 0.5 * precision.determinant().ln()
+            - n / 2.0 * (2.0 * std::f64::consts::PI).ln();
+        self.mean = mean;
+        self.covariance = covariance;
+        Ok(())
+    }
+
+    fn predict_mean_std(&self, x: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        x.iter()
+            .map(|x| {
+                let phi = na::Vector2::new(1.0, *x);
+                let variance = (phi.transpose() * self.covariance * phi)[0];
+                (phi.dot(&self.mean), variance.max(0.0).sqrt())
+            })
+            .unzip()
+    }
+
+    fn log_evidence(&self) -> f64 {
+        self.log_evidence
+    }
+
+    fn noise_variance(&self, x: &[f64]) -> Vec<f64> {
+        vec![self.noise_sigma.powi(2); x.len()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bayesian_linear_regression() {
+        let x = [0.0, 1.0, 2.0, 4.0];
+        let y = [1.1, 2.9, 5.2, 8.8];
+        let mut model = BayesianLinearRegression::new(10.0, 0.1);
+        model.fit(&x, &y).unwrap();
+        let (intercept, slope) = model.weights();
+        assert!((intercept - 1.1).abs() < 0.2);
+        assert!((slope - 1.95).abs() < 0.1);
+
+        // the same model as a GP with a linear kernel
+        let n = x.len();
+        let covariance = na::DMatrix::from_fn(n, n, |i, j| {
+            10.0 * (1.0 + x[i] * x[j]) + if i == j { 0.01 } else { 0.0 }
+        });
+        let y_vector = na::DVector::from_column_slice(&y);
+        let cholesky = covariance.clone().cholesky().unwrap();
+        let expected = -0.5 * y_vector.dot(&cholesky.solve(&y_vector))
+            - 0.5 * covariance.determinant().ln()
+            - n as f64 / 2.0 * (2.0 * std::f64::consts::PI).ln();
+        assert!((model.log_evidence() - expected).abs() < 1e-9);
+
+        let (mean, std) = model.predict_mean_std(&[3.0, 100.0]);
+        assert!((mean[0] - (intercept + 3.0 * slope)).abs() < 1e-9);
+        // the uncertainty in the slope grows away from the data
+        assert!(std[1] > 10.0 * std[0]);
+    }
+
+    #[test]
+    fn test_gaussian_process_model() {
+        let kernel = crate::gp::RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let mut model: GaussianProcess<_> = GaussianProcess::prior(kernel, 0.1);
+        model.fit(&[1.0, 2.0], &[0.5, -0.5]).unwrap();
+        let (mean, std) = model.predict_mean_std(&[1.0, 50.0]);
+        assert!((mean[0] - 0.5).abs() < 0.2);
+        assert!((std[1] - 1.0).abs() < 1e-6);
+        assert_eq!(model.log_evidence(), model.log_marginal_likelihood());
+        let noise = RegressionModel::noise_variance(&model, &[1.0]);
+        assert!((noise[0] - 0.01).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,252 @@
+use nalgebra as na;
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel, KernelParams};
+
+/// An input tagged with the fidelity of the observation made there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FidelityInput<I> {
+    pub x: I,
+    /// Whether this is an expensive high fidelity observation rather than a cheap one.
+    pub high: bool,
+}
+
+/// The distance between the inputs themselves, whatever their fidelity.
+impl<I: GpInput> GpInput for FidelityInput<I> {
+    fn distance(&self, other: &Self) -> f64 {
+        self.x.distance(&other.x)
+    }
+}
+
+/// The covariance of the linear auto-regressive model of Kennedy & O'Hagan (2000), where the
+/// high fidelity function is a scaled low fidelity function plus an independent difference,
+/// `f_high(x) = rho * f_low(x) + delta(x)`.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CoKrigingKernel<K, D = K> {
+    /// The kernel of the low fidelity function.
+    pub low: K,
+    /// The kernel of the difference `delta` between the fidelities.
+    pub difference: D,
+    /// How strongly the high fidelity function follows the low fidelity one.
+    pub rho: f64,
+}
+
+impl<K, D, I> GpKernel<FidelityInput<I>> for CoKrigingKernel<K, D>
+where
+    K: GpKernel<I>,
+    D: GpKernel<I>,
+    I: GpInput,
+{
+    fn compute(&self, a: FidelityInput<I>, b: FidelityInput<I>) -> f64 {
+        let low = self.low.compute(a.x, b.x);
+        match (a.high, b.high) {
+            (false, false) => low,
+            (true, true) => self.rho * self.rho * low + self.difference.compute(a.x, b.x),
+            _ => self.rho * low,
+        }
+    }
+}
+
+/// The hyperparameters of the low fidelity kernel, then the difference kernel, then `rho`.
+impl<K: KernelParams, D: KernelParams> KernelParams for CoKrigingKernel<K, D> {
+    fn param_names(&self) -> Vec<&'static str> {
+        let mut names = self.low.param_names();
+        names.extend(self.difference.param_names());
+        names.push("rho");
+        names
+    }
+
+    fn param_bounds(&self) -> Vec<(f64, f64)> {
+        let mut bounds = self.low.param_bounds();
+        bounds.extend(self.difference.param_bounds());
+        bounds.push((0.0, 10.0));
+        bounds
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut f64> {
+        let mut params = self.low.params_mut();
+        params.extend(self.difference.params_mut());
+        params.push(&mut self.rho);
+        params
+    }
+}
+
+/// A multi-fidelity surrogate (co-kriging): one GP fit jointly to many cheap low fidelity
+/// observations and a few expensive high fidelity ones, predicting the high fidelity function.
+///
+/// ```
+/// use gaussian_processes::gp::{CoKrigingKernel, MultiFidelityGaussianProcess, RbfKernel};
+///
+/// let low = |x: f64| (2.0 * x).sin();
+/// let high = |x: f64| 2.0 * low(x) + 0.1 * x;
+/// let x_low: Vec<f64> = (0..20).map(|i| i as f64 * 0.25).collect();
+/// let y_low: Vec<f64> = x_low.iter().map(|x| low(*x)).collect();
+/// let x_high = [0.0, 2.5, 5.0];
+/// let y_high: Vec<f64> = x_high.iter().map(|x| high(*x)).collect();
+///
+/// let kernel = CoKrigingKernel {
+///     low: RbfKernel {
+///         sigma: 1.0,
+///         length_scale: 0.5,
+///     },
+///     difference: RbfKernel {
+///         sigma: 0.1,
+///         length_scale: 5.0,
+///     },
+///     rho: 2.0,
+/// };
+/// let gp = MultiFidelityGaussianProcess::new(
+///     &x_low, &y_low, &x_high, &y_high, kernel, 1e-4, 1e-4,
+/// )?;
+/// let (mean, _) = gp.predict(&[1.0]);
+/// assert!((mean[0] - high(1.0)).abs() < 0.1);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct MultiFidelityGaussianProcess<K, D = K, I: GpInput = f64>
+where
+    CoKrigingKernel<K, D>: GpKernel<FidelityInput<I>>,
+{
+    gp: GaussianProcess<CoKrigingKernel<K, D>, FidelityInput<I>>,
+}
+
+impl<K, D, I> MultiFidelityGaussianProcess<K, D, I>
+where
+    K: GpKernel<I>,
+    D: GpKernel<I>,
+    I: GpInput,
+{
+    /// Fit to the observations of both fidelities, with the variance of the noise of each.
+    pub fn new(
+        x_low: &[I],
+        y_low: &[f64],
+        x_high: &[I],
+        y_high: &[f64],
+        kernel: CoKrigingKernel<K, D>,
+        noise_low: f64,
+        noise_high: f64,
+    ) -> Result<Self, GpError> {
+        if x_low.len() != y_low.len() || x_high.len() != y_high.len() {
+            return Err(GpError::LengthMismatch {
+                x: x_low.len() + x_high.len(),
+                y: y_low.len() + y_high.len(),
+            });
+        }
+        let x: Vec<FidelityInput<I>> = x_low
+            .iter()
+            .map(|x| FidelityInput { x: *x, high: false })
+            .chain(x_high.iter().map(|x| FidelityInput { x: *x, high: true }))
+            .collect();
+        let y: Vec<f64> = y_low.iter().chain(y_high).copied().collect();
+
+        // the noise of each fidelity as a weight relative to a unit noise variance
+        let weights = na::DVector::from_iterator(
+            x.len(),
+            x.iter()
+                .map(|x| 1.0 / if x.high { noise_high } else { noise_low }),
+        );
+        let gp = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(1.0)
+            .weights(weights)
+            .build_from_slices(&x, &y)?;
+        Ok(Self { gp })
+    }
+
+    /// The mean and variance of the high fidelity function at the inputs.
+    pub fn predict(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        self.predict_fidelity(x, true)
+    }
+
+    /// The mean and variance of the low fidelity function at the inputs.
+    pub fn predict_low(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        self.predict_fidelity(x, false)
+    }
+
+    fn predict_fidelity(&self, x: &[I], high: bool) -> (Vec<f64>, Vec<f64>) {
+        let x: Vec<FidelityInput<I>> = x.iter().map(|x| FidelityInput { x: *x, high }).collect();
+        self.gp.predict_slice(&x)
+    }
+
+    /// The log marginal likelihood of the observations of both fidelities.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.gp.log_marginal_likelihood()
+    }
+
+    /// The joint GP over the inputs tagged with their fidelity.
+    pub fn gp(&self) -> &GaussianProcess<CoKrigingKernel<K, D>, FidelityInput<I>> {
+        &self.gp
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+
+    #[test]
+    fn test_co_kriging_beats_high_fidelity_alone() {
+        let low = |x: f64| (1.5 * x).sin() + 0.2 * x;
+        let high = |x: f64| 1.5 * low(x) - 0.3;
+        let x_low: Vec<f64> = (0..25).map(|i| i as f64 * 0.25).collect();
+        let y_low: Vec<f64> = x_low.iter().map(|x| low(*x)).collect();
+        let x_high = [0.0, 2.0, 4.0, 6.0];
+        let y_high: Vec<f64> = x_high.iter().map(|x| high(*x)).collect();
+
+        let kernel = CoKrigingKernel {
+            low: RbfKernel {
+                sigma: 1.0,
+                length_scale: 0.7,
+            },
+            difference: RbfKernel {
+                sigma: 0.1,
+                length_scale: 10.0,
+            },
+            rho: 1.5,
+        };
+        let gp = MultiFidelityGaussianProcess::new(
+            &x_low,
+            &y_low,
+            &x_high,
+            &y_high,
+            kernel.clone(),
+            1e-4,
+            1e-4,
+        )
+        .unwrap();
+        let high_only =
+            GaussianProcess::from_slices(&x_high, &y_high, kernel.low.clone(), 1e-4).unwrap();
+
+        let test = [1.0, 3.0, 5.0];
+        let (mean, variance) = gp.predict(&test);
+        let (high_only_mean, high_only_variance) = high_only.predict_slice(&test);
+        for i in 0..test.len() {
+            let error = (mean[i] - high(test[i])).abs();
+            assert!(error < 0.05, "{error}");
+            assert!(error < (high_only_mean[i] - high(test[i])).abs());
+            assert!(variance[i] < high_only_variance[i]);
+        }
+
+        // the low fidelity function is interpolated as well
+        let (mean, _) = gp.predict_low(&[1.1]);
+        assert!((mean[0] - low(1.1)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_co_kriging_kernel() {
+        let kernel = CoKrigingKernel {
+            low: RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+            },
+            difference: RbfKernel {
+                sigma: 0.5,
+                length_scale: 1.0,
+            },
+            rho: 2.0,
+        };
+        let at = |x, high| FidelityInput { x, high };
+        assert_eq!(kernel.compute(at(1.0, false), at(1.0, false)), 1.0);
+        assert_eq!(kernel.compute(at(1.0, false), at(1.0, true)), 2.0);
+        assert_eq!(kernel.compute(at(1.0, true), at(1.0, true)), 4.5);
+        assert_eq!(kernel.param_names().last(), Some(&"rho"));
+    }
+}
@@ -0,0 +1,116 @@
+use ndarray::{Array1, ArrayView1, ArrayView2};
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel};
+
+/// Inputs that can be read from ndarray views: one dimensional inputs from an `ArrayView1`, and
+/// `N` dimensional inputs from the rows of an `ArrayView2` with `N` columns.
+pub trait NdarrayInput: GpInput {
+    type View<'a>;
+
+    fn from_view(view: Self::View<'_>) -> Result<Vec<Self>, GpError>;
+
+    /// Read one input from each row of a matrix, with one column per dimension.
+    fn from_rows(rows: ArrayView2<'_, f64>) -> Result<Vec<Self>, GpError>;
+}
+
+impl NdarrayInput for f64 {
+    type View<'a> = ArrayView1<'a, f64>;
+
+    fn from_view(view: Self::View<'_>) -> Result<Vec<Self>, GpError> {
+        Ok(view.to_vec())
+    }
+
+    fn from_rows(rows: ArrayView2<'_, f64>) -> Result<Vec<Self>, GpError> {
+        if rows.ncols() != 1 {
+            return Err(GpError::DimensionMismatch {
+                expected: 1,
+                got: rows.ncols(),
+            });
+        }
+        Ok(rows.column(0).to_vec())
+    }
+}
+
+impl<const N: usize> NdarrayInput for [f64; N] {
+    type View<'a> = ArrayView2<'a, f64>;
+
+    fn from_view(view: Self::View<'_>) -> Result<Vec<Self>, GpError> {
+        Self::from_rows(view)
+    }
+
+    fn from_rows(rows: ArrayView2<'_, f64>) -> Result<Vec<Self>, GpError> {
+        if rows.ncols() != N {
+            return Err(GpError::DimensionMismatch {
+                expected: N,
+                got: rows.ncols(),
+            });
+        }
+        Ok(rows
+            .rows()
+            .into_iter()
+            .map(|row| std::array::from_fn(|i| row[i]))
+            .collect())
+    }
+}
+
+impl<K: GpKernel<I>, I: NdarrayInput> GaussianProcess<K, I> {
+    /// Like [`Self::new`], but taking the training data as ndarray views.
+    pub fn from_ndarray(
+        x: I::View<'_>,
+        y: ArrayView1<'_, f64>,
+        kernel: K,
+        noise_sigma: f64,
+    ) -> Result<GaussianProcess<K, I>, GpError> {
+        Self::from_slices(&I::from_view(x)?, &y.to_vec(), kernel, noise_sigma)
+    }
+
+    /// Like [`Self::predict`], but taking and returning ndarray arrays.
+    pub fn predict_ndarray(&self, x: I::View<'_>) -> Result<(Array1<f64>, Array1<f64>), GpError> {
+        let (mean, variance) = self.predict_slice(&I::from_view(x)?);
+        Ok((Array1::from_vec(mean), Array1::from_vec(variance)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use ndarray::array;
+
+    fn kernel() -> RbfKernel {
+        RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_ndarray_1d() {
+        let x = array![1.0, 2.0, 6.0];
+        let y = array![1.0, 1.0, -1.0];
+        let gp = GaussianProcess::from_ndarray(x.view(), y.view(), kernel(), 0.1).unwrap();
+        let (mean, variance) = gp.predict_ndarray(array![1.5, 4.0].view()).unwrap();
+
+        let (expected_mean, expected_variance) = gp.predict_slice(&[1.5, 4.0]);
+        assert_eq!(mean.to_vec(), expected_mean);
+        assert_eq!(variance.to_vec(), expected_variance);
+    }
+
+    #[test]
+    fn test_ndarray_2d() {
+        let x = array![[0.0, 0.0], [1.0, 1.0]];
+        let y = array![1.0, -1.0];
+        let gp: GaussianProcess<RbfKernel, [f64; 2]> =
+            GaussianProcess::from_ndarray(x.view(), y.view(), kernel(), 0.01).unwrap();
+        let (mean, _) = gp.predict_ndarray(x.view()).unwrap();
+        assert!((mean[0] - 1.0).abs() < 0.1 && (mean[1] + 1.0).abs() < 0.1);
+
+        assert_eq!(
+            gp.predict_ndarray(array![[0.0, 0.0, 0.0]].view()).err(),
+            Some(GpError::DimensionMismatch {
+                expected: 2,
+                got: 3
+            })
+        );
+    }
+}
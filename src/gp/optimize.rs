@@ -0,0 +1,767 @@
+use nalgebra as na;
+use rand::{Rng, SeedableRng};
+
+use super::{GaussianProcess, GpError, GpInput, GpKernel, KernelParams};
+
+/// Smallest and largest value the optimized hyperparameters may take, to keep the covariance
+/// matrix well conditioned.
+pub(super) const PARAM_RANGE: (f64, f64) = (1e-3, 1e3);
+
+/// The optimization has converged when the log marginal likelihood of all vertices of the
+/// simplex, or of all samples of a CMA-ES generation, is within this distance.
+const TOLERANCE: f64 = 1e-6;
+
+/// The size of the initial simplex (or the initial step size of CMA-ES) in each log
+/// hyperparameter: a factor of e when starting from scratch, and about 10% when starting near
+/// the optimum.
+const COLD_STEP: f64 = 1.0;
+const WARM_STEP: f64 = 0.1;
+
+/// The progress of a long running fit of the hyperparameters, passed to the callback of
+/// [`HyperparameterOptimizer::run`] and [`super::HyperparameterSampler::run`] after every
+/// iteration.
+#[derive(Clone, Debug)]
+pub struct FitEvent<'a> {
+    pub iteration: usize,
+    pub log_marginal_likelihood: f64,
+    /// The hyperparameters `[kernel params.., noise]`: the best so far when optimizing, the
+    /// current state of the chain when sampling.
+    pub hyperparameters: &'a [f64],
+}
+
+/// The method [`HyperparameterOptimizer`] searches the hyperparameters with. Neither needs
+/// the gradient of the log marginal likelihood, so any kernel with [`KernelParams`] can be
+/// optimized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum OptimizerConfig {
+    /// The Nelder-Mead simplex method: few evaluations per iteration, but it climbs to the
+    /// nearest local maximum.
+    #[default]
+    NelderMead,
+    /// The covariance matrix adaptation evolution strategy (Hansen, 2016, The CMA evolution
+    /// strategy: a tutorial), sampling a population of hyperparameters each iteration from a
+    /// seeded random number generator. It needs many more evaluations, but it explores widely
+    /// at first and often escapes the local maxima, e.g. those of the period of a periodic
+    /// kernel.
+    CmaEs { seed: u64 },
+}
+
+impl OptimizerConfig {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OptimizerConfig::NelderMead => "Nelder-Mead",
+            OptimizerConfig::CmaEs { .. } => "CMA-ES",
+        }
+    }
+}
+
+/// Maximizes the log marginal likelihood over the kernel hyperparameters and the noise in log
+/// space, using the method of an [`OptimizerConfig`].
+///
+/// The optimization is advanced one iteration at a time with [`Self::step`], so the caller can
+/// show the progress and stop at any point.
+pub struct HyperparameterOptimizer<K, I: GpInput = f64> {
+    kernel: K,
+    x: na::DVector<I>,
+    y: na::DVector<f64>,
+    /// The weights of the observations and the jitter on the diagonal, see
+    /// [`Self::with_observation_noise`].
+    weights: na::DVector<f64>,
+    jitter: f64,
+    search: Search,
+    iteration: usize,
+}
+
+/// The state of the search, with points as log hyperparameters `[kernel params.., noise]`.
+enum Search {
+    /// Vertices of the simplex with their log marginal likelihood, sorted from best to worst.
+    NelderMead(Vec<(Vec<f64>, f64)>),
+    CmaEs(Box<CmaEs>),
+}
+
+impl<K: GpKernel<I> + KernelParams + Clone, I: GpInput> HyperparameterOptimizer<K, I> {
+    /// Start the optimization from the given kernel and noise.
+    pub fn new(x: &na::DVector<I>, y: &na::DVector<f64>, kernel: K, noise_sigma: f64) -> Self {
+        Self::with_config(x, y, kernel, noise_sigma, OptimizerConfig::NelderMead)
+    }
+
+    /// Start the optimization from the given kernel and noise with the given method.
+    pub fn with_config(
+        x: &na::DVector<I>,
+        y: &na::DVector<f64>,
+        kernel: K,
+        noise_sigma: f64,
+        config: OptimizerConfig,
+    ) -> Self {
+        Self::with_step(x, y, kernel, noise_sigma, config, COLD_STEP)
+    }
+
+    /// Start the optimization from a kernel and noise that are already close to the optimum,
+    /// e.g. those optimized before a few points were edited. The search starts out small, so it
+    /// converges in a fraction of the iterations, but it will not leave the local maximum
+    /// around the start.
+    pub fn warm_start(
+        x: &na::DVector<I>,
+        y: &na::DVector<f64>,
+        kernel: K,
+        noise_sigma: f64,
+    ) -> Self {
+        Self::with_step(
+            x,
+            y,
+            kernel,
+            noise_sigma,
+            OptimizerConfig::NelderMead,
+            WARM_STEP,
+        )
+    }
+
+    fn with_step(
+        x: &na::DVector<I>,
+        y: &na::DVector<f64>,
+        kernel: K,
+        noise_sigma: f64,
+        config: OptimizerConfig,
+        step: f64,
+    ) -> Self {
+        let mut kernel = kernel;
+        let mut start: Vec<f64> = kernel.params_mut().into_iter().map(|p| *p).collect();
+        start.push(noise_sigma);
+        let start: Vec<f64> = start
+            .into_iter()
+            .map(|p| p.clamp(PARAM_RANGE.0, PARAM_RANGE.1).ln())
+            .collect();
+
+        let mut optimizer = Self {
+            kernel,
+            x: x.clone(),
+            y: y.clone(),
+            weights: na::DVector::from_element(x.len(), 1.0),
+            jitter: super::EPS,
+            search: Search::NelderMead(Vec::new()),
+            iteration: 0,
+        };
+
+        if let OptimizerConfig::CmaEs { seed } = config {
+            let value = optimizer.evaluate(&start);
+            optimizer.search = Search::CmaEs(Box::new(CmaEs::new(start, value, step, seed)));
+            return optimizer;
+        }
+
+        let mut simplex = vec![start.clone()];
+        for i in 0..start.len() {
+            let mut vertex = start.clone();
+            vertex[i] += step;
+            Self::clamp(&mut vertex);
+            simplex.push(vertex);
+        }
+        let mut simplex: Vec<(Vec<f64>, f64)> = simplex
+            .into_iter()
+            .map(|vertex| {
+                let value = optimizer.evaluate(&vertex);
+                (vertex, value)
+            })
+            .collect();
+        Self::sort(&mut simplex);
+        optimizer.search = Search::NelderMead(simplex);
+        optimizer
+    }
+
+    /// Weigh the observations and set the jitter added to the diagonal, as
+    /// [`super::GaussianProcessBuilder::weights`] and [`super::GaussianProcessBuilder::jitter`]
+    /// do for the fitted process, so the optimum is that of the model that will be fitted. The
+    /// start of the search is evaluated again with them.
+    pub fn with_observation_noise(mut self, weights: na::DVector<f64>, jitter: f64) -> Self {
+        self.weights = weights;
+        self.jitter = jitter;
+        let mut search = std::mem::replace(&mut self.search, Search::NelderMead(Vec::new()));
+        match &mut search {
+            Search::NelderMead(simplex) => {
+                for (vertex, value) in simplex.iter_mut() {
+                    *value = self.evaluate(vertex);
+                }
+                Self::sort(simplex);
+            }
+            Search::CmaEs(cma_es) => cma_es.best.1 = self.evaluate(&cma_es.best.0),
+        }
+        self.search = search;
+        self
+    }
+
+    /// Hyperparameters in log space, clamped to the allowed range.
+    fn clamp(vertex: &mut [f64]) {
+        for p in vertex {
+            *p = p.clamp(PARAM_RANGE.0.ln(), PARAM_RANGE.1.ln());
+        }
+    }
+
+    /// The kernel and noise of a point in log space.
+    fn hyperparameters(&self, vertex: &[f64]) -> (K, f64) {
+        let mut kernel = self.kernel.clone();
+        for (param, value) in kernel.params_mut().into_iter().zip(vertex) {
+            *param = value.exp();
+        }
+        (kernel, vertex[vertex.len() - 1].exp())
+    }
+
+    fn evaluate(&self, vertex: &[f64]) -> f64 {
+        let (kernel, noise) = self.hyperparameters(vertex);
+        // hyperparameters whose covariance matrix is not positive definite are the worst possible
+        let value = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(noise)
+            .jitter(self.jitter)
+            .weights(self.weights.clone())
+            .build(&self.x, &self.y)
+            .map_or(f64::NEG_INFINITY, |gp| gp.log_marginal_likelihood());
+        if value.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            value
+        }
+    }
+
+    fn sort(simplex: &mut [(Vec<f64>, f64)]) {
+        simplex.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+
+    /// The point `centroid + t * (worst - centroid)` on the line through the worst vertex.
+    fn along(&self, simplex: &[(Vec<f64>, f64)], centroid: &[f64], t: f64) -> (Vec<f64>, f64) {
+        let worst = &simplex[simplex.len() - 1].0;
+        let mut vertex: Vec<f64> = centroid
+            .iter()
+            .zip(worst)
+            .map(|(c, w)| c + t * (w - c))
+            .collect();
+        Self::clamp(&mut vertex);
+        let value = self.evaluate(&vertex);
+        (vertex, value)
+    }
+
+    /// Perform one iteration of the method, returning the best log marginal likelihood so far.
+    pub fn step(&mut self) -> f64 {
+        if self.converged() {
+            return self.best_value();
+        }
+        self.iteration += 1;
+
+        match &mut self.search {
+            Search::NelderMead(simplex) => {
+                let mut simplex = std::mem::take(simplex);
+                self.nelder_mead_step(&mut simplex);
+                self.search = Search::NelderMead(simplex);
+            }
+            Search::CmaEs(cma_es) => {
+                let samples = cma_es.sample();
+                let evaluated = samples
+                    .into_iter()
+                    .map(|mut sample| {
+                        Self::clamp(&mut sample);
+                        let value = self.evaluate(&sample);
+                        (sample, value)
+                    })
+                    .collect();
+                if let Search::CmaEs(cma_es) = &mut self.search {
+                    cma_es.update(evaluated);
+                }
+            }
+        }
+        self.best_value()
+    }
+
+    /// One iteration of the Nelder-Mead method.
+    fn nelder_mead_step(&self, simplex: &mut [(Vec<f64>, f64)]) {
+        let n = simplex.len() - 1;
+        let mut centroid = vec![0.0; n];
+        for (vertex, _) in &simplex[..n] {
+            for (c, v) in centroid.iter_mut().zip(vertex) {
+                *c += v / n as f64;
+            }
+        }
+
+        let best = simplex[0].1;
+        let second_worst = simplex[n - 1].1;
+        let worst = simplex[n].1;
+
+        let reflected = self.along(simplex, &centroid, -1.0);
+        if reflected.1 > best {
+            let expanded = self.along(simplex, &centroid, -2.0);
+            simplex[n] = if expanded.1 > reflected.1 {
+                expanded
+            } else {
+                reflected
+            };
+        } else if reflected.1 > second_worst {
+            simplex[n] = reflected;
+        } else {
+            let contracted = if reflected.1 > worst {
+                self.along(simplex, &centroid, -0.5)
+            } else {
+                self.along(simplex, &centroid, 0.5)
+            };
+            if contracted.1 > worst.max(reflected.1) {
+                simplex[n] = contracted;
+            } else {
+                // shrink all vertices towards the best one
+                let best = simplex[0].0.clone();
+                for (vertex, value) in &mut simplex[1..] {
+                    *vertex = best
+                        .iter()
+                        .zip(vertex.iter())
+                        .map(|(b, v)| b + 0.5 * (v - b))
+                        .collect();
+                    *value = self.evaluate(vertex);
+                }
+            }
+        }
+        Self::sort(simplex);
+    }
+
+    /// Iterate until converged or `max_iterations` is reached, calling `callback` after every
+    /// iteration. Returning false from the callback stops early, e.g. when out of time.
+    pub fn run(&mut self, max_iterations: usize, mut callback: impl FnMut(FitEvent<'_>) -> bool) {
+        while !self.converged() && self.iteration < max_iterations {
+            let value = self.step();
+            let hyperparameters: Vec<f64> = self.best_point().0.iter().map(|p| p.exp()).collect();
+            let event = FitEvent {
+                iteration: self.iteration,
+                log_marginal_likelihood: value,
+                hyperparameters: &hyperparameters,
+            };
+            if !callback(event) {
+                break;
+            }
+        }
+    }
+
+    /// Whether further iterations would not improve the result noticeably.
+    pub fn converged(&self) -> bool {
+        match &self.search {
+            Search::NelderMead(simplex) => spread(simplex) < TOLERANCE,
+            Search::CmaEs(cma_es) => cma_es.converged(),
+        }
+    }
+
+    /// Number of iterations performed so far.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// The highest log marginal likelihood found so far.
+    pub fn best_value(&self) -> f64 {
+        self.best_point().1
+    }
+
+    /// The kernel and noise with the highest log marginal likelihood found so far.
+    pub fn best(&self) -> (K, f64) {
+        self.hyperparameters(&self.best_point().0)
+    }
+
+    fn best_point(&self) -> &(Vec<f64>, f64) {
+        match &self.search {
+            Search::NelderMead(simplex) => &simplex[0],
+            Search::CmaEs(cma_es) => &cma_es.best,
+        }
+    }
+}
+
+/// The difference between the highest and the lowest log marginal likelihood of the points.
+fn spread(points: &[(Vec<f64>, f64)]) -> f64 {
+    let values = points.iter().map(|(_, value)| *value);
+    values.clone().fold(f64::NEG_INFINITY, f64::max) - values.fold(f64::INFINITY, f64::min)
+}
+
+/// The state of the covariance matrix adaptation evolution strategy, maximizing a function in
+/// `n` dimensions with the default settings of Hansen (2016).
+struct CmaEs {
+    mean: na::DVector<f64>,
+    step_size: f64,
+    covariance: na::DMatrix<f64>,
+    /// The evolution paths of the step size and of the covariance.
+    path_sigma: na::DVector<f64>,
+    path_c: na::DVector<f64>,
+    /// The recombination weights of the best half of the samples, from the best.
+    weights: Vec<f64>,
+    population: usize,
+    generation: i32,
+    rng: rand::rngs::SmallRng,
+    /// The best point found so far and its value.
+    best: (Vec<f64>, f64),
+    /// The spread of the values of the last generation.
+    spread: f64,
+}
+
+impl CmaEs {
+    fn new(start: Vec<f64>, value: f64, step_size: f64, seed: u64) -> Self {
+        let n = start.len();
+        let population = 4 + (3.0 * (n as f64).ln()).floor() as usize;
+        let parents = population / 2;
+        let weights: Vec<f64> = (1..=parents)
+            .map(|i| (parents as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let total: f64 = weights.iter().sum();
+        Self {
+            mean: na::DVector::from_column_slice(&start),
+            step_size,
+            covariance: na::DMatrix::identity(n, n),
+            path_sigma: na::DVector::zeros(n),
+            path_c: na::DVector::zeros(n),
+            weights: weights.iter().map(|w| w / total).collect(),
+            population,
+            generation: 0,
+            rng: rand::rngs::SmallRng::seed_from_u64(seed),
+            best: (start, value),
+            spread: f64::INFINITY,
+        }
+    }
+
+    /// Whether the last generation had the same value everywhere and the steps became too small
+    /// to change the point noticeably. A generation on a plateau alone, e.g. with all
+    /// hyperparameters at their bounds, does not count.
+    fn converged(&self) -> bool {
+        let (_, scales) = self.decomposition();
+        self.spread < TOLERANCE && self.step_size * scales.max() < TOLERANCE.sqrt()
+    }
+
+    /// The eigenvectors of the covariance and the square roots of its eigenvalues.
+    fn decomposition(&self) -> (na::DMatrix<f64>, na::DVector<f64>) {
+        let symmetric = (&self.covariance + self.covariance.transpose()) * 0.5;
+        let eigen = symmetric.symmetric_eigen();
+        (
+            eigen.eigenvectors,
+            eigen.eigenvalues.map(|e| e.max(1e-20).sqrt()),
+        )
+    }
+
+    /// Draw the points of the next generation.
+    fn sample(&mut self) -> Vec<Vec<f64>> {
+        let (basis, scales) = self.decomposition();
+        let n = self.mean.len();
+        (0..self.population)
+            .map(|_| {
+                let z = na::DVector::from_fn(n, |_, _| {
+                    self.rng.sample::<f64, _>(rand_distr::StandardNormal)
+                });
+                let point = &self.mean + &basis * z.component_mul(&scales) * self.step_size;
+                point.data.into()
+            })
+            .collect()
+    }
+
+    /// Move the distribution towards the best of the evaluated points of a generation.
+    fn update(&mut self, mut evaluated: Vec<(Vec<f64>, f64)>) {
+        evaluated.sort_by(|a, b| b.1.total_cmp(&a.1));
+        self.spread = spread(&evaluated);
+        if evaluated[0].1 > self.best.1 {
+            self.best = evaluated[0].clone();
+        }
+        self.generation += 1;
+
+        let n = self.mean.len() as f64;
+        let mu_eff = 1.0 / self.weights.iter().map(|w| w * w).sum::<f64>();
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_1 = 2.0 / ((n + 1.3).powi(2) + mu_eff);
+        let c_mu =
+            (2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0).powi(2) + mu_eff)).min(1.0 - c_1);
+        let expected_norm = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        // the steps of the best points from the old mean, in units of the step size
+        let steps: Vec<na::DVector<f64>> = evaluated
+            .iter()
+            .take(self.weights.len())
+            .map(|(point, _)| (na::DVector::from_column_slice(point) - &self.mean) / self.step_size)
+            .collect();
+        let mean_step = steps
+            .iter()
+            .zip(&self.weights)
+            .fold(na::DVector::zeros(self.mean.len()), |sum, (step, w)| {
+                sum + step * *w
+            });
+        self.mean += &mean_step * self.step_size;
+
+        let (basis, scales) = self.decomposition();
+        let inverse_sqrt =
+            &basis * na::DMatrix::from_diagonal(&scales.map(|s| 1.0 / s)) * basis.transpose();
+        self.path_sigma = &self.path_sigma * (1.0 - c_sigma)
+            + inverse_sqrt * &mean_step * (c_sigma * (2.0 - c_sigma) * mu_eff).sqrt();
+        let correction = (1.0 - (1.0 - c_sigma).powi(2 * self.generation)).sqrt();
+        let stalled =
+            self.path_sigma.norm() / correction >= (1.4 + 2.0 / (n + 1.0)) * expected_norm;
+        let h_sigma = if stalled { 0.0 } else { 1.0 };
+        self.path_c = &self.path_c * (1.0 - c_c)
+            + &mean_step * (h_sigma * (c_c * (2.0 - c_c) * mu_eff).sqrt());
+
+        let rank_mu = steps.iter().zip(&self.weights).fold(
+            na::DMatrix::zeros(steps[0].len(), steps[0].len()),
+            |sum, (step, w)| sum + step * step.transpose() * *w,
+        );
+        self.covariance = &self.covariance
+            * (1.0 - c_1 - c_mu + (1.0 - h_sigma) * c_1 * c_c * (2.0 - c_c))
+            + &self.path_c * self.path_c.transpose() * c_1
+            + rank_mu * c_mu;
+        self.step_size *=
+            ((c_sigma / d_sigma) * (self.path_sigma.norm() / expected_norm - 1.0)).exp();
+    }
+}
+
+impl<K: GpKernel<I> + KernelParams + Clone, I: GpInput> GaussianProcess<K, I> {
+    /// Fit to new data, e.g. this data with a few points edited, optimizing the hyperparameters
+    /// starting from those of this fit (see [`HyperparameterOptimizer::warm_start`]). This is
+    /// much faster than optimizing from scratch when the data changed little.
+    ///
+    /// The targets are normalized with the same offset and scale as this fit, and the new fit
+    /// keeps its jitter. The new observations are weighted with `weights`, one for each (see
+    /// [`super::GaussianProcessBuilder::weights`]).
+    pub fn fit_incremental(
+        &self,
+        x: &[I],
+        y: &[f64],
+        weights: &[f64],
+        max_iterations: usize,
+    ) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        if weights.len() != x.len() || !weights.iter().all(|w| *w > 0.0) {
+            return Err(GpError::InvalidWeights);
+        }
+        let x = na::DVector::from_column_slice(x);
+        let y = na::DVector::from_column_slice(y).map(|y| (y - self.y_offset) / self.y_scale);
+        let weights = na::DVector::from_column_slice(weights);
+        let mut optimizer =
+            HyperparameterOptimizer::warm_start(&x, &y, self.kernel.clone(), self.noise_sigma)
+                .with_observation_noise(weights.clone(), self.jitter);
+        optimizer.run(max_iterations, |_| true);
+        let (kernel, noise_sigma) = optimizer.best();
+
+        let mut gp = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(noise_sigma)
+            .jitter(self.jitter)
+            .weights(weights)
+            .build(&x, &y)?;
+        gp.y_offset = self.y_offset;
+        gp.y_scale = self.y_scale;
+        Ok(gp)
+    }
+}
+
+/// The Adam method of gradient ascent (Kingma & Ba, 2015), for the parameters that are
+/// trained with gradients instead of the simplex method, such as the weights of a network.
+pub(super) struct Adam {
+    learning_rate: f64,
+    /// The moving averages of the gradient and its square.
+    first_moment: Vec<f64>,
+    second_moment: Vec<f64>,
+    iteration: i32,
+}
+
+impl Adam {
+    const BETA1: f64 = 0.9;
+    const BETA2: f64 = 0.999;
+
+    pub(super) fn new(learning_rate: f64, parameters: usize) -> Self {
+        Self {
+            learning_rate,
+            first_moment: vec![0.0; parameters],
+            second_moment: vec![0.0; parameters],
+            iteration: 0,
+        }
+    }
+
+    pub(super) fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    /// Move the parameters uphill along the gradient.
+    pub(super) fn step(&mut self, params: &mut [f64], gradient: &[f64]) {
+        self.iteration += 1;
+        let first_correction = 1.0 - Self::BETA1.powi(self.iteration);
+        let second_correction = 1.0 - Self::BETA2.powi(self.iteration);
+        for (i, g) in gradient.iter().enumerate() {
+            self.first_moment[i] = Self::BETA1 * self.first_moment[i] + (1.0 - Self::BETA1) * g;
+            self.second_moment[i] =
+                Self::BETA2 * self.second_moment[i] + (1.0 - Self::BETA2) * g * g;
+            let m = self.first_moment[i] / first_correction;
+            let v = self.second_moment[i] / second_correction;
+            params[i] += self.learning_rate * m / (v.sqrt() + 1e-8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{PeriodicKernel, RbfKernel, EPS};
+
+    #[test]
+    fn test_optimizer_improves_likelihood() {
+        let x = na::DVector::from_vec((0..20).map(|i| i as f64 * 0.5).collect());
+        let y = x.map(|x: f64| (x * 0.8).sin());
+        let kernel = RbfKernel {
+            sigma: 10.0,
+            length_scale: 0.05,
+        };
+        let initial = GaussianProcess::new(&x, &y, kernel.clone(), 1.0).log_marginal_likelihood();
+
+        let mut optimizer = HyperparameterOptimizer::new(&x, &y, kernel, 1.0);
+        let mut previous = optimizer.best_value();
+        optimizer.run(500, |event| {
+            assert!(event.log_marginal_likelihood >= previous);
+            assert_eq!(event.hyperparameters.len(), 3);
+            previous = event.log_marginal_likelihood;
+            true
+        });
+        assert!(optimizer.converged() || optimizer.iteration() == 500);
+
+        let (kernel, noise) = optimizer.best();
+        let optimized =
+            GaussianProcess::new(&x, &y, kernel.clone(), noise).log_marginal_likelihood();
+        assert!(optimized > initial + 10.0);
+        // a smooth noise free function needs a long length scale and little noise
+        assert!(kernel.length_scale > 0.5);
+        assert!(noise < 0.1);
+    }
+
+    #[test]
+    fn test_optimizer_uses_weights_and_jitter() {
+        let x = na::DVector::from_vec((0..20).map(|i| i as f64 * 0.5).collect());
+        let y = x.map(|x: f64| (x * 0.8).sin() + 0.1 * (x * 7.0).cos());
+        let weights = na::DVector::from_vec((0..20).map(|i| 1.0 + (i % 3) as f64).collect());
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+
+        let mut optimizer = HyperparameterOptimizer::new(&x, &y, kernel, 0.3)
+            .with_observation_noise(weights.clone(), 1e-4);
+        optimizer.run(500, |_| true);
+
+        // the optimum is that of the model fitted with the same weights and jitter
+        let (kernel, noise) = optimizer.best();
+        let fitted = |weights: na::DVector<f64>, jitter| {
+            GaussianProcess::builder()
+                .kernel(kernel.clone())
+                .noise(noise)
+                .jitter(jitter)
+                .weights(weights)
+                .build(&x, &y)
+                .unwrap()
+                .log_marginal_likelihood()
+        };
+        assert!((fitted(weights.clone(), 1e-4) - optimizer.best_value()).abs() < 1e-9);
+        assert!(
+            (fitted(na::DVector::from_element(20, 1.0), EPS) - optimizer.best_value()).abs() > 0.1
+        );
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let x: Vec<f64> = (0..30).map(|i| i as f64 * 0.3).collect();
+        // alternating offsets the smooth fit explains as noise, keeping the noise off its bound
+        let y: Vec<f64> = x
+            .iter()
+            .enumerate()
+            .map(|(i, x)| 2.0 + (x * 0.8).sin() + if i % 2 == 0 { 0.05 } else { -0.05 })
+            .collect();
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(0.3)
+            .normalize(true)
+            .build_from_slices(&x, &y)
+            .unwrap();
+        let ones = vec![1.0; x.len()];
+        let optimized = gp.fit_incremental(&x, &y, &ones, 1000).unwrap();
+
+        // nudge one point and refit, from the optimum and from scratch
+        let mut edited = y.clone();
+        edited[10] += 0.05;
+        let (x_vector, y_vector) = (
+            na::DVector::from_column_slice(&x),
+            na::DVector::from_column_slice(&edited).map(|y| (y - gp.y_offset) / gp.y_scale),
+        );
+        let (kernel, noise) = (optimized.kernel().clone(), optimized.noise_sigma());
+        let mut cold = HyperparameterOptimizer::new(&x_vector, &y_vector, kernel.clone(), noise);
+        cold.run(1000, |_| true);
+        let mut warm = HyperparameterOptimizer::warm_start(&x_vector, &y_vector, kernel, noise);
+        warm.run(1000, |_| true);
+        assert!(warm.converged() && cold.converged());
+        assert!(
+            warm.iteration() < cold.iteration(),
+            "{} >= {}",
+            warm.iteration(),
+            cold.iteration()
+        );
+        assert!((warm.best_value() - cold.best_value()).abs() < 1e-3);
+
+        let refit = optimized.fit_incremental(&x, &edited, &ones, 1000).unwrap();
+        assert!((refit.y_offset, refit.y_scale) == (gp.y_offset, gp.y_scale));
+        // the smoothed fit follows the nudge
+        let (mean, _) = refit.predict_slice(&[x[10]]);
+        let (before, _) = optimized.predict_slice(&[x[10]]);
+        assert!(mean[0] > before[0] && (mean[0] - edited[10]).abs() < 0.1);
+        assert!(gp.fit_incremental(&x, &edited[1..], &ones, 10).is_err());
+        assert!(gp.fit_incremental(&x, &edited, &ones[1..], 10).is_err());
+
+        // the refit keeps the jitter and uses the weights
+        let mut weights = ones.clone();
+        weights[10] = 100.0;
+        let jittered = GaussianProcess::builder()
+            .kernel(optimized.kernel().clone())
+            .noise(optimized.noise_sigma())
+            .jitter(1e-4)
+            .normalize(true)
+            .build_from_slices(&x, &y)
+            .unwrap();
+        let weighted = jittered
+            .fit_incremental(&x, &edited, &weights, 1000)
+            .unwrap();
+        assert_eq!(weighted.jitter(), 1e-4);
+        assert_eq!(weighted.weights()[10], 100.0);
+    }
+
+    #[test]
+    fn test_cma_es_escapes_local_maximum() {
+        let x: Vec<f64> = (0..25).map(|i| i as f64 * 0.5).collect();
+        let y: Vec<f64> = x
+            .iter()
+            .map(|x| (std::f64::consts::TAU * x / 3.0).sin())
+            .collect();
+        let (x, y) = (
+            na::DVector::from_column_slice(&x),
+            na::DVector::from_column_slice(&y),
+        );
+        let kernel = PeriodicKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+            period: 0.7,
+        };
+
+        // the simplex climbs to an alias of the period at the spacing of the inputs
+        let mut nelder_mead = HyperparameterOptimizer::new(&x, &y, kernel.clone(), 0.3);
+        nelder_mead.run(1000, |_| true);
+        assert!((nelder_mead.best().0.period - 3.0).abs() > 0.5);
+
+        let config = OptimizerConfig::CmaEs { seed: 0 };
+        let mut cma_es = HyperparameterOptimizer::with_config(&x, &y, kernel, 0.3, config);
+        let mut previous = cma_es.best_value();
+        cma_es.run(1000, |event| {
+            assert!(event.log_marginal_likelihood >= previous);
+            previous = event.log_marginal_likelihood;
+            true
+        });
+        assert!(cma_es.converged());
+        assert!(cma_es.best_value() > nelder_mead.best_value() + 5.0);
+        let (kernel, _) = cma_es.best();
+        assert!((kernel.period - 3.0).abs() < 0.01, "{kernel:?}");
+    }
+}
@@ -0,0 +1,98 @@
+use super::{GaussianProcess, GpInput, GpKernel};
+
+/// Apply `f` to every item, in parallel where rayon is available.
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+pub(super) fn parallel_map<T: Send, R: Send>(
+    items: Vec<T>,
+    f: impl Fn(T) -> R + Sync + Send,
+) -> Vec<R> {
+    use rayon::prelude::*;
+    items.into_par_iter().map(f).collect()
+}
+
+#[cfg(not(all(feature = "rayon", not(target_arch = "wasm32"))))]
+pub(super) fn parallel_map<T: Send, R: Send>(
+    items: Vec<T>,
+    f: impl Fn(T) -> R + Sync + Send,
+) -> Vec<R> {
+    items.into_iter().map(f).collect()
+}
+
+impl<K: GpKernel<I> + Sync, I: GpInput + Sync> GaussianProcess<K, I> {
+    /// Like [`Self::predict_slice`], but splitting the inputs between `threads` threads. A
+    /// fitted model is only read when predicting, so one model (e.g. in an `Arc`) can also
+    /// serve predictions from any number of threads of the caller.
+    ///
+    /// ```
+    /// use gaussian_processes::gp::{GaussianProcess, RbfKernel};
+    ///
+    /// let kernel = RbfKernel {
+    ///     sigma: 1.0,
+    ///     length_scale: 1.0,
+    /// };
+    /// let gp = GaussianProcess::from_slices(&[1.0, 2.0, 4.0], &[0.5, -1.0, 0.2], kernel, 0.1)?;
+    /// let x: Vec<f64> = (0..1000).map(|i| i as f64 / 100.0).collect();
+    /// assert_eq!(gp.predict_parallel(&x, 4), gp.predict_slice(&x));
+    /// # Ok::<(), gaussian_processes::gp::GpError>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn predict_parallel(&self, x: &[I], threads: usize) -> (Vec<f64>, Vec<f64>) {
+        if x.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+        let chunk_size = x.len().div_ceil(threads.max(1));
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = x
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(|| self.predict_slice(chunk)))
+                .collect();
+            let mut mean = Vec::with_capacity(x.len());
+            let mut variance = Vec::with_capacity(x.len());
+            for handle in handles {
+                let (m, v) = handle.join().expect("prediction should not panic");
+                mean.extend(m);
+                variance.extend(v);
+            }
+            (mean, variance)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::gp::{Kernel, RbfKernel};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_shared_between_threads() {
+        assert_send_sync::<GaussianProcess<Kernel>>();
+        assert_send_sync::<GaussianProcess<Box<dyn GpKernel + Send + Sync>>>();
+        assert_send_sync::<GaussianProcess<Kernel, [f64; 2]>>();
+
+        let kernel: Box<dyn GpKernel + Send + Sync> = Box::new(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        });
+        let gp = Arc::new(
+            GaussianProcess::from_slices(&[1.0, 2.0, 4.0], &[0.5, -1.0, 0.2], kernel, 0.1).unwrap(),
+        );
+        let expected = gp.predict_slice(&[0.0, 3.0]);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let gp = Arc::clone(&gp);
+                std::thread::spawn(move || gp.predict_slice(&[0.0, 3.0]))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+
+        let x: Vec<f64> = (0..101).map(|i| i as f64 / 10.0).collect();
+        assert_eq!(gp.predict_parallel(&x, 3), gp.predict_slice(&x));
+        assert_eq!(gp.predict_parallel(&[], 3), (Vec::new(), Vec::new()));
+    }
+}
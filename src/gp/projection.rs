@@ -0,0 +1,269 @@
+use nalgebra as na;
+
+use super::optimize::{Adam, PARAM_RANGE};
+use super::{FitEvent, GaussianProcess, GpError, GpKernel, KernelParams};
+
+/// The optimization has converged when an iteration improves the log marginal likelihood by
+/// less than this.
+const TOLERANCE: f64 = 1e-8;
+
+/// Step of the finite differences the gradient is estimated with.
+const STEP: f64 = 1e-6;
+
+/// A kernel on `N` dimensional inputs that projects them linearly to `M` dimensions before
+/// evaluating the base kernel, `k(x, x') = kernel(W x, W x')`.
+///
+/// With `M` smaller than `N` this is supervised dimensionality reduction: when only a few
+/// directions of the inputs matter, learning `W` with a [`ProjectionOptimizer`] finds them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectionKernel<K, const N: usize, const M: usize> {
+    pub projection: na::SMatrix<f64, M, N>,
+    pub kernel: K,
+}
+
+impl<K, const N: usize, const M: usize> ProjectionKernel<K, N, M> {
+    /// Project onto the first `M` inputs, a starting point for learning the projection.
+    pub fn new(kernel: K) -> Self {
+        Self {
+            projection: na::SMatrix::identity(),
+            kernel,
+        }
+    }
+
+    pub fn project(&self, x: [f64; N]) -> [f64; M] {
+        (self.projection * na::SVector::from(x)).into()
+    }
+}
+
+impl<K: GpKernel<[f64; M]>, const N: usize, const M: usize> GpKernel<[f64; N]>
+    for ProjectionKernel<K, N, M>
+{
+    fn compute(&self, x: [f64; N], x2: [f64; N]) -> f64 {
+        self.kernel.compute(self.project(x), self.project(x2))
+    }
+
+    /// Projects every input once instead of once per pair.
+    fn compute_matrix(
+        &self,
+        x: &na::DVector<[f64; N]>,
+        x2: &na::DVector<[f64; N]>,
+    ) -> na::DMatrix<f64> {
+        self.kernel
+            .compute_matrix(&x.map(|x| self.project(x)), &x2.map(|x| self.project(x)))
+    }
+}
+
+/// Learns the projection of a [`ProjectionKernel`] together with the hyperparameters of its
+/// base kernel and the noise, by gradient ascent (Adam) on the log marginal likelihood with
+/// the gradient estimated by finite differences.
+///
+/// Like [`super::HyperparameterOptimizer`], it is advanced one iteration at a time so the
+/// caller can show the progress and stop at any point.
+pub struct ProjectionOptimizer<K, const N: usize, const M: usize> {
+    kernel: ProjectionKernel<K, N, M>,
+    x: na::DVector<[f64; N]>,
+    y: na::DVector<f64>,
+    /// The parameters `[projection.., log kernel params.., log noise]`.
+    params: Vec<f64>,
+    adam: Adam,
+    value: f64,
+    improvement: f64,
+    iteration: usize,
+}
+
+impl<K, const N: usize, const M: usize> ProjectionOptimizer<K, N, M>
+where
+    K: GpKernel<[f64; M]> + KernelParams + Clone,
+{
+    pub fn new(
+        x: &[[f64; N]],
+        y: &[f64],
+        kernel: ProjectionKernel<K, N, M>,
+        noise_sigma: f64,
+    ) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        let mut base = kernel.kernel.clone();
+        let params: Vec<f64> = kernel
+            .projection
+            .iter()
+            .copied()
+            .chain(base.params_mut().into_iter().map(|p| *p))
+            .chain(std::iter::once(noise_sigma))
+            .enumerate()
+            .map(|(i, p)| {
+                if i < M * N {
+                    p
+                } else {
+                    p.clamp(PARAM_RANGE.0, PARAM_RANGE.1).ln()
+                }
+            })
+            .collect();
+        let optimizer = Self {
+            kernel,
+            x: na::DVector::from_column_slice(x),
+            y: na::DVector::from_column_slice(y),
+            adam: Adam::new(0.01, params.len()),
+            params,
+            value: f64::NEG_INFINITY,
+            improvement: f64::INFINITY,
+            iteration: 0,
+        };
+        // fail early if the covariance matrix of the initial kernel is singular
+        optimizer.evaluate(&optimizer.params)?;
+        Ok(optimizer)
+    }
+
+    /// The step size of the gradient ascent, 0.01 by default.
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.adam.set_learning_rate(learning_rate);
+        self
+    }
+
+    /// The kernel and noise with the given parameters.
+    fn hyperparameters(&self, params: &[f64]) -> (ProjectionKernel<K, N, M>, f64) {
+        let mut kernel = self.kernel.clone();
+        kernel.projection = na::SMatrix::from_column_slice(&params[..M * N]);
+        for (param, value) in kernel.kernel.params_mut().into_iter().zip(&params[M * N..]) {
+            *param = value.exp();
+        }
+        (kernel, params[params.len() - 1].exp())
+    }
+
+    fn evaluate(&self, params: &[f64]) -> Result<f64, GpError> {
+        let (kernel, noise) = self.hyperparameters(params);
+        let gp = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(noise)
+            .build(&self.x, &self.y)?;
+        Ok(gp.log_marginal_likelihood())
+    }
+
+    /// Perform one step of gradient ascent, returning the log marginal likelihood before it.
+    pub fn step(&mut self) -> f64 {
+        let Ok(value) = self.evaluate(&self.params) else {
+            // the last step made the covariance matrix singular, so stop here
+            self.improvement = 0.0;
+            return self.value;
+        };
+        let mut shifted = self.params.clone();
+        let gradient: Vec<f64> = (0..self.params.len())
+            .map(|i| {
+                shifted[i] += STEP;
+                let derivative = self
+                    .evaluate(&shifted)
+                    .map_or(0.0, |shifted| (shifted - value) / STEP);
+                shifted[i] = self.params[i];
+                derivative
+            })
+            .collect();
+
+        self.improvement = value - self.value;
+        self.value = value;
+        self.iteration += 1;
+        self.adam.step(&mut self.params, &gradient);
+        let n = self.params.len();
+        Self::clamp(&mut self.params[M * N..n]);
+        value
+    }
+
+    /// Log hyperparameters, clamped to the allowed range.
+    fn clamp(params: &mut [f64]) {
+        for p in params {
+            *p = p.clamp(PARAM_RANGE.0.ln(), PARAM_RANGE.1.ln());
+        }
+    }
+
+    /// Iterate until converged or `max_iterations` is reached, calling `callback` after every
+    /// iteration with the hyperparameters of the base kernel and the noise. Returning false
+    /// from the callback stops early.
+    pub fn run(&mut self, max_iterations: usize, mut callback: impl FnMut(FitEvent<'_>) -> bool) {
+        while !self.converged() && self.iteration < max_iterations {
+            let value = self.step();
+            let hyperparameters: Vec<f64> = self.params[M * N..].iter().map(|p| p.exp()).collect();
+            let event = FitEvent {
+                iteration: self.iteration,
+                log_marginal_likelihood: value,
+                hyperparameters: &hyperparameters,
+            };
+            if !callback(event) {
+                break;
+            }
+        }
+    }
+
+    /// Whether the last iteration did not improve the result noticeably.
+    pub fn converged(&self) -> bool {
+        self.improvement.abs() < TOLERANCE
+    }
+
+    /// The log marginal likelihood with the current parameters.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.evaluate(&self.params).unwrap_or(f64::NEG_INFINITY)
+    }
+
+    /// The learned kernel and noise.
+    pub fn best(&self) -> (ProjectionKernel<K, N, M>, f64) {
+        self.hyperparameters(&self.params)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+
+    #[test]
+    fn test_projection_kernel_compute() {
+        let mut kernel = ProjectionKernel::<_, 3, 1>::new(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        });
+        kernel.projection = na::SMatrix::from_row_slice(&[0.0, 2.0, 0.0]);
+        // only the second input matters, scaled by two
+        assert!(
+            (kernel.compute([5.0, 1.0, -3.0], [0.0, 1.5, 8.0]) - (-0.5f64).exp()).abs() < 1e-12
+        );
+    }
+
+    #[test]
+    fn test_projection_optimizer_finds_direction() {
+        // five inputs, but the target only depends on the fourth one
+        let x: Vec<[f64; 5]> = (0..30)
+            .map(|i| {
+                let i = i as f64;
+                [
+                    (i * 0.37).sin() * 3.0,
+                    (i * 0.91).cos() * 3.0,
+                    (i * 1.3).sin() * 3.0,
+                    (i * 0.53).cos() * 3.0,
+                    (i * 2.1).sin() * 3.0,
+                ]
+            })
+            .collect();
+        let y: Vec<f64> = x.iter().map(|x| x[3].sin()).collect();
+        let mut kernel = ProjectionKernel::<_, 5, 1>::new(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        });
+        kernel.projection = na::SMatrix::from_row_slice(&[0.2; 5]);
+
+        let mut optimizer = ProjectionOptimizer::new(&x, &y, kernel, 0.3)
+            .unwrap()
+            .learning_rate(0.1);
+        let start = optimizer.log_marginal_likelihood();
+        optimizer.run(100, |event| event.hyperparameters.len() == 3);
+        assert!(optimizer.log_marginal_likelihood() > start + 10.0);
+
+        let (kernel, _) = optimizer.best();
+        let weights = kernel.projection / kernel.kernel.length_scale;
+        let relevant = weights[3].abs();
+        for i in [0, 1, 2, 4] {
+            assert!(weights[i].abs() < 0.5 * relevant, "{weights}");
+        }
+    }
+}
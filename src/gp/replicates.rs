@@ -0,0 +1,87 @@
+use super::GpInput;
+
+/// The observation noise estimated from repeated measurements at (nearly) the same input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplicateNoise {
+    /// The pooled within-group variance of the targets, an estimate of the noise variance
+    /// that does not depend on the kernel.
+    pub variance: f64,
+    /// The number of groups with more than one observation.
+    pub groups: usize,
+    /// The degrees of freedom of the estimate, the number of replicated observations minus
+    /// the number of groups. More give a more reliable estimate.
+    pub degrees_of_freedom: usize,
+}
+
+/// Estimate the noise variance from the replicates in the data: the observations are grouped
+/// by input, joining a group when within `tolerance` of its first input, and the variances of
+/// the targets within the groups are pooled. Returns `None` when no input is repeated.
+///
+/// The result can be used as the noise of a GP directly, e.g. to fix it while optimizing the
+/// other hyperparameters.
+///
+/// ```
+/// use gaussian_processes::gp::replicate_noise;
+///
+/// let x = [0.0, 0.0, 1.0, 2.0, 2.0];
+/// let y = [1.0, 3.0, 5.0, 0.0, 2.0];
+/// let noise = replicate_noise(&x, &y, 0.0).unwrap();
+/// assert_eq!(noise.variance, 2.0);
+/// assert_eq!((noise.groups, noise.degrees_of_freedom), (2, 2));
+/// ```
+pub fn replicate_noise<I: GpInput>(x: &[I], y: &[f64], tolerance: f64) -> Option<ReplicateNoise> {
+    // the first input of each group and the indices of its observations
+    let mut groups: Vec<(I, Vec<usize>)> = Vec::new();
+    for (i, x) in x.iter().enumerate() {
+        match groups
+            .iter_mut()
+            .find(|(first, _)| first.distance(x) <= tolerance)
+        {
+            Some((_, members)) => members.push(i),
+            None => groups.push((*x, vec![i])),
+        }
+    }
+
+    let mut sum_of_squares = 0.0;
+    let mut replicated = 0;
+    let mut degrees_of_freedom = 0;
+    for (_, members) in groups.iter().filter(|(_, members)| members.len() > 1) {
+        let mean = members.iter().map(|i| y[*i]).sum::<f64>() / members.len() as f64;
+        sum_of_squares += members.iter().map(|i| (y[*i] - mean).powi(2)).sum::<f64>();
+        replicated += 1;
+        degrees_of_freedom += members.len() - 1;
+    }
+    (degrees_of_freedom > 0).then(|| ReplicateNoise {
+        variance: sum_of_squares / degrees_of_freedom as f64,
+        groups: replicated,
+        degrees_of_freedom,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_replicate_noise() {
+        assert_eq!(replicate_noise(&[0.0, 1.0], &[0.0, 1.0], 0.0), None);
+
+        // near-replicates of a smooth function with a noise variance of 0.25
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(2);
+        let (x, y): (Vec<f64>, Vec<f64>) = (0..2000)
+            .map(|i| {
+                let x = (i / 4) as f64 + 0.001 * (i % 4) as f64;
+                let noise: f64 = rng.sample(rand_distr::StandardNormal);
+                (x, x.sin() + 0.5 * noise)
+            })
+            .unzip();
+        let noise = replicate_noise(&x, &y, 0.01).unwrap();
+        assert_eq!(noise.groups, 500);
+        assert_eq!(noise.degrees_of_freedom, 1500);
+        assert!((noise.variance - 0.25).abs() < 0.03, "{noise:?}");
+
+        // without the tolerance nothing is replicated
+        assert_eq!(replicate_noise(&x, &y, 0.0), None);
+    }
+}
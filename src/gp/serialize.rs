@@ -0,0 +1,294 @@
+use std::path::Path;
+
+use nalgebra as na;
+
+use super::builder::covariance_inverse;
+use super::{GaussianProcess, GpInput, GpKernel, EPS};
+
+/// The version of the model files written by [`GaussianProcess::save`], to be increased when
+/// the saved fields change so that older files are migrated or rejected instead of misread.
+/// Version 1 saved the variance of the noise where version 2 saves its standard deviation.
+pub const MODEL_FORMAT_VERSION: u32 = 2;
+
+/// Errors from saving or loading a model file.
+#[derive(Debug)]
+pub enum ModelFileError {
+    Io(std::io::Error),
+    /// The file is not a valid model, or a model with another kernel or input type.
+    Format(String),
+    /// The file was written with a format version this version cannot read, e.g. by a newer
+    /// version.
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+}
+
+impl std::fmt::Display for ModelFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelFileError::Io(error) => write!(f, "{error}"),
+            ModelFileError::Format(error) => write!(f, "invalid model file: {error}"),
+            ModelFileError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "the model file has format version {found}, but only versions up to {supported} \
+                 are supported"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ModelFileError {}
+
+impl From<std::io::Error> for ModelFileError {
+    fn from(error: std::io::Error) -> Self {
+        ModelFileError::Io(error)
+    }
+}
+
+/// A model file, a fitted Gaussian process tagged with the format version.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct ModelFile<T> {
+    version: u32,
+    model: T,
+}
+
+/// Just the version of a model file, read before the rest to give a clear error for files of
+/// other versions.
+#[derive(serde::Deserialize)]
+struct ModelFileVersion {
+    version: u32,
+}
+
+fn default_jitter() -> f64 {
+    EPS
+}
+
+/// What is saved of a fitted Gaussian process. The inverse covariance matrix is recomputed
+/// when loading, which is cheaper to store and cannot go out of sync with the data.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct SavedGaussianProcess<K, I> {
+    kernel: K,
+    x: Vec<I>,
+    /// The normalized targets.
+    y: Vec<f64>,
+    /// The standard deviation of the noise, which version 1 model files and app state from
+    /// before the jitter was separate saved the variance of instead.
+    noise_sigma: f64,
+    /// Missing in files of models fit before the jitter could be set.
+    #[serde(default = "default_jitter")]
+    jitter: f64,
+    /// Missing in files of models fit before observations had weights, which are then all one.
+    #[serde(default)]
+    weights: Option<Vec<f64>>,
+    y_offset: f64,
+    y_scale: f64,
+}
+
+impl<K, I> serde::Serialize for GaussianProcess<K, I>
+where
+    K: GpKernel<I> + serde::Serialize,
+    I: GpInput + serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedGaussianProcess {
+            kernel: &self.kernel,
+            x: self.x.as_slice().to_vec(),
+            y: self.y.as_slice().to_vec(),
+            noise_sigma: self.noise_sigma,
+            jitter: self.jitter,
+            weights: Some(self.weights.as_slice().to_vec()),
+            y_offset: self.y_offset,
+            y_scale: self.y_scale,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, K, I> serde::Deserialize<'de> for GaussianProcess<K, I>
+where
+    K: GpKernel<I> + serde::Deserialize<'de>,
+    I: GpInput + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SavedGaussianProcess::<K, I>::deserialize(deserializer)?
+            .fit()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl<K: GpKernel<I>, I: GpInput> SavedGaussianProcess<K, I> {
+    /// The Gaussian process, with its inverse covariance matrix recomputed.
+    fn fit(self) -> Result<GaussianProcess<K, I>, super::GpError> {
+        if self.x.len() != self.y.len() {
+            return Err(super::GpError::LengthMismatch {
+                x: self.x.len(),
+                y: self.y.len(),
+            });
+        }
+
+        let x = na::DVector::from_vec(self.x);
+        let weights = match self.weights {
+            Some(weights) if weights.len() == x.len() => na::DVector::from_vec(weights),
+            Some(_) => return Err(super::GpError::InvalidWeights),
+            None => na::DVector::from_element(x.len(), 1.0),
+        };
+        let input_cov_matrix_inv =
+            covariance_inverse(&self.kernel, &x, self.noise_sigma, self.jitter, &weights)?;
+        Ok(GaussianProcess {
+            kernel: self.kernel,
+            x,
+            y: na::DVector::from_vec(self.y),
+            noise_sigma: self.noise_sigma,
+            jitter: self.jitter,
+            weights,
+            y_offset: self.y_offset,
+            y_scale: self.y_scale,
+            input_cov_matrix_inv,
+        })
+    }
+}
+
+impl<K, I> GaussianProcess<K, I>
+where
+    K: GpKernel<I> + serde::Serialize + serde::de::DeserializeOwned,
+    I: GpInput + serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Write the model to a versioned RON file, with the kernel, the training data and the
+    /// normalization of the targets.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ModelFileError> {
+        let file = ModelFile {
+            version: MODEL_FORMAT_VERSION,
+            model: self,
+        };
+        let text = ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())
+            .map_err(|e| ModelFileError::Format(e.to_string()))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Read a model written by [`Self::save`], also by earlier versions.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ModelFileError> {
+        Self::from_model_file(&std::fs::read_to_string(path)?)
+    }
+
+    fn from_model_file(text: &str) -> Result<Self, ModelFileError> {
+        let format = |e: ron::error::SpannedError| ModelFileError::Format(e.to_string());
+        let ModelFileVersion { version } = ron::from_str(text).map_err(format)?;
+        match version {
+            MODEL_FORMAT_VERSION => {
+                let file: ModelFile<Self> = ron::from_str(text).map_err(format)?;
+                Ok(file.model)
+            }
+            1 => {
+                let file: ModelFile<SavedGaussianProcess<K, I>> =
+                    ron::from_str(text).map_err(format)?;
+                let mut saved = file.model;
+                saved.noise_sigma = saved.noise_sigma.max(0.0).sqrt();
+                saved
+                    .fit()
+                    .map_err(|e| ModelFileError::Format(e.to_string()))
+            }
+            _ => Err(ModelFileError::UnsupportedVersion {
+                found: version,
+                supported: MODEL_FORMAT_VERSION,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{Kernel, RbfKernel};
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let x = na::DVector::from_vec(vec![1.0, 2.0, 6.0]);
+        let y = na::DVector::from_vec(vec![10.0, 11.0, 8.0]);
+        let kernel = Kernel::Rbf(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.5,
+        });
+        let gp = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(0.1)
+            .normalize(true)
+            .build(&x, &y)
+            .unwrap();
+
+        let text = ron::to_string(&gp).unwrap();
+        let loaded: GaussianProcess<Kernel> = ron::from_str(&text).unwrap();
+        let test = na::DVector::from_vec(vec![0.0, 1.5, 4.0, 10.0]);
+        let (mean, variance) = gp.predict(&test);
+        let (loaded_mean, loaded_variance) = loaded.predict(&test);
+        assert!((mean - loaded_mean).abs().max() < 1e-9);
+        assert!((variance - loaded_variance).abs().max() < 1e-9);
+        assert_eq!(
+            gp.log_marginal_likelihood(),
+            loaded.log_marginal_likelihood()
+        );
+
+        let mismatched = text.replacen("x:[1.0,", "x:[", 1);
+        assert!(ron::from_str::<GaussianProcess<Kernel>>(&mismatched).is_err());
+    }
+
+    #[test]
+    fn test_model_file() {
+        let x = na::DVector::from_vec(vec![1.0, 2.0, 6.0]);
+        let y = na::DVector::from_vec(vec![10.0, 11.0, 8.0]);
+        let kernel = Kernel::Sum(vec![
+            Kernel::Rbf(RbfKernel {
+                sigma: 1.0,
+                length_scale: 1.5,
+            }),
+            Kernel::Rbf(RbfKernel {
+                sigma: 0.5,
+                length_scale: 10.0,
+            }),
+        ]);
+        let gp = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(0.1)
+            .normalize(true)
+            .build(&x, &y)
+            .unwrap();
+
+        let path = std::env::temp_dir().join("gaussian_processes_test_model.ron");
+        gp.save(&path).unwrap();
+        let loaded = GaussianProcess::<Kernel>::load(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let test = na::DVector::from_vec(vec![0.0, 4.0]);
+        assert!((gp.predict(&test).0 - loaded.predict(&test).0).abs().max() < 1e-9);
+
+        // version 1 saved the variance of the noise
+        let version_1 = text.replacen("version: 2", "version: 1", 1).replacen(
+            "noise_sigma: 0.1,",
+            "noise_sigma: 0.01,",
+            1,
+        );
+        assert!(version_1.contains("noise_sigma: 0.01,"));
+        let migrated = GaussianProcess::<Kernel>::from_model_file(&version_1).unwrap();
+        assert!((migrated.noise_sigma() - 0.1).abs() < 1e-12);
+        assert!(
+            (gp.predict(&test).1 - migrated.predict(&test).1)
+                .abs()
+                .max()
+                < 1e-9
+        );
+
+        let newer = text.replacen("version: 2", "version: 3", 1);
+        assert!(matches!(
+            GaussianProcess::<Kernel>::from_model_file(&newer),
+            Err(ModelFileError::UnsupportedVersion {
+                found: 3,
+                supported: 2
+            })
+        ));
+        assert!(matches!(
+            GaussianProcess::<Kernel, [f64; 2]>::from_model_file(&text),
+            Err(ModelFileError::Format(_))
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+use nalgebra as na;
+
+use super::{
+    init_heuristics, GaussianProcess, GpError, GpKernel, HyperparameterOptimizer, Kernel, RbfKernel,
+};
+
+impl<K: GpKernel<f64>> GaussianProcess<K, f64> {
+    /// Predict the mean and variance of the derivative of the latent function at the inputs.
+    ///
+    /// The derivative of a GP is itself a GP, whose covariances are the derivatives of the
+    /// kernel. These are approximated with central differences, so any kernel works, but the
+    /// result is only meaningful for differentiable functions: the Matérn 1/2 kernel gives
+    /// functions without a derivative and an exploding variance.
+    pub fn predict_derivative(&self, x: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        // small relative to the data, as that is what the length scales are fit to
+        let span = self.x.max() - self.x.min();
+        let h = 1e-5 * if span > 0.0 { span } else { 1.0 };
+        let k = |a: f64, b: f64| self.kernel.compute(a, b);
+        let alpha = &self.input_cov_matrix_inv * &self.y;
+
+        x.iter()
+            .map(|&x| {
+                // the covariance between the derivative at x and the training points
+                let dk = self.x.map(|xi| (k(x + h, xi) - k(x - h, xi)) / (2.0 * h));
+                let mean = dk.dot(&alpha);
+                // the prior variance of the derivative
+                let ddk = (k(x + h, x + h) - k(x + h, x - h) - k(x - h, x + h) + k(x - h, x - h))
+                    / (4.0 * h * h);
+                let variance = ddk - dk.dot(&(&self.input_cov_matrix_inv * &dk)) + self.jitter;
+                (
+                    self.y_scale * mean,
+                    variance.max(0.0) * self.y_scale.powi(2),
+                )
+            })
+            .unzip()
+    }
+}
+
+/// The settings of [`smooth`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmoothOptions {
+    /// The kernel to start the optimization from, for the standardized targets. By default an
+    /// RBF kernel with the length scale from [`init_heuristics`].
+    pub kernel: Option<Kernel>,
+    /// The standard deviation of the noise to start the optimization from, for the standardized
+    /// targets.
+    pub noise_sigma: f64,
+    /// The maximum number of iterations of the hyperparameter optimization, 0 to use the kernel
+    /// and noise as they are.
+    pub max_iterations: usize,
+}
+
+impl Default for SmoothOptions {
+    fn default() -> Self {
+        Self {
+            kernel: None,
+            noise_sigma: 0.1,
+            max_iterations: 1000,
+        }
+    }
+}
+
+/// The result of [`smooth`], at each input.
+pub struct Smoothed {
+    /// The mean and standard deviation of the smoothed function.
+    pub value: Vec<f64>,
+    pub value_std: Vec<f64>,
+    /// The mean and standard deviation of its derivative.
+    pub derivative: Vec<f64>,
+    pub derivative_std: Vec<f64>,
+    /// The fitted GP, with the optimized hyperparameters.
+    pub gp: GaussianProcess<Kernel>,
+}
+
+/// Smooth noisy observations and estimate their derivative, with uncertainties.
+///
+/// The targets are standardized, the kernel hyperparameters and the noise are optimized for
+/// the maximum log marginal likelihood, and the fitted GP is evaluated at the inputs. This is
+/// a principled smoother that picks its own amount of smoothing.
+///
+/// ```
+/// use gaussian_processes::gp::{smooth, SmoothOptions};
+///
+/// let x: Vec<f64> = (0..50).map(|i| i as f64 * 0.2).collect();
+/// let y: Vec<f64> = x.iter().map(|x| 3.0 * x.sin() + 10.0).collect();
+/// let smoothed = smooth(&x, &y, &SmoothOptions::default())?;
+/// assert!((smoothed.value[20] - y[20]).abs() < 0.05);
+/// assert!((smoothed.derivative[20] - 3.0 * x[20].cos()).abs() < 0.1);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub fn smooth(x: &[f64], y: &[f64], options: &SmoothOptions) -> Result<Smoothed, GpError> {
+    if x.len() != y.len() {
+        return Err(GpError::LengthMismatch {
+            x: x.len(),
+            y: y.len(),
+        });
+    }
+    let kernel = options.kernel.clone().unwrap_or_else(|| {
+        Kernel::Rbf(RbfKernel {
+            sigma: 1.0,
+            length_scale: init_heuristics(x, y).length_scale,
+        })
+    });
+
+    // optimize for the standardized targets, as the normalized GP sees them
+    let y = na::DVector::from_column_slice(y);
+    let mean = y.mean();
+    let std = y.map(|y| (y - mean).powi(2)).mean().sqrt();
+    let standardized = y.map(|y| (y - mean) / if std > 0.0 { std } else { 1.0 });
+    let x = na::DVector::from_column_slice(x);
+    let mut optimizer =
+        HyperparameterOptimizer::new(&x, &standardized, kernel, options.noise_sigma);
+    optimizer.run(options.max_iterations, |_| true);
+    let (kernel, noise_sigma) = optimizer.best();
+
+    let gp = GaussianProcess::builder()
+        .kernel(kernel)
+        .noise(noise_sigma)
+        .normalize(true)
+        .build(&x, &y)?;
+    let (value, variance) = gp.predict(&x);
+    let (derivative, derivative_variance) = gp.predict_derivative(x.as_slice());
+    Ok(Smoothed {
+        value: value.data.into(),
+        value_std: variance.iter().map(|v| v.sqrt()).collect(),
+        derivative,
+        derivative_std: derivative_variance.iter().map(|v| v.sqrt()).collect(),
+        gp,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_predict_derivative() {
+        // the derivative of the posterior mean, and the exact derivative variance of the RBF
+        let x = [0.0, 0.5, 1.2, 2.0, 3.1];
+        let y = [0.0, 0.4, 0.9, 0.9, 0.0];
+        let kernel = RbfKernel {
+            sigma: 1.5,
+            length_scale: 0.7,
+        };
+        let gp = GaussianProcess::from_slices(&x, &y, kernel.clone(), 0.01).unwrap();
+        let test = [0.8, 2.5, 20.0];
+        let (mean, variance) = gp.predict_derivative(&test);
+        for i in 0..test.len() {
+            let h = 1e-4;
+            let (ahead, _) = gp.predict_slice(&[test[i] + h]);
+            let (behind, _) = gp.predict_slice(&[test[i] - h]);
+            let expected = (ahead[0] - behind[0]) / (2.0 * h);
+            assert!((mean[i] - expected).abs() < 1e-5, "{mean:?}");
+            assert!(variance[i] > 0.0);
+        }
+        // far from the data, the prior variance of the derivative: sigma / length_scale^2
+        assert!((variance[2] - 1.5 / 0.49).abs() < 1e-3, "{variance:?}");
+    }
+
+    #[test]
+    fn test_smooth() {
+        let x: Vec<f64> = (0..80).map(|i| i as f64 * 0.1).collect();
+        let noise = [0.05, -0.08, 0.02, 0.07, -0.04];
+        let y: Vec<f64> = x
+            .iter()
+            .enumerate()
+            .map(|(i, x)| 100.0 + 5.0 * (0.8 * x).sin() + noise[i % noise.len()])
+            .collect();
+        let smoothed = smooth(&x, &y, &SmoothOptions::default()).unwrap();
+        assert_eq!(smoothed.value.len(), x.len());
+        for i in (5..75).step_by(10) {
+            let slope = 4.0 * (0.8 * x[i]).cos();
+            assert!((smoothed.value[i] - (y[i] - noise[i % noise.len()])).abs() < 0.1);
+            assert!((smoothed.derivative[i] - slope).abs() < 0.3, "{i}");
+            assert!(smoothed.derivative_std[i] > 0.0 && smoothed.derivative_std[i] < 0.3);
+        }
+
+        let fixed = SmoothOptions {
+            max_iterations: 0,
+            ..Default::default()
+        };
+        let unoptimized = smooth(&x, &y, &fixed).unwrap();
+        assert!(smoothed.gp.log_marginal_likelihood() > unoptimized.gp.log_marginal_likelihood());
+        assert!(smooth(&x, &y[1..], &fixed).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use super::{Kernel, PeriodicKernel};
+
+/// How many frequencies the periodogram evaluates per the lowest frequency, so that the peaks
+/// are not missed between them.
+const OVERSAMPLING: f64 = 5.0;
+
+/// The Lomb-Scargle periodogram of unevenly sampled data (Lomb, 1976; Scargle, 1982): the power
+/// of the best fitting sinusoid at each frequency, normalized by the variance of the data.
+///
+/// The mean of the targets is removed first. For evenly sampled data this is the classical
+/// periodogram computed by an FFT.
+pub fn lomb_scargle(x: &[f64], y: &[f64], frequencies: &[f64]) -> Vec<f64> {
+    let n = y.len().max(1) as f64;
+    let mean = y.iter().sum::<f64>() / n;
+    let variance = y.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / n;
+    frequencies
+        .iter()
+        .map(|frequency| {
+            let omega = std::f64::consts::TAU * frequency;
+            // the offset that makes the sine and cosine terms independent
+            let (sin2, cos2) = x.iter().fold((0.0, 0.0), |(s, c), x| {
+                let (sin, cos) = (2.0 * omega * x).sin_cos();
+                (s + sin, c + cos)
+            });
+            let tau = sin2.atan2(cos2) / (2.0 * omega);
+
+            let (mut yc, mut ys, mut cc, mut ss) = (0.0, 0.0, 0.0, 0.0);
+            for (x, y) in x.iter().zip(y) {
+                let (sin, cos) = (omega * (x - tau)).sin_cos();
+                yc += (y - mean) * cos;
+                ys += (y - mean) * sin;
+                cc += cos * cos;
+                ss += sin * sin;
+            }
+            if variance > 0.0 && cc > 0.0 && ss > 0.0 {
+                0.5 * (yc * yc / cc + ys * ys / ss) / variance
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// The periods of the (at most) `count` strongest peaks of the Lomb-Scargle periodogram, from
+/// the strongest.
+///
+/// A straight line is fit and removed first, so a trend does not show up as a long period. The
+/// periods searched range from the mean spacing of the inputs to their range.
+///
+/// ```
+/// use gaussian_processes::gp::dominant_periods;
+/// use std::f64::consts::TAU;
+///
+/// let x: Vec<f64> = (0..100).map(|i| i as f64 * 0.3 + (i as f64).sin() * 0.1).collect();
+/// let y: Vec<f64> = x.iter().map(|x| (TAU * x / 2.5).sin() + 0.1 * x).collect();
+/// let periods = dominant_periods(&x, &y, 1);
+/// assert!((periods[0] - 2.5).abs() < 0.05);
+/// ```
+pub fn dominant_periods(x: &[f64], y: &[f64], count: usize) -> Vec<f64> {
+    let n = x.len().min(y.len());
+    if n < 3 {
+        return Vec::new();
+    }
+    let (x, y) = (&x[..n], &y[..n]);
+    let min = x.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let span = max - min;
+    if span <= 0.0 {
+        return Vec::new();
+    }
+
+    // remove the least squares line
+    let x_mean = x.iter().sum::<f64>() / n as f64;
+    let y_mean = y.iter().sum::<f64>() / n as f64;
+    let covariance: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| (x - x_mean) * (y - y_mean))
+        .sum();
+    let slope = covariance / x.iter().map(|x| (x - x_mean).powi(2)).sum::<f64>();
+    let detrended: Vec<f64> = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| y - y_mean - slope * (x - x_mean))
+        .collect();
+
+    // from one cycle over the data up to the Nyquist frequency of the mean spacing
+    let step = 1.0 / (OVERSAMPLING * span);
+    let highest = 0.5 * (n - 1) as f64 / span;
+    let frequencies: Vec<f64> = (1..)
+        .map(|i| i as f64 * step)
+        .take_while(|f| *f <= highest)
+        .collect();
+    let power = lomb_scargle(x, &detrended, &frequencies);
+
+    let mut peaks: Vec<usize> = (0..power.len())
+        .filter(|i| {
+            let left = i.checked_sub(1).map_or(0.0, |j| power[j]);
+            let right = power.get(i + 1).copied().unwrap_or(0.0);
+            power[*i] > left && power[*i] >= right
+        })
+        .collect();
+    peaks.sort_by(|a, b| power[*b].total_cmp(&power[*a]));
+    peaks
+        .into_iter()
+        .take(count)
+        .map(|i| 1.0 / frequencies[i])
+        .collect()
+}
+
+impl Kernel {
+    /// The periodic kernels among the terms of the kernel, in order.
+    fn periodic_kernels_mut(&mut self) -> Vec<&mut PeriodicKernel> {
+        match self {
+            Kernel::Periodic(k) => vec![k],
+            Kernel::Sum(kernels) | Kernel::Product(kernels) => kernels
+                .iter_mut()
+                .flat_map(Kernel::periodic_kernels_mut)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Set the periods of the periodic terms of the kernel to the dominant periods of the data,
+    /// the first term to the strongest one and so on, as a starting point for optimizing them.
+    ///
+    /// The log marginal likelihood has many local maxima in the period, so the optimizer
+    /// rarely finds the right period from a poor one. Returns false if the kernel has no
+    /// periodic terms or no period was found.
+    pub fn init_periods(&mut self, x: &[f64], y: &[f64]) -> bool {
+        let mut kernels = self.periodic_kernels_mut();
+        let periods = dominant_periods(x, y, kernels.len());
+        for (kernel, period) in kernels.iter_mut().zip(&periods) {
+            kernel.period = *period;
+        }
+        !periods.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::RbfKernel;
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn test_lomb_scargle() {
+        // whole cycles of a sinusoid have all their power, half the number of points, at its
+        // frequency
+        let x: Vec<f64> = (0..200).map(|i| i as f64 * 0.1).collect();
+        let y: Vec<f64> = x.iter().map(|x| (TAU * 0.5 * x).sin()).collect();
+        let power = lomb_scargle(&x, &y, &[0.25, 0.5, 1.0]);
+        assert!((power[1] - 100.0).abs() < 1.0, "{power:?}");
+        assert!(power[0] < 1.0 && power[2] < 1.0);
+        assert_eq!(lomb_scargle(&x, &vec![1.0; x.len()], &[0.5]), vec![0.0]);
+    }
+
+    #[test]
+    fn test_init_periods() {
+        // unevenly sampled, two periods and a trend
+        let x: Vec<f64> = (0..300)
+            .map(|i| i as f64 * 0.1 + 0.04 * (i as f64 * 1.7).sin())
+            .collect();
+        let y: Vec<f64> = x
+            .iter()
+            .map(|x| 2.0 * (TAU * x / 7.0).sin() + (TAU * x / 1.3).cos() + 0.2 * x)
+            .collect();
+        let periods = dominant_periods(&x, &y, 2);
+        assert!((periods[0] - 7.0).abs() < 0.2, "{periods:?}");
+        assert!((periods[1] - 1.3).abs() < 0.02, "{periods:?}");
+
+        let periodic = |period| {
+            Kernel::Periodic(PeriodicKernel {
+                sigma: 1.0,
+                length_scale: 1.0,
+                period,
+            })
+        };
+        let rbf = Kernel::Rbf(RbfKernel {
+            sigma: 1.0,
+            length_scale: 10.0,
+        });
+        let mut kernel = Kernel::Sum(vec![rbf.clone(), periodic(1.0), periodic(1.0)]);
+        assert!(kernel.init_periods(&x, &y));
+        let periods: Vec<f64> = kernel
+            .periodic_kernels_mut()
+            .iter()
+            .map(|k| k.period)
+            .collect();
+        assert!((periods[0] - 7.0).abs() < 0.2 && (periods[1] - 1.3).abs() < 0.02);
+
+        let mut kernel = rbf;
+        assert!(!kernel.init_periods(&x, &y));
+        assert!(dominant_periods(&[1.0, 2.0], &[0.0, 1.0], 1).is_empty());
+    }
+}
@@ -0,0 +1,297 @@
+use nalgebra as na;
+
+use super::{GpError, GpInput, GpKernel, RegressionModel};
+
+/// Maximum number of iterations when finding the mode of the posterior.
+const MAX_ITERATIONS: usize = 100;
+
+/// The iterations stop when the objective improves less than this.
+const TOLERANCE: f64 = 1e-9;
+
+/// The log of the gamma function, using the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let t = x + 5.5;
+    let series: f64 = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c / (x + 1.0 + i as f64))
+        .sum();
+    (x + 0.5) * t.ln() - t
+        + (2.0 * std::f64::consts::PI).sqrt().ln()
+        + (1.000_000_000_190_015 + series).ln()
+        - x.ln()
+}
+
+/// The median, which unlike the mean is not dragged away by outliers.
+fn median(y: &[f64]) -> f64 {
+    if y.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = y.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+/// Gaussian process regression with Student-t distributed noise, whose heavy tails let single
+/// outliers be explained as noise instead of dragging the whole posterior mean towards them.
+///
+/// The posterior over the latent function is approximated by the Laplace approximation around
+/// its mode, which is found by iteratively reweighted least squares (the Student-t is a mixture
+/// of Gaussians of different variances). The likelihood is not log-concave, so where a point is
+/// so far off that its curvature is negative it is clamped to zero, and the point does not
+/// reduce the predictive variance. See Vanhatalo et al. (2009), Gaussian process regression
+/// with Student-t likelihood, and Rasmussen & Williams, section 3.4.
+pub struct StudentTGaussianProcess<K: GpKernel<I>, I: GpInput = f64> {
+    kernel: K,
+    x: na::DVector<I>,
+    /// The median of the targets, subtracted before fitting.
+    y_offset: f64,
+    /// The scale of the noise, which is its standard deviation in the Gaussian limit.
+    noise_sigma: f64,
+    degrees_of_freedom: f64,
+    /// Added to the noise variance of every point so the covariance stays positive definite.
+    jitter: f64,
+    /// Gradient of the log likelihood at the mode, which equals `K^-1 f` there.
+    gradient: na::DVector<f64>,
+    /// Square root of the negative Hessian of the log likelihood at the mode, clamped at zero.
+    sqrt_w: na::DVector<f64>,
+    /// Cholesky factor of `I + sqrt(W) K sqrt(W)`.
+    l: na::DMatrix<f64>,
+    /// How much each observation counts in the fit at the mode, from one for points that agree
+    /// with the fit down towards zero for outliers.
+    observation_weights: na::DVector<f64>,
+    log_marginal_likelihood: f64,
+}
+
+impl<K: GpKernel<I>, I: GpInput> StudentTGaussianProcess<K, I> {
+    /// Fit to the observations, with the scale `noise_sigma` and the degrees of freedom
+    /// of the noise. Few degrees of freedom give heavy tails, while many approach Gaussian noise.
+    /// The `jitter` is added to the diagonal as in [`super::GaussianProcessBuilder::jitter`].
+    pub fn new(
+        x: &[I],
+        y: &[f64],
+        kernel: K,
+        noise_sigma: f64,
+        degrees_of_freedom: f64,
+        jitter: f64,
+    ) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        let n = x.len();
+        let x = na::DVector::from_column_slice(x);
+        let y_offset = median(y);
+        let y = na::DVector::from_iterator(n, y.iter().map(|y| y - y_offset));
+        let k = kernel.compute_matrix(&x, &x);
+        let nu = degrees_of_freedom;
+        let noise_variance = noise_sigma.powi(2);
+        let scale2 = nu * noise_variance;
+
+        let normalization = ln_gamma((nu + 1.0) / 2.0)
+            - ln_gamma(nu / 2.0)
+            - 0.5 * (std::f64::consts::PI * scale2).ln();
+        let log_likelihood = |f: &na::DVector<f64>| -> f64 {
+            y.zip_map(f, |y, f| {
+                normalization - (nu + 1.0) / 2.0 * (1.0 + (y - f).powi(2) / scale2).ln()
+            })
+            .sum()
+        };
+
+        // the mode, where each point is a Gaussian observation with a variance that grows with
+        // its residual
+        let mut f = na::DVector::zeros(n);
+        let mut objective = f64::NEG_INFINITY;
+        let mut observation_weights = na::DVector::from_element(n, 1.0);
+        for _ in 0..MAX_ITERATIONS {
+            let noise = observation_weights.map(|w| noise_variance / w + jitter);
+            let cholesky = na::Cholesky::new(&k + na::DMatrix::from_diagonal(&noise))
+                .ok_or(GpError::NotInvertible)?;
+            let a = cholesky.solve(&y);
+            f = &k * &a;
+
+            let new_objective = -0.5 * a.dot(&f) + log_likelihood(&f);
+            observation_weights = y.zip_map(&f, |y, f| {
+                (nu + 1.0) * noise_variance / (scale2 + (y - f).powi(2))
+            });
+            let converged = (new_objective - objective).abs() < TOLERANCE;
+            objective = new_objective;
+            if converged {
+                break;
+            }
+        }
+
+        let gradient = y.zip_map(&f, |y, f| (nu + 1.0) * (y - f) / (scale2 + (y - f).powi(2)));
+        let sqrt_w = y.zip_map(&f, |y, f| {
+            let r2 = (y - f).powi(2);
+            ((nu + 1.0) * (scale2 - r2) / (scale2 + r2).powi(2))
+                .max(0.0)
+                .sqrt()
+        });
+        let l = na::Cholesky::new(
+            na::DMatrix::identity(n, n)
+                + na::DMatrix::from_diagonal(&sqrt_w) * &k * na::DMatrix::from_diagonal(&sqrt_w),
+        )
+        .ok_or(GpError::NotInvertible)?
+        .l();
+        let log_marginal_likelihood = objective - l.diagonal().map(f64::ln).sum();
+
+        Ok(Self {
+            kernel,
+            x,
+            y_offset,
+            noise_sigma,
+            degrees_of_freedom,
+            jitter,
+            gradient,
+            sqrt_w,
+            l,
+            observation_weights,
+            log_marginal_likelihood,
+        })
+    }
+
+    /// The mean and variance of the latent function at the inputs.
+    pub fn predict(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        let x = na::DVector::from_column_slice(x);
+        let k_star = self.kernel.compute_matrix(&self.x, &x);
+        let mean = k_star.transpose() * &self.gradient;
+
+        let mut scaled = k_star;
+        for mut column in scaled.column_iter_mut() {
+            column.component_mul_assign(&self.sqrt_w);
+        }
+        let v = self
+            .l
+            .solve_lower_triangular(&scaled)
+            .expect("should be invertible");
+        let variance = (0..x.len())
+            .map(|i| (self.kernel.compute(x[i], x[i]) - v.column(i).norm_squared()).max(0.0))
+            .collect();
+        (mean.iter().map(|m| m + self.y_offset).collect(), variance)
+    }
+
+    /// How much each observation counts in the fit, from one for points that agree with it down
+    /// towards zero for outliers.
+    pub fn observation_weights(&self) -> &[f64] {
+        self.observation_weights.as_slice()
+    }
+
+    /// The Laplace approximation of the log marginal likelihood.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.log_marginal_likelihood
+    }
+
+    pub fn degrees_of_freedom(&self) -> f64 {
+        self.degrees_of_freedom
+    }
+}
+
+impl<K: GpKernel<I> + Clone, I: GpInput> RegressionModel<I> for StudentTGaussianProcess<K, I> {
+    fn fit(&mut self, x: &[I], y: &[f64]) -> Result<(), GpError> {
+        *self = Self::new(
+            x,
+            y,
+            self.kernel.clone(),
+            self.noise_sigma,
+            self.degrees_of_freedom,
+            self.jitter,
+        )?;
+        Ok(())
+    }
+
+    fn predict_mean_std(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        let (mean, variance) = self.predict(x);
+        (mean, variance.into_iter().map(f64::sqrt).collect())
+    }
+
+    fn log_evidence(&self) -> f64 {
+        self.log_marginal_likelihood
+    }
+
+    /// The variance of the Student-t noise, which is infinite for two or fewer degrees of
+    /// freedom.
+    fn noise_variance(&self, x: &[I]) -> Vec<f64> {
+        let nu = self.degrees_of_freedom;
+        let variance = if nu > 2.0 {
+            nu * self.noise_sigma.powi(2) / (nu - 2.0)
+        } else {
+            f64::INFINITY
+        };
+        vec![variance; x.len()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{GaussianProcess, RbfKernel, EPS};
+
+    #[test]
+    fn test_ln_gamma() {
+        assert!(ln_gamma(1.0).abs() < 1e-10);
+        assert!((ln_gamma(5.0) - 24.0f64.ln()).abs() < 1e-10);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_student_t_ignores_outlier() {
+        let x: Vec<f64> = (0..15).map(|i| i as f64 * 0.5).collect();
+        let mut y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+        y[7] += 5.0;
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+
+        let robust = StudentTGaussianProcess::new(&x, &y, kernel.clone(), 0.01, 4.0, EPS).unwrap();
+        let gaussian = GaussianProcess::from_slices(&x, &y, kernel, 0.01).unwrap();
+        let (robust_mean, _) = robust.predict(&[x[7]]);
+        let (gaussian_mean, _) = gaussian.predict_slice(&[x[7]]);
+        assert!((robust_mean[0] - x[7].sin()).abs() < 0.1, "{robust_mean:?}");
+        assert!((gaussian_mean[0] - x[7].sin()).abs() > 1.0);
+
+        // the outlier is down-weighted, the other points are not
+        let weights = robust.observation_weights();
+        assert!(weights[7] < 0.01);
+        assert!(weights.iter().enumerate().all(|(i, w)| i == 7 || *w > 0.9));
+
+        // the heavy tails explain the outlier better than Gaussian noise
+        assert!(robust.log_marginal_likelihood() > gaussian.log_marginal_likelihood());
+    }
+
+    #[test]
+    fn test_student_t_approaches_gaussian() {
+        let x = [0.0, 1.0, 2.0, 3.0];
+        // a median of zero, so both return to the same prior mean
+        let y = [0.5, 0.0, -0.3, 0.0];
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let robust = StudentTGaussianProcess::new(&x, &y, kernel.clone(), 0.1, 1e6, EPS).unwrap();
+        let gaussian = GaussianProcess::from_slices(&x, &y, kernel, 0.1).unwrap();
+        let test = [0.5, 1.5, 2.5];
+        let (mean, variance) = robust.predict(&test);
+        let (gaussian_mean, gaussian_variance) = gaussian.predict_slice(&test);
+        for i in 0..test.len() {
+            assert!((mean[i] - gaussian_mean[i]).abs() < 1e-3);
+            assert!((variance[i] - gaussian_variance[i]).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use nalgebra as na;
+use rand::{Rng, SeedableRng};
+
+use super::parallel::parallel_map;
+use super::{GaussianProcess, GpInput, GpKernel, KernelParams};
+
+/// The number of points evaluated in parallel before they are passed on, in order.
+const BATCH_SIZE: usize = 256;
+
+/// The hyperparameters `[kernel params.., noise]` a [`sweep`] evaluates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SweepSpace {
+    /// Every combination of the given values of each hyperparameter, the last one changing
+    /// fastest.
+    Grid(Vec<Vec<f64>>),
+    /// Points drawn log-uniformly between the bounds of each hyperparameter, from a seeded
+    /// random number generator so the sweep can be repeated.
+    Random {
+        bounds: Vec<(f64, f64)>,
+        samples: usize,
+        seed: u64,
+    },
+}
+
+impl SweepSpace {
+    /// A grid of `n` values of each hyperparameter, given as `(low, high, n)`, evenly spaced
+    /// in log space between the bounds.
+    pub fn log_grid(axes: &[(f64, f64, usize)]) -> Self {
+        SweepSpace::Grid(
+            axes.iter()
+                .map(|&(low, high, n)| {
+                    (0..n)
+                        .map(|i| match i {
+                            0 => low,
+                            i if i == n - 1 => high,
+                            i => low * (high / low).powf(i as f64 / (n - 1) as f64),
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// The number of hyperparameters of each point.
+    pub fn dimensions(&self) -> usize {
+        match self {
+            SweepSpace::Grid(values) => values.len(),
+            SweepSpace::Random { bounds, .. } => bounds.len(),
+        }
+    }
+
+    /// The number of points.
+    pub fn len(&self) -> usize {
+        match self {
+            SweepSpace::Grid(values) if values.is_empty() => 0,
+            SweepSpace::Grid(values) => values.iter().map(Vec::len).product(),
+            SweepSpace::Random { samples, .. } => *samples,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The points in the order they are evaluated.
+    pub fn points(&self) -> Box<dyn Iterator<Item = Vec<f64>> + '_> {
+        match self {
+            SweepSpace::Grid(values) => Box::new((0..self.len()).map(move |mut index| {
+                let mut point = vec![0.0; values.len()];
+                for (p, values) in point.iter_mut().zip(values).rev() {
+                    *p = values[index % values.len()];
+                    index /= values.len();
+                }
+                point
+            })),
+            SweepSpace::Random {
+                bounds,
+                samples,
+                seed,
+            } => {
+                let mut rng = rand::rngs::SmallRng::seed_from_u64(*seed);
+                Box::new((0..*samples).map(move |_| {
+                    bounds
+                        .iter()
+                        .map(|(low, high)| rng.gen_range(low.ln()..=high.ln()).exp())
+                        .collect()
+                }))
+            }
+        }
+    }
+}
+
+/// The fit of the data at one point of a [`sweep`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepPoint {
+    /// The hyperparameters `[kernel params.., noise]`.
+    pub hyperparameters: Vec<f64>,
+    pub log_marginal_likelihood: f64,
+    /// The sum of the log densities of the observations under their leave-one-out predictive
+    /// distributions, which estimates how well the model predicts new data.
+    pub loo_log_predictive: f64,
+}
+
+/// Errors from running a sweep.
+#[derive(Debug)]
+pub enum SweepError {
+    Io(std::io::Error),
+    /// There is not one target for every input.
+    LengthMismatch {
+        x: usize,
+        y: usize,
+    },
+    /// The points of the sweep space do not have one value for every hyperparameter of the
+    /// kernel and the noise.
+    ParameterCount {
+        expected: usize,
+        got: usize,
+    },
+}
+
+impl std::fmt::Display for SweepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SweepError::Io(error) => write!(f, "{error}"),
+            SweepError::LengthMismatch { x, y } => write!(f, "got {x} inputs but {y} targets"),
+            SweepError::ParameterCount { expected, got } => write!(
+                f,
+                "the kernel and the noise have {expected} hyperparameters but the sweep has {got}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+impl From<std::io::Error> for SweepError {
+    fn from(error: std::io::Error) -> Self {
+        SweepError::Io(error)
+    }
+}
+
+/// Evaluate the log marginal likelihood and the leave-one-out log predictive density of the
+/// data at every point of the space, with the kernel's hyperparameters and the noise set to the
+/// point. The points are evaluated in parallel in batches when the `rayon` feature is enabled,
+/// and passed to `callback` in order as they are done. Returning false from the callback stops
+/// the sweep.
+///
+/// ```
+/// use gaussian_processes::gp::{sweep, RbfKernel, SweepSpace};
+///
+/// let x: Vec<f64> = (0..20).map(|i| i as f64 * 0.5).collect();
+/// let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+/// let kernel = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// };
+/// // length scale, sigma and noise
+/// let space = SweepSpace::Grid(vec![vec![0.1, 1.0, 10.0], vec![1.0], vec![0.01]]);
+/// let mut best = (f64::NEG_INFINITY, 0.0);
+/// sweep(&x, &y, &kernel, &space, |point| {
+///     if point.log_marginal_likelihood > best.0 {
+///         best = (point.log_marginal_likelihood, point.hyperparameters[0]);
+///     }
+///     true
+/// })?;
+/// assert_eq!(best.1, 1.0);
+/// # Ok::<(), gaussian_processes::gp::SweepError>(())
+/// ```
+pub fn sweep<K, I>(
+    x: &[I],
+    y: &[f64],
+    kernel: &K,
+    space: &SweepSpace,
+    mut callback: impl FnMut(&SweepPoint) -> bool,
+) -> Result<(), SweepError>
+where
+    K: GpKernel<I> + KernelParams + Clone + Send + Sync,
+    I: GpInput + Send + Sync,
+{
+    check(x.len(), y.len(), kernel, space)?;
+    let (x, y) = (
+        na::DVector::from_column_slice(x),
+        na::DVector::from_column_slice(y),
+    );
+    let mut points = space.points().peekable();
+    while points.peek().is_some() {
+        let batch: Vec<Vec<f64>> = points.by_ref().take(BATCH_SIZE).collect();
+        let evaluated = parallel_map(batch, |hyperparameters| {
+            evaluate(&x, &y, kernel, hyperparameters)
+        });
+        for point in &evaluated {
+            if !callback(point) {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check<K: KernelParams>(
+    x: usize,
+    y: usize,
+    kernel: &K,
+    space: &SweepSpace,
+) -> Result<(), SweepError> {
+    if x != y {
+        return Err(SweepError::LengthMismatch { x, y });
+    }
+    let expected = kernel.param_names().len() + 1;
+    if space.dimensions() != expected {
+        return Err(SweepError::ParameterCount {
+            expected,
+            got: space.dimensions(),
+        });
+    }
+    Ok(())
+}
+
+fn evaluate<K: GpKernel<I> + KernelParams + Clone, I: GpInput>(
+    x: &na::DVector<I>,
+    y: &na::DVector<f64>,
+    kernel: &K,
+    hyperparameters: Vec<f64>,
+) -> SweepPoint {
+    let mut kernel = kernel.clone();
+    for (param, value) in kernel.params_mut().into_iter().zip(&hyperparameters) {
+        *param = *value;
+    }
+    let noise = hyperparameters[hyperparameters.len() - 1];
+    let gp = GaussianProcess::new(x, y, kernel, noise);
+    let (mean, variance) = gp.leave_one_out();
+    let loo_log_predictive = (0..y.len())
+        .map(|i| {
+            -0.5 * (std::f64::consts::TAU * variance[i]).ln()
+                - 0.5 * (y[i] - mean[i]).powi(2) / variance[i]
+        })
+        .sum();
+    SweepPoint {
+        log_marginal_likelihood: gp.log_marginal_likelihood(),
+        loo_log_predictive,
+        hyperparameters,
+    }
+}
+
+/// Run a [`sweep`] and write the results as CSV as they come in, one row per point with the
+/// hyperparameters (named after [`KernelParams::param_names`] and "noise") followed by the log
+/// marginal likelihood and the leave-one-out log predictive density. Returns the number of rows
+/// written.
+pub fn sweep_csv<K, I>(
+    x: &[I],
+    y: &[f64],
+    kernel: &K,
+    space: &SweepSpace,
+    mut writer: impl std::io::Write,
+) -> Result<usize, SweepError>
+where
+    K: GpKernel<I> + KernelParams + Clone + Send + Sync,
+    I: GpInput + Send + Sync,
+{
+    check(x.len(), y.len(), kernel, space)?;
+    let mut names = kernel.param_names();
+    names.push("noise");
+    // number repeated names, e.g. the sigmas of the terms of a sum
+    let columns: Vec<String> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let name = name.replace(' ', "_");
+            match names[..i]
+                .iter()
+                .filter(|other| *other == &names[i])
+                .count()
+            {
+                0 => name,
+                repeats => format!("{name}_{}", repeats + 1),
+            }
+        })
+        .collect();
+    writeln!(
+        writer,
+        "{},log_marginal_likelihood,loo_log_predictive",
+        columns.join(",")
+    )?;
+
+    let mut rows = 0;
+    let mut result = Ok(());
+    sweep(x, y, kernel, space, |point| {
+        let values: Vec<String> = point
+            .hyperparameters
+            .iter()
+            .map(|value| value.to_string())
+            .collect();
+        result = writeln!(
+            writer,
+            "{},{},{}",
+            values.join(","),
+            point.log_marginal_likelihood,
+            point.loo_log_predictive
+        );
+        rows += 1;
+        result.is_ok()
+    })?;
+    result?;
+    writer.flush()?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{Kernel, RbfKernel};
+
+    #[test]
+    fn test_sweep_space() {
+        let grid = SweepSpace::Grid(vec![vec![1.0, 2.0], vec![3.0, 4.0, 5.0]]);
+        assert_eq!(grid.len(), 6);
+        let points: Vec<Vec<f64>> = grid.points().collect();
+        assert_eq!(points[0], vec![1.0, 3.0]);
+        assert_eq!(points[1], vec![1.0, 4.0]);
+        assert_eq!(points[5], vec![2.0, 5.0]);
+        assert!(SweepSpace::Grid(Vec::new()).is_empty());
+
+        let log_grid = SweepSpace::log_grid(&[(0.01, 1.0, 3)]);
+        let SweepSpace::Grid(values) = &log_grid else {
+            unreachable!()
+        };
+        assert_eq!(values[0][0], 0.01);
+        assert!((values[0][1] - 0.1).abs() < 1e-12);
+        assert_eq!(values[0][2], 1.0);
+
+        let random = SweepSpace::Random {
+            bounds: vec![(0.1, 10.0), (1.0, 1.0)],
+            samples: 100,
+            seed: 3,
+        };
+        let points: Vec<Vec<f64>> = random.points().collect();
+        assert_eq!(points.len(), 100);
+        assert!(points
+            .iter()
+            .all(|p| (0.1..=10.0).contains(&p[0]) && p[1] == 1.0));
+        // log-uniform, so about half are below one
+        let below = points.iter().filter(|p| p[0] < 1.0).count();
+        assert!((35..65).contains(&below));
+        assert_eq!(random.points().collect::<Vec<_>>(), points);
+    }
+
+    #[test]
+    fn test_sweep_csv() {
+        let x: Vec<f64> = (0..15).map(|i| i as f64 * 0.4).collect();
+        let y: Vec<f64> = x.iter().map(|x| x.cos()).collect();
+        let rbf = Kernel::Rbf(RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        });
+        let kernel = Kernel::Sum(vec![rbf.clone(), rbf]);
+        let space = SweepSpace::log_grid(&[(0.1, 10.0, 2); 5]);
+
+        let mut csv = Vec::new();
+        assert_eq!(sweep_csv(&x, &y, &kernel, &space, &mut csv).unwrap(), 32);
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "length_scale,sigma,length_scale_2,sigma_2,noise,log_marginal_likelihood,\
+             loo_log_predictive"
+        );
+        assert_eq!(lines.len(), 33);
+
+        // the rows match evaluating the points one by one
+        let fields: Vec<f64> = lines[7].split(',').map(|f| f.parse().unwrap()).collect();
+        let point = evaluate(
+            &na::DVector::from_column_slice(&x),
+            &na::DVector::from_column_slice(&y),
+            &kernel,
+            fields[..5].to_vec(),
+        );
+        assert_eq!(fields[5], point.log_marginal_likelihood);
+        assert_eq!(fields[6], point.loo_log_predictive);
+
+        let mut stopped = 0;
+        sweep(&x, &y, &kernel, &space, |_| {
+            stopped += 1;
+            stopped < 3
+        })
+        .unwrap();
+        assert_eq!(stopped, 3);
+        let wrong = SweepSpace::log_grid(&[(0.1, 10.0, 2); 3]);
+        assert!(matches!(
+            sweep(&x, &y, &kernel, &wrong, |_| true),
+            Err(SweepError::ParameterCount {
+                expected: 5,
+                got: 3
+            })
+        ));
+        let mut csv = Vec::new();
+        assert!(sweep_csv(&x, &y, &kernel, &wrong, &mut csv).is_err());
+        assert!(csv.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+/// Which transformations to apply to one-dimensional training data before fitting, in the order
+/// they are applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TransformOptions {
+    /// Shift and scale the inputs to zero mean and unit variance.
+    pub standardize_x: bool,
+    /// Take the logarithm of the targets, ignored while any of them is not positive.
+    pub log_y: bool,
+    /// Subtract the least squares line through the (logarithms of the) targets.
+    pub detrend: bool,
+    /// Shift and scale the targets to zero mean and unit variance.
+    pub standardize_y: bool,
+}
+
+/// A transformation of training data fit with [`DataTransform::fit`], so a model can be fit to
+/// data that is easier to model and its predictions mapped back to the original units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DataTransform {
+    x_offset: f64,
+    x_scale: f64,
+    log_y: bool,
+    /// The intercept and slope of the line subtracted from the targets, in the transformed
+    /// inputs.
+    trend: (f64, f64),
+    y_offset: f64,
+    y_scale: f64,
+}
+
+impl Default for DataTransform {
+    fn default() -> Self {
+        Self {
+            x_offset: 0.0,
+            x_scale: 1.0,
+            log_y: false,
+            trend: (0.0, 0.0),
+            y_offset: 0.0,
+            y_scale: 1.0,
+        }
+    }
+}
+
+/// The mean and the standard deviation of the values, with a standard deviation of one for
+/// constant values so dividing by it is safe.
+fn mean_std(values: impl ExactSizeIterator<Item = f64> + Clone) -> (f64, f64) {
+    let n = values.len().max(1) as f64;
+    let mean = values.clone().sum::<f64>() / n;
+    let variance = values.map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let std = variance.sqrt();
+    (mean, if std > 0.0 { std } else { 1.0 })
+}
+
+impl DataTransform {
+    /// Fit the transformations selected in the options to the training data.
+    pub fn fit(x: &[f64], y: &[f64], options: TransformOptions) -> Self {
+        let mut transform = Self::default();
+        if options.standardize_x && !x.is_empty() {
+            (transform.x_offset, transform.x_scale) = mean_std(x.iter().copied());
+        }
+        transform.log_y = options.log_y && y.iter().all(|y| *y > 0.0);
+
+        let x: Vec<f64> = x.iter().map(|x| transform.input(*x)).collect();
+        let mut y: Vec<f64> = y
+            .iter()
+            .map(|y| if transform.log_y { y.ln() } else { *y })
+            .collect();
+        if options.detrend && x.len() > 1 {
+            let (x_mean, _) = mean_std(x.iter().copied());
+            let (y_mean, _) = mean_std(y.iter().copied());
+            let covariance: f64 = x
+                .iter()
+                .zip(&y)
+                .map(|(x, y)| (x - x_mean) * (y - y_mean))
+                .sum();
+            let variance: f64 = x.iter().map(|x| (x - x_mean).powi(2)).sum();
+            let slope = if variance > 0.0 {
+                covariance / variance
+            } else {
+                0.0
+            };
+            transform.trend = (y_mean - slope * x_mean, slope);
+            for (x, y) in x.iter().zip(&mut y) {
+                *y -= transform.trend.0 + transform.trend.1 * x;
+            }
+        }
+        if options.standardize_y && !y.is_empty() {
+            (transform.y_offset, transform.y_scale) = mean_std(y.iter().copied());
+        }
+        transform
+    }
+
+    /// Whether the transformation leaves the data unchanged.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The transformed input.
+    pub fn input(&self, x: f64) -> f64 {
+        (x - self.x_offset) / self.x_scale
+    }
+
+    /// The original input of a transformed one.
+    pub fn inverse_input(&self, x: f64) -> f64 {
+        x * self.x_scale + self.x_offset
+    }
+
+    /// The transformed target at the original input.
+    pub fn target(&self, x: f64, y: f64) -> f64 {
+        let y = if self.log_y { y.ln() } else { y };
+        let trend = self.trend.0 + self.trend.1 * self.input(x);
+        (y - trend - self.y_offset) / self.y_scale
+    }
+
+    /// Transform the training data.
+    pub fn apply(&self, x: &[f64], y: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        x.iter()
+            .zip(y)
+            .map(|(x, y)| (self.input(*x), self.target(*x, *y)))
+            .unzip()
+    }
+
+    /// Map a Gaussian prediction with the given mean and standard deviation of a transformed
+    /// target back to the original units at the original input. After a logarithm the
+    /// prediction is log-normal, and its mean and standard deviation are returned.
+    pub fn inverse_prediction(&self, x: f64, mean: f64, std: f64) -> (f64, f64) {
+        let trend = self.trend.0 + self.trend.1 * self.input(x);
+        let (mean, std) = (
+            mean * self.y_scale + self.y_offset + trend,
+            std * self.y_scale,
+        );
+        if self.log_y {
+            let variance = std * std;
+            let mean = (mean + 0.5 * variance).exp();
+            (mean, mean * variance.exp_m1().sqrt())
+        } else {
+            (mean, std)
+        }
+    }
+
+    /// [`Self::inverse_prediction`] for predictions at several inputs.
+    pub fn inverse_predictions(
+        &self,
+        x: &[f64],
+        mean: &[f64],
+        std: &[f64],
+    ) -> (Vec<f64>, Vec<f64>) {
+        x.iter()
+            .zip(mean.iter().zip(std))
+            .map(|(x, (mean, std))| self.inverse_prediction(*x, *mean, *std))
+            .unzip()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transform_round_trip() {
+        let x = [1.0, 2.0, 4.0, 7.0];
+        let y = [2.0, 3.5, 9.0, 30.0];
+        let options = TransformOptions {
+            standardize_x: true,
+            log_y: true,
+            detrend: true,
+            standardize_y: true,
+        };
+        let transform = DataTransform::fit(&x, &y, options);
+        assert!(!transform.is_identity());
+        let (tx, ty) = transform.apply(&x, &y);
+
+        let (x_mean, x_std) = mean_std(tx.iter().copied());
+        assert!(x_mean.abs() < 1e-12 && (x_std - 1.0).abs() < 1e-12);
+        let (y_mean, y_std) = mean_std(ty.iter().copied());
+        assert!(y_mean.abs() < 1e-12 && (y_std - 1.0).abs() < 1e-12);
+        // the residuals of the line fit are uncorrelated with the inputs
+        let correlation: f64 = tx.iter().zip(&ty).map(|(x, y)| x * y).sum();
+        assert!(correlation.abs() < 1e-12);
+
+        for ((x, y), (tx, ty)) in x.iter().zip(&y).zip(tx.iter().zip(&ty)) {
+            assert!((transform.inverse_input(*tx) - x).abs() < 1e-12);
+            let (mean, std) = transform.inverse_prediction(*x, *ty, 0.0);
+            assert!((mean - y).abs() < 1e-9 && std == 0.0);
+        }
+    }
+
+    #[test]
+    fn test_transform_log_normal() {
+        let options = TransformOptions {
+            log_y: true,
+            ..Default::default()
+        };
+        let transform = DataTransform::fit(&[0.0, 1.0], &[1.0, 2.0], options);
+        let (mean, std) = transform.inverse_prediction(0.0, 0.0, 0.5);
+        assert!((mean - 0.125f64.exp()).abs() < 1e-12);
+        assert!((std - mean * (0.25f64.exp() - 1.0).sqrt()).abs() < 1e-12);
+
+        // the logarithm is skipped for targets that are not all positive
+        let transform = DataTransform::fit(&[0.0, 1.0], &[-1.0, 2.0], options);
+        assert!(transform.is_identity());
+        assert_eq!(
+            DataTransform::fit(&[], &[], Default::default()),
+            DataTransform::default()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use nalgebra as na;
+
+use super::{GaussianProcess, RbfKernel};
+
+impl GaussianProcess<RbfKernel, f64> {
+    /// Predict at test inputs that are themselves uncertain, each normally distributed with the
+    /// given mean and variance, returning the mean and variance of the latent function
+    /// averaged over the input distribution.
+    ///
+    /// The result is the exact first two moments of the (non-Gaussian) predictive distribution,
+    /// computed in closed form for the RBF kernel (Girard et al., 2003, Gaussian process priors
+    /// with uncertain inputs). The variance grows where the function changes quickly around the
+    /// input, and with zero input variance this is the same as [`Self::predict_slice`].
+    pub fn predict_uncertain_input(&self, mean: &[f64], variance: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        assert_eq!(
+            mean.len(),
+            variance.len(),
+            "there must be one input variance for every input mean"
+        );
+        let sigma = self.kernel.sigma;
+        let length_scale2 = self.kernel.length_scale.powi(2);
+        let beta = &self.input_cov_matrix_inv * &self.y;
+        let n = self.x.len();
+
+        mean.iter()
+            .zip(variance)
+            .map(|(&mu, &v)| {
+                // E[k(x, x_i)] over the input distribution
+                let q = na::DVector::from_fn(n, |i, _| {
+                    sigma
+                        * (length_scale2 / (length_scale2 + v)).sqrt()
+                        * (-0.5 * (mu - self.x[i]).powi(2) / (length_scale2 + v)).exp()
+                });
+                let m = q.dot(&beta);
+
+                // E[k(x, x_i) k(x, x_j)] over the input distribution
+                let scale = sigma * sigma * (length_scale2 / (length_scale2 + 2.0 * v)).sqrt();
+                let q2 = na::DMatrix::from_fn(n, n, |i, j| {
+                    let middle = (self.x[i] + self.x[j]) / 2.0;
+                    scale
+                        * (-(self.x[i] - self.x[j]).powi(2) / (4.0 * length_scale2)).exp()
+                        * (-(mu - middle).powi(2) / (length_scale2 + 2.0 * v)).exp()
+                });
+                let variance =
+                    sigma - self.input_cov_matrix_inv.dot(&q2) + beta.dot(&(&q2 * &beta)) - m * m
+                        + self.jitter;
+
+                (
+                    self.y_offset + self.y_scale * m,
+                    variance.max(0.0) * self.y_scale.powi(2),
+                )
+            })
+            .unzip()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_predict_uncertain_input() {
+        let x = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let y = [0.0, 0.8, 0.9, 0.1, -0.8];
+        let kernel = RbfKernel {
+            sigma: 1.0,
+            length_scale: 1.0,
+        };
+        let gp = GaussianProcess::from_slices(&x, &y, kernel, 0.01).unwrap();
+
+        // certain inputs give the ordinary prediction
+        let test = [0.5, 2.5];
+        let (mean, variance) = gp.predict_uncertain_input(&test, &[0.0, 0.0]);
+        let (expected_mean, expected_variance) = gp.predict_slice(&test);
+        for i in 0..test.len() {
+            assert!((mean[i] - expected_mean[i]).abs() < 1e-9);
+            assert!((variance[i] - expected_variance[i]).abs() < 1e-9);
+        }
+
+        // the moments match numerical integration over the input distribution
+        let (mu, v) = (2.5, 0.3);
+        let (mean, variance) = gp.predict_uncertain_input(&[mu], &[v]);
+        let grid: Vec<f64> = (0..=4000)
+            .map(|i| mu + (i as f64 / 4000.0 - 0.5) * 16.0 * v.sqrt())
+            .collect();
+        let density: Vec<f64> = grid
+            .iter()
+            .map(|x| (-0.5 * (x - mu).powi(2) / v).exp())
+            .collect();
+        let total: f64 = density.iter().sum();
+        let (grid_mean, grid_variance) = gp.predict_slice(&grid);
+        let expected_mean: f64 = (0..grid.len())
+            .map(|i| density[i] * grid_mean[i])
+            .sum::<f64>()
+            / total;
+        let second_moment: f64 = (0..grid.len())
+            .map(|i| density[i] * (grid_variance[i] + grid_mean[i].powi(2)))
+            .sum::<f64>()
+            / total;
+        assert!((mean[0] - expected_mean).abs() < 1e-6);
+        assert!((variance[0] - (second_moment - expected_mean.powi(2))).abs() < 1e-6);
+        // and the variance is inflated where the function is steep
+        assert!(variance[0] > gp.predict_slice(&[mu]).1[0]);
+    }
+}
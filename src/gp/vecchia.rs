@@ -0,0 +1,184 @@
+use nalgebra as na;
+
+use super::{GpError, GpInput, GpKernel, EPS};
+
+/// The indices of the (at most) `count` points of `x` closest to `point`.
+fn nearest<I: GpInput>(x: &[I], point: I, count: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..x.len()).collect();
+    let distance = |i: &usize| x[*i].distance(&point);
+    if count < indices.len() {
+        indices.select_nth_unstable_by(count, |a, b| distance(a).total_cmp(&distance(b)));
+        indices.truncate(count);
+    }
+    indices
+}
+
+/// A scalable approximation of GP regression for large datasets (Vecchia, 1988; Datta et al.,
+/// 2016, nearest-neighbor Gaussian processes).
+///
+/// The joint density of the observations is factored in their given order as
+/// `p(y_1) p(y_2 | y_1) ... p(y_n | y_1, ..., y_n-1)`, and each observation is only conditioned
+/// on its `neighbors` nearest predecessors instead of all of them. Fitting then costs
+/// `O(n m^3)` for `m` neighbors instead of `O(n^3)`, and predictions condition on the `m`
+/// nearest training points. More neighbors are more accurate, and with `n - 1` of them this is
+/// exact. The order matters: sorted inputs in one dimension work well. Noise weakens how well
+/// the nearest points screen off the others, so densely sampled noisy data needs more neighbors.
+///
+/// ```
+/// use gaussian_processes::gp::{RbfKernel, VecchiaGaussianProcess};
+///
+/// let x: Vec<f64> = (0..2000).map(|i| i as f64 * 0.01).collect();
+/// let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+/// let kernel = RbfKernel {
+///     sigma: 1.0,
+///     length_scale: 1.0,
+/// };
+/// let gp = VecchiaGaussianProcess::new(&x, &y, kernel, 0.01, 10)?;
+/// let (mean, _) = gp.predict(&[3.005]);
+/// assert!((mean[0] - 3.005f64.sin()).abs() < 0.01);
+/// # Ok::<(), gaussian_processes::gp::GpError>(())
+/// ```
+pub struct VecchiaGaussianProcess<K: GpKernel<I>, I: GpInput = f64> {
+    kernel: K,
+    x: Vec<I>,
+    y: Vec<f64>,
+    noise_sigma: f64,
+    neighbors: usize,
+    log_marginal_likelihood: f64,
+}
+
+impl<K: GpKernel<I>, I: GpInput> VecchiaGaussianProcess<K, I> {
+    /// Fit to the observations, conditioning each on its `neighbors` nearest predecessors.
+    pub fn new(
+        x: &[I],
+        y: &[f64],
+        kernel: K,
+        noise_sigma: f64,
+        neighbors: usize,
+    ) -> Result<Self, GpError> {
+        if x.len() != y.len() {
+            return Err(GpError::LengthMismatch {
+                x: x.len(),
+                y: y.len(),
+            });
+        }
+        let mut gp = Self {
+            kernel,
+            x: x.to_vec(),
+            y: y.to_vec(),
+            noise_sigma,
+            neighbors,
+            log_marginal_likelihood: 0.0,
+        };
+
+        // the sum of the log densities of each observation given its conditioning set
+        let mut log_marginal_likelihood = 0.0;
+        for i in 0..x.len() {
+            let conditioning = nearest(&x[..i], x[i], neighbors);
+            let (mean, variance) = gp.condition(&conditioning, x[i])?;
+            let variance = variance + noise_sigma.powi(2);
+            log_marginal_likelihood -= 0.5
+                * ((y[i] - mean).powi(2) / variance
+                    + variance.ln()
+                    + (2.0 * std::f64::consts::PI).ln());
+        }
+        gp.log_marginal_likelihood = log_marginal_likelihood;
+        Ok(gp)
+    }
+
+    /// The mean and variance of the latent function at `x` given the observations at the
+    /// `conditioning` indices.
+    fn condition(&self, conditioning: &[usize], x: I) -> Result<(f64, f64), GpError> {
+        let m = conditioning.len();
+        let points = na::DVector::from_iterator(m, conditioning.iter().map(|i| self.x[*i]));
+        let values = na::DVector::from_iterator(m, conditioning.iter().map(|i| self.y[*i]));
+        let k = self.kernel.compute_matrix(&points, &points)
+            + na::DMatrix::identity(m, m) * (self.noise_sigma.powi(2) + EPS);
+        let cholesky = na::Cholesky::new(k).ok_or(GpError::NotInvertible)?;
+        let k_star = self
+            .kernel
+            .compute_matrix(&points, &na::DVector::from_element(1, x))
+            .column(0)
+            .into_owned();
+        let mean = k_star.dot(&cholesky.solve(&values));
+        let v = cholesky
+            .l_dirty()
+            .solve_lower_triangular(&k_star)
+            .expect("the Cholesky factor has a positive diagonal");
+        let variance = self.kernel.compute(x, x) - v.dot(&v) + EPS;
+        Ok((mean, variance.max(0.0)))
+    }
+
+    /// The mean and variance of the latent function at the inputs, each conditioned on its
+    /// nearest training points.
+    pub fn predict(&self, x: &[I]) -> (Vec<f64>, Vec<f64>) {
+        x.iter()
+            .map(|x| {
+                let conditioning = nearest(&self.x, *x, self.neighbors);
+                self.condition(&conditioning, *x)
+                    .expect("the covariance of the training points was invertible when fitting")
+            })
+            .unzip()
+    }
+
+    /// The Vecchia approximation of the log marginal likelihood, which approaches the exact one
+    /// with more neighbors.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.log_marginal_likelihood
+    }
+
+    /// The number of points each observation and prediction is conditioned on.
+    pub fn neighbors(&self) -> usize {
+        self.neighbors
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gp::{GaussianProcess, RbfKernel};
+
+    fn data(n: usize) -> (Vec<f64>, Vec<f64>) {
+        let x: Vec<f64> = (0..n).map(|i| i as f64 * 10.0 / n as f64).collect();
+        let y = x.iter().map(|x| x.sin() + 0.3 * (3.0 * x).cos()).collect();
+        (x, y)
+    }
+
+    fn kernel() -> RbfKernel {
+        RbfKernel {
+            sigma: 1.0,
+            length_scale: 0.8,
+        }
+    }
+
+    #[test]
+    fn test_vecchia_exact_with_all_neighbors() {
+        let (x, y) = data(20);
+        let exact = GaussianProcess::from_slices(&x, &y, kernel(), 0.05).unwrap();
+        let vecchia = VecchiaGaussianProcess::new(&x, &y, kernel(), 0.05, 20).unwrap();
+        assert!((vecchia.log_marginal_likelihood() - exact.log_marginal_likelihood()).abs() < 1e-6);
+
+        let test = [0.25, 4.1, 12.0];
+        let (mean, variance) = vecchia.predict(&test);
+        let (exact_mean, exact_variance) = exact.predict_slice(&test);
+        for i in 0..test.len() {
+            assert!((mean[i] - exact_mean[i]).abs() < 1e-6);
+            assert!((variance[i] - exact_variance[i]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_vecchia_accuracy_improves_with_neighbors() {
+        let (x, y) = data(300);
+        let exact = GaussianProcess::from_slices(&x, &y, kernel(), 0.05)
+            .unwrap()
+            .log_marginal_likelihood();
+        let error = |neighbors| {
+            let vecchia = VecchiaGaussianProcess::new(&x, &y, kernel(), 0.05, neighbors).unwrap();
+            (vecchia.log_marginal_likelihood() - exact).abs()
+        };
+        let (coarse, fine) = (error(2), error(30));
+        assert!(fine < coarse, "{fine} >= {coarse}");
+        assert!(fine < 0.02 * exact.abs(), "{fine}");
+    }
+}
@@ -0,0 +1,133 @@
+use nalgebra as na;
+use rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+
+use super::{GaussianProcess, HyperparameterOptimizer, Kernel};
+
+/// A Gaussian process with one dimensional inputs for use from JavaScript.
+///
+/// Kernels are given and returned in RON, e.g. `Rbf((sigma: 1.0, length_scale: 1.0))` or
+/// `Sum([Rbf((sigma: 1.0, length_scale: 1.0)), Periodic((sigma: 1.0, length_scale: 1.0,
+/// period: 2.0))])`.
+///
+/// ```js
+/// const gp = GaussianProcess.fit(
+///   new Float64Array([1, 2, 4]),
+///   new Float64Array([0.5, -1, 0.2]),
+///   "Rbf((sigma: 1.0, length_scale: 1.0))",
+///   0.1,
+/// );
+/// const prediction = gp.predict(new Float64Array([1.5, 3]));
+/// console.log(prediction.mean, prediction.variance);
+/// ```
+#[wasm_bindgen(js_name = GaussianProcess)]
+pub struct WasmGaussianProcess {
+    gp: GaussianProcess<Kernel>,
+}
+
+/// The predictive mean and variance at a number of inputs.
+#[wasm_bindgen(js_name = Prediction)]
+pub struct WasmPrediction {
+    mean: Vec<f64>,
+    variance: Vec<f64>,
+}
+
+#[wasm_bindgen(js_class = Prediction)]
+impl WasmPrediction {
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> Vec<f64> {
+        self.mean.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn variance(&self) -> Vec<f64> {
+        self.variance.clone()
+    }
+}
+
+#[wasm_bindgen(js_class = GaussianProcess)]
+impl WasmGaussianProcess {
+    /// Fit a Gaussian process to the training data with the kernel given in RON.
+    pub fn fit(
+        x: Vec<f64>,
+        y: Vec<f64>,
+        kernel: &str,
+        noise_sigma: f64,
+    ) -> Result<WasmGaussianProcess, JsError> {
+        let kernel: Kernel = ron::from_str(kernel)?;
+        let gp = GaussianProcess::from_slices(&x, &y, kernel, noise_sigma)?;
+        Ok(Self { gp })
+    }
+
+    pub fn predict(&self, x: Vec<f64>) -> WasmPrediction {
+        let (mean, variance) = self.gp.predict_slice(&x);
+        WasmPrediction { mean, variance }
+    }
+
+    /// Draw `n` function samples from the posterior at the inputs, concatenated into one
+    /// array of `n * x.length` values.
+    pub fn sample(&self, x: Vec<f64>, n: usize, seed: u64) -> Vec<f64> {
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(seed);
+        self.gp
+            .sample(&na::DVector::from_vec(x), n, &mut rng)
+            .iter()
+            .flat_map(|sample| sample.iter().copied())
+            .collect()
+    }
+
+    /// Optimize the kernel hyperparameters and the noise by maximizing the log marginal
+    /// likelihood, and refit with them.
+    pub fn optimize(&mut self, max_iterations: usize) -> Result<(), JsError> {
+        let mut optimizer = HyperparameterOptimizer::new(
+            &self.gp.x,
+            &self.gp.targets(),
+            self.gp.kernel.clone(),
+            self.gp.noise_sigma,
+        );
+        optimizer.run(max_iterations, |_| true);
+        let (kernel, noise_sigma) = optimizer.best();
+        self.gp = GaussianProcess::builder()
+            .kernel(kernel)
+            .noise(noise_sigma)
+            .build(&self.gp.x, &self.gp.targets())?;
+        Ok(())
+    }
+
+    /// The kernel in RON.
+    pub fn kernel(&self) -> Result<String, JsError> {
+        Ok(ron::to_string(&self.gp.kernel)?)
+    }
+
+    #[wasm_bindgen(js_name = noiseSigma)]
+    pub fn noise_sigma(&self) -> f64 {
+        self.gp.noise_sigma
+    }
+
+    #[wasm_bindgen(js_name = logMarginalLikelihood)]
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.gp.log_marginal_likelihood()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wasm_api() {
+        let kernel = "Rbf((sigma: 1.0, length_scale: 1.0))";
+        let mut gp =
+            WasmGaussianProcess::fit(vec![1.0, 2.0, 4.0], vec![0.5, -1.0, 0.2], kernel, 0.1)
+                .ok()
+                .unwrap();
+        let prediction = gp.predict(vec![1.5, 3.0]);
+        assert_eq!(prediction.mean().len(), 2);
+        assert_eq!(prediction.variance().len(), 2);
+        assert_eq!(gp.sample(vec![1.5, 3.0, 5.0], 4, 0).len(), 12);
+
+        let before = gp.log_marginal_likelihood();
+        assert!(gp.optimize(100).is_ok());
+        assert!(gp.log_marginal_likelihood() >= before);
+        assert!(gp.kernel().ok().unwrap().starts_with("Rbf"));
+    }
+}
@@ -1,6 +1,8 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+#[cfg(feature = "gui")]
 mod app;
+#[cfg(feature = "gui")]
 pub use app::App;
 
-mod gp;
+pub mod gp;